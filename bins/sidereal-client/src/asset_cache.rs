@@ -0,0 +1,253 @@
+//! Versioned cache for gateway-streamed assets.
+//!
+//! `/world/me` lists every streamed asset with a content version. The client
+//! keeps the versions it last downloaded in `data/cache_stream/manifest.json`
+//! and, on each login, fetches only assets whose version changed (or that were
+//! never cached) and deletes cached files the server no longer lists. A
+//! missing or unreadable manifest simply means everything is fetched again.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path, PathBuf};
+
+pub const CACHE_STREAM_DIR: &str = "data/cache_stream";
+const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamAssetDescriptor {
+    pub asset_id: String,
+    pub relative_cache_path: String,
+    /// Empty for gateways that do not version assets; such assets are always
+    /// fetched.
+    #[serde(default)]
+    pub version: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedAsset {
+    pub version: String,
+    pub relative_cache_path: String,
+}
+
+/// Asset id -> cached version and location.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetCacheManifest {
+    pub assets: BTreeMap<String, CachedAsset>,
+}
+
+impl AssetCacheManifest {
+    pub fn from_descriptors(descriptors: &[StreamAssetDescriptor]) -> Self {
+        let assets = descriptors
+            .iter()
+            .map(|descriptor| {
+                (
+                    descriptor.asset_id.clone(),
+                    CachedAsset {
+                        version: descriptor.version.clone(),
+                        relative_cache_path: descriptor.relative_cache_path.clone(),
+                    },
+                )
+            })
+            .collect();
+        Self { assets }
+    }
+
+    pub fn load(cache_dir: &Path) -> Self {
+        std::fs::read(cache_dir.join(MANIFEST_FILE))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Self>(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, cache_dir: &Path) -> Result<(), String> {
+        std::fs::create_dir_all(cache_dir).map_err(|err| err.to_string())?;
+        let bytes = serde_json::to_vec_pretty(self).map_err(|err| err.to_string())?;
+        std::fs::write(cache_dir.join(MANIFEST_FILE), bytes).map_err(|err| err.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManifestDiff {
+    /// Asset ids to download, sorted.
+    pub to_fetch: Vec<String>,
+    /// Cached relative paths no server asset uses any more, sorted.
+    pub to_delete: Vec<String>,
+}
+
+/// Compares the cached manifest with the server's. An asset is fetched when it
+/// is new, its version changed, it is unversioned, or it moved to another
+/// cache path; a cached file is deleted when no server asset still uses its
+/// path.
+pub fn diff_manifests(cached: &AssetCacheManifest, server: &AssetCacheManifest) -> ManifestDiff {
+    let to_fetch = server
+        .assets
+        .iter()
+        .filter(|(asset_id, server_asset)| {
+            server_asset.version.is_empty() || cached.assets.get(*asset_id) != Some(*server_asset)
+        })
+        .map(|(asset_id, _)| asset_id.clone())
+        .collect();
+
+    let live_paths = server
+        .assets
+        .values()
+        .map(|asset| asset.relative_cache_path.as_str())
+        .collect::<BTreeSet<_>>();
+    let to_delete = cached
+        .assets
+        .values()
+        .map(|asset| asset.relative_cache_path.as_str())
+        .filter(|path| !live_paths.contains(path))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+
+    ManifestDiff {
+        to_fetch,
+        to_delete,
+    }
+}
+
+pub fn cache_dir(asset_root: &str) -> PathBuf {
+    PathBuf::from(asset_root).join(CACHE_STREAM_DIR)
+}
+
+/// Joins a server-supplied `relative_cache_path` onto `cache_dir`, rejecting
+/// anything that could resolve outside it: empty and absolute paths and any
+/// `..` component.
+pub fn cache_target(cache_dir: &Path, relative_cache_path: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(relative_cache_path);
+    if relative_cache_path.is_empty() || relative.is_absolute() {
+        return Err(format!(
+            "asset cache path {relative_cache_path:?} must be a non-empty relative path"
+        ));
+    }
+    for component in relative.components() {
+        match component {
+            Component::Normal(_) | Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(format!(
+                    "asset cache path {relative_cache_path:?} escapes the cache directory"
+                ));
+            }
+        }
+    }
+    Ok(cache_dir.join(relative))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(entries: &[(&str, &str, &str)]) -> AssetCacheManifest {
+        let descriptors = entries
+            .iter()
+            .map(|(asset_id, version, path)| StreamAssetDescriptor {
+                asset_id: asset_id.to_string(),
+                relative_cache_path: path.to_string(),
+                version: version.to_string(),
+            })
+            .collect::<Vec<_>>();
+        AssetCacheManifest::from_descriptors(&descriptors)
+    }
+
+    #[test]
+    fn empty_cache_fetches_everything() {
+        let server = manifest(&[
+            ("starfield_wgsl", "a1", "shaders/starfield.wgsl"),
+            (
+                "corvette_01_png",
+                "b2",
+                "models/corvette_01/corvette_01.png",
+            ),
+        ]);
+        let diff = diff_manifests(&AssetCacheManifest::default(), &server);
+        assert_eq!(diff.to_fetch, vec!["corvette_01_png", "starfield_wgsl"]);
+        assert!(diff.to_delete.is_empty());
+    }
+
+    #[test]
+    fn only_changed_assets_are_fetched() {
+        let cached = manifest(&[
+            ("starfield_wgsl", "a1", "shaders/starfield.wgsl"),
+            (
+                "corvette_01_png",
+                "b2",
+                "models/corvette_01/corvette_01.png",
+            ),
+        ]);
+        let server = manifest(&[
+            ("starfield_wgsl", "a2", "shaders/starfield.wgsl"),
+            (
+                "corvette_01_png",
+                "b2",
+                "models/corvette_01/corvette_01.png",
+            ),
+        ]);
+        let diff = diff_manifests(&cached, &server);
+        assert_eq!(diff.to_fetch, vec!["starfield_wgsl"]);
+        assert!(diff.to_delete.is_empty());
+        assert_eq!(diff_manifests(&server, &server), ManifestDiff::default());
+    }
+
+    #[test]
+    fn removed_and_moved_assets_prune_their_old_files() {
+        let cached = manifest(&[
+            ("starfield_wgsl", "a1", "shaders/starfield.wgsl"),
+            ("old_hull_gltf", "c1", "models/old_hull/old_hull.gltf"),
+            (
+                "space_background_wgsl",
+                "d1",
+                "shaders/space_background.wgsl",
+            ),
+        ]);
+        let server = manifest(&[
+            ("starfield_wgsl", "a1", "shaders/starfield.wgsl"),
+            (
+                "space_background_wgsl",
+                "d1",
+                "shaders/simple_space_background.wgsl",
+            ),
+        ]);
+        let diff = diff_manifests(&cached, &server);
+        assert_eq!(diff.to_fetch, vec!["space_background_wgsl"]);
+        assert_eq!(
+            diff.to_delete,
+            vec![
+                "models/old_hull/old_hull.gltf",
+                "shaders/space_background.wgsl"
+            ]
+        );
+    }
+
+    #[test]
+    fn cache_paths_stay_under_the_cache_dir() {
+        let cache_dir = Path::new("data/cache_stream");
+        assert_eq!(
+            cache_target(cache_dir, "models/corvette_01/corvette_01.gltf"),
+            Ok(PathBuf::from(
+                "data/cache_stream/models/corvette_01/corvette_01.gltf"
+            ))
+        );
+        for traversal in [
+            "../../etc/passwd",
+            "models/../../outside.bin",
+            "/etc/passwd",
+            "",
+        ] {
+            assert!(
+                cache_target(cache_dir, traversal).is_err(),
+                "{traversal:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn unversioned_assets_are_always_fetched() {
+        let cached = manifest(&[("starfield_wgsl", "", "shaders/starfield.wgsl")]);
+        let diff = diff_manifests(&cached, &cached);
+        assert_eq!(diff.to_fetch, vec!["starfield_wgsl"]);
+        assert!(diff.to_delete.is_empty());
+    }
+}
@@ -4,6 +4,7 @@ use bevy::input::keyboard::{Key, KeyboardInput};
 use bevy::prelude::*;
 use bevy::state::state_scoped::DespawnOnExit;
 
+use crate::server_select::{self, MAX_RECENT_SERVERS, RecentServers};
 use crate::{
     AssetRootPath, AuthAction, ClientAppState, ClientSession, FocusField, active_field_mut,
     dialog_ui, is_printable_char, mask, submit_auth_request,
@@ -45,6 +46,12 @@ struct AuthUiCursor {
     field: FocusField,
 }
 
+#[derive(Component)]
+struct AuthUiRecentServerSlot(usize);
+
+#[derive(Component)]
+struct AuthUiRecentServerLabel(usize);
+
 #[derive(Component)]
 struct AuthUiButton(AuthButtonKind);
 
@@ -53,6 +60,7 @@ enum AuthButtonKind {
     Submit,
     SwitchFlow(AuthAction),
     Focus(FocusField),
+    RecentServer(usize),
 }
 
 #[derive(Resource)]
@@ -83,6 +91,7 @@ pub fn register_auth_ui(app: &mut App) {
             update_auth_text,
             update_auth_field_layout,
             update_auth_field_content,
+            update_recent_server_slots,
         )
             .run_if(in_state(ClientAppState::Auth)),
     );
@@ -169,6 +178,23 @@ fn setup_auth_screen(mut commands: Commands<'_, '_>, fonts: Res<'_, crate::Embed
                     FocusField::NewPassword,
                     true,
                 );
+                spawn_input_field(
+                    panel,
+                    &font_regular,
+                    "Gateway (host:port)",
+                    FocusField::ServerGateway,
+                    false,
+                );
+                spawn_input_field(
+                    panel,
+                    &font_regular,
+                    "Replication (host:port)",
+                    FocusField::ServerReplication,
+                    false,
+                );
+                for index in 0..MAX_RECENT_SERVERS {
+                    spawn_recent_server_slot(panel, &font_regular, index);
+                }
 
                 panel
                     .spawn((
@@ -220,6 +246,7 @@ fn setup_auth_screen(mut commands: Commands<'_, '_>, fonts: Res<'_, crate::Embed
                             "Forgot Confirm",
                             AuthAction::ForgotConfirm,
                         );
+                        spawn_flow_button(row, &font_regular, "Server", AuthAction::SelectServer);
                     });
 
                 panel.spawn((
@@ -342,6 +369,38 @@ fn spawn_flow_button(
         });
 }
 
+fn spawn_recent_server_slot(parent: &mut ChildSpawnerCommands, font: &Handle<Font>, index: usize) {
+    parent
+        .spawn((
+            Button,
+            AuthUiButton(AuthButtonKind::RecentServer(index)),
+            AuthUiRecentServerSlot(index),
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Px(30.0),
+                padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                border_radius: BorderRadius::all(Val::Px(6.0)),
+                justify_content: JustifyContent::FlexStart,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.18, 0.2, 0.26, 0.85)),
+            Visibility::Hidden,
+        ))
+        .with_children(|button| {
+            button.spawn((
+                Text::new(""),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 13.0,
+                    ..default()
+                },
+                TextColor(Color::srgba(0.83, 0.89, 0.95, 0.95)),
+                AuthUiRecentServerLabel(index),
+            ));
+        });
+}
+
 fn animate_auth_background(
     time: Res<'_, Time>,
     mut bg_query: Query<'_, '_, &mut BackgroundColor, With<AuthUiBackdrop>>,
@@ -366,6 +425,7 @@ fn handle_auth_keyboard_input(
     mut next_state: ResMut<'_, NextState<ClientAppState>>,
     mut session: ResMut<'_, ClientSession>,
     mut dialog_queue: ResMut<'_, dialog_ui::DialogQueue>,
+    mut recent_servers: ResMut<'_, RecentServers>,
     asset_root: Res<'_, AssetRootPath>,
 ) {
     let mut submit = false;
@@ -395,6 +455,11 @@ fn handle_auth_keyboard_input(
                 session.focus = FocusField::ResetToken;
                 session.ui_dirty = true;
             }
+            Key::F5 => {
+                session.selected_action = AuthAction::SelectServer;
+                session.focus = FocusField::ServerGateway;
+                session.ui_dirty = true;
+            }
             Key::Tab => {
                 session.focus = next_focus_field(session.selected_action, session.focus);
                 session.ui_dirty = true;
@@ -422,15 +487,67 @@ fn handle_auth_keyboard_input(
     }
 
     if submit {
-        submit_auth_request(
+        submit_current_flow(
             &mut session,
             &mut next_state,
             &mut dialog_queue,
+            &mut recent_servers,
             &asset_root,
         );
     }
 }
 
+fn submit_current_flow(
+    session: &mut ClientSession,
+    next_state: &mut NextState<ClientAppState>,
+    dialog_queue: &mut dialog_ui::DialogQueue,
+    recent_servers: &mut RecentServers,
+    asset_root: &AssetRootPath,
+) {
+    if session.selected_action == AuthAction::SelectServer {
+        let gateway = session.server_gateway.clone();
+        let replication = session.server_replication.clone();
+        select_server(
+            session,
+            dialog_queue,
+            recent_servers,
+            &gateway,
+            &replication,
+        );
+    } else {
+        submit_auth_request(session, next_state, dialog_queue, asset_root);
+    }
+}
+
+fn select_server(
+    session: &mut ClientSession,
+    dialog_queue: &mut dialog_ui::DialogQueue,
+    recent_servers: &mut RecentServers,
+    gateway: &str,
+    replication: &str,
+) {
+    match server_select::apply_server_profile(session, gateway, replication) {
+        Ok(profile) => {
+            session.status = format!(
+                "Server set: gateway {}, replication {}. F1 to login.",
+                session.gateway_url, profile.replication
+            );
+            recent_servers.remember(profile);
+            if let Err(err) = recent_servers.save() {
+                eprintln!("failed to save recent servers: {err}");
+            }
+        }
+        Err(err) => {
+            session.status = format!("Server selection failed: {err}");
+            dialog_queue.push_error(
+                "Invalid Server",
+                format!("Server addresses must be host:port.\n\nDetails: {err}"),
+            );
+        }
+    }
+    session.ui_dirty = true;
+}
+
 fn handle_auth_button_interactions(
     mut interactions: Query<
         '_,
@@ -446,6 +563,7 @@ fn handle_auth_button_interactions(
     mut next_state: ResMut<'_, NextState<ClientAppState>>,
     mut session: ResMut<'_, ClientSession>,
     mut dialog_queue: ResMut<'_, dialog_ui::DialogQueue>,
+    mut recent_servers: ResMut<'_, RecentServers>,
     asset_root: Res<'_, AssetRootPath>,
 ) {
     for (interaction, button, mut bg, input_box) in &mut interactions {
@@ -461,10 +579,11 @@ fn handle_auth_button_interactions(
                 match button.0 {
                     AuthButtonKind::Submit => {
                         *bg = BackgroundColor(Color::srgb(0.16, 0.38, 0.74));
-                        submit_auth_request(
+                        submit_current_flow(
                             &mut session,
                             &mut next_state,
                             &mut dialog_queue,
+                            &mut recent_servers,
                             &asset_root,
                         );
                     }
@@ -479,6 +598,19 @@ fn handle_auth_button_interactions(
                         session.ui_dirty = true;
                         *bg = BackgroundColor(Color::srgba(0.12, 0.15, 0.21, 0.98));
                     }
+                    AuthButtonKind::RecentServer(index) => {
+                        *bg = BackgroundColor(Color::srgba(0.25, 0.28, 0.36, 0.9));
+                        let Some(profile) = recent_servers.profiles.get(index).cloned() else {
+                            continue;
+                        };
+                        select_server(
+                            &mut session,
+                            &mut dialog_queue,
+                            &mut recent_servers,
+                            &profile.gateway,
+                            &profile.replication,
+                        );
+                    }
                 }
             }
             Interaction::Hovered => {
@@ -487,7 +619,7 @@ fn handle_auth_button_interactions(
                 } else {
                     *bg = match button.0 {
                         AuthButtonKind::Submit => BackgroundColor(Color::srgb(0.24, 0.5, 0.9)),
-                        AuthButtonKind::SwitchFlow(_) => {
+                        AuthButtonKind::SwitchFlow(_) | AuthButtonKind::RecentServer(_) => {
                             BackgroundColor(Color::srgba(0.22, 0.25, 0.32, 0.88))
                         }
                         AuthButtonKind::Focus(_) => {
@@ -502,7 +634,7 @@ fn handle_auth_button_interactions(
                 } else {
                     *bg = match button.0 {
                         AuthButtonKind::Submit => BackgroundColor(Color::srgb(0.2, 0.46, 0.85)),
-                        AuthButtonKind::SwitchFlow(_) => {
+                        AuthButtonKind::SwitchFlow(_) | AuthButtonKind::RecentServer(_) => {
                             BackgroundColor(Color::srgba(0.18, 0.2, 0.26, 0.85))
                         }
                         AuthButtonKind::Focus(_) => {
@@ -552,15 +684,22 @@ fn update_auth_text(
 
 fn update_auth_field_layout(
     session: Res<'_, ClientSession>,
-    mut field_containers: Query<'_, '_, (&AuthUiFieldContainer, &mut Visibility)>,
+    mut field_containers: Query<'_, '_, (&AuthUiFieldContainer, &mut Visibility, &mut Node)>,
     mut input_boxes: Query<'_, '_, (&AuthUiInputBox, &mut BorderColor)>,
 ) {
-    for (container, mut visibility) in &mut field_containers {
-        *visibility = if is_field_visible(session.selected_action, container.field) {
+    for (container, mut visibility, mut node) in &mut field_containers {
+        let visible = is_field_visible(session.selected_action, container.field);
+        *visibility = if visible {
             Visibility::Visible
         } else {
             Visibility::Hidden
         };
+        // Collapse hidden fields so the server fields don't pad out the other flows.
+        node.display = if visible {
+            Display::Flex
+        } else {
+            Display::None
+        };
     }
 
     for (input_box, mut border) in &mut input_boxes {
@@ -586,6 +725,8 @@ fn update_auth_field_content(
             FocusField::Password => session.password.as_str(),
             FocusField::ResetToken => session.reset_token.as_str(),
             FocusField::NewPassword => session.new_password.as_str(),
+            FocusField::ServerGateway => session.server_gateway.as_str(),
+            FocusField::ServerReplication => session.server_replication.as_str(),
         };
 
         text.0 = if input.is_password {
@@ -607,12 +748,42 @@ fn update_auth_field_content(
     }
 }
 
+fn update_recent_server_slots(
+    session: Res<'_, ClientSession>,
+    recent_servers: Res<'_, RecentServers>,
+    mut slots: Query<'_, '_, (&AuthUiRecentServerSlot, &mut Visibility, &mut Node)>,
+    mut labels: Query<'_, '_, (&AuthUiRecentServerLabel, &mut Text)>,
+) {
+    let show = session.selected_action == AuthAction::SelectServer;
+    for (slot, mut visibility, mut node) in &mut slots {
+        let visible = show && slot.0 < recent_servers.profiles.len();
+        *visibility = if visible {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+        node.display = if visible {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+    for (label, mut text) in &mut labels {
+        text.0 = recent_servers
+            .profiles
+            .get(label.0)
+            .map(|profile| format!("Recent: {} / {}", profile.gateway, profile.replication))
+            .unwrap_or_default();
+    }
+}
+
 fn flow_title(action: AuthAction) -> &'static str {
     match action {
         AuthAction::Login => "Login",
         AuthAction::Register => "Register",
         AuthAction::ForgotRequest => "Request Password Reset",
         AuthAction::ForgotConfirm => "Confirm Password Reset",
+        AuthAction::SelectServer => "Select Server",
     }
 }
 
@@ -622,6 +793,7 @@ fn submit_label(action: AuthAction) -> &'static str {
         AuthAction::Register => "Create Account",
         AuthAction::ForgotRequest => "Request Reset Token",
         AuthAction::ForgotConfirm => "Set New Password",
+        AuthAction::SelectServer => "Use Server",
     }
 }
 
@@ -634,6 +806,12 @@ fn is_field_visible(action: AuthAction, field: FocusField) -> bool {
         AuthAction::ForgotConfirm => {
             matches!(field, FocusField::ResetToken | FocusField::NewPassword)
         }
+        AuthAction::SelectServer => {
+            matches!(
+                field,
+                FocusField::ServerGateway | FocusField::ServerReplication
+            )
+        }
     }
 }
 
@@ -641,6 +819,7 @@ fn first_focus_field(action: AuthAction) -> FocusField {
     match action {
         AuthAction::Login | AuthAction::Register | AuthAction::ForgotRequest => FocusField::Email,
         AuthAction::ForgotConfirm => FocusField::ResetToken,
+        AuthAction::SelectServer => FocusField::ServerGateway,
     }
 }
 
@@ -655,5 +834,9 @@ fn next_focus_field(action: AuthAction, current: FocusField) -> FocusField {
             FocusField::ResetToken => FocusField::NewPassword,
             _ => FocusField::ResetToken,
         },
+        AuthAction::SelectServer => match current {
+            FocusField::ServerGateway => FocusField::ServerReplication,
+            _ => FocusField::ServerGateway,
+        },
     }
 }
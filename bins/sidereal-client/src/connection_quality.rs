@@ -0,0 +1,190 @@
+//! Network health shown on the HUD: round-trip time from ping/pong echoes and
+//! snapshot loss from gaps in the `ReplicationStateMessage::seq` numbers
+//! received.
+//!
+//! State snapshots travel on an unreliable channel, so a `seq` that never
+//! arrives is a lost packet. Replication numbers every message it sends a
+//! client consecutively, whatever its tick stride, so each missing number is
+//! one lost snapshot.
+
+use bevy::prelude::*;
+use std::collections::{BTreeSet, HashMap};
+
+/// Seconds between pings.
+pub const PING_INTERVAL_S: f64 = 1.0;
+/// A ping without a pong after this long is forgotten.
+const PING_TIMEOUT_S: f64 = 10.0;
+/// Snapshot sequence numbers the loss estimate is computed over.
+const LOSS_WINDOW: usize = 64;
+/// Weight of a new RTT sample in the smoothed estimate (as in TCP's SRTT).
+const RTT_SMOOTHING: f32 = 0.125;
+/// Above either threshold the link is shown as degraded.
+pub const DEGRADED_RTT_MS: f32 = 250.0;
+pub const DEGRADED_LOSS: f32 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkState {
+    Disconnected,
+    Connected,
+    Degraded,
+}
+
+impl LinkState {
+    pub fn classify(connected: bool, rtt_ms: Option<f32>, loss: Option<f32>) -> Self {
+        if !connected {
+            Self::Disconnected
+        } else if rtt_ms.is_some_and(|rtt| rtt > DEGRADED_RTT_MS)
+            || loss.is_some_and(|loss| loss > DEGRADED_LOSS)
+        {
+            Self::Degraded
+        } else {
+            Self::Connected
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Disconnected => "DISCONNECTED",
+            Self::Connected => "connected",
+            Self::Degraded => "DEGRADED",
+        }
+    }
+}
+
+/// The HUD status line. Unknown RTT or loss (no samples yet) shows as `--`.
+pub fn format_connection_status(
+    state: LinkState,
+    rtt_ms: Option<f32>,
+    loss: Option<f32>,
+) -> String {
+    let rtt = rtt_ms.map_or_else(|| "--".to_string(), |rtt| format!("{rtt:.0} ms"));
+    let loss = loss.map_or_else(|| "--".to_string(), |loss| format!("{:.1}%", loss * 100.0));
+    format!("Net: {} | RTT {rtt} | loss {loss}", state.label())
+}
+
+#[derive(Debug, Clone, Default, Resource)]
+pub struct ConnectionQuality {
+    smoothed_rtt_ms: Option<f32>,
+    pings_in_flight: HashMap<u64, f64>,
+    next_nonce: u64,
+    last_ping_s: Option<f64>,
+    received_seqs: BTreeSet<u64>,
+}
+
+impl ConnectionQuality {
+    /// Returns the nonce of a ping to send at `now_s`, if one is due.
+    pub fn ping_due(&mut self, now_s: f64) -> Option<u64> {
+        if self
+            .last_ping_s
+            .is_some_and(|last| now_s - last < PING_INTERVAL_S)
+        {
+            return None;
+        }
+        self.pings_in_flight
+            .retain(|_, sent_s| now_s - *sent_s <= PING_TIMEOUT_S);
+        let nonce = self.next_nonce;
+        self.next_nonce = self.next_nonce.wrapping_add(1);
+        self.last_ping_s = Some(now_s);
+        self.pings_in_flight.insert(nonce, now_s);
+        Some(nonce)
+    }
+
+    /// Folds in the round trip of the ping `nonce`; unknown or timed-out
+    /// nonces are ignored.
+    pub fn on_pong(&mut self, nonce: u64, now_s: f64) {
+        let Some(sent_s) = self.pings_in_flight.remove(&nonce) else {
+            return;
+        };
+        let sample_ms = ((now_s - sent_s) * 1000.0) as f32;
+        self.smoothed_rtt_ms = Some(match self.smoothed_rtt_ms {
+            Some(rtt) => rtt + RTT_SMOOTHING * (sample_ms - rtt),
+            None => sample_ms,
+        });
+    }
+
+    /// Records a replication snapshot's `seq`. Duplicates count once, and
+    /// numbers arriving out of order still fill their gap. Zero (an unnumbered
+    /// message) is ignored.
+    pub fn on_snapshot(&mut self, seq: u64) {
+        if seq == 0 {
+            return;
+        }
+        self.received_seqs.insert(seq);
+        while self.received_seqs.len() > LOSS_WINDOW {
+            self.received_seqs.pop_first();
+        }
+    }
+
+    pub fn rtt_ms(&self) -> Option<f32> {
+        self.smoothed_rtt_ms
+    }
+
+    /// Fraction of snapshots missing from the window, once two have arrived.
+    pub fn loss(&self) -> Option<f32> {
+        if self.received_seqs.len() < 2 {
+            return None;
+        }
+        let first = *self.received_seqs.first()?;
+        let last = *self.received_seqs.last()?;
+        let expected = last - first + 1;
+        Some(1.0 - self.received_seqs.len() as f32 / expected as f32)
+    }
+
+    /// Forgets everything measured, e.g. after a reconnect restarts the
+    /// sequence numbers.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_line_formats_rtt_loss_and_state() {
+        let healthy = LinkState::classify(true, Some(42.4), Some(0.0));
+        assert_eq!(healthy, LinkState::Connected);
+        assert_eq!(
+            format_connection_status(healthy, Some(42.4), Some(0.0)),
+            "Net: connected | RTT 42 ms | loss 0.0%"
+        );
+
+        let lossy = LinkState::classify(true, Some(80.0), Some(0.125));
+        assert_eq!(lossy, LinkState::Degraded);
+        assert_eq!(
+            format_connection_status(lossy, Some(80.0), Some(0.125)),
+            "Net: DEGRADED | RTT 80 ms | loss 12.5%"
+        );
+
+        assert_eq!(
+            LinkState::classify(true, Some(310.0), None),
+            LinkState::Degraded
+        );
+        assert_eq!(
+            format_connection_status(LinkState::classify(false, None, None), None, None),
+            "Net: DISCONNECTED | RTT -- | loss --"
+        );
+    }
+
+    #[test]
+    fn pings_and_snapshot_gaps_feed_the_estimates() {
+        let mut quality = ConnectionQuality::default();
+        let nonce = quality.ping_due(10.0).expect("first ping is due");
+        assert_eq!(quality.ping_due(10.5), None);
+        quality.on_pong(nonce, 10.08);
+        quality.on_pong(nonce, 10.5);
+        assert!((quality.rtt_ms().expect("rtt sampled") - 80.0).abs() < 0.01);
+
+        assert_eq!(quality.loss(), None);
+        // Twenty snapshots numbered 1 to 20, with 5 and 10 lost, 13 delivered
+        // twice and 16 arriving after 17.
+        for seq in (1..=20).filter(|seq| *seq != 5 && *seq != 10 && *seq != 16) {
+            quality.on_snapshot(seq);
+        }
+        quality.on_snapshot(13);
+        quality.on_snapshot(16);
+        quality.on_snapshot(0);
+        assert!((quality.loss().expect("loss measured") - 0.1).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,174 @@
+//! Server-acknowledged controlled entity.
+//!
+//! Until replication sends a `ControlledEntityMessage`, the client treats the
+//! ship id from `/world/me` as its own. Once the server has spoken, its
+//! assignment is authoritative: updates for that id reconcile the local ship,
+//! and everything else (including the `/world/me` id, if the server disagrees)
+//! is a remote entity.
+
+use bevy::prelude::*;
+use sidereal_net::WorldStateDelta;
+
+/// The controlled ship's state as the client simulates it, in wire
+/// conventions (`heading_rad` as replication sends it).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LocalKinematics {
+    pub position_m: Vec3,
+    pub velocity_mps: Vec3,
+    pub heading_rad: f32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Resource)]
+pub struct ControlledEntityAssignment {
+    /// Whether the server has sent an assignment this session.
+    pub acknowledged: bool,
+    pub entity_id: Option<String>,
+}
+
+impl ControlledEntityAssignment {
+    /// Records the server's assignment. Returns true when it changed.
+    pub fn acknowledge(&mut self, entity_id: Option<String>) -> bool {
+        let changed = !self.acknowledged || self.entity_id != entity_id;
+        self.acknowledged = true;
+        self.entity_id = entity_id;
+        changed
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Whether `update_entity_id` is the entity this client controls.
+    /// `local_ship_entity_id` is the id the local ship was spawned with and
+    /// only decides while no assignment has been acknowledged.
+    pub fn is_controlled(
+        &self,
+        update_entity_id: &str,
+        local_ship_entity_id: Option<&str>,
+    ) -> bool {
+        if self.acknowledged {
+            self.entity_id.as_deref() == Some(update_entity_id)
+        } else {
+            local_ship_entity_id == Some(update_entity_id)
+        }
+    }
+
+    /// `world` as this client holds it: remote entities as received (they are
+    /// only interpolated, never predicted) and the controlled one with its
+    /// spatial properties replaced by `local`. Only properties the update
+    /// carries are replaced, so the hash covers the same fields on both sides.
+    pub fn local_view(
+        &self,
+        world: &WorldStateDelta,
+        local_ship_entity_id: Option<&str>,
+        local: LocalKinematics,
+    ) -> WorldStateDelta {
+        let mut view = world.clone();
+        for update in &mut view.updates {
+            if !self.is_controlled(&update.entity_id, local_ship_entity_id) {
+                continue;
+            }
+            let Some(properties) = update.properties.as_object_mut() else {
+                continue;
+            };
+            for (key, value) in [
+                ("position_m", serde_json::json!(local.position_m.to_array())),
+                (
+                    "velocity_mps",
+                    serde_json::json!(local.velocity_mps.to_array()),
+                ),
+                ("heading_rad", serde_json::json!(local.heading_rad)),
+            ] {
+                if let Some(slot) = properties.get_mut(key) {
+                    *slot = value;
+                }
+            }
+        }
+        view
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_ship_id_decides_until_the_server_acknowledges() {
+        let assignment = ControlledEntityAssignment::default();
+        assert!(assignment.is_controlled("ship:a", Some("ship:a")));
+        assert!(!assignment.is_controlled("ship:b", Some("ship:a")));
+        assert!(!assignment.is_controlled("ship:a", None));
+    }
+
+    #[test]
+    fn server_assignment_overrides_the_local_ship_id() {
+        let mut assignment = ControlledEntityAssignment::default();
+        assert!(assignment.acknowledge(Some("ship:b".to_string())));
+        assert!(assignment.is_controlled("ship:b", Some("ship:a")));
+        assert!(!assignment.is_controlled("ship:a", Some("ship:a")));
+
+        // Controlling nothing means no update is reconciled locally.
+        assert!(assignment.acknowledge(None));
+        assert!(!assignment.is_controlled("ship:a", Some("ship:a")));
+        assert!(!assignment.is_controlled("ship:b", Some("ship:a")));
+    }
+
+    #[test]
+    fn repeated_assignment_is_not_a_change() {
+        let mut assignment = ControlledEntityAssignment::default();
+        assert!(assignment.acknowledge(Some("ship:a".to_string())));
+        assert!(!assignment.acknowledge(Some("ship:a".to_string())));
+        assignment.reset();
+        assert!(!assignment.acknowledged);
+        assert!(assignment.acknowledge(Some("ship:a".to_string())));
+    }
+
+    #[test]
+    fn local_view_replaces_only_the_controlled_entity_state() {
+        let update = |entity_id: &str| sidereal_net::WorldDeltaEntity {
+            entity_id: entity_id.to_string(),
+            labels: Vec::new(),
+            properties: serde_json::json!({
+                "position_m": [1.0, 2.0, 0.0],
+                "velocity_mps": [0.0, 0.0, 0.0],
+                "heading_rad": 0.5,
+            }),
+            components: Vec::new(),
+            removed: false,
+            removed_component_ids: Vec::new(),
+            removal_reason: None,
+        };
+        let world = WorldStateDelta {
+            updates: vec![update("ship:mine"), update("ship:other")],
+        };
+        let mut assignment = ControlledEntityAssignment::default();
+        assignment.acknowledge(Some("ship:mine".to_string()));
+        let local = LocalKinematics {
+            position_m: Vec3::new(1.5, 2.0, 0.0),
+            velocity_mps: Vec3::new(3.0, 0.0, 0.0),
+            heading_rad: 0.5,
+        };
+
+        let view = assignment.local_view(&world, None, local);
+        assert_eq!(
+            view.updates[0].properties["position_m"],
+            serde_json::json!([1.5, 2.0, 0.0])
+        );
+        assert_eq!(
+            view.updates[0].properties["velocity_mps"],
+            serde_json::json!([3.0, 0.0, 0.0])
+        );
+        assert_eq!(view.updates[1], world.updates[1]);
+        assert_ne!(view.state_hash(), world.state_hash());
+
+        let agreeing = LocalKinematics {
+            position_m: Vec3::new(1.0, 2.0, 0.0),
+            velocity_mps: Vec3::ZERO,
+            heading_rad: 0.5,
+        };
+        assert_eq!(
+            assignment.local_view(&world, None, agreeing).state_hash(),
+            world.state_hash()
+        );
+    }
+}
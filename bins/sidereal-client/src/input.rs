@@ -0,0 +1,154 @@
+//! Per-frame input capture shared by local prediction and the network stream.
+//!
+//! The keyboard is sampled once per frame into an `InputSnapshot`. The local
+//! action queue only receives actions when that snapshot changes (the flight
+//! computer holds its state between actions). The network stream coalesces the
+//! frames of each sim tick (`input_batching`) and sends a tick on change plus a
+//! keepalive cadence so a dropped packet on the unreliable input channel is
+//! recovered.
+
+use bevy::prelude::*;
+use sidereal_game::{ActionQueue, EntityAction};
+use sidereal_input_map::{InputChangeGate, RawInputState, map_raw_input, snapshot_axes};
+use sidereal_net::actions_from_axis_inputs;
+use sidereal_sim_core::InputSnapshot;
+
+use crate::ControlledShip;
+
+/// Sim ticks between repeated sends of an unchanged snapshot.
+pub const INPUT_KEEPALIVE_TICKS: u32 = 30;
+
+#[derive(Debug, Resource)]
+pub struct ClientInputState {
+    pub snapshot: InputSnapshot,
+    /// Last snapshot turned into local actions, per controlled entity.
+    applied_locally: Option<(Entity, InputSnapshot)>,
+    pub network_gate: InputChangeGate,
+}
+
+impl Default for ClientInputState {
+    fn default() -> Self {
+        Self {
+            snapshot: InputSnapshot::default(),
+            applied_locally: None,
+            network_gate: InputChangeGate::new(INPUT_KEEPALIVE_TICKS),
+        }
+    }
+}
+
+impl ClientInputState {
+    /// Returns the actions to enqueue for `entity`, or `None` when the snapshot
+    /// already applied to it has not changed.
+    pub fn take_local_actions(&mut self, entity: Entity) -> Option<Vec<EntityAction>> {
+        if self.applied_locally == Some((entity, self.snapshot)) {
+            return None;
+        }
+        self.applied_locally = Some((entity, self.snapshot));
+        Some(snapshot_actions(&self.snapshot))
+    }
+
+    pub fn reset(&mut self) {
+        self.snapshot = InputSnapshot::default();
+        self.applied_locally = None;
+        self.network_gate.reset();
+    }
+}
+
+pub fn snapshot_actions(snapshot: &InputSnapshot) -> Vec<EntityAction> {
+    let (thrust, turn, brake) = snapshot_axes(snapshot);
+    actions_from_axis_inputs(thrust, turn, brake)
+}
+
+pub fn capture_input_snapshot(
+    input: Option<Res<'_, ButtonInput<KeyCode>>>,
+    mut state: ResMut<'_, ClientInputState>,
+) {
+    let pressed = |key: KeyCode| input.as_ref().is_some_and(|keys| keys.pressed(key));
+    state.snapshot = map_raw_input(RawInputState {
+        up: pressed(KeyCode::KeyW),
+        down: pressed(KeyCode::KeyS),
+        left: pressed(KeyCode::KeyA),
+        right: pressed(KeyCode::KeyD),
+        brake: pressed(KeyCode::Space),
+    });
+}
+
+/// Converts the frame's input snapshot into EntityActions for the local controlled ship.
+/// Runs in FixedUpdate before SiderealGamePlugin's action processing systems.
+pub fn client_input_to_actions(
+    mut state: ResMut<'_, ClientInputState>,
+    mut ship_query: Query<'_, '_, (Entity, &mut ActionQueue), With<ControlledShip>>,
+) {
+    let Ok((entity, mut queue)) = ship_query.single_mut() else {
+        return;
+    };
+    if let Some(actions) = state.take_local_actions(entity) {
+        for action in actions {
+            queue.push(action);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forward() -> InputSnapshot {
+        map_raw_input(RawInputState {
+            up: true,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn unchanged_frames_do_not_enqueue_actions() {
+        let ship = Entity::from_raw_u32(7).expect("valid entity index");
+        let mut state = ClientInputState::default();
+
+        assert_eq!(
+            state.take_local_actions(ship),
+            Some(vec![EntityAction::ThrustNeutral, EntityAction::YawNeutral])
+        );
+        assert_eq!(state.take_local_actions(ship), None);
+        assert_eq!(state.take_local_actions(ship), None);
+
+        state.snapshot = forward();
+        assert_eq!(
+            state.take_local_actions(ship),
+            Some(vec![EntityAction::ThrustForward, EntityAction::YawNeutral])
+        );
+        assert_eq!(state.take_local_actions(ship), None);
+    }
+
+    #[test]
+    fn new_controlled_entity_receives_current_snapshot() {
+        let first = Entity::from_raw_u32(1).expect("valid entity index");
+        let second = Entity::from_raw_u32(2).expect("valid entity index");
+        let mut state = ClientInputState {
+            snapshot: forward(),
+            ..Default::default()
+        };
+
+        assert!(state.take_local_actions(first).is_some());
+        assert!(state.take_local_actions(first).is_none());
+        assert!(state.take_local_actions(second).is_some());
+    }
+
+    #[test]
+    fn local_and_network_actions_come_from_one_snapshot() {
+        let snapshot = map_raw_input(RawInputState {
+            down: true,
+            left: true,
+            ..Default::default()
+        });
+        let (thrust, turn, brake) = snapshot_axes(&snapshot);
+        let message = sidereal_net::ClientInputMessage::from_axis_inputs(
+            String::new(),
+            1,
+            thrust,
+            turn,
+            brake,
+        );
+        assert_eq!(message.actions, snapshot_actions(&snapshot));
+    }
+}
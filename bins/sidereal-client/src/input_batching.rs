@@ -0,0 +1,133 @@
+//! Coalesces per-frame input into one input message per sim tick.
+//!
+//! The client renders far faster than the simulation ticks, so sending every
+//! frame floods the input channel with messages the server folds into the
+//! same tick anyway. Frames within a tick overwrite each other and the last
+//! one stands for the tick, which is sent once the next tick starts. Each
+//! message repeats the previous few ticks so one lost packet loses no tick.
+
+use bevy::prelude::*;
+use sidereal_core::SIM_TICK_HZ;
+use sidereal_input_map::snapshot_axes;
+use sidereal_net::{ClientInputMessage, TickInput};
+use sidereal_sim_core::InputSnapshot;
+use std::collections::VecDeque;
+
+/// Earlier ticks repeated in each message.
+pub const INPUT_REDUNDANT_TICKS: usize = 3;
+
+/// The sim tick `elapsed_s` falls in.
+pub fn sim_tick_at(elapsed_s: f64) -> u64 {
+    (elapsed_s * f64::from(SIM_TICK_HZ)) as u64
+}
+
+#[derive(Debug, Default, Resource)]
+pub struct InputBatcher {
+    current: Option<(u64, InputSnapshot)>,
+    sent: VecDeque<TickInput>,
+}
+
+impl InputBatcher {
+    /// Records this frame's snapshot for `tick`, replacing any earlier frame
+    /// of the same tick. Returns the tick that just finished and its
+    /// coalesced snapshot when `tick` moves on.
+    pub fn push_frame(
+        &mut self,
+        tick: u64,
+        snapshot: InputSnapshot,
+    ) -> Option<(u64, InputSnapshot)> {
+        match self.current {
+            Some((current_tick, _)) if tick <= current_tick => {
+                self.current = Some((current_tick, snapshot));
+                None
+            }
+            finished => {
+                self.current = Some((tick, snapshot));
+                finished
+            }
+        }
+    }
+
+    /// The message for a finished tick, carrying the ticks sent before it.
+    pub fn message(
+        &mut self,
+        player_entity_id: String,
+        tick: u64,
+        snapshot: InputSnapshot,
+    ) -> ClientInputMessage {
+        let (thrust, turn, brake) = snapshot_axes(&snapshot);
+        let mut message =
+            ClientInputMessage::from_axis_inputs(player_entity_id, tick, thrust, turn, brake);
+        message.earlier_ticks = self.sent.iter().cloned().collect();
+
+        self.sent
+            .push_back(TickInput::from_axis_inputs(tick, thrust, turn, brake));
+        while self.sent.len() > INPUT_REDUNDANT_TICKS {
+            self.sent.pop_front();
+        }
+        message
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sidereal_game::EntityAction;
+    use sidereal_input_map::{RawInputState, map_raw_input};
+
+    fn snapshot(up: bool, left: bool) -> InputSnapshot {
+        map_raw_input(RawInputState {
+            up,
+            left,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn frames_within_one_tick_coalesce_into_one_action_set() {
+        let mut batcher = InputBatcher::default();
+        assert_eq!(batcher.push_frame(5, snapshot(false, false)), None);
+        assert_eq!(batcher.push_frame(5, snapshot(true, false)), None);
+        assert_eq!(batcher.push_frame(5, snapshot(true, true)), None);
+
+        let (tick, coalesced) = batcher
+            .push_frame(6, snapshot(false, false))
+            .expect("tick 5 finished");
+        assert_eq!(tick, 5);
+        let message = batcher.message("player:p".to_string(), tick, coalesced);
+        assert_eq!(message.tick, 5);
+        assert_eq!(
+            message.resolved_actions(),
+            vec![EntityAction::ThrustForward, EntityAction::YawLeft]
+        );
+        assert!(message.earlier_ticks.is_empty());
+    }
+
+    #[test]
+    fn messages_repeat_the_last_few_ticks() {
+        let mut batcher = InputBatcher::default();
+        for tick in 1..=5 {
+            batcher.message("player:p".to_string(), tick, snapshot(true, false));
+        }
+        let message = batcher.message("player:p".to_string(), 6, snapshot(false, false));
+        assert_eq!(
+            message
+                .earlier_ticks
+                .iter()
+                .map(|input| input.tick)
+                .collect::<Vec<_>>(),
+            vec![3, 4, 5]
+        );
+    }
+
+    #[test]
+    fn sim_ticks_follow_the_tick_rate() {
+        assert_eq!(sim_tick_at(0.0), 0);
+        assert_eq!(sim_tick_at(0.5 / f64::from(SIM_TICK_HZ)), 0);
+        assert_eq!(sim_tick_at(1.0), u64::from(SIM_TICK_HZ));
+    }
+}
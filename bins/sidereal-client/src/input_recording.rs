@@ -0,0 +1,203 @@
+//! Input recording and replay for reproducing flight bugs.
+//!
+//! With `SIDEREAL_CLIENT_INPUT_RECORD_PATH` set, every input message sent while
+//! in world is appended to that file as one JSON `RecordedInput` per line, so
+//! a crash still leaves a usable log. With `SIDEREAL_CLIENT_INPUT_REPLAY_PATH`
+//! set, `send_lightyear_input_messages` sends the recorded messages instead of
+//! live input, keeping their original tick spacing.
+
+use bevy::prelude::*;
+use sidereal_net::ClientInputMessage;
+use sidereal_sim_core::{InputRecording, RecordedInput};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, LineWriter, Write};
+use std::path::Path;
+
+#[derive(Default, Resource)]
+pub struct InputRecorder {
+    file: Option<LineWriter<File>>,
+}
+
+impl InputRecorder {
+    pub fn from_env() -> Self {
+        let Ok(path) = std::env::var("SIDEREAL_CLIENT_INPUT_RECORD_PATH") else {
+            return Self::default();
+        };
+        match Self::create(Path::new(&path)) {
+            Ok(recorder) => {
+                println!("native client recording input to {path}");
+                recorder
+            }
+            Err(err) => {
+                eprintln!("native client cannot record input to {path}: {err}");
+                Self::default()
+            }
+        }
+    }
+
+    /// Starts a new recording at `path`, truncating any previous one.
+    pub fn create(path: &Path) -> std::io::Result<Self> {
+        Ok(Self {
+            file: Some(LineWriter::new(File::create(path)?)),
+        })
+    }
+
+    /// Appends `message`. A write failure stops the recording rather than
+    /// leaving a log with gaps.
+    pub fn record(&mut self, message: &ClientInputMessage) {
+        let Some(file) = self.file.as_mut() else {
+            return;
+        };
+        let frame = RecordedInput {
+            tick: message.tick,
+            input: message,
+        };
+        let written = serde_json::to_string(&frame)
+            .map_err(|err| err.to_string())
+            .and_then(|line| writeln!(file, "{line}").map_err(|err| err.to_string()));
+        if let Err(err) = written {
+            eprintln!("native client stopped input recording: {err}");
+            self.file = None;
+        }
+    }
+}
+
+/// Reads a recording written by `InputRecorder`.
+pub fn load_recording(path: &Path) -> Result<InputRecording<ClientInputMessage>, String> {
+    let file = File::open(path).map_err(|err| err.to_string())?;
+    let mut recording = InputRecording::default();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|err| err.to_string())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let frame = serde_json::from_str::<RecordedInput<ClientInputMessage>>(&line)
+            .map_err(|err| format!("line {}: {err}", index + 1))?;
+        if !recording.push(frame.tick, frame.input) {
+            return Err(format!(
+                "line {}: tick {} out of order",
+                index + 1,
+                frame.tick
+            ));
+        }
+    }
+    Ok(recording)
+}
+
+/// Recorded messages still to be sent.
+#[derive(Debug, Default, Resource)]
+pub struct InputReplay {
+    pending: VecDeque<RecordedInput<ClientInputMessage>>,
+    /// Live tick the replay started on and the first recorded tick.
+    origin: Option<(u64, u64)>,
+}
+
+impl InputReplay {
+    pub fn from_env() -> Self {
+        let Ok(path) = std::env::var("SIDEREAL_CLIENT_INPUT_REPLAY_PATH") else {
+            return Self::default();
+        };
+        match load_recording(Path::new(&path)) {
+            Ok(recording) => {
+                println!(
+                    "native client replaying {} input messages from {path}",
+                    recording.len()
+                );
+                Self::from_recording(recording)
+            }
+            Err(err) => {
+                eprintln!("native client cannot replay input from {path}: {err}");
+                Self::default()
+            }
+        }
+    }
+
+    pub fn from_recording(recording: InputRecording<ClientInputMessage>) -> Self {
+        Self {
+            pending: recording.frames.into(),
+            origin: None,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Messages due by live `tick`. The first call anchors the recording's
+    /// first tick to `tick`; later messages follow at their recorded offsets.
+    pub fn due(&mut self, tick: u64) -> Vec<ClientInputMessage> {
+        let Some(first) = self.pending.front() else {
+            return Vec::new();
+        };
+        let (start_tick, first_recorded_tick) = *self.origin.get_or_insert((tick, first.tick));
+        let elapsed = tick.saturating_sub(start_tick);
+        let mut due = Vec::new();
+        while self
+            .pending
+            .front()
+            .is_some_and(|frame| frame.tick - first_recorded_tick <= elapsed)
+        {
+            if let Some(frame) = self.pending.pop_front() {
+                due.push(frame.input);
+            }
+        }
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(tick: u64, thrust: f32, turn: f32, brake: bool) -> ClientInputMessage {
+        ClientInputMessage::from_axis_inputs("player:rec".to_string(), tick, thrust, turn, brake)
+    }
+
+    #[test]
+    fn recording_then_loading_preserves_the_message_sequence() {
+        let path =
+            std::env::temp_dir().join(format!("sidereal_input_{}.jsonl", uuid::Uuid::new_v4()));
+        let sent = vec![
+            message(10, 1.0, 0.0, false),
+            message(11, 1.0, 1.0, false),
+            message(11, 0.0, -1.0, false),
+            message(40, 0.0, 0.0, true),
+        ];
+        let mut recorder = InputRecorder::create(&path).expect("recording file should open");
+        for message in &sent {
+            recorder.record(message);
+        }
+        drop(recorder);
+
+        let loaded = load_recording(&path).expect("recording should load");
+        std::fs::remove_file(&path).ok();
+        let messages = loaded
+            .frames
+            .into_iter()
+            .map(|frame| frame.input)
+            .collect::<Vec<_>>();
+        assert_eq!(messages, sent);
+    }
+
+    #[test]
+    fn replay_keeps_recorded_tick_spacing() {
+        let mut recording = InputRecording::default();
+        for message in [
+            message(100, 1.0, 0.0, false),
+            message(100, 1.0, 1.0, false),
+            message(103, 0.0, 0.0, true),
+        ] {
+            recording.push(message.tick, message);
+        }
+        let mut replay = InputReplay::from_recording(recording);
+
+        let ticks =
+            |messages: Vec<ClientInputMessage>| messages.iter().map(|m| m.tick).collect::<Vec<_>>();
+        assert_eq!(ticks(replay.due(7)), vec![100, 100]);
+        assert!(replay.due(9).is_empty());
+        assert_eq!(ticks(replay.due(10)), vec![103]);
+        assert!(!replay.is_active());
+        assert!(replay.due(11).is_empty());
+    }
+}
@@ -1,11 +1,39 @@
 #[cfg(not(target_arch = "wasm32"))]
+mod asset_cache;
+#[cfg(not(target_arch = "wasm32"))]
 mod auth_ui;
+#[cfg(not(target_arch = "wasm32"))]
+mod connection_quality;
+#[cfg(not(target_arch = "wasm32"))]
+mod controlled_entity;
 
 #[cfg(not(target_arch = "wasm32"))]
 mod dialog_ui;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod input;
+#[cfg(not(target_arch = "wasm32"))]
+mod input_batching;
+#[cfg(not(target_arch = "wasm32"))]
+mod input_recording;
 #[cfg(not(target_arch = "wasm32"))]
 mod prediction;
+#[cfg(not(target_arch = "wasm32"))]
+mod reconnect;
+#[cfg(not(target_arch = "wasm32"))]
+mod removal_notice;
+#[cfg(not(target_arch = "wasm32"))]
+mod replicated_components;
+#[cfg(not(target_arch = "wasm32"))]
+mod replication_stats;
+#[cfg(not(target_arch = "wasm32"))]
+mod server_select;
+#[cfg(not(target_arch = "wasm32"))]
+mod starfield_tuning;
+#[cfg(not(target_arch = "wasm32"))]
+mod subscription;
+#[cfg(not(target_arch = "wasm32"))]
+mod targeting;
 
 #[cfg(not(target_arch = "wasm32"))]
 use avian3d::prelude::*;
@@ -19,42 +47,59 @@ use bevy::render::RenderPlugin;
 use bevy::render::render_resource::AsBindGroup;
 use bevy::render::settings::{Backends, RenderCreation, WgpuSettings};
 #[cfg(not(target_arch = "wasm32"))]
-use bevy::window::{PresentMode, Window, WindowPlugin};
-#[cfg(not(target_arch = "wasm32"))]
 use bevy::shader::ShaderRef;
 #[cfg(not(target_arch = "wasm32"))]
 use bevy::sprite_render::{AlphaMode2d, Material2d, Material2dPlugin, MeshMaterial2d};
 #[cfg(not(target_arch = "wasm32"))]
 use bevy::state::state_scoped::DespawnOnExit;
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::window::{PresentMode, Window, WindowPlugin};
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::asset_cache::{AssetCacheManifest, StreamAssetDescriptor, diff_manifests};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::connection_quality::{ConnectionQuality, LinkState, format_connection_status};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::controlled_entity::{ControlledEntityAssignment, LocalKinematics};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::prediction::{
     EntitySnapshot, RemoteEntity, SnapshotBuffer, interpolate_remote_entities,
 };
 #[cfg(not(target_arch = "wasm32"))]
+use crate::reconnect::{ReconnectOutcome, ReconnectState};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::starfield_tuning::{StarfieldTuning, StarfieldWarp};
+#[cfg(not(target_arch = "wasm32"))]
 use bevy_remote::RemotePlugin;
 #[cfg(not(target_arch = "wasm32"))]
 use bevy_remote::http::RemoteHttpPlugin;
 #[cfg(not(target_arch = "wasm32"))]
 use lightyear::prelude::client::ClientPlugins;
 #[cfg(not(target_arch = "wasm32"))]
-use lightyear::prelude::client::{Client, Connect, Connected, RawClient};
+use lightyear::prelude::client::{Client, Connect, Connected, Disconnected, RawClient};
 #[cfg(not(target_arch = "wasm32"))]
 use lightyear::prelude::{
     ChannelRegistry, LocalAddr, MessageManager, MessageReceiver, MessageSender, PeerAddr,
     Transport, UdpIo,
 };
 #[cfg(not(target_arch = "wasm32"))]
+use sidereal_core::physics::PhysicsConfig;
+#[cfg(not(target_arch = "wasm32"))]
 use sidereal_core::remote_inspect::RemoteInspectConfig;
 #[cfg(not(target_arch = "wasm32"))]
+use sidereal_core::world_seed::WorldSeed;
+#[cfg(not(target_arch = "wasm32"))]
 use sidereal_game::{
     ActionCapabilities, ActionQueue, Engine, EntityAction, EntityGuid, FlightComputer, FuelTank,
     HealthPool, MountedOn, OwnerId, PositionM, SiderealGamePlugin, VelocityMps,
 };
 #[cfg(not(target_arch = "wasm32"))]
 use sidereal_net::{
-    ClientAuthMessage, ClientInputMessage, ControlChannel, InputChannel, ReplicationStateMessage,
-    StateChannel, register_lightyear_protocol,
+    ChannelConfigs, ClientAuthMessage, ClientAuthRejectedMessage, ClientInputMessage,
+    ControlChannel, ControlledEntityMessage, EntityDetailMessage, HeadingQuantizer, InputChannel,
+    NetError, PingMessage, PongMessage, RemovalReason, ReplicationStateMessage,
+    RequestEntityDetail, StateChannel, SubscriptionFilter, TargetLockMessage,
+    register_lightyear_protocol_with_channels,
 };
 #[cfg(not(target_arch = "wasm32"))]
 use std::collections::HashMap;
@@ -83,6 +128,7 @@ enum AuthAction {
     Register,
     ForgotRequest,
     ForgotConfirm,
+    SelectServer,
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -92,12 +138,18 @@ enum FocusField {
     Password,
     ResetToken,
     NewPassword,
+    ServerGateway,
+    ServerReplication,
 }
 
 #[cfg(not(target_arch = "wasm32"))]
 #[derive(Debug, Resource)]
 struct ClientSession {
     gateway_url: String,
+    replication_udp_addr: SocketAddr,
+    transport_target_changed: bool,
+    server_gateway: String,
+    server_replication: String,
     selected_action: AuthAction,
     focus: FocusField,
     email: String,
@@ -128,12 +180,39 @@ struct StarfieldMotionState {
     drift_xy: Vec2,
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+impl StarfieldMotionState {
+    /// Starts the drift at an offset derived from the world seed, so every
+    /// player in one world sees the same sky.
+    fn seeded(world_seed: WorldSeed) -> Self {
+        let bits = world_seed.derive("starfield");
+        let unit = |value: u64| (value & 0xFF_FFFF) as f32 / (1u64 << 24) as f32;
+        Self {
+            prev_velocity_xy: Vec2::ZERO,
+            drift_xy: Vec2::new(unit(bits >> 40), unit(bits >> 16)),
+        }
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 impl Default for ClientSession {
     fn default() -> Self {
+        let gateway_url =
+            std::env::var("GATEWAY_URL").unwrap_or_else(|_| "http://127.0.0.1:8080".to_string());
+        let default_replication_addr = SocketAddr::from(([127, 0, 0, 1], 7001));
+        let replication_udp_addr = match std::env::var("REPLICATION_UDP_ADDR") {
+            Ok(raw) => raw.parse::<SocketAddr>().unwrap_or_else(|err| {
+                eprintln!("invalid REPLICATION_UDP_ADDR: {err}; using {default_replication_addr}");
+                default_replication_addr
+            }),
+            Err(_) => default_replication_addr,
+        };
         Self {
-            gateway_url: std::env::var("GATEWAY_URL")
-                .unwrap_or_else(|_| "http://127.0.0.1:8080".to_string()),
+            server_gateway: gateway_url.clone(),
+            server_replication: replication_udp_addr.to_string(),
+            gateway_url,
+            replication_udp_addr,
+            transport_target_changed: false,
             selected_action: AuthAction::Login,
             focus: FocusField::Email,
             email: "pilot@example.com".to_string(),
@@ -142,8 +221,9 @@ impl Default for ClientSession {
             new_password: "new-very-strong-password".to_string(),
             access_token: None,
             refresh_token: None,
-            status: "Ready. F1 Login, F2 Register, F3 Forgot Request, F4 Forgot Confirm."
-                .to_string(),
+            status:
+                "Ready. F1 Login, F2 Register, F3 Forgot Request, F4 Forgot Confirm, F5 Server."
+                    .to_string(),
             world_snapshot: None,
             ui_dirty: true,
         }
@@ -199,13 +279,6 @@ struct ForgotConfirmResponse {
     accepted: bool,
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-struct StreamAssetDescriptor {
-    asset_id: String,
-    relative_cache_path: String,
-}
-
 #[cfg(not(target_arch = "wasm32"))]
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 struct WorldMeResponse {
@@ -221,6 +294,10 @@ struct WorldMeResponse {
     engine_ramp_to_max_s: f32,
     model_asset_id: String,
     starfield_shader_asset_id: String,
+    /// Shared with the server for deterministic procedural content; absent
+    /// from older gateways.
+    #[serde(default)]
+    world_seed: u64,
     assets: Vec<StreamAssetDescriptor>,
 }
 
@@ -257,6 +334,25 @@ struct RemoteShip {
     entity_id: String,
 }
 
+/// Heading step the server quantizes to; must match `REPLICATION_HEADING_STEPS`.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Resource, Debug, Clone, Copy)]
+struct WireHeading(HeadingQuantizer);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl WireHeading {
+    /// Reads `SIDEREAL_CLIENT_HEADING_STEPS` (`0` = server sends full precision).
+    fn from_env() -> Self {
+        Self(
+            std::env::var("SIDEREAL_CLIENT_HEADING_STEPS")
+                .ok()
+                .and_then(|v| v.parse::<u32>().ok())
+                .map(HeadingQuantizer::new)
+                .unwrap_or_default(),
+        )
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[derive(Resource, Default)]
 struct RemoteShipRegistry {
@@ -390,6 +486,20 @@ fn main() {
             std::process::exit(2);
         }
     };
+    let physics_cfg = match PhysicsConfig::from_env() {
+        Ok(cfg) => cfg,
+        Err(err) => {
+            eprintln!("invalid CLIENT physics config: {err}");
+            std::process::exit(2);
+        }
+    };
+    let channel_cfg = match ChannelConfigs::from_env() {
+        Ok(cfg) => cfg,
+        Err(err) => {
+            eprintln!("invalid CLIENT channel config: {err}");
+            std::process::exit(2);
+        }
+    };
 
     let asset_root = std::env::var("SIDEREAL_ASSET_ROOT").unwrap_or_else(|_| ".".to_string());
 
@@ -429,25 +539,46 @@ fn main() {
         }
     }
 
-    app.add_plugins(PhysicsPlugins::default().with_length_unit(1.0));
-    app.insert_resource(Gravity(Vec3::ZERO));
+    app.add_plugins(PhysicsPlugins::default().with_length_unit(physics_cfg.length_unit));
+    app.insert_resource(SubstepCount(physics_cfg.substeps));
+    app.insert_resource(Gravity(Vec3::from_array(physics_cfg.gravity_mps2)));
     app.add_plugins(SiderealGamePlugin);
     app.add_plugins(ClientPlugins::default());
-    register_lightyear_protocol(&mut app);
+    register_lightyear_protocol_with_channels(&mut app, &channel_cfg);
     configure_remote(&mut app, &remote_cfg);
     app.insert_resource(AssetRootPath(asset_root));
     app.insert_resource(ClientSession::default());
     app.insert_resource(ClientNetworkTick::default());
     app.insert_resource(ClientAuthSyncState::default());
     app.insert_resource(StarfieldMotionState::default());
+    app.insert_resource(StarfieldTuning::from_env());
+    app.insert_resource(WireHeading::from_env());
     app.insert_resource(RemoteShipRegistry::default());
+    app.insert_resource(ControlledEntityAssignment::default());
+    app.insert_resource(ReconnectState::from_env());
+    app.insert_resource(ConnectionQuality::default());
+    app.insert_resource(input_batching::InputBatcher::default());
+    app.insert_resource(replication_stats::ReplicationStats::default());
+    app.insert_resource(targeting::TargetLock::default());
+    app.insert_resource(targeting::TargetDetail::default());
+    app.insert_resource(subscription::ClientSubscription::from_env());
+    app.insert_resource(replicated_components::ReplicatedComponentCache::default());
     app.add_observer(log_native_client_connected);
+    app.add_observer(track_native_client_disconnected);
     app.add_systems(Startup, start_lightyear_client_transport);
+    app.add_systems(Update, retry_native_client_connect);
 
-    // Input-to-action runs in FixedUpdate before game systems
+    // Input is sampled once per frame; input-to-action runs in FixedUpdate before game systems
+    app.insert_resource(input::ClientInputState::default());
+    app.insert_resource(input_recording::InputRecorder::from_env());
+    app.insert_resource(input_recording::InputReplay::from_env());
+    app.add_systems(
+        PreUpdate,
+        input::capture_input_snapshot.after(bevy::input::InputSystems),
+    );
     app.add_systems(
         FixedUpdate,
-        client_input_to_actions
+        input::client_input_to_actions
             .before(sidereal_game::validate_action_capabilities)
             .run_if(in_state(ClientAppState::InWorld)),
     );
@@ -457,9 +588,15 @@ fn main() {
             Update,
             (
                 ensure_client_transport_channels,
-                send_lightyear_auth_messages,
+                apply_reconnect_outcome,
+                send_lightyear_auth_messages.after(apply_reconnect_outcome),
                 send_lightyear_input_messages,
-                receive_lightyear_replication_messages,
+                exchange_connection_pings,
+                send_target_lock_messages.after(send_lightyear_auth_messages),
+                receive_auth_rejection_messages,
+                receive_controlled_entity_messages.after(apply_reconnect_outcome),
+                send_subscription_filter.after(receive_controlled_entity_messages),
+                receive_lightyear_replication_messages.after(receive_controlled_entity_messages),
             ),
         );
         app.add_systems(Startup, || {
@@ -469,15 +606,22 @@ fn main() {
         insert_embedded_fonts(&mut app);
         app.init_state::<ClientAppState>();
         auth_ui::register_auth_ui(&mut app);
+        app.insert_resource(server_select::RecentServers::load_from_env());
+        app.add_systems(Update, server_select::restart_transport_on_server_change);
         dialog_ui::register_dialog_ui(&mut app);
         app.add_systems(OnEnter(ClientAppState::InWorld), spawn_world_scene);
         app.add_systems(
             Update,
             (
                 ensure_client_transport_channels,
-                send_lightyear_auth_messages,
+                apply_reconnect_outcome,
+                send_lightyear_auth_messages.after(apply_reconnect_outcome),
                 send_lightyear_input_messages,
-                receive_lightyear_replication_messages,
+                exchange_connection_pings,
+                send_target_lock_messages.after(send_lightyear_auth_messages),
+                receive_controlled_entity_messages.after(apply_reconnect_outcome),
+                send_subscription_filter.after(receive_controlled_entity_messages),
+                receive_lightyear_replication_messages.after(receive_controlled_entity_messages),
             ),
         );
         app.add_systems(
@@ -487,6 +631,13 @@ fn main() {
                 interpolate_remote_entities.after(receive_lightyear_replication_messages),
                 sync_backdrop_fullscreen_system,
                 update_topdown_camera_system,
+                targeting::cycle_target_lock
+                    .after(receive_lightyear_replication_messages)
+                    .before(update_hud_system),
+                receive_entity_detail_messages
+                    .after(targeting::cycle_target_lock)
+                    .before(update_hud_system),
+                targeting::draw_target_lock_highlight.after(interpolate_remote_entities),
                 update_hud_system,
                 logout_to_auth_system,
                 update_starfield_material_system,
@@ -642,7 +793,10 @@ fn configure_remote(app: &mut App, cfg: &RemoteInspectConfig) {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-fn start_lightyear_client_transport(mut commands: Commands<'_, '_>) {
+fn start_lightyear_client_transport(
+    mut commands: Commands<'_, '_>,
+    session: Res<'_, ClientSession>,
+) {
     let local_addr = std::env::var("CLIENT_UDP_BIND")
         .unwrap_or_else(|_| "127.0.0.1:7003".to_string())
         .parse::<SocketAddr>();
@@ -653,16 +807,7 @@ fn start_lightyear_client_transport(mut commands: Commands<'_, '_>) {
             return;
         }
     };
-    let remote_addr = std::env::var("REPLICATION_UDP_ADDR")
-        .unwrap_or_else(|_| "127.0.0.1:7001".to_string())
-        .parse::<SocketAddr>();
-    let remote_addr = match remote_addr {
-        Ok(v) => v,
-        Err(err) => {
-            eprintln!("invalid REPLICATION_UDP_ADDR: {err}");
-            return;
-        }
-    };
+    let remote_addr = session.replication_udp_addr;
 
     let client = commands
         .spawn((
@@ -762,6 +907,8 @@ fn submit_auth_request(
             session.status = "Password reset confirmed. Switch to Login (F1).".to_string();
             Ok((None, None::<String>))
         })(),
+        // Server selection never talks to the gateway; the auth UI applies it directly.
+        AuthAction::SelectServer => Ok((None, None::<String>)),
     };
 
     match result {
@@ -825,7 +972,20 @@ fn fetch_world_and_stream_assets(
         .json::<WorldMeResponse>()
         .map_err(|err| err.to_string())?;
 
-    for asset in &world.assets {
+    let cache_dir = asset_cache::cache_dir(asset_root);
+    let cached = AssetCacheManifest::load(&cache_dir);
+    let server = AssetCacheManifest::from_descriptors(&world.assets);
+    let diff = diff_manifests(&cached, &server);
+    let targets = world
+        .assets
+        .iter()
+        .map(|asset| asset_cache::cache_target(&cache_dir, &asset.relative_cache_path))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (asset, target) in world.assets.iter().zip(targets) {
+        if !diff.to_fetch.contains(&asset.asset_id) && target.exists() {
+            continue;
+        }
         let bytes = client
             .get(format!("{gateway_url}/assets/stream/{}", asset.asset_id))
             .bearer_auth(access_token)
@@ -836,15 +996,28 @@ fn fetch_world_and_stream_assets(
             .bytes()
             .map_err(|err| err.to_string())?;
 
-        let target = std::path::PathBuf::from(asset_root)
-            .join("data/cache_stream")
-            .join(&asset.relative_cache_path);
         if let Some(parent) = target.parent() {
             std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
         }
         std::fs::write(&target, &bytes).map_err(|err| err.to_string())?;
     }
 
+    for relative_path in &diff.to_delete {
+        let orphan = match asset_cache::cache_target(&cache_dir, relative_path) {
+            Ok(orphan) => orphan,
+            Err(err) => {
+                eprintln!("skipping cached asset prune: {err}");
+                continue;
+            }
+        };
+        if let Err(err) = std::fs::remove_file(&orphan)
+            && err.kind() != std::io::ErrorKind::NotFound
+        {
+            eprintln!("failed to prune cached asset {}: {err}", orphan.display());
+        }
+    }
+    server.save(&cache_dir)?;
+
     Ok(world)
 }
 
@@ -860,11 +1033,13 @@ fn spawn_world_scene(
     mut session: ResMut<'_, ClientSession>,
     mut shaders: ResMut<'_, Assets<bevy::shader::Shader>>,
     asset_root: Res<'_, AssetRootPath>,
+    mut starfield_motion: ResMut<'_, StarfieldMotionState>,
 ) {
     let Some(world) = session.world_snapshot.clone() else {
         session.status = "No world snapshot available for scene spawn.".to_string();
         return;
     };
+    *starfield_motion = StarfieldMotionState::seeded(WorldSeed(world.world_seed));
 
     reload_streamed_shaders(&asset_server, &mut shaders, &asset_root.0);
 
@@ -1063,36 +1238,6 @@ fn spawn_world_scene(
     });
 }
 
-/// Converts keyboard input into EntityActions for the local controlled ship.
-/// Runs in FixedUpdate before SiderealGamePlugin's action processing systems.
-#[cfg(not(target_arch = "wasm32"))]
-fn client_input_to_actions(
-    input: Res<'_, ButtonInput<KeyCode>>,
-    mut ship_query: Query<'_, '_, &mut ActionQueue, With<ControlledShip>>,
-) {
-    let Ok(mut queue) = ship_query.single_mut() else {
-        return;
-    };
-
-    if input.pressed(KeyCode::Space) {
-        queue.push(EntityAction::Brake);
-    } else if input.pressed(KeyCode::KeyW) {
-        queue.push(EntityAction::ThrustForward);
-    } else if input.pressed(KeyCode::KeyS) {
-        queue.push(EntityAction::ThrustReverse);
-    } else {
-        queue.push(EntityAction::ThrustNeutral);
-    }
-
-    if input.pressed(KeyCode::KeyA) {
-        queue.push(EntityAction::YawLeft);
-    } else if input.pressed(KeyCode::KeyD) {
-        queue.push(EntityAction::YawRight);
-    } else {
-        queue.push(EntityAction::YawNeutral);
-    }
-}
-
 /// Syncs Avian physics state to Transform and gameplay components for the controlled ship
 #[cfg(not(target_arch = "wasm32"))]
 #[allow(clippy::type_complexity)]
@@ -1228,8 +1373,9 @@ fn enforce_frame_rate_cap_system(mut frame_cap: ResMut<'_, FrameRateCap>) {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
+#[allow(clippy::too_many_arguments)]
 fn send_lightyear_input_messages(
-    input: Option<Res<'_, ButtonInput<KeyCode>>>,
+    mut input_state: ResMut<'_, input::ClientInputState>,
     app_state: Option<Res<'_, State<ClientAppState>>>,
     session: Res<'_, ClientSession>,
     mut tick: ResMut<'_, ClientNetworkTick>,
@@ -1239,6 +1385,11 @@ fn send_lightyear_input_messages(
         &mut MessageSender<ClientInputMessage>,
         (With<Client>, With<Connected>),
     >,
+    mut recorder: ResMut<'_, input_recording::InputRecorder>,
+    mut replay: ResMut<'_, input_recording::InputReplay>,
+    mut batcher: ResMut<'_, input_batching::InputBatcher>,
+    replication_stats: Res<'_, replication_stats::ReplicationStats>,
+    time: Res<'_, Time<Real>>,
 ) {
     tick.0 = tick.0.saturating_add(1);
     if senders.is_empty() {
@@ -1252,51 +1403,44 @@ fn send_lightyear_input_messages(
         .as_ref()
         .is_some_and(|state| **state == ClientAppState::InWorld);
 
-    let (player_entity_id, thrust, turn, brake) = if in_world_state {
+    let message = if in_world_state {
         let Some(world) = &session.world_snapshot else {
             return;
         };
-        let brake = input
-            .as_ref()
-            .is_some_and(|keys| keys.pressed(KeyCode::Space));
-        let thrust = if brake {
-            0.0
-        } else if input
-            .as_ref()
-            .is_some_and(|keys| keys.pressed(KeyCode::KeyW))
-        {
-            1.0
-        } else if input
-            .as_ref()
-            .is_some_and(|keys| keys.pressed(KeyCode::KeyS))
-        {
-            -0.7
-        } else {
-            0.0
-        };
-        let turn = if input
-            .as_ref()
-            .is_some_and(|keys| keys.pressed(KeyCode::KeyA))
-        {
-            1.0
-        } else if input
-            .as_ref()
-            .is_some_and(|keys| keys.pressed(KeyCode::KeyD))
-        {
-            -1.0
-        } else {
-            0.0
+        if replay.is_active() {
+            // Recorded messages are re-stamped so the server accepts them for
+            // this session.
+            for mut message in replay.due(tick.0) {
+                message.player_entity_id = world.player_entity_id.clone();
+                message.tick = tick.0;
+                message.seen_state_tick = replication_stats.last_tick;
+                for mut sender in &mut senders {
+                    sender.send::<InputChannel>(message.clone());
+                }
+            }
+            return;
+        }
+        let sim_tick = input_batching::sim_tick_at(time.elapsed_secs_f64());
+        let Some((finished_tick, snapshot)) = batcher.push_frame(sim_tick, input_state.snapshot)
+        else {
+            return;
         };
-        (world.player_entity_id.clone(), thrust, turn, brake)
+        if !input_state.network_gate.should_send(snapshot) {
+            return;
+        }
+        let mut message = batcher.message(world.player_entity_id.clone(), finished_tick, snapshot);
+        message.seen_state_tick = replication_stats.last_tick;
+        message
     } else {
         if !tick.0.is_multiple_of(30) {
             return;
         }
-        ("transport:probe".to_string(), 0.0, 0.0, false)
+        ClientInputMessage::from_axis_inputs("transport:probe".to_string(), tick.0, 0.0, 0.0, false)
     };
 
-    let message =
-        ClientInputMessage::from_axis_inputs(player_entity_id, tick.0, thrust, turn, brake);
+    if in_world_state {
+        recorder.record(&message);
+    }
     for mut sender in &mut senders {
         sender.send::<InputChannel>(message.clone());
     }
@@ -1335,6 +1479,7 @@ fn send_lightyear_auth_messages(
         let auth_message = ClientAuthMessage {
             player_entity_id: world.player_entity_id.clone(),
             access_token: access_token.clone(),
+            protocol_version: sidereal_core::PROTOCOL_VERSION,
         };
         sender.send::<ControlChannel>(auth_message);
         auth_state.sent_for_client_entities.insert(client_entity);
@@ -1345,9 +1490,282 @@ fn send_lightyear_auth_messages(
 fn log_native_client_connected(
     trigger: On<Add, Connected>,
     clients: Query<'_, '_, (), With<Client>>,
+    time: Res<'_, Time<Real>>,
+    mut reconnect: ResMut<'_, ReconnectState>,
+    mut quality: ResMut<'_, ConnectionQuality>,
+    mut replication_stats: ResMut<'_, replication_stats::ReplicationStats>,
+    mut batcher: ResMut<'_, input_batching::InputBatcher>,
 ) {
     if clients.get(trigger.entity).is_ok() {
         println!("native client lightyear transport connected");
+        quality.reset();
+        replication_stats.reset();
+        // The server applies each connection's ticks afresh.
+        batcher.reset();
+        if let Some(outcome) = reconnect.on_connected(time.elapsed_secs_f64()) {
+            println!("native client reconnected; {outcome:?} world state");
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn track_native_client_disconnected(
+    trigger: On<Remove, Connected>,
+    clients: Query<'_, '_, (), With<Client>>,
+    time: Res<'_, Time<Real>>,
+    mut reconnect: ResMut<'_, ReconnectState>,
+) {
+    if clients.get(trigger.entity).is_ok() {
+        println!("native client lightyear transport disconnected");
+        reconnect.on_disconnected(time.elapsed_secs_f64());
+    }
+}
+
+/// Re-triggers `Connect` while the transport is down, as often as
+/// `ReconnectState::retry_due` allows.
+#[cfg(not(target_arch = "wasm32"))]
+fn retry_native_client_connect(
+    mut commands: Commands<'_, '_>,
+    clients: Query<'_, '_, Entity, (With<RawClient>, With<Disconnected>)>,
+    time: Res<'_, Time<Real>>,
+    mut reconnect: ResMut<'_, ReconnectState>,
+) {
+    let Some(client) = clients.iter().next() else {
+        return;
+    };
+    if reconnect.retry_due(time.elapsed_secs_f64()) {
+        commands.trigger(Connect { entity: client });
+        println!("native client lightyear UDP retrying connection");
+    }
+}
+
+/// Sends a ping every `PING_INTERVAL_S` and times the pongs that come back.
+#[cfg(not(target_arch = "wasm32"))]
+fn exchange_connection_pings(
+    time: Res<'_, Time<Real>>,
+    mut quality: ResMut<'_, ConnectionQuality>,
+    mut clients: Query<
+        '_,
+        '_,
+        (
+            &mut MessageSender<PingMessage>,
+            &mut MessageReceiver<PongMessage>,
+        ),
+        (With<Client>, With<Connected>),
+    >,
+) {
+    let now_s = time.elapsed_secs_f64();
+    for (mut sender, mut receiver) in &mut clients {
+        for pong in receiver.receive() {
+            quality.on_pong(pong.nonce, now_s);
+        }
+        if let Some(nonce) = quality.ping_due(now_s) {
+            sender.send::<InputChannel>(PingMessage { nonce });
+        }
+    }
+}
+
+/// Tells replication about each target lock change on the control channel.
+#[cfg(not(target_arch = "wasm32"))]
+fn send_target_lock_messages(
+    session: Res<'_, ClientSession>,
+    mut target_lock: ResMut<'_, targeting::TargetLock>,
+    mut senders: Query<
+        '_,
+        '_,
+        (
+            &mut MessageSender<TargetLockMessage>,
+            &mut MessageSender<RequestEntityDetail>,
+        ),
+        (With<Client>, With<Connected>),
+    >,
+) {
+    if senders.is_empty() {
+        return;
+    }
+    let Some(world) = session.world_snapshot.as_ref() else {
+        return;
+    };
+    let Some(target_entity_id) = target_lock.take_unsent() else {
+        return;
+    };
+    let detail_request = target_entity_id
+        .clone()
+        .map(|entity_id| RequestEntityDetail { entity_id });
+    let message = TargetLockMessage {
+        player_entity_id: world.player_entity_id.clone(),
+        target_entity_id,
+    };
+    for (mut lock_sender, mut detail_sender) in &mut senders {
+        lock_sender.send::<ControlChannel>(message.clone());
+        if let Some(request) = &detail_request {
+            detail_sender.send::<ControlChannel>(request.clone());
+        }
+    }
+}
+
+/// Keeps replication's answer to the detail request for the locked target.
+#[cfg(not(target_arch = "wasm32"))]
+fn receive_entity_detail_messages(
+    mut receivers: Query<
+        '_,
+        '_,
+        &mut MessageReceiver<EntityDetailMessage>,
+        (With<Client>, With<Connected>),
+    >,
+    target_lock: Res<'_, targeting::TargetLock>,
+    mut target_detail: ResMut<'_, targeting::TargetDetail>,
+) {
+    for mut receiver in &mut receivers {
+        for message in receiver.receive() {
+            let decoded = match message.decode_world() {
+                Ok(decoded) => decoded,
+                Err(err) => {
+                    eprintln!(
+                        "WARNING native client dropped entity detail for {}: {err}",
+                        message.entity_id
+                    );
+                    continue;
+                }
+            };
+            let detail = decoded
+                .world
+                .updates
+                .into_iter()
+                .find(|update| update.entity_id == message.entity_id);
+            target_detail.apply(target_lock.entity_id.as_deref(), &message.entity_id, detail);
+        }
+    }
+}
+
+/// Tells the player why replication refused this client's auth; a protocol
+/// mismatch means this build is out of date and cannot connect at all.
+#[cfg(not(target_arch = "wasm32"))]
+fn receive_auth_rejection_messages(
+    mut receivers: Query<
+        '_,
+        '_,
+        &mut MessageReceiver<ClientAuthRejectedMessage>,
+        (With<Client>, With<Connected>),
+    >,
+    mut session: ResMut<'_, ClientSession>,
+    mut dialog_queue: ResMut<'_, dialog_ui::DialogQueue>,
+) {
+    for mut receiver in &mut receivers {
+        for message in receiver.receive() {
+            eprintln!(
+                "native client auth rejected by replication: {}",
+                message.error
+            );
+            match message.error {
+                NetError::ProtocolMismatch { expected, got } => {
+                    session.status = "Update required to connect.".to_string();
+                    dialog_queue.push_error(
+                        "Update Required",
+                        format!(
+                            "This client speaks protocol version {got}, but the server requires \
+                             version {expected}.\n\nUpdate the client to connect."
+                        ),
+                    );
+                }
+                NetError::Decode(err) => {
+                    session.status = "Server rejected this client.".to_string();
+                    dialog_queue.push_error(
+                        "Connection Rejected",
+                        format!("The server rejected this client's login.\n\nDetails: {err}"),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Sends the configured `SubscriptionFilter` once per bound connection.
+#[cfg(not(target_arch = "wasm32"))]
+fn send_subscription_filter(
+    mut subscription: ResMut<'_, subscription::ClientSubscription>,
+    mut senders: Query<
+        '_,
+        '_,
+        (Entity, &mut MessageSender<SubscriptionFilter>),
+        (With<Client>, With<Connected>),
+    >,
+) {
+    subscription.retain_connected(|entity| senders.contains(entity));
+    for (client_entity, mut sender) in &mut senders {
+        if let Some(filter) = subscription.take_unsent(client_entity) {
+            sender.send::<ControlChannel>(filter);
+        }
+    }
+}
+
+/// Applies a reconnect decision. Either way the server needs the auth message
+/// again to rebind this client; a rebuild also drops everything the stream
+/// will resend, while a preserve leaves the scene for the next snapshot to
+/// re-sync.
+#[cfg(not(target_arch = "wasm32"))]
+fn apply_reconnect_outcome(
+    mut commands: Commands<'_, '_>,
+    mut reconnect: ResMut<'_, ReconnectState>,
+    mut auth_state: ResMut<'_, ClientAuthSyncState>,
+    mut remote_registry: ResMut<'_, RemoteShipRegistry>,
+    mut component_cache: ResMut<'_, replicated_components::ReplicatedComponentCache>,
+    mut controlled_assignment: ResMut<'_, ControlledEntityAssignment>,
+    mut target_lock: ResMut<'_, targeting::TargetLock>,
+) {
+    let Some(outcome) = reconnect.take_outcome() else {
+        return;
+    };
+    auth_state.sent_for_client_entities.clear();
+    // The server forgot the lock with the old connection.
+    target_lock.mark_unsent();
+    if outcome == ReconnectOutcome::Rebuild {
+        for (_, entity) in remote_registry.by_entity_id.drain() {
+            commands.entity(entity).despawn();
+        }
+        component_cache.clear();
+        controlled_assignment.reset();
+    }
+}
+
+/// Applies the server's controlled-entity assignment: the local ship takes the
+/// assigned id, and a remote ship already spawned under that id is despawned so
+/// the player's ship is not shown twice.
+#[cfg(not(target_arch = "wasm32"))]
+fn receive_controlled_entity_messages(
+    mut commands: Commands<'_, '_>,
+    mut receivers: Query<
+        '_,
+        '_,
+        (Entity, &mut MessageReceiver<ControlledEntityMessage>),
+        (With<Client>, With<Connected>),
+    >,
+    mut assignment: ResMut<'_, ControlledEntityAssignment>,
+    mut subscription: ResMut<'_, subscription::ClientSubscription>,
+    mut controlled_query: Query<'_, '_, &mut ControlledShip>,
+    mut remote_registry: ResMut<'_, RemoteShipRegistry>,
+) {
+    for (client_entity, mut receiver) in &mut receivers {
+        for message in receiver.receive() {
+            // Only a bound client is told what it controls.
+            subscription.mark_bound(client_entity);
+            if !assignment.acknowledge(message.controlled_entity_id.clone()) {
+                continue;
+            }
+            println!(
+                "native client controlled entity acknowledged player_entity_id={} controlled_entity_id={:?}",
+                message.player_entity_id, message.controlled_entity_id
+            );
+            let Some(entity_id) = message.controlled_entity_id else {
+                continue;
+            };
+            for mut ship in &mut controlled_query {
+                ship.entity_id = entity_id.clone();
+            }
+            if let Some(duplicate) = remote_registry.by_entity_id.remove(&entity_id) {
+                commands.entity(duplicate).despawn();
+            }
+        }
     }
 }
 
@@ -1378,15 +1796,26 @@ fn receive_lightyear_replication_messages(
         ),
     >,
     mut remote_registry: ResMut<'_, RemoteShipRegistry>,
+    controlled_assignment: Res<'_, ControlledEntityAssignment>,
+    wire_heading: Res<'_, WireHeading>,
     mut remote_query: Query<'_, '_, &mut SnapshotBuffer, With<RemoteShip>>,
+    mut component_cache: ResMut<'_, replicated_components::ReplicatedComponentCache>,
+    mut reconnect: ResMut<'_, ReconnectState>,
+    mut quality: ResMut<'_, ConnectionQuality>,
+    mut replication_stats: ResMut<'_, replication_stats::ReplicationStats>,
     time: Res<'_, Time>,
     mut meshes: ResMut<'_, Assets<Mesh>>,
     mut materials: ResMut<'_, Assets<StandardMaterial>>,
 ) {
     for mut receiver in &mut receivers {
         for message in receiver.receive() {
-            let world = match message.decode_world() {
-                Ok(w) => w,
+            quality.on_snapshot(message.seq);
+            if !replication_stats.accept(message.seq) {
+                continue;
+            }
+            replication_stats.last_tick = message.tick;
+            let decoded = match message.decode_world() {
+                Ok(decoded) => decoded,
                 Err(err) => {
                     let error_msg = format!(
                         "Failed to decode replication state at tick {}.\n\n\
@@ -1405,14 +1834,68 @@ fn receive_lightyear_replication_messages(
                     continue;
                 }
             };
+            for skipped in &decoded.skipped {
+                eprintln!(
+                    "WARNING native client skipped malformed replicated entity tick={} index={} entity_id={} error={}",
+                    message.tick,
+                    skipped.index,
+                    skipped.entity_id.as_deref().unwrap_or("<none>"),
+                    skipped.error
+                );
+            }
+            let world = decoded.world;
+            // The server hashed its state for `message.tick`; compare the
+            // client's own, i.e. the controlled ship as predicted before this
+            // message corrects it. A world missing skipped entities cannot
+            // hash like the server's.
+            if decoded.skipped.is_empty() {
+                let local_ship = controlled_query
+                    .iter()
+                    .next()
+                    .map(|(ship, pos, vel, rot, _)| {
+                        (
+                            ship.entity_id.clone(),
+                            LocalKinematics {
+                                position_m: pos.0,
+                                velocity_mps: vel.0,
+                                heading_rad: -rot.0.to_euler(EulerRot::ZYX).0,
+                            },
+                        )
+                    });
+                let local_world = match &local_ship {
+                    Some((ship_entity_id, local)) => {
+                        controlled_assignment.local_view(&world, Some(ship_entity_id), *local)
+                    }
+                    None => world.clone(),
+                };
+                if !message.state_hash_matches(&local_world, &wire_heading.0) {
+                    eprintln!(
+                        "WARNING native client state hash mismatch tick={} server={:#018x} client={:#018x}",
+                        message.tick,
+                        message.state_hash,
+                        local_world.state_hash_with(&wire_heading.0)
+                    );
+                }
+            }
 
             let dt = time.delta_secs();
+            // The first snapshot after a reconnect replaces whatever the
+            // client extrapolated through the outage instead of blending.
+            let resync = reconnect.take_resync();
 
             for update in &world.updates {
+                component_cache.apply_update(update);
                 if update.removed {
                     if let Some(entity) = remote_registry.by_entity_id.remove(&update.entity_id) {
                         commands.entity(entity).despawn();
                     }
+                    if update.removal_reason == Some(RemovalReason::Destroyed) {
+                        println!("native client saw {} destroyed", update.entity_id);
+                    }
+                    continue;
+                }
+                // Removal-only updates carry no spatial state to apply.
+                if update.is_component_removal() {
                     continue;
                 }
 
@@ -1425,16 +1908,27 @@ fn receive_lightyear_replication_messages(
                     .unwrap_or(0.0) as f32;
 
                 // Check if this is our controlled ship
-                let is_controlled = controlled_query
+                let local_ship_entity_id = controlled_query
                     .iter()
-                    .any(|(ship, ..)| ship.entity_id == update.entity_id);
+                    .next()
+                    .map(|(ship, ..)| ship.entity_id.as_str());
+                let is_controlled =
+                    controlled_assignment.is_controlled(&update.entity_id, local_ship_entity_id);
 
                 if is_controlled {
                     // Reconciliation: smooth-correct toward server state
                     if let Ok((_, mut pos, mut vel, mut rot, mut hp)) =
                         controlled_query.single_mut()
                     {
-                        if let Some(server_pos) = position {
+                        if resync {
+                            if let Some(server_pos) = position {
+                                pos.0 = server_pos;
+                            }
+                            if let Some(server_vel) = velocity {
+                                vel.0 = server_vel;
+                            }
+                            rot.0 = Quat::from_rotation_z(-heading);
+                        } else if let Some(server_pos) = position {
                             let error = (server_pos - pos.0).length();
                             if error > HARD_SNAP_THRESHOLD_M {
                                 pos.0 = server_pos;
@@ -1449,7 +1943,10 @@ fn receive_lightyear_replication_messages(
                         }
                         let server_rot = Quat::from_rotation_z(-heading);
                         let angle_diff = rot.0.angle_between(server_rot);
-                        if angle_diff > 0.01 {
+                        // Less than one wire step apart is quantization, not a turn.
+                        let local_heading = -rot.0.to_euler(EulerRot::ZYX).0;
+                        if angle_diff > 0.01 && !wire_heading.0.within_step(local_heading, heading)
+                        {
                             let blend = (SMOOTH_CORRECTION_RATE * dt).min(1.0);
                             rot.0 = rot.0.slerp(server_rot, blend);
                         }
@@ -1478,6 +1975,9 @@ fn receive_lightyear_replication_messages(
                     if let Some(entity) = remote_registry.by_entity_id.get(&update.entity_id) {
                         // Update existing remote ship snapshot buffer
                         if let Ok(mut buffer) = remote_query.get_mut(*entity) {
+                            if resync {
+                                buffer.snapshots.clear();
+                            }
                             buffer.push(snapshot);
                         }
                     } else {
@@ -1519,6 +2019,11 @@ fn receive_lightyear_replication_messages(
                 }
             }
 
+            if let Some(notice) = removal_notice::session_notice(&world.updates) {
+                session.status = notice.status.to_string();
+                dialog_queue.push_warning(notice.title, notice.message);
+                continue;
+            }
             session.status = format!(
                 "Replication stream active. tick={} updates={}",
                 message.tick,
@@ -1557,10 +2062,14 @@ fn ensure_client_transport_channels(
         if !transport.has_receiver::<StateChannel>() {
             transport.add_receiver_from_registry::<StateChannel>(&registry);
         }
+        if !transport.has_receiver::<ControlChannel>() {
+            transport.add_receiver_from_registry::<ControlChannel>(&registry);
+        }
     }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
+#[allow(clippy::too_many_arguments)]
 fn update_hud_system(
     ship_query: Query<
         '_,
@@ -1569,6 +2078,13 @@ fn update_hud_system(
         With<ControlledShip>,
     >,
     mut hud_query: Query<'_, '_, &mut Text, With<HudText>>,
+    connected_clients: Query<'_, '_, (), (With<Client>, With<Connected>)>,
+    quality: Res<'_, ConnectionQuality>,
+    replication_stats: Res<'_, replication_stats::ReplicationStats>,
+    target_lock: Res<'_, targeting::TargetLock>,
+    target_detail: Res<'_, targeting::TargetDetail>,
+    remote_registry: Res<'_, RemoteShipRegistry>,
+    remote_transforms: Query<'_, '_, &Transform, With<RemoteShip>>,
 ) {
     let Ok((transform, velocity, health, fc)) = ship_query.single() else {
         return;
@@ -1580,8 +2096,33 @@ fn update_hud_system(
     let pos = transform.translation;
     let vel = velocity.0;
     let heading_rad = transform.rotation.to_euler(EulerRot::ZYX).0;
+    let (rtt_ms, loss) = (quality.rtt_ms(), quality.loss());
+    let link_state = LinkState::classify(!connected_clients.is_empty(), rtt_ms, loss);
+    let network_status = format!(
+        "{} | {}",
+        format_connection_status(link_state, rtt_ms, loss),
+        replication_stats::format_replication_stats(&replication_stats)
+    );
+    let target_status = match target_lock.entity_id.as_deref() {
+        Some(target) => {
+            let range = remote_registry
+                .by_entity_id
+                .get(target)
+                .and_then(|entity| remote_transforms.get(*entity).ok())
+                .map(|target_transform| target_transform.translation.distance(pos));
+            let mut status = match range {
+                Some(range) => format!("Target: {target} | range {range:.0} m"),
+                None => format!("Target: {target}"),
+            };
+            if let Some(modules) = target_detail.loadout_len() {
+                status.push_str(&format!(" | loadout {modules} modules"));
+            }
+            status
+        }
+        None => "Target: none".to_string(),
+    };
     let content = format!(
-        "SIDEREAL FLIGHT\nCoords: [{:.2}, {:.2}, {:.2}]\nVelocity m/s: [{:.2}, {:.2}, {:.2}] | speed {:.2}\nHeading(rad): {:.2} | throttle: {:.2}\nHealth: {:.1}/{:.1}\nControls: W/S thrust, A/D turn, SPACE brake, ESC logout",
+        "SIDEREAL FLIGHT\nCoords: [{:.2}, {:.2}, {:.2}]\nVelocity m/s: [{:.2}, {:.2}, {:.2}] | speed {:.2}\nHeading(rad): {:.2} | throttle: {:.2}\nHealth: {:.1}/{:.1}\n{}\n{}\nControls: W/S thrust, A/D turn, SPACE brake, TAB/SHIFT+TAB target, ESC logout",
         pos.x,
         pos.y,
         pos.z,
@@ -1592,18 +2133,25 @@ fn update_hud_system(
         heading_rad,
         fc.throttle,
         health.current,
-        health.maximum
+        health.maximum,
+        target_status,
+        network_status
     );
     content.clone_into(&mut **text);
 }
 
 #[cfg(not(target_arch = "wasm32"))]
+#[allow(clippy::too_many_arguments)]
 fn logout_to_auth_system(
     input: Res<'_, ButtonInput<KeyCode>>,
     mut next_state: ResMut<'_, NextState<ClientAppState>>,
     mut session: ResMut<'_, ClientSession>,
     mut remote_registry: ResMut<'_, RemoteShipRegistry>,
+    mut component_cache: ResMut<'_, replicated_components::ReplicatedComponentCache>,
+    mut input_state: ResMut<'_, input::ClientInputState>,
     mut auth_state: ResMut<'_, ClientAuthSyncState>,
+    mut controlled_assignment: ResMut<'_, ControlledEntityAssignment>,
+    mut target_lock: ResMut<'_, targeting::TargetLock>,
 ) {
     if !input.just_pressed(KeyCode::Escape) {
         return;
@@ -1615,7 +2163,11 @@ fn logout_to_auth_system(
     session.status = "Logged out. Back on auth screen.".to_string();
     session.ui_dirty = true;
     remote_registry.by_entity_id.clear();
+    component_cache.clear();
+    input_state.reset();
     auth_state.sent_for_client_entities.clear();
+    controlled_assignment.reset();
+    target_lock.reset();
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -1624,6 +2176,7 @@ fn update_starfield_material_system(
     ship_query: Query<'_, '_, &LinearVelocity, With<ControlledShip>>,
     window_query: Query<'_, '_, &Window, With<bevy::window::PrimaryWindow>>,
     mut motion: ResMut<'_, StarfieldMotionState>,
+    tuning: Res<'_, StarfieldTuning>,
     starfield_query: Query<'_, '_, &MeshMaterial2d<StarfieldMaterial>, With<StarfieldBackdrop>>,
     mut materials: ResMut<'_, Assets<StarfieldMaterial>>,
 ) {
@@ -1642,18 +2195,12 @@ fn update_starfield_material_system(
     };
     motion.prev_velocity_xy = velocity_xy;
 
-    motion.drift_xy += velocity_xy * (0.00014 * dt);
+    motion.drift_xy += velocity_xy * (tuning.drift_factor * dt);
     motion.drift_xy.x = motion.drift_xy.x.rem_euclid(1.0);
     motion.drift_xy.y = motion.drift_xy.y.rem_euclid(1.0);
 
     let speed = velocity_xy.length();
-    let speed_warp_start = 70.0;
-    let speed_warp_full = 320.0;
-    let accel_warp_full = 120.0;
-    let speed_norm = ((speed - speed_warp_start) / (speed_warp_full - speed_warp_start))
-        .clamp(0.0, 1.0);
-    let accel_norm = (acceleration_xy.length() / accel_warp_full).clamp(0.0, 1.0);
-    let warp = (speed_norm * 0.8 + accel_norm * 0.2).clamp(0.0, 1.0);
+    let StarfieldWarp { warp, accel_norm } = tuning.warp(speed, acceleration_xy.length());
     let intensity = 1.15 + warp * 0.9;
     let alpha = 0.94;
     let velocity_dir = if speed > 0.001 {
@@ -1712,6 +2259,8 @@ fn active_field_mut(session: &mut ClientSession) -> &mut String {
         FocusField::Password => &mut session.password,
         FocusField::ResetToken => &mut session.reset_token,
         FocusField::NewPassword => &mut session.new_password,
+        FocusField::ServerGateway => &mut session.server_gateway,
+        FocusField::ServerReplication => &mut session.server_replication,
     }
 }
 
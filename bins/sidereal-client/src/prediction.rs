@@ -22,6 +22,8 @@ use bevy::prelude::*;
 use sidereal_sim_core::{ControlTuning, EntityKinematics, InputSnapshot};
 use std::collections::VecDeque;
 
+use crate::input::ClientInputState;
+
 // ===== Controlled Entity Prediction =====
 
 /// Component marking the locally-controlled entity
@@ -109,7 +111,7 @@ pub fn predict_controlled_entity(
         With<ControlledEntity>,
     >,
     time: Res<Time>,
-    input: Res<ButtonInput<KeyCode>>,
+    input: Res<ClientInputState>,
     mut client_tick: ResMut<ClientTick>,
 ) {
     let Ok((controlled, mut history, mut transform)) = query.single_mut() else {
@@ -119,19 +121,15 @@ pub fn predict_controlled_entity(
     client_tick.0 += 1;
     let current_tick = client_tick.0;
 
-    // Capture current input
-    let input_snap = InputSnapshot {
-        thrust_forward: input.pressed(KeyCode::KeyW),
-        thrust_reverse: input.pressed(KeyCode::KeyS),
-        yaw_left: input.pressed(KeyCode::KeyA),
-        yaw_right: input.pressed(KeyCode::KeyD),
-    };
+    // The frame's snapshot, the same one the action queue and network use
+    let input_snap = input.snapshot;
 
     // Get current state from transform
     let current_state = EntityKinematics {
         position_m: transform.translation.to_array(),
         velocity_mps: [0.0, 0.0, 0.0], // TODO: track velocity component
         heading_rad: -transform.rotation.to_euler(EulerRot::ZYX).0, // Z rotation
+        orientation: transform.rotation.to_array(),
     };
 
     // Step forward
@@ -0,0 +1,189 @@
+//! Client world state across a transport reconnect.
+//!
+//! A reconnect within `SIDEREAL_CLIENT_RECONNECT_GRACE_MS` of the disconnect
+//! keeps the scene: the controlled ship, remote ships and their snapshot
+//! buffers stay, and the first snapshot after reconnecting re-syncs them
+//! (hard snap for the controlled ship, fresh buffers for remote ships). A
+//! longer outage rebuilds instead: remote ships, the replicated component cache
+//! and the controlled-entity assignment are dropped and repopulated from the
+//! stream, as on a fresh login.
+//!
+//! While the transport is down the client retries `Connect` itself, waiting
+//! `INITIAL_RETRY_DELAY_S` after the drop and doubling the wait after each
+//! attempt up to `MAX_RETRY_DELAY_S`.
+
+use bevy::prelude::*;
+
+pub const DEFAULT_RECONNECT_GRACE_MS: u64 = 5_000;
+pub const INITIAL_RETRY_DELAY_S: f64 = 0.5;
+pub const MAX_RETRY_DELAY_S: f64 = 8.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReconnectOutcome {
+    Preserve,
+    Rebuild,
+}
+
+#[derive(Debug, Clone, Resource)]
+pub struct ReconnectState {
+    grace_s: f64,
+    has_connected: bool,
+    disconnected_at_s: Option<f64>,
+    pending: Option<ReconnectOutcome>,
+    resync_pending: bool,
+    next_retry_at_s: Option<f64>,
+    retry_delay_s: f64,
+}
+
+impl Default for ReconnectState {
+    fn default() -> Self {
+        Self::new(DEFAULT_RECONNECT_GRACE_MS)
+    }
+}
+
+impl ReconnectState {
+    pub fn new(grace_ms: u64) -> Self {
+        Self {
+            grace_s: grace_ms as f64 / 1000.0,
+            has_connected: false,
+            disconnected_at_s: None,
+            pending: None,
+            resync_pending: false,
+            next_retry_at_s: None,
+            retry_delay_s: INITIAL_RETRY_DELAY_S,
+        }
+    }
+
+    /// Reads `SIDEREAL_CLIENT_RECONNECT_GRACE_MS` (`0` = always rebuild).
+    pub fn from_env() -> Self {
+        Self::new(
+            std::env::var("SIDEREAL_CLIENT_RECONNECT_GRACE_MS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(DEFAULT_RECONNECT_GRACE_MS),
+        )
+    }
+
+    /// Records the transport going down at `now_s`. Only the first drop of an
+    /// outage counts, so downtime is measured from when it began.
+    pub fn on_disconnected(&mut self, now_s: f64) {
+        if self.has_connected && self.disconnected_at_s.is_none() {
+            self.disconnected_at_s = Some(now_s);
+            self.next_retry_at_s = Some(now_s + INITIAL_RETRY_DELAY_S);
+            self.retry_delay_s = INITIAL_RETRY_DELAY_S;
+        }
+    }
+
+    /// Records the transport coming up at `now_s` and decides what to do with
+    /// the existing scene. Returns `None` for the session's first connection.
+    pub fn on_connected(&mut self, now_s: f64) -> Option<ReconnectOutcome> {
+        self.next_retry_at_s = None;
+        if !self.has_connected {
+            self.has_connected = true;
+            return None;
+        }
+        let disconnected_at_s = self.disconnected_at_s.take()?;
+        let outcome = if self.grace_s > 0.0 && now_s - disconnected_at_s <= self.grace_s {
+            ReconnectOutcome::Preserve
+        } else {
+            ReconnectOutcome::Rebuild
+        };
+        self.pending = Some(outcome);
+        self.resync_pending = true;
+        Some(outcome)
+    }
+
+    /// Whether to trigger another `Connect` at `now_s`. Only during an outage;
+    /// each attempt doubles the wait before the next one.
+    pub fn retry_due(&mut self, now_s: f64) -> bool {
+        match self.next_retry_at_s {
+            Some(retry_at_s) if now_s >= retry_at_s => {
+                self.retry_delay_s = (self.retry_delay_s * 2.0).min(MAX_RETRY_DELAY_S);
+                self.next_retry_at_s = Some(now_s + self.retry_delay_s);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The reconnect outcome not yet applied to the scene.
+    pub fn take_outcome(&mut self) -> Option<ReconnectOutcome> {
+        self.pending.take()
+    }
+
+    /// True once after a reconnect, for the first snapshot received.
+    pub fn take_resync(&mut self) -> bool {
+        std::mem::take(&mut self.resync_pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_connection_is_not_a_reconnect() {
+        let mut state = ReconnectState::new(2_000);
+        state.on_disconnected(0.5);
+        assert_eq!(state.on_connected(1.0), None);
+        assert_eq!(state.take_outcome(), None);
+        assert!(!state.take_resync());
+    }
+
+    #[test]
+    fn short_outage_preserves_and_long_outage_rebuilds() {
+        let mut state = ReconnectState::new(2_000);
+        state.on_connected(0.0);
+
+        state.on_disconnected(10.0);
+        // A second drop during the same outage does not restart the clock.
+        state.on_disconnected(11.5);
+        assert_eq!(state.on_connected(12.0), Some(ReconnectOutcome::Preserve));
+        assert_eq!(state.take_outcome(), Some(ReconnectOutcome::Preserve));
+        assert_eq!(state.take_outcome(), None);
+        assert!(state.take_resync());
+        assert!(!state.take_resync());
+
+        state.on_disconnected(20.0);
+        assert_eq!(state.on_connected(22.5), Some(ReconnectOutcome::Rebuild));
+        assert_eq!(state.take_outcome(), Some(ReconnectOutcome::Rebuild));
+        assert!(state.take_resync());
+    }
+
+    #[test]
+    fn zero_grace_always_rebuilds() {
+        let mut state = ReconnectState::new(0);
+        state.on_connected(0.0);
+        state.on_disconnected(1.0);
+        assert_eq!(state.on_connected(1.001), Some(ReconnectOutcome::Rebuild));
+        state.on_disconnected(2.0);
+        assert_eq!(state.on_connected(2.0), Some(ReconnectOutcome::Rebuild));
+    }
+
+    #[test]
+    fn retries_back_off_during_an_outage_and_stop_on_reconnect() {
+        let mut state = ReconnectState::new(2_000);
+        assert!(!state.retry_due(100.0));
+        state.on_connected(0.0);
+        assert!(!state.retry_due(100.0));
+
+        state.on_disconnected(10.0);
+        assert!(!state.retry_due(10.4));
+        assert!(state.retry_due(10.5));
+        assert!(!state.retry_due(11.4));
+        assert!(state.retry_due(11.5));
+        assert!(!state.retry_due(13.4));
+        assert!(state.retry_due(13.5));
+        // The wait stops doubling at the cap.
+        assert!(state.retry_due(17.5));
+        assert!(state.retry_due(25.5));
+        assert!(!state.retry_due(33.4));
+        assert!(state.retry_due(33.5));
+
+        state.on_connected(34.0);
+        assert!(!state.retry_due(100.0));
+        state.on_disconnected(200.0);
+        assert!(!state.retry_due(200.4));
+        assert!(state.retry_due(200.5));
+    }
+}
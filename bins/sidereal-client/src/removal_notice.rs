@@ -0,0 +1,81 @@
+//! What the player is told when replication removes entities.
+//!
+//! Each removal carries a `RemovalReason`. Entities that left scanner range
+//! (or whose reason is unknown) disappear silently and destroyed ones are only
+//! logged. A removal that ends this session's view of the world, another
+//! client taking the player over or the server shutting down, raises one
+//! dialog per state message rather than one per entity.
+
+use sidereal_net::{RemovalReason, WorldDeltaEntity};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionNotice {
+    pub title: &'static str,
+    pub message: &'static str,
+    pub status: &'static str,
+}
+
+/// The notice for the first session-ending removal among `updates`, if any.
+pub fn session_notice(updates: &[WorldDeltaEntity]) -> Option<SessionNotice> {
+    updates
+        .iter()
+        .filter(|update| update.removed)
+        .find_map(|update| match update.removal_reason? {
+            RemovalReason::Unauthorized => Some(SessionNotice {
+                title: "Signed In Elsewhere",
+                message: "This player signed in from another client, so this one no longer \
+                          receives the world.",
+                status: "Session taken over by another client.",
+            }),
+            RemovalReason::ServerShutdown => Some(SessionNotice {
+                title: "Server Shutting Down",
+                message: "The server is shutting down. Reconnect once it is back.",
+                status: "Server shutting down.",
+            }),
+            RemovalReason::OutOfRange | RemovalReason::Destroyed => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn removal(entity_id: &str, reason: Option<RemovalReason>) -> WorldDeltaEntity {
+        WorldDeltaEntity {
+            entity_id: entity_id.to_string(),
+            labels: Vec::new(),
+            properties: serde_json::json!({}),
+            components: Vec::new(),
+            removed: true,
+            removed_component_ids: Vec::new(),
+            removal_reason: reason,
+        }
+    }
+
+    #[test]
+    fn range_and_destruction_removals_raise_no_notice() {
+        let updates = vec![
+            removal("ship:1", Some(RemovalReason::OutOfRange)),
+            removal("ship:2", Some(RemovalReason::Destroyed)),
+            removal("ship:3", None),
+        ];
+        assert_eq!(session_notice(&updates), None);
+    }
+
+    #[test]
+    fn session_ending_removals_raise_one_notice() {
+        let updates = vec![
+            removal("ship:1", Some(RemovalReason::ServerShutdown)),
+            removal("ship:2", Some(RemovalReason::ServerShutdown)),
+        ];
+        assert_eq!(
+            session_notice(&updates).map(|notice| notice.title),
+            Some("Server Shutting Down")
+        );
+        assert_eq!(
+            session_notice(&[removal("ship:1", Some(RemovalReason::Unauthorized))])
+                .map(|notice| notice.title),
+            Some("Signed In Elsewhere")
+        );
+    }
+}
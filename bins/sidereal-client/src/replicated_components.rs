@@ -0,0 +1,110 @@
+//! Client-side copy of the components replicated for each entity.
+//!
+//! Full updates upsert the components they carry; `removed_component_ids`
+//! drops just the named components so the server does not have to resend the
+//! whole component list when one component goes away.
+
+use bevy::prelude::*;
+use sidereal_net::{WorldComponentDelta, WorldDeltaEntity};
+use std::collections::HashMap;
+
+#[derive(Debug, Resource, Default)]
+pub struct ReplicatedComponentCache {
+    pub by_entity_id: HashMap<String, Vec<WorldComponentDelta>>,
+}
+
+impl ReplicatedComponentCache {
+    pub fn apply_update(&mut self, update: &WorldDeltaEntity) {
+        if update.removed {
+            self.by_entity_id.remove(&update.entity_id);
+            return;
+        }
+        let components = self
+            .by_entity_id
+            .entry(update.entity_id.clone())
+            .or_default();
+        components.retain(|component| {
+            !update
+                .removed_component_ids
+                .contains(&component.component_id)
+        });
+        for incoming in &update.components {
+            match components
+                .iter_mut()
+                .find(|component| component.component_id == incoming.component_id)
+            {
+                Some(existing) => *existing = incoming.clone(),
+                None => components.push(incoming.clone()),
+            }
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn components(&self, entity_id: &str) -> &[WorldComponentDelta] {
+        self.by_entity_id
+            .get(entity_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn clear(&mut self) {
+        self.by_entity_id.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn component(kind: &str) -> WorldComponentDelta {
+        WorldComponentDelta {
+            component_id: format!("ship:1:{kind}"),
+            component_kind: kind.to_string(),
+            properties: serde_json::json!({}),
+        }
+    }
+
+    fn update(components: Vec<WorldComponentDelta>, removed_ids: &[&str]) -> WorldDeltaEntity {
+        WorldDeltaEntity {
+            entity_id: "ship:1".to_string(),
+            labels: Vec::new(),
+            properties: serde_json::json!({}),
+            components,
+            removed: false,
+            removed_component_ids: removed_ids.iter().map(|id| id.to_string()).collect(),
+            removal_reason: None,
+        }
+    }
+
+    #[test]
+    fn removed_component_is_dropped_and_others_persist() {
+        let mut cache = ReplicatedComponentCache::default();
+        cache.apply_update(&update(
+            vec![
+                component("display_name"),
+                component("flight_computer"),
+                component("health_pool"),
+            ],
+            &[],
+        ));
+
+        cache.apply_update(&update(Vec::new(), &["ship:1:flight_computer"]));
+
+        let kinds = cache
+            .components("ship:1")
+            .iter()
+            .map(|c| c.component_kind.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, vec!["display_name", "health_pool"]);
+    }
+
+    #[test]
+    fn entity_removal_drops_all_components() {
+        let mut cache = ReplicatedComponentCache::default();
+        cache.apply_update(&update(vec![component("display_name")], &[]));
+        let mut removal = update(Vec::new(), &[]);
+        removal.removed = true;
+        cache.apply_update(&removal);
+        assert!(cache.components("ship:1").is_empty());
+    }
+}
@@ -0,0 +1,95 @@
+//! Ordering of the replication state stream, shown on the HUD.
+//!
+//! Replication numbers each client's state messages from 1. They travel on an
+//! unreliable channel, so one can arrive after a newer one; applying it would
+//! roll the world back, so it is dropped instead. Messages with seq 0 come
+//! from servers that predate numbering and are always applied.
+
+use bevy::prelude::*;
+
+#[derive(Debug, Clone, Default, Resource)]
+pub struct ReplicationStats {
+    last_seq: Option<u64>,
+    /// Messages applied.
+    pub applied: u64,
+    /// Sequence numbers skipped over, i.e. messages lost or still in flight
+    /// when a newer one arrived.
+    pub missing: u64,
+    /// Messages dropped for arriving after a newer one (or twice).
+    pub stale: u64,
+    /// Tick of the newest message applied, sent back with each input as the
+    /// world the player saw.
+    pub last_tick: u64,
+}
+
+impl ReplicationStats {
+    /// Records a state message and returns whether to apply it.
+    pub fn accept(&mut self, seq: u64) -> bool {
+        if seq == 0 {
+            self.applied += 1;
+            return true;
+        }
+        match self.last_seq {
+            Some(last) if seq <= last => {
+                self.stale += 1;
+                false
+            }
+            last => {
+                if let Some(last) = last {
+                    self.missing += seq - last - 1;
+                }
+                self.last_seq = Some(seq);
+                self.applied += 1;
+                true
+            }
+        }
+    }
+
+    /// Forgets the stream, e.g. after a reconnect restarts its numbering.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// The HUD line for the stream's ordering counters.
+pub fn format_replication_stats(stats: &ReplicationStats) -> String {
+    format!(
+        "Stream: gaps {} | stale dropped {}",
+        stats.missing, stats.stale
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn older_seq_after_a_newer_one_is_ignored() {
+        let mut stats = ReplicationStats::default();
+        assert!(stats.accept(1));
+        assert!(stats.accept(3));
+        assert!(!stats.accept(2));
+        assert!(!stats.accept(3));
+        assert!(stats.accept(4));
+
+        assert_eq!(stats.applied, 3);
+        assert_eq!(stats.missing, 1);
+        assert_eq!(stats.stale, 2);
+        assert_eq!(
+            format_replication_stats(&stats),
+            "Stream: gaps 1 | stale dropped 2"
+        );
+    }
+
+    #[test]
+    fn unnumbered_messages_are_always_applied() {
+        let mut stats = ReplicationStats::default();
+        assert!(stats.accept(5));
+        assert!(stats.accept(0));
+        assert!(stats.accept(0));
+        assert_eq!(stats.stale, 0);
+
+        stats.reset();
+        assert!(stats.accept(1));
+    }
+}
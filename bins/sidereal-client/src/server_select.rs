@@ -0,0 +1,297 @@
+//! Server selection for the native client.
+//!
+//! The auth screen exposes a "Server" flow (F5) with gateway and replication
+//! `host:port` fields. Applying a selection updates `ClientSession.gateway_url`
+//! and the Lightyear peer address; the transport is restarted before the player
+//! authenticates so the in-world session always talks to the selected server.
+//! Successful selections are remembered in a small JSON file so recent servers
+//! can be picked again without retyping.
+
+use bevy::prelude::*;
+use lightyear::prelude::client::{Connect, RawClient};
+use lightyear::prelude::{LocalAddr, MessageManager, PeerAddr, UdpIo};
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::path::PathBuf;
+
+use crate::ClientSession;
+
+pub const MAX_RECENT_SERVERS: usize = 5;
+
+/// A gateway/replication endpoint pair, both stored as validated `host:port`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerProfile {
+    pub gateway: String,
+    pub replication: String,
+}
+
+#[derive(Debug, Resource)]
+pub struct RecentServers {
+    pub profiles: Vec<ServerProfile>,
+    path: PathBuf,
+}
+
+impl RecentServers {
+    pub fn load_from_env() -> Self {
+        let path = std::env::var("SIDEREAL_CLIENT_RECENT_SERVERS_PATH")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("./client_cache/recent_servers.json"));
+        let profiles = std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Vec<ServerProfile>>(&bytes).ok())
+            .unwrap_or_default();
+        Self { profiles, path }
+    }
+
+    /// Moves `profile` to the front of the list, dropping duplicates and the
+    /// oldest entries beyond `MAX_RECENT_SERVERS`.
+    pub fn remember(&mut self, profile: ServerProfile) {
+        self.profiles.retain(|existing| existing != &profile);
+        self.profiles.insert(0, profile);
+        self.profiles.truncate(MAX_RECENT_SERVERS);
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        let bytes = serde_json::to_vec_pretty(&self.profiles).map_err(|err| err.to_string())?;
+        std::fs::write(&self.path, bytes).map_err(|err| err.to_string())
+    }
+}
+
+/// Validates a `host:port` entry and returns it normalized (trimmed, host
+/// lowercased). Hosts may be hostnames, IPv4 literals, or bracketed IPv6.
+pub fn validate_server_entry(raw: &str) -> Result<String, String> {
+    let entry = raw.trim();
+    if entry.is_empty() {
+        return Err("server address is required".to_string());
+    }
+    let (host, port) = entry
+        .rsplit_once(':')
+        .ok_or_else(|| format!("expected host:port, got '{entry}'"))?;
+    let port = port
+        .parse::<u16>()
+        .ok()
+        .filter(|port| *port != 0)
+        .ok_or_else(|| format!("invalid port in '{entry}'"))?;
+
+    if let Some(v6) = host.strip_prefix('[').and_then(|h| h.strip_suffix(']')) {
+        v6.parse::<std::net::Ipv6Addr>()
+            .map_err(|_| format!("invalid IPv6 address in '{entry}'"))?;
+        return Ok(format!("[{}]:{port}", v6.to_ascii_lowercase()));
+    }
+    if host.parse::<IpAddr>().is_ok() {
+        return Ok(format!("{host}:{port}"));
+    }
+    if !is_valid_hostname(host) {
+        return Err(format!("invalid host in '{entry}'"));
+    }
+    Ok(format!("{}:{port}", host.to_ascii_lowercase()))
+}
+
+fn is_valid_hostname(host: &str) -> bool {
+    if host.is_empty() || host.len() > 253 {
+        return false;
+    }
+    // All-numeric dotted names would be malformed IPv4 literals.
+    if host.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return false;
+    }
+    host.split('.').all(|label| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    })
+}
+
+/// Accepts a gateway entry with or without an `http://`/`https://` scheme and
+/// returns the base URL used for gateway requests.
+pub fn gateway_url_from_entry(raw: &str) -> Result<String, String> {
+    let trimmed = raw.trim().trim_end_matches('/');
+    let (scheme, rest) = if let Some(rest) = trimmed.strip_prefix("https://") {
+        ("https", rest)
+    } else if let Some(rest) = trimmed.strip_prefix("http://") {
+        ("http", rest)
+    } else {
+        ("http", trimmed)
+    };
+    Ok(format!("{scheme}://{}", validate_server_entry(rest)?))
+}
+
+fn resolve_replication_addr(entry: &str) -> Result<SocketAddr, String> {
+    entry
+        .to_socket_addrs()
+        .map_err(|err| format!("could not resolve '{entry}': {err}"))?
+        .next()
+        .ok_or_else(|| format!("could not resolve '{entry}'"))
+}
+
+/// Validates both endpoints and points the session at them. The session is
+/// left untouched when either entry is rejected.
+pub fn apply_server_profile(
+    session: &mut ClientSession,
+    gateway_entry: &str,
+    replication_entry: &str,
+) -> Result<ServerProfile, String> {
+    let gateway_url = gateway_url_from_entry(gateway_entry)?;
+    let replication = validate_server_entry(replication_entry)?;
+    let replication_addr = resolve_replication_addr(&replication)?;
+
+    session.gateway_url = gateway_url;
+    if session.replication_udp_addr != replication_addr {
+        session.replication_udp_addr = replication_addr;
+        session.transport_target_changed = true;
+    }
+    session.server_gateway = gateway_entry.trim().to_string();
+    session.server_replication = replication.clone();
+    Ok(ServerProfile {
+        gateway: gateway_entry.trim().trim_end_matches('/').to_string(),
+        replication,
+    })
+}
+
+/// Reconnects the Lightyear client when the selected replication address changes.
+pub fn restart_transport_on_server_change(
+    mut commands: Commands<'_, '_>,
+    mut session: ResMut<'_, ClientSession>,
+    clients: Query<'_, '_, (Entity, &LocalAddr), With<RawClient>>,
+) {
+    if !session.transport_target_changed {
+        return;
+    }
+    session.transport_target_changed = false;
+
+    let mut local_addr = None;
+    for (entity, addr) in &clients {
+        local_addr = Some(addr.0);
+        commands.entity(entity).despawn();
+    }
+    let Some(local_addr) = local_addr else {
+        return;
+    };
+    let remote_addr = session.replication_udp_addr;
+    let client = commands
+        .spawn((
+            Name::new("native-client-lightyear"),
+            RawClient,
+            UdpIo::default(),
+            MessageManager::default(),
+            LocalAddr(local_addr),
+            PeerAddr(remote_addr),
+        ))
+        .id();
+    commands.trigger(Connect { entity: client });
+    println!("native client lightyear UDP reconnecting {local_addr} -> {remote_addr}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_entry_accepts_host_port_forms() {
+        assert_eq!(
+            validate_server_entry("127.0.0.1:7001").as_deref(),
+            Ok("127.0.0.1:7001")
+        );
+        assert_eq!(
+            validate_server_entry(" Play.Sidereal.Example:8080 ").as_deref(),
+            Ok("play.sidereal.example:8080")
+        );
+        assert_eq!(
+            validate_server_entry("localhost:7001").as_deref(),
+            Ok("localhost:7001")
+        );
+        assert_eq!(
+            validate_server_entry("[::1]:7001").as_deref(),
+            Ok("[::1]:7001")
+        );
+    }
+
+    #[test]
+    fn server_entry_rejects_malformed_input() {
+        for raw in [
+            "",
+            "localhost",
+            "localhost:",
+            ":7001",
+            "localhost:0",
+            "localhost:70000",
+            "localhost:port",
+            "bad host:7001",
+            "-bad.example:7001",
+            "999.1.1.1:7001",
+            "[not-v6]:7001",
+            "http://localhost:7001",
+        ] {
+            assert!(validate_server_entry(raw).is_err(), "accepted {raw:?}");
+        }
+    }
+
+    #[test]
+    fn gateway_entry_keeps_or_defaults_scheme() {
+        assert_eq!(
+            gateway_url_from_entry("127.0.0.1:8080").as_deref(),
+            Ok("http://127.0.0.1:8080")
+        );
+        assert_eq!(
+            gateway_url_from_entry("https://gw.example:443/").as_deref(),
+            Ok("https://gw.example:443")
+        );
+        assert!(gateway_url_from_entry("ftp://gw.example:21").is_err());
+    }
+
+    #[test]
+    fn selecting_server_updates_session() {
+        let mut session = ClientSession::default();
+        session.transport_target_changed = false;
+
+        let profile = apply_server_profile(&mut session, "10.0.0.5:8080", "10.0.0.5:7101")
+            .expect("valid profile");
+
+        assert_eq!(session.gateway_url, "http://10.0.0.5:8080");
+        assert_eq!(
+            session.replication_udp_addr,
+            "10.0.0.5:7101".parse::<SocketAddr>().unwrap()
+        );
+        assert!(session.transport_target_changed);
+        assert_eq!(profile.replication, "10.0.0.5:7101");
+    }
+
+    #[test]
+    fn rejected_selection_leaves_session_untouched() {
+        let mut session = ClientSession::default();
+        let gateway_url = session.gateway_url.clone();
+        let replication_addr = session.replication_udp_addr;
+
+        assert!(apply_server_profile(&mut session, "10.0.0.5:8080", "nope").is_err());
+        assert_eq!(session.gateway_url, gateway_url);
+        assert_eq!(session.replication_udp_addr, replication_addr);
+    }
+
+    #[test]
+    fn recent_servers_dedupe_and_cap() {
+        let mut recent = RecentServers {
+            profiles: Vec::new(),
+            path: PathBuf::from("unused.json"),
+        };
+        let profile = |n: usize| ServerProfile {
+            gateway: format!("10.0.0.{n}:8080"),
+            replication: format!("10.0.0.{n}:7001"),
+        };
+        for n in 0..(MAX_RECENT_SERVERS + 2) {
+            recent.remember(profile(n));
+        }
+        recent.remember(profile(3));
+
+        assert_eq!(recent.profiles.len(), MAX_RECENT_SERVERS);
+        assert_eq!(recent.profiles[0], profile(3));
+        assert_eq!(
+            recent.profiles.iter().filter(|p| **p == profile(3)).count(),
+            1
+        );
+    }
+}
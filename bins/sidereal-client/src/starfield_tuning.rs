@@ -0,0 +1,141 @@
+//! Tuning for the starfield warp effect.
+//!
+//! Warp ramps in with ship speed between `speed_warp_start_mps` and
+//! `speed_warp_full_mps`, with a smaller contribution from acceleration up to
+//! `accel_warp_full_mps2`. Every value can be overridden with a
+//! `SIDEREAL_CLIENT_STARFIELD_*` env var so the feel can be tuned without a
+//! rebuild; invalid values fall back to the defaults.
+
+use bevy::prelude::*;
+
+/// Share of the warp driven by speed; the rest comes from acceleration.
+const SPEED_WARP_WEIGHT: f32 = 0.8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Resource)]
+pub struct StarfieldTuning {
+    pub speed_warp_start_mps: f32,
+    pub speed_warp_full_mps: f32,
+    pub accel_warp_full_mps2: f32,
+    /// Starfield scroll per metre travelled.
+    pub drift_factor: f32,
+}
+
+impl Default for StarfieldTuning {
+    fn default() -> Self {
+        Self {
+            speed_warp_start_mps: 70.0,
+            speed_warp_full_mps: 320.0,
+            accel_warp_full_mps2: 120.0,
+            drift_factor: 0.00014,
+        }
+    }
+}
+
+/// Normalized warp inputs for one frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StarfieldWarp {
+    /// Combined warp in `[0, 1]`.
+    pub warp: f32,
+    /// Acceleration share in `[0, 1]`, passed to the shader separately.
+    pub accel_norm: f32,
+}
+
+impl StarfieldTuning {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let read = |name: &str, default: f32| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<f32>().ok())
+                .filter(|v| v.is_finite() && *v >= 0.0)
+                .unwrap_or(default)
+        };
+        let speed_warp_start_mps = read(
+            "SIDEREAL_CLIENT_STARFIELD_WARP_START_MPS",
+            defaults.speed_warp_start_mps,
+        );
+        let speed_warp_full_mps = read(
+            "SIDEREAL_CLIENT_STARFIELD_WARP_FULL_MPS",
+            defaults.speed_warp_full_mps,
+        );
+        let tuning = Self {
+            speed_warp_start_mps,
+            speed_warp_full_mps,
+            accel_warp_full_mps2: read(
+                "SIDEREAL_CLIENT_STARFIELD_ACCEL_WARP_FULL_MPS2",
+                defaults.accel_warp_full_mps2,
+            ),
+            drift_factor: read(
+                "SIDEREAL_CLIENT_STARFIELD_DRIFT_FACTOR",
+                defaults.drift_factor,
+            ),
+        };
+        if tuning.speed_warp_full_mps <= tuning.speed_warp_start_mps {
+            eprintln!(
+                "ignoring starfield warp speeds: full ({}) must exceed start ({})",
+                tuning.speed_warp_full_mps, tuning.speed_warp_start_mps
+            );
+            return Self {
+                speed_warp_start_mps: defaults.speed_warp_start_mps,
+                speed_warp_full_mps: defaults.speed_warp_full_mps,
+                ..tuning
+            };
+        }
+        tuning
+    }
+
+    pub fn warp(&self, speed_mps: f32, accel_mps2: f32) -> StarfieldWarp {
+        let speed_norm = ((speed_mps - self.speed_warp_start_mps)
+            / (self.speed_warp_full_mps - self.speed_warp_start_mps))
+            .clamp(0.0, 1.0);
+        let accel_norm = if self.accel_warp_full_mps2 > 0.0 {
+            (accel_mps2 / self.accel_warp_full_mps2).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        StarfieldWarp {
+            warp: (speed_norm * SPEED_WARP_WEIGHT + accel_norm * (1.0 - SPEED_WARP_WEIGHT))
+                .clamp(0.0, 1.0),
+            accel_norm,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warp_is_zero_below_start_speed_and_full_at_both_limits() {
+        let tuning = StarfieldTuning::default();
+        assert_eq!(tuning.warp(50.0, 0.0).warp, 0.0);
+        let full = tuning.warp(400.0, 500.0);
+        assert!((full.warp - 1.0).abs() < 1e-6);
+        assert_eq!(full.accel_norm, 1.0);
+    }
+
+    #[test]
+    fn speed_and_acceleration_blend_by_weight() {
+        let tuning = StarfieldTuning::default();
+        // Halfway through the speed ramp, coasting.
+        let coasting = tuning.warp(195.0, 0.0);
+        assert!((coasting.warp - 0.4).abs() < 1e-5);
+        // Stationary but accelerating at half the full-warp acceleration.
+        let launching = tuning.warp(0.0, 60.0);
+        assert!((launching.accel_norm - 0.5).abs() < 1e-6);
+        assert!((launching.warp - 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn custom_tuning_moves_the_ramp() {
+        let tuning = StarfieldTuning {
+            speed_warp_start_mps: 10.0,
+            speed_warp_full_mps: 20.0,
+            accel_warp_full_mps2: 0.0,
+            ..Default::default()
+        };
+        let warp = tuning.warp(15.0, 1_000.0);
+        assert!((warp.warp - 0.4).abs() < 1e-6);
+        assert_eq!(warp.accel_norm, 0.0);
+    }
+}
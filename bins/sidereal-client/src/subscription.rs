@@ -0,0 +1,113 @@
+//! Which entity kinds this client asks replication to stream.
+//!
+//! `SIDEREAL_CLIENT_SUBSCRIBE_KINDS` lists the kinds (comma-separated, e.g.
+//! `ship,asteroid`); unset or empty subscribes to everything and sends
+//! nothing. Replication only accepts a filter from a bound client, so it is
+//! sent once per connection after the first `ControlledEntityMessage` shows
+//! the server has bound this client.
+
+use bevy::prelude::*;
+use sidereal_net::SubscriptionFilter;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Default, Resource)]
+pub struct ClientSubscription {
+    pub filter: SubscriptionFilter,
+    bound_client_entities: HashSet<Entity>,
+    sent_for_client_entities: HashSet<Entity>,
+}
+
+impl ClientSubscription {
+    pub fn new(include_kinds: Vec<String>) -> Self {
+        Self {
+            filter: SubscriptionFilter { include_kinds },
+            ..Self::default()
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(
+            std::env::var("SIDEREAL_CLIENT_SUBSCRIBE_KINDS")
+                .map(|v| parse_include_kinds(&v))
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Records that the server has bound `client_entity`.
+    pub fn mark_bound(&mut self, client_entity: Entity) {
+        self.bound_client_entities.insert(client_entity);
+    }
+
+    /// The filter to send on `client_entity`, once per bound connection.
+    /// `None` for the default filter, which the server already assumes.
+    pub fn take_unsent(&mut self, client_entity: Entity) -> Option<SubscriptionFilter> {
+        if self.filter.include_kinds.is_empty()
+            || !self.bound_client_entities.contains(&client_entity)
+            || !self.sent_for_client_entities.insert(client_entity)
+        {
+            return None;
+        }
+        Some(self.filter.clone())
+    }
+
+    /// Forgets connections that are gone, so a reconnect sends again.
+    pub fn retain_connected(&mut self, connected: impl Fn(Entity) -> bool) {
+        self.bound_client_entities
+            .retain(|entity| connected(*entity));
+        self.sent_for_client_entities
+            .retain(|entity| connected(*entity));
+    }
+}
+
+fn parse_include_kinds(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|kind| !kind.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kinds_are_trimmed_and_blank_entries_dropped() {
+        assert_eq!(
+            parse_include_kinds(" Ship, ,asteroid,"),
+            vec!["ship".to_string(), "asteroid".to_string()]
+        );
+        assert!(parse_include_kinds("").is_empty());
+    }
+
+    #[test]
+    fn filter_is_sent_once_per_bound_connection() {
+        let first = Entity::from_raw_u32(1).unwrap();
+        let second = Entity::from_raw_u32(2).unwrap();
+        let mut subscription = ClientSubscription::new(vec!["ship".to_string()]);
+
+        assert_eq!(subscription.take_unsent(first), None);
+        subscription.mark_bound(first);
+        assert_eq!(
+            subscription.take_unsent(first),
+            Some(SubscriptionFilter {
+                include_kinds: vec!["ship".to_string()],
+            })
+        );
+        assert_eq!(subscription.take_unsent(first), None);
+
+        subscription.retain_connected(|entity| entity == second);
+        subscription.mark_bound(second);
+        assert!(subscription.take_unsent(second).is_some());
+        subscription.mark_bound(first);
+        assert!(subscription.take_unsent(first).is_some());
+    }
+
+    #[test]
+    fn default_filter_is_never_sent() {
+        let client = Entity::from_raw_u32(1).unwrap();
+        let mut subscription = ClientSubscription::default();
+        subscription.mark_bound(client);
+        assert_eq!(subscription.take_unsent(client), None);
+    }
+}
@@ -0,0 +1,296 @@
+//! Target selection from the remote contacts the client currently sees.
+//!
+//! TAB cycles forward through contacts sorted by entity id, SHIFT+TAB
+//! backward, both wrapping around. A locked contact that drops out of the
+//! stream is released. Every change is sent to replication once as a
+//! `TargetLockMessage`, so server-side systems such as weapon aim-off know
+//! what the player is locked onto. Locking a contact also sends a
+//! `RequestEntityDetail`; the `EntityDetailMessage` that answers it is kept in
+//! `TargetDetail` for the HUD while the lock holds.
+
+use bevy::prelude::*;
+use sidereal_net::WorldDeltaEntity;
+
+use crate::RemoteShipRegistry;
+
+/// Radius of the HUD ring drawn around the locked contact.
+const TARGET_RING_RADIUS_M: f32 = 6.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetCycle {
+    Next,
+    Previous,
+}
+
+/// The contact after (or before) `current` in `contacts`, which must be
+/// sorted. Without a current target, `Next` starts at the first contact and
+/// `Previous` at the last. A current target no longer in the list continues
+/// from where it would sort. `None` only when there are no contacts.
+pub fn cycle_target(
+    contacts: &[String],
+    current: Option<&str>,
+    direction: TargetCycle,
+) -> Option<String> {
+    if contacts.is_empty() {
+        return None;
+    }
+    let len = contacts.len();
+    let index = match (current, direction) {
+        (None, TargetCycle::Next) => 0,
+        (None, TargetCycle::Previous) => len - 1,
+        (Some(current), _) => {
+            match (
+                contacts.binary_search_by(|contact| contact.as_str().cmp(current)),
+                direction,
+            ) {
+                (Ok(index), TargetCycle::Next) => (index + 1) % len,
+                (Ok(index), TargetCycle::Previous) => (index + len - 1) % len,
+                (Err(insert_at), TargetCycle::Next) => insert_at % len,
+                (Err(insert_at), TargetCycle::Previous) => (insert_at + len - 1) % len,
+            }
+        }
+    };
+    Some(contacts[index].clone())
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Resource)]
+pub struct TargetLock {
+    pub entity_id: Option<String>,
+    /// Whether the server has yet to hear about the current lock.
+    unsent: bool,
+}
+
+impl TargetLock {
+    pub fn cycle(&mut self, contacts: &[String], direction: TargetCycle) {
+        let next = cycle_target(contacts, self.entity_id.as_deref(), direction);
+        self.set(next);
+    }
+
+    /// Releases the lock when its entity is not among `contacts`.
+    pub fn retain_contacts(&mut self, contacts: &[String]) {
+        if self
+            .entity_id
+            .as_ref()
+            .is_some_and(|locked| !contacts.contains(locked))
+        {
+            self.set(None);
+        }
+    }
+
+    /// The lock to send, once per change.
+    pub fn take_unsent(&mut self) -> Option<Option<String>> {
+        std::mem::take(&mut self.unsent).then(|| self.entity_id.clone())
+    }
+
+    /// Sends the current lock again, e.g. to a server that lost it when the
+    /// connection dropped.
+    pub fn mark_unsent(&mut self) {
+        self.unsent = true;
+    }
+
+    /// Drops the lock without telling the server, for a session that is over.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn set(&mut self, entity_id: Option<String>) {
+        if self.entity_id != entity_id {
+            self.entity_id = entity_id;
+            self.unsent = true;
+        }
+    }
+}
+
+/// Replication's detail view of the locked target: its properties, including
+/// the `loadout` summary when the player's relation to it allows one.
+#[derive(Debug, Clone, Default, PartialEq, Resource)]
+pub struct TargetDetail {
+    pub entity_id: Option<String>,
+    pub properties: serde_json::Value,
+}
+
+impl TargetDetail {
+    /// Stores the detail replication sent for `entity_id`. Answers for a
+    /// contact that is no longer `locked` are dropped, and an empty answer
+    /// (the server no longer shows the contact) clears what was stored.
+    pub fn apply(
+        &mut self,
+        locked: Option<&str>,
+        entity_id: &str,
+        detail: Option<WorldDeltaEntity>,
+    ) {
+        if locked != Some(entity_id) {
+            return;
+        }
+        *self = match detail {
+            Some(detail) => Self {
+                entity_id: Some(entity_id.to_string()),
+                properties: detail.properties,
+            },
+            None => Self::default(),
+        };
+    }
+
+    /// Forgets the detail once the lock has moved off its entity.
+    pub fn retain_lock(&mut self, locked: Option<&str>) {
+        if self.entity_id.is_some() && self.entity_id.as_deref() != locked {
+            *self = Self::default();
+        }
+    }
+
+    /// Number of modules in the target's loadout, if the server sent one.
+    pub fn loadout_len(&self) -> Option<usize> {
+        self.properties
+            .get("loadout")
+            .and_then(|loadout| loadout.as_array())
+            .map(Vec::len)
+    }
+}
+
+fn sorted_contacts(registry: &RemoteShipRegistry) -> Vec<String> {
+    let mut contacts = registry.by_entity_id.keys().cloned().collect::<Vec<_>>();
+    contacts.sort();
+    contacts
+}
+
+pub fn cycle_target_lock(
+    input: Option<Res<'_, ButtonInput<KeyCode>>>,
+    registry: Res<'_, RemoteShipRegistry>,
+    mut lock: ResMut<'_, TargetLock>,
+    mut detail: ResMut<'_, TargetDetail>,
+) {
+    let contacts = sorted_contacts(&registry);
+    lock.retain_contacts(&contacts);
+    if let Some(keys) = input
+        && keys.just_pressed(KeyCode::Tab)
+    {
+        let direction = if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+            TargetCycle::Previous
+        } else {
+            TargetCycle::Next
+        };
+        lock.cycle(&contacts, direction);
+    }
+    detail.retain_lock(lock.entity_id.as_deref());
+}
+
+pub fn draw_target_lock_highlight(
+    lock: Res<'_, TargetLock>,
+    registry: Res<'_, RemoteShipRegistry>,
+    transforms: Query<'_, '_, &GlobalTransform>,
+    mut gizmos: Gizmos<'_, '_>,
+) {
+    let Some(transform) = lock
+        .entity_id
+        .as_ref()
+        .and_then(|locked| registry.by_entity_id.get(locked))
+        .and_then(|entity| transforms.get(*entity).ok())
+    else {
+        return;
+    };
+    gizmos.circle(
+        Isometry3d::from_translation(transform.translation()),
+        TARGET_RING_RADIUS_M,
+        Color::srgb(1.0, 0.85, 0.2),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contacts(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn cycling_walks_sorted_contacts_and_wraps() {
+        let list = contacts(&["ship:a", "ship:b", "ship:c"]);
+        let next = |current| cycle_target(&list, current, TargetCycle::Next);
+        let previous = |current| cycle_target(&list, current, TargetCycle::Previous);
+
+        assert_eq!(next(None).as_deref(), Some("ship:a"));
+        assert_eq!(next(Some("ship:a")).as_deref(), Some("ship:b"));
+        assert_eq!(next(Some("ship:c")).as_deref(), Some("ship:a"));
+        assert_eq!(previous(None).as_deref(), Some("ship:c"));
+        assert_eq!(previous(Some("ship:a")).as_deref(), Some("ship:c"));
+        assert_eq!(previous(Some("ship:b")).as_deref(), Some("ship:a"));
+
+        // A vanished target continues from where it sorted.
+        assert_eq!(next(Some("ship:bb")).as_deref(), Some("ship:c"));
+        assert_eq!(previous(Some("ship:bb")).as_deref(), Some("ship:b"));
+        assert_eq!(next(Some("ship:z")).as_deref(), Some("ship:a"));
+        assert_eq!(cycle_target(&[], Some("ship:a"), TargetCycle::Next), None);
+    }
+
+    #[test]
+    fn lock_clears_when_target_disappears_and_sends_each_change_once() {
+        let mut lock = TargetLock::default();
+        assert_eq!(lock.take_unsent(), None);
+
+        lock.cycle(&contacts(&["ship:a", "ship:b"]), TargetCycle::Next);
+        assert_eq!(lock.entity_id.as_deref(), Some("ship:a"));
+        assert_eq!(lock.take_unsent(), Some(Some("ship:a".to_string())));
+        assert_eq!(lock.take_unsent(), None);
+
+        lock.retain_contacts(&contacts(&["ship:a", "ship:c"]));
+        assert_eq!(lock.take_unsent(), None);
+
+        lock.retain_contacts(&contacts(&["ship:c"]));
+        assert_eq!(lock.entity_id, None);
+        assert_eq!(lock.take_unsent(), Some(None));
+
+        lock.cycle(&[], TargetCycle::Next);
+        assert_eq!(lock.take_unsent(), None);
+    }
+
+    fn detail_update(entity_id: &str, properties: serde_json::Value) -> WorldDeltaEntity {
+        WorldDeltaEntity {
+            entity_id: entity_id.to_string(),
+            labels: vec!["Entity".to_string(), "Ship".to_string()],
+            properties,
+            components: Vec::new(),
+            removed: false,
+            removed_component_ids: Vec::new(),
+            removal_reason: None,
+        }
+    }
+
+    #[test]
+    fn detail_is_kept_only_for_the_locked_target() {
+        let mut detail = TargetDetail::default();
+        let loadout = serde_json::json!({"loadout": [{"entity_id": "module:1"}]});
+
+        detail.apply(
+            Some("ship:b"),
+            "ship:a",
+            Some(detail_update("ship:a", loadout.clone())),
+        );
+        assert_eq!(detail, TargetDetail::default());
+
+        detail.apply(
+            Some("ship:a"),
+            "ship:a",
+            Some(detail_update("ship:a", loadout)),
+        );
+        assert_eq!(detail.entity_id.as_deref(), Some("ship:a"));
+        assert_eq!(detail.loadout_len(), Some(1));
+
+        detail.retain_lock(Some("ship:a"));
+        assert_eq!(detail.loadout_len(), Some(1));
+        detail.retain_lock(Some("ship:b"));
+        assert_eq!(detail, TargetDetail::default());
+
+        detail.apply(
+            Some("ship:b"),
+            "ship:b",
+            Some(detail_update(
+                "ship:b",
+                serde_json::json!({"entity_id": "ship:b"}),
+            )),
+        );
+        assert_eq!(detail.loadout_len(), None);
+        detail.apply(Some("ship:b"), "ship:b", None);
+        assert_eq!(detail.entity_id, None);
+    }
+}
@@ -1,8 +1,8 @@
 use anyhow::Context;
 use sidereal_gateway::api::app_with_service;
 use sidereal_gateway::auth::{
-    AuthConfig, AuthService, BootstrapDispatcher, DirectBootstrapDispatcher, PostgresAuthStore,
-    UdpBootstrapDispatcher,
+    AuthConfig, AuthService, BootstrapDispatcher, CompositeBootstrapDispatcher,
+    DirectBootstrapDispatcher, PostgresAuthStore, UdpBootstrapDispatcher,
 };
 use std::net::SocketAddr;
 use std::sync::Arc;
@@ -28,6 +28,15 @@ async fn main() -> anyhow::Result<()> {
                     .await
                     .context("invalid replication control UDP config")?,
             )
+        } else if bootstrap_mode.eq_ignore_ascii_case("both") {
+            Arc::new(CompositeBootstrapDispatcher::new(vec![
+                Arc::new(DirectBootstrapDispatcher::from_env()),
+                Arc::new(
+                    UdpBootstrapDispatcher::from_env()
+                        .await
+                        .context("invalid replication control UDP config")?,
+                ),
+            ]))
         } else {
             Arc::new(DirectBootstrapDispatcher::from_env())
         };
@@ -46,8 +55,11 @@ async fn main() -> anyhow::Result<()> {
         .await
         .with_context(|| format!("failed to bind gateway on {socket_addr}"))?;
     println!("sidereal-gateway listening on {socket_addr}");
-    axum::serve(listener, app_with_service(service))
-        .await
-        .context("gateway server failed")?;
+    axum::serve(
+        listener,
+        app_with_service(service).into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .context("gateway server failed")?;
     Ok(())
 }
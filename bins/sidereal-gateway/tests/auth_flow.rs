@@ -7,7 +7,7 @@ use sidereal_gateway::auth::{
     AuthConfig, AuthError, AuthService, BootstrapCommand, BootstrapDispatcher, InMemoryAuthStore,
     RecordingBootstrapDispatcher,
 };
-use sidereal_persistence::{GraphEntityRecord, GraphPersistence};
+use sidereal_persistence::{GraphComponentRecord, GraphEntityRecord, GraphPersistence};
 use std::sync::Arc;
 use tower::ServiceExt;
 
@@ -297,6 +297,7 @@ async fn register_then_world_me_returns_starter_ship_and_assets() {
 
     assert_eq!(world_me_json["model_asset_id"], "corvette_01");
     assert_eq!(world_me_json["starfield_shader_asset_id"], "starfield_wgsl");
+    assert!(world_me_json["world_seed"].is_u64());
     assert!(
         world_me_json["ship_entity_id"]
             .as_str()
@@ -346,20 +347,25 @@ impl BootstrapDispatcher for PersistingBootstrapDispatcher {
                     components: Vec::new(),
                 },
                 GraphEntityRecord {
-                    entity_id: ship_entity_id,
+                    entity_id: ship_entity_id.clone(),
                     labels: vec!["Entity".to_string(), "Ship".to_string()],
                     properties: serde_json::json!({
                         "owner_account_id": command.account_id.to_string(),
                         "name": "Corvette",
-                        "asset_id": "corvette_01",
-                        "starfield_shader_asset_id": "starfield_wgsl",
                         "position_m": [0.0, 0.0, 0.0],
                         "velocity_mps": [0.0, 0.0, 0.0],
                         "heading_rad": 0.0,
                         "health": 100.0,
                         "max_health": 100.0
                     }),
-                    components: Vec::new(),
+                    components: vec![GraphComponentRecord {
+                        component_id: format!("{ship_entity_id}:visual_assets"),
+                        component_kind: "visual_assets".to_string(),
+                        properties: serde_json::json!({
+                            "model_asset_id": "corvette_01",
+                            "starfield_shader_asset_id": "starfield_wgsl",
+                        }),
+                    }],
                 },
             ];
             persistence
@@ -0,0 +1,271 @@
+use bevy::prelude::*;
+use sidereal_core::world_seed::WorldSeed;
+use std::collections::HashMap;
+
+use sidereal_game::{
+    AsteroidFieldSpec, AsteroidSpawn, GeneratedComponentRegistry, generate_asteroid_field,
+};
+use sidereal_net::{WorldComponentDelta, WorldDeltaEntity};
+use sidereal_persistence::GraphEntityRecord;
+
+use crate::{
+    ReplicationRuntime, ReplicationWorldSeed, component_registry_or_empty, component_type_path_map,
+    parse_guid_from_entity_id, parse_vec3_value,
+};
+
+pub const ASTEROID_LABEL: &str = "Asteroid";
+const DEFAULT_FIELD_RADIUS_M: f32 = 2_000.0;
+/// `WorldSeed::derive` purpose for the field seed.
+const FIELD_SEED_PURPOSE: &str = "asteroid_field";
+
+/// Reads the asteroid field config. The field is disabled unless
+/// `REPLICATION_ASTEROID_FIELD_COUNT` is set to a positive value. Its seed is
+/// derived from `world_seed` unless `REPLICATION_ASTEROID_FIELD_SEED` pins it.
+pub fn asteroid_field_spec_from_env(world_seed: WorldSeed) -> Option<AsteroidFieldSpec> {
+    let count = std::env::var("REPLICATION_ASTEROID_FIELD_COUNT")
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .filter(|v| *v > 0)?;
+    let radius = std::env::var("REPLICATION_ASTEROID_FIELD_RADIUS_M")
+        .ok()
+        .and_then(|v| v.parse::<f32>().ok())
+        .filter(|v| v.is_finite() && *v > 0.0)
+        .unwrap_or(DEFAULT_FIELD_RADIUS_M);
+    let seed = std::env::var("REPLICATION_ASTEROID_FIELD_SEED")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or_else(|| world_seed.derive(FIELD_SEED_PURPOSE));
+    let center = std::env::var("REPLICATION_ASTEROID_FIELD_CENTER_M")
+        .ok()
+        .and_then(|v| parse_center(&v))
+        .unwrap_or(Vec3::ZERO);
+    Some(AsteroidFieldSpec {
+        center,
+        radius,
+        count,
+        seed,
+    })
+}
+
+fn parse_center(raw: &str) -> Option<Vec3> {
+    let parts = raw
+        .split(',')
+        .map(|part| part.trim().parse::<f32>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    match parts.as_slice() {
+        [x, y] => Some(Vec3::new(*x, *y, 0.0)),
+        [x, y, z] => Some(Vec3::new(*x, *y, *z)),
+        _ => None,
+    }
+}
+
+/// True when any persisted asteroid already belongs to the configured field.
+pub fn asteroid_field_exists(records: &[GraphEntityRecord], spec: &AsteroidFieldSpec) -> bool {
+    let field_id = spec.field_id();
+    records.iter().any(|record| {
+        record.labels.iter().any(|label| label == ASTEROID_LABEL)
+            && record
+                .properties
+                .get("asteroid_field_id")
+                .and_then(|v| v.as_str())
+                == Some(field_id.as_str())
+    })
+}
+
+pub fn asteroid_field_world_delta(
+    spec: &AsteroidFieldSpec,
+    asteroids: &[AsteroidSpawn],
+    type_paths: &HashMap<String, String>,
+) -> Vec<WorldDeltaEntity> {
+    let field_id = spec.field_id();
+    asteroids
+        .iter()
+        .map(|asteroid| {
+            let entity_id = asteroid.entity_id();
+            let position = asteroid.position_m;
+            WorldDeltaEntity {
+                entity_id: entity_id.clone(),
+                labels: vec!["Entity".to_string(), ASTEROID_LABEL.to_string()],
+                properties: serde_json::json!({
+                    "entity_id": entity_id.as_str(),
+                    "asteroid_field_id": field_id.as_str(),
+                    "position_m": [position.x, position.y, position.z],
+                    "velocity_mps": [0.0, 0.0, 0.0],
+                    "heading_rad": 0.0,
+                    "radius_m": asteroid.radius_m,
+                    "mass_kg": asteroid.mass_kg,
+                    "health": asteroid.health,
+                    "max_health": asteroid.health,
+                }),
+                components: vec![
+                    WorldComponentDelta {
+                        component_id: format!("{entity_id}:display_name"),
+                        component_kind: "display_name".to_string(),
+                        properties: crate::wrap_component_payload(
+                            "display_name",
+                            serde_json::json!("Asteroid"),
+                            type_paths,
+                        ),
+                    },
+                    WorldComponentDelta {
+                        component_id: format!("{entity_id}:health_pool"),
+                        component_kind: "health_pool".to_string(),
+                        properties: crate::wrap_component_payload(
+                            "health_pool",
+                            serde_json::json!({
+                                "current": asteroid.health,
+                                "maximum": asteroid.health,
+                            }),
+                            type_paths,
+                        ),
+                    },
+                    WorldComponentDelta {
+                        component_id: format!("{entity_id}:mass_kg"),
+                        component_kind: "mass_kg".to_string(),
+                        properties: crate::wrap_component_payload(
+                            "mass_kg",
+                            serde_json::json!(asteroid.mass_kg),
+                            type_paths,
+                        ),
+                    },
+                ],
+                removed: false,
+                removed_component_ids: Vec::new(),
+                removal_reason: None,
+            }
+        })
+        .collect()
+}
+
+/// Rebuilds an asteroid spawn from its persisted graph record.
+pub fn asteroid_spawn_from_record(record: &GraphEntityRecord) -> Option<AsteroidSpawn> {
+    let entity_guid = parse_guid_from_entity_id(&record.entity_id).ok()?;
+    let props = &record.properties;
+    let position_m = props.get("position_m").and_then(parse_vec3_value)?;
+    let number = |key: &str| props.get(key).and_then(|v| v.as_f64()).map(|v| v as f32);
+    Some(AsteroidSpawn {
+        entity_guid,
+        position_m,
+        radius_m: number("radius_m")?,
+        mass_kg: number("mass_kg")?,
+        health: number("health").unwrap_or(0.0),
+    })
+}
+
+/// Startup system that persists the configured asteroid field once. Later
+/// startups find the field in the graph and leave it untouched; the asteroids
+/// are spawned by simulation hydration either way.
+pub fn seed_asteroid_field(
+    component_registry: Option<Res<'_, GeneratedComponentRegistry>>,
+    world_seed: Option<Res<'_, ReplicationWorldSeed>>,
+    runtime: Option<NonSendMut<'_, ReplicationRuntime>>,
+) {
+    let Some(world_seed) = world_seed else {
+        eprintln!("asteroid field seeding skipped; world seed unavailable");
+        return;
+    };
+    let Some(spec) = asteroid_field_spec_from_env(world_seed.0) else {
+        return;
+    };
+    let Some(mut runtime) = runtime else {
+        eprintln!("asteroid field seeding skipped; persistence unavailable");
+        return;
+    };
+    let persistence = &mut runtime.persistence;
+    let records = match persistence
+        .reader
+        .load_graph_records_filtered(&[ASTEROID_LABEL], None)
+    {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("asteroid field seeding skipped; graph load failed: {err}");
+            return;
+        }
+    };
+    if asteroid_field_exists(&records, &spec) {
+        println!("asteroid field {} already persisted", spec.field_id());
+        return;
+    }
+
+    let type_paths =
+        component_type_path_map(&component_registry_or_empty(component_registry.as_deref()));
+    let asteroids = generate_asteroid_field(&spec);
+    let delta = asteroid_field_world_delta(&spec, &asteroids, &type_paths);
+    if let Err(err) = persistence.writer.persist_world_delta(&delta, 0) {
+        eprintln!("asteroid field seeding failed: {err}");
+        return;
+    }
+    println!(
+        "seeded asteroid field {} with {} asteroids",
+        spec.field_id(),
+        asteroids.len()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec() -> AsteroidFieldSpec {
+        AsteroidFieldSpec {
+            center: Vec3::new(500.0, 500.0, 0.0),
+            radius: 1_000.0,
+            count: 8,
+            seed: 1234,
+        }
+    }
+
+    fn as_records(delta: Vec<WorldDeltaEntity>) -> Vec<GraphEntityRecord> {
+        delta
+            .into_iter()
+            .map(|entity| GraphEntityRecord {
+                entity_id: entity.entity_id,
+                labels: entity.labels,
+                properties: entity.properties,
+                components: Vec::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn field_is_detected_once_persisted() {
+        let spec = spec();
+        assert!(!asteroid_field_exists(&[], &spec));
+
+        let asteroids = generate_asteroid_field(&spec);
+        let records = as_records(asteroid_field_world_delta(
+            &spec,
+            &asteroids,
+            &HashMap::new(),
+        ));
+        assert!(asteroid_field_exists(&records, &spec));
+
+        let other_seed = AsteroidFieldSpec { seed: 4321, ..spec };
+        assert!(!asteroid_field_exists(&records, &other_seed));
+    }
+
+    #[test]
+    fn persisted_asteroid_records_roundtrip_to_spawns() {
+        let spec = spec();
+        let asteroids = generate_asteroid_field(&spec);
+        let records = as_records(asteroid_field_world_delta(
+            &spec,
+            &asteroids,
+            &HashMap::new(),
+        ));
+        let restored = records
+            .iter()
+            .map(asteroid_spawn_from_record)
+            .collect::<Option<Vec<_>>>()
+            .expect("all records restore");
+        assert_eq!(restored, asteroids);
+    }
+
+    #[test]
+    fn parse_center_accepts_two_or_three_components() {
+        assert_eq!(parse_center("10, -5"), Some(Vec3::new(10.0, -5.0, 0.0)));
+        assert_eq!(parse_center("1,2,3"), Some(Vec3::new(1.0, 2.0, 3.0)));
+        assert_eq!(parse_center("1"), None);
+        assert_eq!(parse_center("a,b"), None);
+    }
+}
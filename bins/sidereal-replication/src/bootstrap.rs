@@ -1,5 +1,6 @@
 use postgres::{Client, NoTls};
 use serde::Deserialize;
+use sidereal_core::entity_ids::TypedEntityId;
 use std::collections::HashSet;
 use std::time::{SystemTime, UNIX_EPOCH};
 use thiserror::Error;
@@ -12,12 +13,16 @@ pub struct BootstrapWireMessage {
     pub kind: String,
     pub account_id: String,
     pub player_entity_id: String,
+    /// Set by the gateway per registration; nil when an older gateway omits it.
+    #[serde(default)]
+    pub correlation_id: Uuid,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BootstrapCommand {
     pub account_id: Uuid,
     pub player_entity_id: String,
+    pub correlation_id: Uuid,
 }
 
 impl TryFrom<BootstrapWireMessage> for BootstrapCommand {
@@ -32,7 +37,7 @@ impl TryFrom<BootstrapWireMessage> for BootstrapCommand {
         }
         let account_id = Uuid::parse_str(&value.account_id)
             .map_err(|_| BootstrapError::Validation("invalid account_id uuid".to_string()))?;
-        let expected_player_entity_id = format!("player:{account_id}");
+        let expected_player_entity_id = TypedEntityId::player(account_id).to_string();
         if value.player_entity_id != expected_player_entity_id {
             return Err(BootstrapError::Validation(
                 "player_entity_id must match player:<account_uuid>".to_string(),
@@ -42,6 +47,7 @@ impl TryFrom<BootstrapWireMessage> for BootstrapCommand {
         Ok(Self {
             account_id,
             player_entity_id: value.player_entity_id,
+            correlation_id: value.correlation_id,
         })
     }
 }
@@ -50,6 +56,7 @@ impl TryFrom<BootstrapWireMessage> for BootstrapCommand {
 pub struct BootstrapHandleResult {
     pub account_id: Uuid,
     pub player_entity_id: String,
+    pub correlation_id: Uuid,
     pub applied: bool,
 }
 
@@ -77,11 +84,19 @@ impl<S: BootstrapStore> BootstrapProcessor<S> {
     ) -> Result<BootstrapHandleResult, BootstrapError> {
         let message: BootstrapWireMessage = serde_json::from_slice(payload)
             .map_err(|err| BootstrapError::Serialization(err.to_string()))?;
-        let command = BootstrapCommand::try_from(message)?;
+        let correlation_id = message.correlation_id;
+        let command = BootstrapCommand::try_from(message).inspect_err(|err| {
+            eprintln!("replication bootstrap rejected correlation_id={correlation_id}: {err}");
+        })?;
         let applied = self.store.apply_bootstrap_if_absent(&command)?;
+        println!(
+            "replication bootstrap handled correlation_id={} account_id={} applied={applied}",
+            command.correlation_id, command.account_id
+        );
         Ok(BootstrapHandleResult {
             account_id: command.account_id,
             player_entity_id: command.player_entity_id,
+            correlation_id: command.correlation_id,
             applied,
         })
     }
@@ -185,6 +200,7 @@ impl BootstrapStore for InMemoryBootstrapStore {
         self.events.push(BootstrapHandleResult {
             account_id: command.account_id,
             player_entity_id: command.player_entity_id.clone(),
+            correlation_id: command.correlation_id,
             applied,
         });
         Ok(applied)
@@ -235,6 +251,7 @@ mod tests {
 
         assert!(first.applied);
         assert!(!second.applied);
+        assert!(first.correlation_id.is_nil());
     }
 
     #[test]
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+/// Decides which replication ticks emit a diagnostic summary. An interval of
+/// zero disables sampling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TickSampler {
+    every_n_ticks: u64,
+}
+
+impl TickSampler {
+    pub fn new(every_n_ticks: u64) -> Self {
+        Self { every_n_ticks }
+    }
+
+    /// Reads `REPLICATION_TICK_LOG_EVERY`; unset or `0` disables the summary.
+    pub fn from_env() -> Self {
+        let every_n_ticks = std::env::var("REPLICATION_TICK_LOG_EVERY")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(0);
+        Self::new(every_n_ticks)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.every_n_ticks > 0
+    }
+
+    pub fn should_emit(&self, tick: u64) -> bool {
+        self.is_enabled() && tick.is_multiple_of(self.every_n_ticks)
+    }
+}
+
+/// Per-tick outbound counters, summed over every client the tick was sent to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TickSummary {
+    pub tick: u64,
+    pub clients: usize,
+    pub entities: usize,
+    pub removals: usize,
+    pub bytes: usize,
+    pub encode_errors: usize,
+    pub send_errors: usize,
+}
+
+impl fmt::Display for TickSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "replication tick={} clients={} entities={} removals={} bytes={} encode_errors={} send_errors={}",
+            self.tick,
+            self.clients,
+            self.entities,
+            self.removals,
+            self.bytes,
+            self.encode_errors,
+            self.send_errors
+        )
+    }
+}
+
+/// Cumulative bytes exchanged with one connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ConnectionBytes {
+    pub sent: u64,
+    pub received: u64,
+}
+
+/// Per-connection byte totals, keyed by whatever identifies a connection.
+/// Totals live as long as the connection; `retain_live` drops the rest.
+#[derive(Debug, Clone)]
+pub struct ByteAccounting<K> {
+    by_connection: HashMap<K, ConnectionBytes>,
+}
+
+impl<K> Default for ByteAccounting<K> {
+    fn default() -> Self {
+        Self {
+            by_connection: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Eq + Hash + Copy> ByteAccounting<K> {
+    pub fn record_sent(&mut self, connection: K, bytes: usize) {
+        let totals = self.by_connection.entry(connection).or_default();
+        totals.sent = totals.sent.saturating_add(bytes as u64);
+    }
+
+    pub fn record_received(&mut self, connection: K, bytes: usize) {
+        let totals = self.by_connection.entry(connection).or_default();
+        totals.received = totals.received.saturating_add(bytes as u64);
+    }
+
+    pub fn get(&self, connection: K) -> ConnectionBytes {
+        self.by_connection
+            .get(&connection)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (K, ConnectionBytes)> + '_ {
+        self.by_connection
+            .iter()
+            .map(|(connection, totals)| (*connection, *totals))
+    }
+
+    /// Forgets every connection `is_live` rejects.
+    pub fn retain_live(&mut self, mut is_live: impl FnMut(&K) -> bool) {
+        self.by_connection
+            .retain(|connection, _| is_live(connection));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sampler_emits_only_on_interval() {
+        let sampler = TickSampler::new(10);
+        let emitted = (1..=30)
+            .filter(|tick| sampler.should_emit(*tick))
+            .collect::<Vec<_>>();
+        assert_eq!(emitted, vec![10, 20, 30]);
+    }
+
+    #[test]
+    fn zero_interval_never_emits() {
+        let sampler = TickSampler::new(0);
+        assert!(!sampler.is_enabled());
+        assert!((0..100).all(|tick| !sampler.should_emit(tick)));
+    }
+
+    #[test]
+    fn summary_formats_as_key_value_pairs() {
+        let summary = TickSummary {
+            tick: 40,
+            clients: 2,
+            entities: 7,
+            removals: 1,
+            bytes: 2048,
+            ..Default::default()
+        };
+        assert_eq!(
+            summary.to_string(),
+            "replication tick=40 clients=2 entities=7 removals=1 bytes=2048 encode_errors=0 send_errors=0"
+        );
+    }
+
+    #[test]
+    fn byte_accounting_sums_per_connection_and_resets_on_cleanup() {
+        let mut accounting = ByteAccounting::default();
+        for bytes in [120, 64, 2_048] {
+            accounting.record_sent(1u32, bytes);
+        }
+        accounting.record_received(1, 40);
+        accounting.record_received(1, 38);
+        accounting.record_sent(2, 500);
+        assert_eq!(
+            accounting.get(1),
+            ConnectionBytes {
+                sent: 2_232,
+                received: 78
+            }
+        );
+        assert_eq!(accounting.get(2).sent, 500);
+
+        accounting.retain_live(|connection| *connection == 2);
+        assert_eq!(accounting.get(1), ConnectionBytes::default());
+        assert_eq!(accounting.iter().count(), 1);
+        // A connection that comes back starts from zero.
+        accounting.record_received(1, 10);
+        assert_eq!(accounting.get(1).received, 10);
+    }
+}
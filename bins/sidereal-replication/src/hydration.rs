@@ -0,0 +1,187 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use uuid::Uuid;
+
+pub const DEFAULT_HYDRATION_BATCH_SIZE: usize = 256;
+
+/// How far startup hydration has got.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HydrationProgress {
+    pub processed: usize,
+    pub total: usize,
+}
+
+impl HydrationProgress {
+    pub fn is_complete(&self) -> bool {
+        self.processed >= self.total
+    }
+}
+
+/// FIFO of graph records still to be spawned, drained a bounded batch per
+/// frame so a large world does not stall boot. Records are spawned in queue
+/// order, so callers enqueue parents before the records that reference them.
+#[derive(Debug, Clone)]
+pub struct HydrationQueue<T> {
+    pending: VecDeque<T>,
+    batch_size: usize,
+    processed: usize,
+    total: usize,
+    cancelled: bool,
+}
+
+impl<T> HydrationQueue<T> {
+    /// A `batch_size` of zero drains everything in the first batch.
+    pub fn new(records: impl IntoIterator<Item = T>, batch_size: usize) -> Self {
+        let pending = records.into_iter().collect::<VecDeque<_>>();
+        let total = pending.len();
+        Self {
+            pending,
+            batch_size,
+            processed: 0,
+            total,
+            cancelled: false,
+        }
+    }
+
+    /// Reads `REPLICATION_HYDRATION_BATCH_SIZE` (`0` = no cap).
+    pub fn from_env(records: impl IntoIterator<Item = T>) -> Self {
+        let batch_size = std::env::var("REPLICATION_HYDRATION_BATCH_SIZE")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(DEFAULT_HYDRATION_BATCH_SIZE);
+        Self::new(records, batch_size)
+    }
+
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Takes the next batch. Empty once the queue is drained or cancelled.
+    pub fn next_batch(&mut self) -> Vec<T> {
+        let take = if self.batch_size == 0 {
+            self.pending.len()
+        } else {
+            self.batch_size.min(self.pending.len())
+        };
+        self.processed += take;
+        self.pending.drain(..take).collect()
+    }
+
+    /// Drops every record not yet handed out. Progress then reports the
+    /// reduced total.
+    pub fn cancel(&mut self) {
+        self.pending.clear();
+        self.total = self.processed;
+        self.cancelled = true;
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn progress(&self) -> HydrationProgress {
+        HydrationProgress {
+            processed: self.processed,
+            total: self.total,
+        }
+    }
+}
+
+/// EntityGuids already handed to a hydrated entity. `MountedOn` and parent
+/// links resolve by guid, so two entities sharing one would silently attach
+/// modules to the wrong hull.
+#[derive(Debug, Clone, Default)]
+pub struct HydratedGuids {
+    owner_by_guid: HashMap<Uuid, String>,
+}
+
+impl HydratedGuids {
+    /// Records `guid` as belonging to `entity_id`. If another entity already
+    /// holds it, returns that entity's id and leaves the claim unchanged.
+    pub fn claim(&mut self, guid: Uuid, entity_id: &str) -> Result<(), String> {
+        match self.owner_by_guid.entry(guid) {
+            Entry::Occupied(owner) => Err(owner.get().clone()),
+            Entry::Vacant(slot) => {
+                slot.insert(entity_id.to_string());
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queue_drains_n_per_batch_in_order() {
+        let mut queue = HydrationQueue::new(0..7, 3);
+        let mut batches = Vec::new();
+        while !queue.is_empty() {
+            batches.push(queue.next_batch());
+        }
+        assert_eq!(batches, vec![vec![0, 1, 2], vec![3, 4, 5], vec![6]]);
+        assert!(queue.next_batch().is_empty());
+        assert!(queue.progress().is_complete());
+        assert_eq!(queue.progress().processed, 7);
+    }
+
+    #[test]
+    fn progress_tracks_each_batch() {
+        let mut queue = HydrationQueue::new(["a", "b", "c", "d"], 2);
+        assert_eq!(
+            queue.progress(),
+            HydrationProgress {
+                processed: 0,
+                total: 4
+            }
+        );
+        queue.next_batch();
+        assert_eq!(queue.progress().processed, 2);
+        assert!(!queue.progress().is_complete());
+    }
+
+    #[test]
+    fn zero_batch_size_drains_everything_at_once() {
+        let mut queue = HydrationQueue::new(0..500, 0);
+        assert_eq!(queue.next_batch().len(), 500);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn duplicate_guid_is_claimed_only_once() {
+        let guid = Uuid::new_v4();
+        let mut guids = HydratedGuids::default();
+        assert!(guids.claim(guid, &format!("ship:{guid}")).is_ok());
+        assert_eq!(
+            guids.claim(guid, &format!("hardpoint:{guid}")),
+            Err(format!("ship:{guid}"))
+        );
+        // The original owner keeps the guid.
+        assert_eq!(
+            guids.claim(guid, &format!("module:{guid}")),
+            Err(format!("ship:{guid}"))
+        );
+        assert!(guids.claim(Uuid::new_v4(), "ship:other").is_ok());
+    }
+
+    #[test]
+    fn cancel_drops_remaining_records() {
+        let mut queue = HydrationQueue::new(0..10, 4);
+        queue.next_batch();
+        queue.cancel();
+        assert!(queue.is_cancelled());
+        assert!(queue.next_batch().is_empty());
+        assert_eq!(
+            queue.progress(),
+            HydrationProgress {
+                processed: 4,
+                total: 4
+            }
+        );
+    }
+}
@@ -0,0 +1,59 @@
+use sidereal_core::SIM_TICK_HZ;
+
+/// How far a client's input tick may run ahead of the time elapsed since its
+/// last applied tick: one second of sim ticks, for jitter and batching.
+pub const MAX_INPUT_TICK_LEAD: u64 = SIM_TICK_HZ as u64;
+
+/// The newest input tick applied for one client and when, in seconds of real
+/// time, it was applied.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AppliedInputTick {
+    pub tick: u64,
+    pub applied_at_s: f64,
+}
+
+impl AppliedInputTick {
+    /// Newest tick the client may send at `now_s`. Its tick clock runs at
+    /// `SIM_TICK_HZ` like the server's, so it cannot honestly have moved
+    /// further than the time since `applied_at_s` plus `MAX_INPUT_TICK_LEAD`.
+    /// A tick past this is dropped rather than recorded, so one bogus tick
+    /// (e.g. `u64::MAX`) cannot make every later input look stale.
+    pub fn newest_allowed(&self, now_s: f64) -> u64 {
+        let elapsed_ticks = ((now_s - self.applied_at_s).max(0.0) * f64::from(SIM_TICK_HZ)) as u64;
+        self.tick
+            .saturating_add(elapsed_ticks)
+            .saturating_add(MAX_INPUT_TICK_LEAD)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allowance_grows_with_elapsed_time() {
+        let last = AppliedInputTick {
+            tick: 100,
+            applied_at_s: 10.0,
+        };
+        assert_eq!(last.newest_allowed(10.0), 100 + MAX_INPUT_TICK_LEAD);
+        // Two seconds later the client may be 60 ticks further on.
+        assert_eq!(last.newest_allowed(12.0), 160 + MAX_INPUT_TICK_LEAD);
+        // A clock that went backwards grants nothing extra.
+        assert_eq!(last.newest_allowed(5.0), 100 + MAX_INPUT_TICK_LEAD);
+    }
+
+    #[test]
+    fn huge_ticks_are_out_of_bounds() {
+        let last = AppliedInputTick {
+            tick: 100,
+            applied_at_s: 10.0,
+        };
+        assert!(u64::MAX > last.newest_allowed(3_600.0));
+        let saturated = AppliedInputTick {
+            tick: u64::MAX - 1,
+            applied_at_s: 0.0,
+        };
+        assert_eq!(saturated.newest_allowed(1.0), u64::MAX);
+    }
+}
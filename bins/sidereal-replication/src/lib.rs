@@ -1,2 +1,12 @@
 pub mod bootstrap;
+pub mod diagnostics;
+pub mod hydration;
+pub mod input_ticks;
+pub mod priority;
+pub mod readiness;
+pub mod reconnect_grace;
+pub mod rewind;
+pub mod separation;
+pub mod session;
 pub mod state;
+pub mod weapons;
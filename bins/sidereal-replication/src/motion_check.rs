@@ -0,0 +1,293 @@
+//! Server-side motion sanity checks for player-controlled entities.
+//!
+//! Inputs only select thrust/yaw intents, so every legitimate step of a
+//! controlled ship is bounded by its engines. Before each tick's inputs are
+//! applied, the transition made by the previous physics step is compared with
+//! those bounds; on a violation the player's queued inputs are rejected and the
+//! offender is logged. A step in which the ship was touching anything is
+//! skipped, since contact impulses are not engine thrust.
+
+use avian3d::prelude::{
+    Collisions, Gravity, LinearDamping, LinearVelocity, Position, Rotation, SubstepCount,
+};
+use bevy::prelude::*;
+use sidereal_game::{
+    ActionQueue, Engine, EntityAction, EntityGuid, FlightComputer, MountedOn, TotalMassKg,
+    max_commanded_accel_mps2,
+};
+use sidereal_sim_core::{EntityKinematics, MotionLimits, check_motion_transition};
+use std::collections::HashMap;
+
+use crate::SimulatedControlledEntity;
+
+const DEFAULT_MOTION_TOLERANCE: f32 = 1.5;
+
+#[derive(Debug, Resource)]
+pub struct MotionCheckState {
+    /// Multiplier on engine limits; see `REPLICATION_MOTION_TOLERANCE`.
+    pub tolerance: f32,
+    last_kinematics: HashMap<Entity, EntityKinematics>,
+    pub violations_by_player: HashMap<String, u32>,
+}
+
+impl Default for MotionCheckState {
+    fn default() -> Self {
+        Self {
+            tolerance: DEFAULT_MOTION_TOLERANCE,
+            last_kinematics: HashMap::new(),
+            violations_by_player: HashMap::new(),
+        }
+    }
+}
+
+impl MotionCheckState {
+    pub fn from_env() -> Self {
+        let tolerance = std::env::var("REPLICATION_MOTION_TOLERANCE")
+            .ok()
+            .and_then(|v| v.parse::<f32>().ok())
+            .filter(|v| v.is_finite() && *v >= 1.0)
+            .unwrap_or(DEFAULT_MOTION_TOLERANCE);
+        Self {
+            tolerance,
+            ..Self::default()
+        }
+    }
+}
+
+/// Limits for a hull: the most acceleration its flight controls can command
+/// at `speed_mps` (thrust, brakes and overspeed governor) plus gravity, and
+/// the flight computer's turn rate. `drag_per_s` is in the check's
+/// `1 - drag * dt` form; see `avian_drag_per_s`.
+pub fn hull_motion_limits(
+    thrust_n: f32,
+    mass_kg: f32,
+    speed_mps: f32,
+    turn_rate_deg_s: f32,
+    drag_per_s: f32,
+    gravity_mps2: f32,
+    tolerance: f32,
+) -> MotionLimits {
+    MotionLimits {
+        max_accel_mps2: max_commanded_accel_mps2(thrust_n, mass_kg, speed_mps) + gravity_mps2,
+        max_yaw_rate_rad_per_s: turn_rate_deg_s.to_radians(),
+        drag_per_s,
+        tolerance,
+    }
+}
+
+/// Avian damps velocity by `1 / (1 + c * h)` on each of `substeps` substeps
+/// of a `dt_s` step. Returns the coefficient that removes the same fraction
+/// of velocity in the check's single `1 - drag * dt` step.
+pub fn avian_drag_per_s(damping_per_s: f32, dt_s: f32, substeps: u32) -> f32 {
+    if dt_s <= 0.0 {
+        return 0.0;
+    }
+    let substeps = substeps.max(1);
+    let substep_s = dt_s / substeps as f32;
+    let retained = (1.0 + damping_per_s.max(0.0) * substep_s).powi(-(substeps as i32));
+    (1.0 - retained) / dt_s
+}
+
+/// Inputs that only release a control. A rejected queue keeps them, so a
+/// flagged player can still let go of thrust or yaw.
+fn is_release(action: EntityAction) -> bool {
+    matches!(
+        action,
+        EntityAction::ThrustNeutral | EntityAction::YawNeutral
+    )
+}
+
+fn kinematics(
+    position: &Position,
+    velocity: &LinearVelocity,
+    rotation: &Rotation,
+) -> EntityKinematics {
+    EntityKinematics {
+        position_m: position.0.to_array(),
+        velocity_mps: velocity.0.to_array(),
+        heading_rad: rotation.0.to_euler(EulerRot::ZYX).0,
+        orientation: rotation.0.to_array(),
+    }
+}
+
+/// Runs in `FixedPreUpdate`: each sample is the state the previous physics step
+/// left, one fixed tick after the last sample, and a rejected queue is cut
+/// down to its releases before this tick's flight systems read it.
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+pub fn validate_controlled_entity_motion(
+    time: Res<'_, Time>,
+    substeps: Option<Res<'_, SubstepCount>>,
+    gravity: Option<Res<'_, Gravity>>,
+    collisions: Collisions<'_, '_>,
+    mut state: ResMut<'_, MotionCheckState>,
+    mut ships: Query<
+        '_,
+        '_,
+        (
+            Entity,
+            &SimulatedControlledEntity,
+            &EntityGuid,
+            &Position,
+            &LinearVelocity,
+            &Rotation,
+            &FlightComputer,
+            Option<&TotalMassKg>,
+            Option<&LinearDamping>,
+            &mut ActionQueue,
+        ),
+    >,
+    engines: Query<'_, '_, (&MountedOn, &Engine)>,
+) {
+    let dt = time.delta_secs();
+    let substeps = substeps.map_or(1, |substeps| substeps.0);
+    let gravity_mps2 = gravity.map_or(0.0, |gravity| gravity.0.length());
+    let mut thrust_by_parent = HashMap::new();
+    for (mounted_on, engine) in &engines {
+        *thrust_by_parent
+            .entry(mounted_on.parent_entity_id)
+            .or_insert(0.0) += engine.thrust_n.abs();
+    }
+
+    let state = &mut *state;
+    state
+        .last_kinematics
+        .retain(|entity, _| ships.contains(*entity));
+    for (
+        entity,
+        controlled,
+        guid,
+        position,
+        velocity,
+        rotation,
+        computer,
+        mass,
+        damping,
+        mut queue,
+    ) in &mut ships
+    {
+        let next = kinematics(position, velocity, rotation);
+        let Some(prev) = state.last_kinematics.insert(entity, next) else {
+            continue;
+        };
+        if collisions.entities_colliding_with(entity).next().is_some() {
+            continue;
+        }
+        let Some(mass) = mass else {
+            continue;
+        };
+        let limits = hull_motion_limits(
+            thrust_by_parent.get(&guid.0).copied().unwrap_or(0.0),
+            mass.0,
+            Vec3::from_array(prev.velocity_mps).length(),
+            computer.turn_rate_deg_s,
+            avian_drag_per_s(damping.map_or(0.0, |d| d.0), dt, substeps),
+            gravity_mps2,
+            state.tolerance,
+        );
+        if let Err(violation) = check_motion_transition(&prev, &next, &limits, dt) {
+            let count = state
+                .violations_by_player
+                .entry(controlled.player_entity_id.clone())
+                .or_insert(0);
+            *count += 1;
+            eprintln!(
+                "replication rejected inputs for player={} entity={}: {violation} (violations={count})",
+                controlled.player_entity_id, controlled.entity_id
+            );
+            queue.pending.retain(|action| is_release(*action));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hull_limits_follow_thrust_to_mass() {
+        let limits = hull_motion_limits(30_000.0, 15_000.0, 100.0, 90.0, 0.0, 0.0, 1.5);
+        assert_eq!(limits.max_accel_mps2, 2.0);
+        assert!((limits.max_yaw_rate_rad_per_s - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+
+        let coasting = EntityKinematics {
+            velocity_mps: [100.0, 0.0, 0.0],
+            ..Default::default()
+        };
+        let thrusting = EntityKinematics {
+            position_m: [100.0 * 0.1, 0.0, 0.0],
+            velocity_mps: [100.0 + 2.0 * 0.1, 0.0, 0.0],
+            ..Default::default()
+        };
+        assert!(check_motion_transition(&coasting, &thrusting, &limits, 0.1).is_ok());
+    }
+
+    #[test]
+    fn engineless_hull_cannot_accelerate() {
+        let limits = hull_motion_limits(0.0, 15_000.0, 0.0, 90.0, 0.12, 0.0, 1.5);
+        let prev = EntityKinematics::default();
+        let next = EntityKinematics {
+            position_m: [0.5, 0.0, 0.0],
+            velocity_mps: [5.0, 0.0, 0.0],
+            ..Default::default()
+        };
+        assert!(check_motion_transition(&prev, &next, &limits, 0.1).is_err());
+    }
+
+    #[test]
+    fn engineless_hull_may_coast_down_after_a_bump() {
+        // A turret knocked to 4 m/s sheds speed to its passive brake and
+        // heavy damping, neither of which is engine thrust.
+        let (dt, damping, substeps) = (1.0 / 30.0, 10.0, 6);
+        let limits = hull_motion_limits(
+            0.0,
+            4_000.0,
+            4.0,
+            90.0,
+            avian_drag_per_s(damping, dt, substeps),
+            0.0,
+            1.0,
+        );
+        let mut velocity = 4.0_f32;
+        let substep = dt / substeps as f32;
+        for _ in 0..substeps {
+            velocity = velocity / (1.0 + damping * substep) - 1.5 * substep;
+        }
+        let prev = EntityKinematics {
+            velocity_mps: [4.0, 0.0, 0.0],
+            ..Default::default()
+        };
+        let next = EntityKinematics {
+            position_m: [velocity * dt, 0.0, 0.0],
+            velocity_mps: [velocity, 0.0, 0.0],
+            ..Default::default()
+        };
+        assert!(check_motion_transition(&prev, &next, &limits, dt).is_ok());
+    }
+
+    #[test]
+    fn avian_drag_matches_the_substepped_damping() {
+        let (dt, damping) = (1.0 / 30.0, 0.12);
+        assert!((avian_drag_per_s(damping, dt, 1) - damping / (1.0 + damping * dt)).abs() < 1e-5);
+        let retained = (1.0 + damping * dt / 6.0).powi(-6);
+        assert!((1.0 - avian_drag_per_s(damping, dt, 6) * dt - retained).abs() < 1e-6);
+        assert_eq!(avian_drag_per_s(damping, 0.0, 6), 0.0);
+    }
+
+    #[test]
+    fn rejection_keeps_only_releases() {
+        let mut queue = ActionQueue {
+            pending: vec![
+                EntityAction::ThrustForward,
+                EntityAction::YawNeutral,
+                EntityAction::YawLeft,
+                EntityAction::ThrustNeutral,
+            ],
+        };
+        queue.pending.retain(|action| is_release(*action));
+        assert_eq!(
+            queue.pending,
+            vec![EntityAction::YawNeutral, EntityAction::ThrustNeutral]
+        );
+    }
+}
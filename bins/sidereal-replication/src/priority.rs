@@ -0,0 +1,186 @@
+use bevy::math::Vec3;
+use sidereal_net::WorldDeltaEntity;
+use std::collections::{HashMap, HashSet};
+
+/// Score given to an entity on its first send, so newly visible entities go
+/// ahead of everything already known to clients.
+pub const FIRST_SEND_ACTIVITY: f32 = f32::MAX;
+
+/// Replicated state an activity score is derived from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActivitySample {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub health: f32,
+}
+
+/// Score per unit of change since the last send. Velocity and health changes
+/// weigh more than distance because a ship coasting in a straight line is
+/// cheap for clients to extrapolate, while a burn or a hit is not.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActivityWeights {
+    pub per_position_m: f32,
+    pub per_velocity_mps: f32,
+    pub per_health: f32,
+}
+
+impl Default for ActivityWeights {
+    fn default() -> Self {
+        Self {
+            per_position_m: 1.0,
+            per_velocity_mps: 4.0,
+            per_health: 10.0,
+        }
+    }
+}
+
+pub fn activity_score(
+    previous: &ActivitySample,
+    current: &ActivitySample,
+    weights: &ActivityWeights,
+) -> f32 {
+    (current.position - previous.position).length() * weights.per_position_m
+        + (current.velocity - previous.velocity).length() * weights.per_velocity_mps
+        + (current.health - previous.health).abs() * weights.per_health
+}
+
+/// Last sent sample per entity id, used to score the next send.
+#[derive(Debug, Clone, Default)]
+pub struct ActivityTracker {
+    weights: ActivityWeights,
+    last_sent: HashMap<String, ActivitySample>,
+}
+
+impl ActivityTracker {
+    pub fn new(weights: ActivityWeights) -> Self {
+        Self {
+            weights,
+            last_sent: HashMap::new(),
+        }
+    }
+
+    /// Scores `sample` against the last one sent for `entity_id` and records it
+    /// as the new baseline.
+    pub fn score(&mut self, entity_id: &str, sample: ActivitySample) -> f32 {
+        match self.last_sent.insert(entity_id.to_string(), sample) {
+            Some(previous) => activity_score(&previous, &sample, &self.weights),
+            None => FIRST_SEND_ACTIVITY,
+        }
+    }
+
+    /// Forgets entities not in `live`, so one that reappears scores as new.
+    pub fn retain_live(&mut self, live: &HashSet<String>) {
+        self.last_sent
+            .retain(|entity_id, _| live.contains(entity_id));
+    }
+}
+
+/// Orders `updates` most active first. Unscored updates (removals, modules,
+/// hardpoints) keep their relative order after every scored one.
+pub fn order_by_activity(updates: &mut [WorldDeltaEntity], activity: &HashMap<String, f32>) {
+    updates.sort_by(|a, b| {
+        let score = |update: &WorldDeltaEntity| activity.get(&update.entity_id).copied();
+        match (score(a), score(b)) {
+            (Some(a), Some(b)) => b.total_cmp(&a),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(position: Vec3, velocity: Vec3, health: f32) -> ActivitySample {
+        ActivitySample {
+            position,
+            velocity,
+            health,
+        }
+    }
+
+    #[test]
+    fn large_change_scores_higher_than_drift() {
+        let mut tracker = ActivityTracker::default();
+        let start = sample(Vec3::ZERO, Vec3::new(5.0, 0.0, 0.0), 100.0);
+        assert_eq!(tracker.score("ship:drift", start), FIRST_SEND_ACTIVITY);
+        assert_eq!(tracker.score("ship:burn", start), FIRST_SEND_ACTIVITY);
+
+        let drifting = tracker.score(
+            "ship:drift",
+            sample(Vec3::new(0.17, 0.0, 0.0), Vec3::new(5.0, 0.0, 0.0), 100.0),
+        );
+        let burning = tracker.score(
+            "ship:burn",
+            sample(Vec3::new(2.0, 1.0, 0.0), Vec3::new(40.0, 12.0, 0.0), 100.0),
+        );
+        assert!(burning > drifting);
+        assert!(drifting < 1.0);
+    }
+
+    #[test]
+    fn damage_raises_the_score() {
+        let weights = ActivityWeights::default();
+        let before = sample(Vec3::ZERO, Vec3::ZERO, 100.0);
+        let still = activity_score(&before, &before, &weights);
+        let hit = activity_score(&before, &sample(Vec3::ZERO, Vec3::ZERO, 85.0), &weights);
+        assert_eq!(still, 0.0);
+        assert!(hit > still);
+    }
+
+    #[test]
+    fn score_is_relative_to_the_last_send() {
+        let mut tracker = ActivityTracker::default();
+        let moving = sample(Vec3::ZERO, Vec3::new(30.0, 0.0, 0.0), 100.0);
+        tracker.score("ship:a", sample(Vec3::ZERO, Vec3::ZERO, 100.0));
+        let accelerating = tracker.score("ship:a", moving);
+        let holding = tracker.score("ship:a", moving);
+        assert!(accelerating > 0.0);
+        assert_eq!(holding, 0.0);
+
+        tracker.retain_live(&HashSet::new());
+        assert_eq!(tracker.score("ship:a", moving), FIRST_SEND_ACTIVITY);
+    }
+
+    #[test]
+    fn updates_are_ordered_most_active_first() {
+        let update = |entity_id: &str| WorldDeltaEntity {
+            entity_id: entity_id.to_string(),
+            labels: Vec::new(),
+            properties: serde_json::json!({}),
+            components: Vec::new(),
+            removed: false,
+            removed_component_ids: Vec::new(),
+            removal_reason: None,
+        };
+        let mut updates = vec![
+            update("hardpoint:a"),
+            update("ship:drift"),
+            update("module:b"),
+            update("ship:burn"),
+            update("ship:new"),
+        ];
+        let activity = HashMap::from([
+            ("ship:drift".to_string(), 0.2),
+            ("ship:burn".to_string(), 48.0),
+            ("ship:new".to_string(), FIRST_SEND_ACTIVITY),
+        ]);
+        order_by_activity(&mut updates, &activity);
+        let order = updates
+            .iter()
+            .map(|update| update.entity_id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            order,
+            [
+                "ship:new",
+                "ship:burn",
+                "ship:drift",
+                "hardpoint:a",
+                "module:b"
+            ]
+        );
+    }
+}
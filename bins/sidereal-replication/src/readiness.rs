@@ -0,0 +1,187 @@
+use std::fmt::Display;
+use std::time::Duration;
+
+/// What replication does when the database never becomes reachable at startup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnavailablePolicy {
+    /// Exit the process with a nonzero status.
+    Exit,
+    /// Keep the process up for inspection but never start the client listener.
+    Degraded,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadinessConfig {
+    /// Total time spent backing off before giving up.
+    pub window: Duration,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub on_unavailable: UnavailablePolicy,
+}
+
+impl Default for ReadinessConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(30),
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(5),
+            on_unavailable: UnavailablePolicy::Exit,
+        }
+    }
+}
+
+impl ReadinessConfig {
+    /// Reads `REPLICATION_DB_READY_TIMEOUT_S` and `REPLICATION_DB_UNAVAILABLE_MODE`
+    /// (`exit` or `degraded`).
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let window = std::env::var("REPLICATION_DB_READY_TIMEOUT_S")
+            .ok()
+            .and_then(|v| v.parse::<f32>().ok())
+            .filter(|v| v.is_finite() && *v >= 0.0)
+            .map(Duration::from_secs_f32)
+            .unwrap_or(defaults.window);
+        let on_unavailable = match std::env::var("REPLICATION_DB_UNAVAILABLE_MODE") {
+            Ok(mode) if mode.eq_ignore_ascii_case("degraded") => UnavailablePolicy::Degraded,
+            _ => UnavailablePolicy::Exit,
+        };
+        Self {
+            window,
+            on_unavailable,
+            ..defaults
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ReadinessOutcome<T> {
+    Ready {
+        value: T,
+        attempts: u32,
+    },
+    Unavailable {
+        policy: UnavailablePolicy,
+        attempts: u32,
+        last_error: String,
+    },
+}
+
+/// Calls `connect` until it succeeds or the backoff window is spent. Backoff
+/// doubles from `initial_backoff` up to `max_backoff`; the final sleep is
+/// trimmed so the total never exceeds `window`.
+pub fn wait_for_database<T, E: Display>(
+    config: &ReadinessConfig,
+    mut connect: impl FnMut() -> Result<T, E>,
+    mut sleep: impl FnMut(Duration),
+) -> ReadinessOutcome<T> {
+    let mut attempts = 0;
+    let mut waited = Duration::ZERO;
+    let mut backoff = config.initial_backoff;
+    loop {
+        attempts += 1;
+        let err = match connect() {
+            Ok(value) => return ReadinessOutcome::Ready { value, attempts },
+            Err(err) => err.to_string(),
+        };
+        let remaining = config.window.saturating_sub(waited);
+        if remaining.is_zero() {
+            return ReadinessOutcome::Unavailable {
+                policy: config.on_unavailable,
+                attempts,
+                last_error: err,
+            };
+        }
+        let delay = backoff.min(remaining);
+        eprintln!(
+            "replication database not ready (attempt {attempts}): {err}; retrying in {:.2}s",
+            delay.as_secs_f32()
+        );
+        sleep(delay);
+        waited += delay;
+        backoff = (backoff * 2).min(config.max_backoff);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(policy: UnavailablePolicy) -> ReadinessConfig {
+        ReadinessConfig {
+            window: Duration::from_secs(3),
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(1),
+            on_unavailable: policy,
+        }
+    }
+
+    #[test]
+    fn retries_failures_until_connect_succeeds() {
+        let mut results = vec![Err("refused"), Err("refused"), Ok("db")].into_iter();
+        let mut sleeps = Vec::new();
+
+        let outcome = wait_for_database(
+            &config(UnavailablePolicy::Exit),
+            || results.next().expect("connect called too often"),
+            |delay| sleeps.push(delay),
+        );
+
+        match outcome {
+            ReadinessOutcome::Ready { value, attempts } => {
+                assert_eq!(value, "db");
+                assert_eq!(attempts, 3);
+            }
+            other => panic!("expected ready, got {other:?}"),
+        }
+        assert_eq!(
+            sleeps,
+            vec![Duration::from_millis(500), Duration::from_secs(1)]
+        );
+    }
+
+    #[test]
+    fn gives_up_with_policy_once_window_is_spent() {
+        let mut sleeps = Vec::new();
+        let outcome = wait_for_database(
+            &config(UnavailablePolicy::Degraded),
+            || Err::<(), _>("refused"),
+            |delay| sleeps.push(delay),
+        );
+
+        match outcome {
+            ReadinessOutcome::Unavailable {
+                policy,
+                attempts,
+                last_error,
+            } => {
+                assert_eq!(policy, UnavailablePolicy::Degraded);
+                assert_eq!(attempts, 5);
+                assert_eq!(last_error, "refused");
+            }
+            other => panic!("expected unavailable, got {other:?}"),
+        }
+        // 0.5 + 1 + 1 + 0.5 (trimmed to the window)
+        assert_eq!(sleeps.iter().sum::<Duration>(), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn zero_window_fails_after_single_attempt() {
+        let config = ReadinessConfig {
+            window: Duration::ZERO,
+            ..config(UnavailablePolicy::Exit)
+        };
+        let outcome = wait_for_database(
+            &config,
+            || Err::<(), _>("refused"),
+            |_| panic!("must not sleep"),
+        );
+        assert!(matches!(
+            outcome,
+            ReadinessOutcome::Unavailable {
+                policy: UnavailablePolicy::Exit,
+                attempts: 1,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::time::Duration;
+
+/// What a disconnect timer decided for one player this update.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraceEvent {
+    /// The player's session just ended while they had a ship; the ship keeps
+    /// simulating, drifting, until they reconnect or the window runs out.
+    Started(String),
+    /// The player did not reconnect within the window.
+    Expired(String),
+}
+
+/// Grace window between a player's client disconnecting and their ship being
+/// treated as left behind. A brief drop that reconnects inside the window
+/// finds the ship exactly where it drifted to; once the window runs out the
+/// ship is persisted and, if `despawn_on_expiry` is set, removed from the
+/// simulation.
+///
+/// Only players seen with a live session are timed, so ships hydrated for
+/// players who are simply offline at startup are left alone.
+#[derive(Debug, Clone)]
+pub struct ReconnectGrace {
+    pub window: Duration,
+    pub despawn_on_expiry: bool,
+    bound_last_update: HashSet<String>,
+    disconnected_at_s: HashMap<String, f64>,
+}
+
+impl Default for ReconnectGrace {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(30), false)
+    }
+}
+
+impl ReconnectGrace {
+    pub fn new(window: Duration, despawn_on_expiry: bool) -> Self {
+        Self {
+            window,
+            despawn_on_expiry,
+            bound_last_update: HashSet::new(),
+            disconnected_at_s: HashMap::new(),
+        }
+    }
+
+    /// Reads `REPLICATION_RECONNECT_GRACE_S` (seconds, default 30) and
+    /// `REPLICATION_DESPAWN_AFTER_GRACE` (`1`/`true`, default off).
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let window = std::env::var("REPLICATION_RECONNECT_GRACE_S")
+            .ok()
+            .and_then(|v| v.parse::<f32>().ok())
+            .filter(|v| v.is_finite() && *v >= 0.0)
+            .map(Duration::from_secs_f32)
+            .unwrap_or(defaults.window);
+        let despawn_on_expiry = std::env::var("REPLICATION_DESPAWN_AFTER_GRACE")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(defaults.despawn_on_expiry);
+        Self::new(window, despawn_on_expiry)
+    }
+
+    /// Whether `player_entity_id` is disconnected with its window still open.
+    pub fn is_waiting(&self, player_entity_id: &str) -> bool {
+        self.disconnected_at_s.contains_key(player_entity_id)
+    }
+
+    /// Advances the timers. `bound` holds players with an authenticated
+    /// session, `with_ships` players that currently have a simulated ship.
+    /// A player who reconnects, or whose ship is gone, stops being timed.
+    /// Events are sorted by player id.
+    pub fn update(
+        &mut self,
+        bound: &HashSet<String>,
+        with_ships: &HashSet<String>,
+        now_s: f64,
+    ) -> Vec<GraceEvent> {
+        let keep = |player: &String| !bound.contains(player) && with_ships.contains(player);
+        self.disconnected_at_s.retain(|player, _| keep(player));
+
+        let mut events = Vec::new();
+        let just_disconnected = self
+            .bound_last_update
+            .iter()
+            .filter(|player| keep(player))
+            .collect::<BTreeSet<_>>();
+        for player in just_disconnected {
+            self.disconnected_at_s.insert(player.clone(), now_s);
+            events.push(GraceEvent::Started(player.clone()));
+        }
+        let timed_out = self
+            .disconnected_at_s
+            .iter()
+            .filter(|(_, since_s)| now_s - **since_s >= self.window.as_secs_f64())
+            .map(|(player, _)| player.clone())
+            .collect::<BTreeSet<_>>();
+        for player in timed_out {
+            self.disconnected_at_s.remove(&player);
+            events.push(GraceEvent::Expired(player));
+        }
+        self.bound_last_update = bound.clone();
+        events.sort_by(|a, b| grace_event_player(a).cmp(grace_event_player(b)));
+        events
+    }
+}
+
+fn grace_event_player(event: &GraceEvent) -> &str {
+    match event {
+        GraceEvent::Started(player) | GraceEvent::Expired(player) => player,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn players(ids: &[&str]) -> HashSet<String> {
+        ids.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn reconnect_within_window_keeps_the_ship() {
+        let mut grace = ReconnectGrace::new(Duration::from_secs(10), true);
+        let ships = players(&["player:a"]);
+        assert!(
+            grace
+                .update(&players(&["player:a"]), &ships, 0.0)
+                .is_empty()
+        );
+
+        assert_eq!(
+            grace.update(&players(&[]), &ships, 1.0),
+            vec![GraceEvent::Started("player:a".to_string())]
+        );
+        assert!(grace.is_waiting("player:a"));
+        assert!(grace.update(&players(&[]), &ships, 9.0).is_empty());
+
+        assert!(
+            grace
+                .update(&players(&["player:a"]), &ships, 10.5)
+                .is_empty()
+        );
+        assert!(!grace.is_waiting("player:a"));
+        assert!(
+            grace
+                .update(&players(&["player:a"]), &ships, 60.0)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn timeout_expires_once_and_offline_players_are_never_timed() {
+        let mut grace = ReconnectGrace::new(Duration::from_secs(10), false);
+        // player:z's ship was hydrated while they were offline.
+        let ships = players(&["player:a", "player:b", "player:z"]);
+        grace.update(&players(&["player:a", "player:b"]), &ships, 0.0);
+
+        assert_eq!(
+            grace.update(&players(&[]), &ships, 5.0),
+            vec![
+                GraceEvent::Started("player:a".to_string()),
+                GraceEvent::Started("player:b".to_string()),
+            ]
+        );
+        assert!(grace.update(&players(&[]), &ships, 14.9).is_empty());
+        assert_eq!(
+            grace.update(&players(&[]), &ships, 15.0),
+            vec![
+                GraceEvent::Expired("player:a".to_string()),
+                GraceEvent::Expired("player:b".to_string()),
+            ]
+        );
+        assert!(grace.update(&players(&[]), &ships, 100.0).is_empty());
+        assert!(!grace.is_waiting("player:z"));
+    }
+}
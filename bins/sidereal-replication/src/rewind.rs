@@ -0,0 +1,128 @@
+use bevy::math::Vec3;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Half a second of history at the 30 Hz sim rate.
+pub const DEFAULT_REWIND_WINDOW_TICKS: u64 = 15;
+
+/// Recent server-side positions per entity id, so an interaction can be
+/// resolved against where the target was on the tick the acting player last
+/// saw rather than where it is now. History older than `window_ticks` behind
+/// an entity's newest sample is dropped, which also bounds how far a laggy
+/// client can rewind.
+#[derive(Debug, Clone)]
+pub struct PositionHistory {
+    window_ticks: u64,
+    by_entity: HashMap<String, VecDeque<(u64, Vec3)>>,
+}
+
+impl Default for PositionHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_REWIND_WINDOW_TICKS)
+    }
+}
+
+impl PositionHistory {
+    pub fn new(window_ticks: u64) -> Self {
+        Self {
+            window_ticks,
+            by_entity: HashMap::new(),
+        }
+    }
+
+    /// Reads `REPLICATION_REWIND_WINDOW_TICKS`.
+    pub fn from_env() -> Self {
+        let window_ticks = std::env::var("REPLICATION_REWIND_WINDOW_TICKS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_REWIND_WINDOW_TICKS);
+        Self::new(window_ticks)
+    }
+
+    pub fn window_ticks(&self) -> u64 {
+        self.window_ticks
+    }
+
+    /// Records `position` for `entity_id` on `tick`. Ticks must not go
+    /// backwards; a second sample on the same tick replaces the first.
+    pub fn record(&mut self, entity_id: &str, tick: u64, position: Vec3) {
+        let samples = self.by_entity.entry(entity_id.to_string()).or_default();
+        match samples.back_mut() {
+            Some((last_tick, last_position)) if *last_tick == tick => *last_position = position,
+            Some((last_tick, _)) if *last_tick > tick => return,
+            _ => samples.push_back((tick, position)),
+        }
+        let oldest_kept = tick.saturating_sub(self.window_ticks);
+        while samples.front().is_some_and(|(t, _)| *t < oldest_kept) {
+            samples.pop_front();
+        }
+    }
+
+    /// Position of `entity_id` as of `tick`: the latest sample at or before
+    /// it. Ticks older than the window clamp to the oldest sample kept and
+    /// ticks past the newest sample return the newest, so a client can never
+    /// rewind further than the window allows.
+    pub fn position_at(&self, entity_id: &str, tick: u64) -> Option<Vec3> {
+        let samples = self.by_entity.get(entity_id)?;
+        let (oldest_tick, oldest_position) = *samples.front()?;
+        if tick <= oldest_tick {
+            return Some(oldest_position);
+        }
+        samples
+            .iter()
+            .rev()
+            .find(|(sample_tick, _)| *sample_tick <= tick)
+            .map(|(_, position)| *position)
+    }
+
+    /// Forgets entities not in `live`.
+    pub fn retain_live(&mut self, live: &HashSet<String>) {
+        self.by_entity
+            .retain(|entity_id, _| live.contains(entity_id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: f32) -> Vec3 {
+        Vec3::new(x, 0.0, 0.0)
+    }
+
+    #[test]
+    fn lookup_returns_the_position_on_a_past_tick() {
+        let mut history = PositionHistory::new(10);
+        for tick in 100..=105 {
+            history.record("ship:a", tick, at(tick as f32));
+        }
+        assert_eq!(history.position_at("ship:a", 102), Some(at(102.0)));
+        assert_eq!(history.position_at("ship:a", 105), Some(at(105.0)));
+        // Past the newest sample is the newest position.
+        assert_eq!(history.position_at("ship:a", 140), Some(at(105.0)));
+        assert_eq!(history.position_at("ship:b", 102), None);
+    }
+
+    #[test]
+    fn gaps_resolve_to_the_latest_earlier_sample() {
+        let mut history = PositionHistory::new(10);
+        history.record("ship:a", 10, at(1.0));
+        history.record("ship:a", 14, at(2.0));
+        history.record("ship:a", 14, at(3.0));
+        assert_eq!(history.position_at("ship:a", 13), Some(at(1.0)));
+        assert_eq!(history.position_at("ship:a", 14), Some(at(3.0)));
+    }
+
+    #[test]
+    fn rewind_beyond_the_window_clamps_to_the_oldest_kept_sample() {
+        let mut history = PositionHistory::new(4);
+        for tick in 0..=20 {
+            history.record("ship:a", tick, at(tick as f32));
+        }
+        assert_eq!(history.position_at("ship:a", 16), Some(at(16.0)));
+        assert_eq!(history.position_at("ship:a", 15), Some(at(16.0)));
+        assert_eq!(history.position_at("ship:a", 0), Some(at(16.0)));
+
+        history.retain_live(&HashSet::new());
+        assert_eq!(history.position_at("ship:a", 20), None);
+    }
+}
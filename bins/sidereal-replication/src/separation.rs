@@ -0,0 +1,91 @@
+use bevy::math::Vec3;
+use sidereal_game::DeterministicRng;
+use std::f32::consts::TAU;
+
+/// Roughly two ship hulls apart.
+pub const DEFAULT_MIN_SEPARATION_M: f32 = 12.0;
+/// `WorldSeed::derive` purpose for the separation stream, so one world always
+/// spreads the same stored positions the same way.
+pub const SEPARATION_SEED_PURPOSE: &str = "hydration_separation";
+
+/// Reads `REPLICATION_HYDRATION_MIN_SEPARATION_M` (`0` disables the pass).
+pub fn min_separation_from_env() -> f32 {
+    std::env::var("REPLICATION_HYDRATION_MIN_SEPARATION_M")
+        .ok()
+        .and_then(|v| v.parse::<f32>().ok())
+        .filter(|v| v.is_finite() && *v >= 0.0)
+        .unwrap_or(DEFAULT_MIN_SEPARATION_M)
+}
+
+/// Moves positions apart so no two end up closer than `min_separation_m`.
+/// Positions are placed in slice order; one that crowds an already placed
+/// position is tried at random XY offsets from where it was stored, drawn
+/// from a `DeterministicRng` seeded with `seed`, on a ring that widens with
+/// every failed try. Positions that are already clear are left untouched, so
+/// the same input and seed always produce the same output.
+pub fn separate_positions(positions: &mut [Vec3], min_separation_m: f32, seed: u64) {
+    if min_separation_m <= 0.0 {
+        return;
+    }
+    let mut rng = DeterministicRng::new(seed);
+    let crowded = |placed: &[Vec3], candidate: Vec3| {
+        placed
+            .iter()
+            .any(|other| other.distance(candidate) < min_separation_m)
+    };
+    for index in 1..positions.len() {
+        let (placed, rest) = positions.split_at_mut(index);
+        let stored = rest[0];
+        let mut candidate = stored;
+        let mut attempt = 0u32;
+        while crowded(placed, candidate) {
+            let angle = rng.next_f32() * TAU;
+            let radius = min_separation_m * (1.0 + attempt as f32 * 0.25);
+            candidate = stored + Vec3::new(angle.cos(), angle.sin(), 0.0) * radius;
+            attempt += 1;
+        }
+        rest[0] = candidate;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED: u64 = 0x5EA7_0001;
+
+    fn min_pairwise_distance(positions: &[Vec3]) -> f32 {
+        let mut min = f32::MAX;
+        for (i, a) in positions.iter().enumerate() {
+            for b in &positions[i + 1..] {
+                min = min.min(a.distance(*b));
+            }
+        }
+        min
+    }
+
+    #[test]
+    fn co_located_positions_are_spread_reproducibly() {
+        let mut positions = vec![Vec3::ZERO; 8];
+        separate_positions(&mut positions, 10.0, SEED);
+        assert!(min_pairwise_distance(&positions) >= 10.0);
+        assert_eq!(positions[0], Vec3::ZERO);
+        assert!(positions.iter().all(|position| position.z == 0.0));
+
+        let mut again = vec![Vec3::ZERO; 8];
+        separate_positions(&mut again, 10.0, SEED);
+        assert_eq!(positions, again);
+    }
+
+    #[test]
+    fn clear_positions_and_zero_separation_are_untouched() {
+        let spread = vec![Vec3::ZERO, Vec3::new(50.0, 0.0, 0.0)];
+        let mut positions = spread.clone();
+        separate_positions(&mut positions, 10.0, SEED);
+        assert_eq!(positions, spread);
+
+        let mut stacked = vec![Vec3::ONE; 3];
+        separate_positions(&mut stacked, 0.0, SEED);
+        assert_eq!(stacked, vec![Vec3::ONE; 3]);
+    }
+}
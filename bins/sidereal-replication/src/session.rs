@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Bumped whenever the snapshot layout changes; older payloads are ignored.
+pub const SESSION_SNAPSHOT_VERSION: u32 = 2;
+
+/// How long a saved session stays usable after its player was last bound.
+pub const DEFAULT_SESSION_TTL_S: u64 = 300;
+
+/// Reads `REPLICATION_SESSION_SNAPSHOT_TTL_S`.
+pub fn session_ttl_s_from_env() -> u64 {
+    std::env::var("REPLICATION_SESSION_SNAPSHOT_TTL_S")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_SESSION_TTL_S)
+}
+
+/// What replication knew about one authenticated player when it shut down.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerSessionSnapshot {
+    pub player_entity_id: String,
+    /// Last broadcast position of the player's controlled entity.
+    pub last_position_m: Option<[f32; 3]>,
+    /// Entity ids the player's client had been sent and not yet told to
+    /// remove, sorted.
+    pub visible_entity_ids: Vec<String>,
+    /// When the player was last seen bound, unix seconds. Carried unchanged
+    /// through restarts the player sits out.
+    pub saved_at_epoch_s: u64,
+}
+
+impl PlayerSessionSnapshot {
+    pub fn is_expired(&self, now_epoch_s: u64, ttl_s: u64) -> bool {
+        now_epoch_s.saturating_sub(self.saved_at_epoch_s) > ttl_s
+    }
+}
+
+/// Client session state written on shutdown and read back on the next boot,
+/// so a player reconnecting after a quick restart resumes from where the
+/// server left off instead of from nothing. Best-effort: a missing or
+/// unreadable snapshot only means clients re-sync from scratch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    pub version: u32,
+    pub tick: u64,
+    pub players: Vec<PlayerSessionSnapshot>,
+}
+
+impl SessionSnapshot {
+    /// Players are sorted by id so the same state always encodes the same way.
+    pub fn new(tick: u64, mut players: Vec<PlayerSessionSnapshot>) -> Self {
+        players.sort_by(|a, b| a.player_entity_id.cmp(&b.player_entity_id));
+        for player in &mut players {
+            player.visible_entity_ids.sort();
+            player.visible_entity_ids.dedup();
+        }
+        Self {
+            version: SESSION_SNAPSHOT_VERSION,
+            tick,
+            players,
+        }
+    }
+
+    pub fn encode(&self) -> Result<String, String> {
+        serde_json::to_string(self).map_err(|err| err.to_string())
+    }
+
+    pub fn decode(payload: &str) -> Result<Self, String> {
+        let snapshot = serde_json::from_str::<Self>(payload).map_err(|err| err.to_string())?;
+        if snapshot.version != SESSION_SNAPSHOT_VERSION {
+            return Err(format!(
+                "session snapshot version {} is not {SESSION_SNAPSHOT_VERSION}",
+                snapshot.version
+            ));
+        }
+        Ok(snapshot)
+    }
+
+    /// Drops sessions older than `ttl_s`.
+    pub fn retain_fresh(&mut self, now_epoch_s: u64, ttl_s: u64) {
+        self.players
+            .retain(|player| !player.is_expired(now_epoch_s, ttl_s));
+    }
+
+    pub fn into_players_by_id(self) -> HashMap<String, PlayerSessionSnapshot> {
+        self.players
+            .into_iter()
+            .map(|player| (player.player_entity_id.clone(), player))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player(id: &str, position: Option<[f32; 3]>, visible: &[&str]) -> PlayerSessionSnapshot {
+        PlayerSessionSnapshot {
+            player_entity_id: id.to_string(),
+            last_position_m: position,
+            visible_entity_ids: visible.iter().map(ToString::to_string).collect(),
+            saved_at_epoch_s: 1_000,
+        }
+    }
+
+    #[test]
+    fn snapshot_roundtrips_through_its_encoding() {
+        let snapshot = SessionSnapshot::new(
+            812,
+            vec![
+                player("player:b", None, &[]),
+                player(
+                    "player:a",
+                    Some([12.5, -3.0, 0.0]),
+                    &["ship:2", "asteroid:9", "ship:2"],
+                ),
+            ],
+        );
+        assert_eq!(snapshot.players[0].player_entity_id, "player:a");
+        assert_eq!(
+            snapshot.players[0].visible_entity_ids,
+            vec!["asteroid:9", "ship:2"]
+        );
+
+        let payload = snapshot.encode().expect("snapshot should encode");
+        let decoded = SessionSnapshot::decode(&payload).expect("snapshot should decode");
+        assert_eq!(decoded, snapshot);
+
+        let by_id = decoded.into_players_by_id();
+        assert_eq!(by_id["player:a"].last_position_m, Some([12.5, -3.0, 0.0]));
+        assert!(by_id["player:b"].visible_entity_ids.is_empty());
+    }
+
+    #[test]
+    fn unknown_version_and_garbage_are_rejected() {
+        let mut snapshot = SessionSnapshot::new(1, vec![player("player:a", None, &["ship:1"])]);
+        snapshot.version = SESSION_SNAPSHOT_VERSION + 1;
+        let payload = snapshot.encode().expect("snapshot should encode");
+        assert!(SessionSnapshot::decode(&payload).is_err());
+        assert!(SessionSnapshot::decode("{\"players\":").is_err());
+    }
+
+    #[test]
+    fn sessions_past_the_ttl_are_pruned() {
+        let mut stale = player("player:stale", None, &["ship:1"]);
+        stale.saved_at_epoch_s = 600;
+        let mut snapshot = SessionSnapshot::new(1, vec![player("player:a", None, &[]), stale]);
+
+        assert!(!snapshot.players[0].is_expired(1_300, 300));
+        assert!(snapshot.players[1].is_expired(1_300, 300));
+        snapshot.retain_fresh(1_300, 300);
+        assert_eq!(snapshot.players.len(), 1);
+        assert_eq!(snapshot.players[0].player_entity_id, "player:a");
+
+        snapshot.retain_fresh(1_301, 300);
+        assert!(snapshot.players.is_empty());
+    }
+}
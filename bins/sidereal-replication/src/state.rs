@@ -1,9 +1,147 @@
 use sidereal_net::{NetEnvelope, WorldDeltaEntity, WorldStateDelta};
-use sidereal_persistence::{GraphPersistence, PersistenceError};
+use sidereal_persistence::labels::{LabelValidation, LabelValidator};
+use sidereal_persistence::pool::{GraphPersistencePool, PooledGraphPersistence};
+use sidereal_persistence::{GraphEntityRecord, GraphPersistence, PersistenceError, PgSession};
 use std::collections::{HashMap, HashSet};
 
+/// Read side of the graph used by hydration.
+pub trait GraphReader {
+    fn load_graph_records(
+        &mut self,
+    ) -> std::result::Result<Vec<GraphEntityRecord>, PersistenceError>;
+}
+
+/// Write side of the graph used by interval flushes and snapshot markers.
+pub trait GraphWriter {
+    fn persist_world_delta(
+        &mut self,
+        updates: &[WorldDeltaEntity],
+        tick: u64,
+    ) -> std::result::Result<(), PersistenceError>;
+    fn persist_snapshot_marker(
+        &mut self,
+        snapshot_tick: u64,
+        entity_count: usize,
+    ) -> std::result::Result<(), PersistenceError>;
+}
+
+impl<S: PgSession> GraphReader for GraphPersistence<S> {
+    fn load_graph_records(
+        &mut self,
+    ) -> std::result::Result<Vec<GraphEntityRecord>, PersistenceError> {
+        GraphPersistence::load_graph_records(self)
+    }
+}
+
+impl<S: PgSession> GraphWriter for GraphPersistence<S> {
+    fn persist_world_delta(
+        &mut self,
+        updates: &[WorldDeltaEntity],
+        tick: u64,
+    ) -> std::result::Result<(), PersistenceError> {
+        GraphPersistence::persist_world_delta(self, updates, tick)
+    }
+
+    fn persist_snapshot_marker(
+        &mut self,
+        snapshot_tick: u64,
+        entity_count: usize,
+    ) -> std::result::Result<(), PersistenceError> {
+        GraphPersistence::persist_snapshot_marker(self, snapshot_tick, entity_count)
+    }
+}
+
+/// Replication's persistence handles, both checked out of one
+/// `GraphPersistencePool` for the runtime's lifetime. Reads and writes go
+/// through separate sessions so a large graph load never queues behind a
+/// flush and a flush never waits on a load.
+pub struct ReplicationPersistence<R = PooledGraphPersistence, W = PooledGraphPersistence> {
+    pub reader: R,
+    pub writer: W,
+}
+
+impl ReplicationPersistence {
+    pub fn from_pool(pool: &GraphPersistencePool) -> std::result::Result<Self, PersistenceError> {
+        let mut writer = pool.get()?;
+        if let Some(min_entries) = structured_inventory_min_entries_from_env() {
+            writer = writer.with_structured_inventory(min_entries);
+        }
+        if let Some(min_bytes) = component_json_min_bytes_from_env() {
+            writer = writer.with_component_json_threshold(min_bytes);
+        }
+        writer = writer.with_label_validator(label_validator_from_env());
+        Ok(Self {
+            reader: pool.get()?,
+            writer,
+        })
+    }
+}
+
+pub const DEFAULT_DATABASE_POOL_SIZE: u32 = 4;
+
+/// Opens replication's graph pool with `REPLICATION_DATABASE_POOL_SIZE`
+/// connections (default 4, at least 3): the runtime keeps its read and write
+/// sessions checked out and the rest serve one-off work such as starter ship
+/// bootstrap.
+pub fn graph_pool_from_env(
+    database_url: &str,
+) -> std::result::Result<GraphPersistencePool, PersistenceError> {
+    let max_size = std::env::var("REPLICATION_DATABASE_POOL_SIZE")
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .unwrap_or(DEFAULT_DATABASE_POOL_SIZE)
+        .max(3);
+    GraphPersistencePool::builder()
+        .max_size(max_size)
+        .build(database_url)
+}
+
+/// Inventories with at least this many entries are also written as `:Item`
+/// nodes. Unset keeps every inventory as a JSON component only.
+fn structured_inventory_min_entries_from_env() -> Option<usize> {
+    std::env::var("REPLICATION_STRUCTURED_INVENTORY_MIN_ENTRIES")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|v| *v > 0)
+}
+
+/// Components whose serialized payload reaches this size are stored as one
+/// `properties_json` string. Unset keeps per-key storage for every component.
+fn component_json_min_bytes_from_env() -> Option<usize> {
+    std::env::var("REPLICATION_COMPONENT_JSON_MIN_BYTES")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+}
+
+/// Reads `REPLICATION_LABEL_VALIDATION` (`off`, `warn` or `reject`; default
+/// `warn`) and the comma-separated `REPLICATION_EXTRA_LABELS` accepted on top
+/// of the built-in label set.
+fn label_validator_from_env() -> LabelValidator {
+    let mode = std::env::var("REPLICATION_LABEL_VALIDATION")
+        .ok()
+        .and_then(|v| LabelValidation::parse(&v))
+        .unwrap_or_default();
+    let extra_labels = std::env::var("REPLICATION_EXTRA_LABELS").unwrap_or_default();
+    LabelValidator::new(mode).with_extra_labels(
+        extra_labels
+            .split(',')
+            .map(str::trim)
+            .filter(|label| !label.is_empty()),
+    )
+}
+
+pub const DEFAULT_MAX_PENDING_UPDATES: usize = 50_000;
+
+/// Reads `REPLICATION_MAX_PENDING_UPDATES` (`0` = no cap).
+pub fn max_pending_updates_from_env() -> usize {
+    std::env::var("REPLICATION_MAX_PENDING_UPDATES")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MAX_PENDING_UPDATES)
+}
+
 pub fn hydrate_known_entity_ids(
-    persistence: &mut GraphPersistence,
+    persistence: &mut impl GraphReader,
 ) -> std::result::Result<HashSet<String>, PersistenceError> {
     let records = persistence.load_graph_records()?;
     Ok(records
@@ -25,11 +163,125 @@ pub fn ingest_world_delta(
         } else {
             known_entities.insert(update.entity_id.clone());
         }
+        let update = fold_into_pending(pending_updates.remove(&update.entity_id), update);
         pending_updates.insert(update.entity_id.clone(), update);
     }
     has_removals
 }
 
+/// Entity counts from one ingested delta.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct IngestStats {
+    /// Updates for entities that were not known before.
+    pub added: usize,
+    /// Updates for already known entities.
+    pub updated: usize,
+    pub removed: usize,
+}
+
+impl IngestStats {
+    pub fn has_removals(&self) -> bool {
+        self.removed > 0
+    }
+}
+
+/// Same end state as `ingest_world_delta`, but sized up front and applied in
+/// two passes: removals first, then the updates not superseded by a later
+/// removal of the same entity in this delta. Superseded updates are dropped
+/// and not counted.
+pub fn ingest_world_delta_batched(
+    known_entities: &mut HashSet<String>,
+    pending_updates: &mut HashMap<String, WorldDeltaEntity>,
+    delta: WorldStateDelta,
+) -> IngestStats {
+    let mut stats = IngestStats::default();
+    let mut last_removal_index = HashMap::new();
+    for (index, update) in delta.updates.iter().enumerate() {
+        if update.removed {
+            last_removal_index.insert(update.entity_id.clone(), index);
+        }
+    }
+    pending_updates.reserve(delta.updates.len());
+    known_entities.reserve(delta.updates.len() - last_removal_index.len());
+
+    let mut updates = Vec::with_capacity(delta.updates.len());
+    for (index, update) in delta.updates.into_iter().enumerate() {
+        let superseded_at = last_removal_index.get(&update.entity_id).copied();
+        if !update.removed {
+            if superseded_at.is_none_or(|removal| index > removal) {
+                updates.push(update);
+            }
+            continue;
+        }
+        stats.removed += 1;
+        if superseded_at == Some(index) {
+            known_entities.remove(&update.entity_id);
+            pending_updates.insert(update.entity_id.clone(), update);
+        }
+    }
+
+    for update in updates {
+        if known_entities.insert(update.entity_id.clone()) {
+            stats.added += 1;
+        } else {
+            stats.updated += 1;
+        }
+        let update = fold_into_pending(pending_updates.remove(&update.entity_id), update);
+        pending_updates.insert(update.entity_id.clone(), update);
+    }
+    stats
+}
+
+/// Combines a newly ingested update with the one already queued for the same
+/// entity so neither a component removal nor the update is lost before the
+/// flush.
+fn fold_into_pending(
+    pending: Option<WorldDeltaEntity>,
+    mut update: WorldDeltaEntity,
+) -> WorldDeltaEntity {
+    let Some(pending) = pending else {
+        return update;
+    };
+    if update.is_component_removal() {
+        return merge_component_removal(pending, update);
+    }
+    if pending.removed || update.removed {
+        return update;
+    }
+    // A full update replaces the queued one, but components the queued one
+    // removed stay removed unless this update brings them back.
+    for component_id in pending.removed_component_ids {
+        let re_added = update
+            .components
+            .iter()
+            .any(|component| component.component_id == component_id);
+        if !re_added && !update.removed_component_ids.contains(&component_id) {
+            update.removed_component_ids.push(component_id);
+        }
+    }
+    update
+}
+
+fn merge_component_removal(
+    mut pending: WorldDeltaEntity,
+    removal: WorldDeltaEntity,
+) -> WorldDeltaEntity {
+    if pending.removed {
+        return pending;
+    }
+    pending.components.retain(|component| {
+        !removal
+            .removed_component_ids
+            .contains(&component.component_id)
+    });
+    for component_id in removal.removed_component_ids {
+        if !pending.removed_component_ids.contains(&component_id) {
+            pending.removed_component_ids.push(component_id);
+        }
+    }
+    pending
+}
+
 pub fn ingest_world_envelope(
     known_entities: &mut HashSet<String>,
     pending_updates: &mut HashMap<String, WorldDeltaEntity>,
@@ -38,19 +290,519 @@ pub fn ingest_world_envelope(
     ingest_world_delta(known_entities, pending_updates, envelope.payload)
 }
 
+/// Component ids each entity carried the last time it was collected, so a
+/// component that goes away is reported in `removed_component_ids` instead of
+/// lingering on clients and in the graph.
+#[derive(Debug, Default)]
+pub struct CollectedComponentIds {
+    by_entity_id: HashMap<String, HashSet<String>>,
+}
+
+impl CollectedComponentIds {
+    /// Remembers `update`'s components and lists those it carried last time
+    /// but no longer does in its `removed_component_ids`. The first update
+    /// seen for an entity removes nothing.
+    pub fn mark_removed(&mut self, update: &mut WorldDeltaEntity) {
+        let current = update
+            .components
+            .iter()
+            .map(|component| component.component_id.clone())
+            .collect::<HashSet<_>>();
+        if let Some(previous) = self.by_entity_id.get(&update.entity_id) {
+            let mut removed = previous.difference(&current).cloned().collect::<Vec<_>>();
+            removed.sort();
+            update.removed_component_ids = removed;
+        }
+        self.by_entity_id.insert(update.entity_id.clone(), current);
+    }
+
+    /// Forgets entities not in `live`.
+    pub fn retain_live(&mut self, live: &HashSet<String>) {
+        self.by_entity_id
+            .retain(|entity_id, _| live.contains(entity_id));
+    }
+}
+
+/// Orders updates by `entity_id` so broadcasts and persistence batches do not
+/// depend on ECS or hash map iteration order.
+pub fn sort_updates_by_entity_id(updates: &mut [WorldDeltaEntity]) {
+    updates.sort_by(|a, b| a.entity_id.cmp(&b.entity_id));
+}
+
 pub fn flush_pending_updates(
-    persistence: &mut GraphPersistence,
+    persistence: &mut impl GraphWriter,
     pending_updates: &mut HashMap<String, WorldDeltaEntity>,
     tick: u64,
 ) -> std::result::Result<usize, PersistenceError> {
     if pending_updates.is_empty() {
         return Ok(0);
     }
-    let batch = pending_updates
+    let mut batch = pending_updates
         .drain()
         .map(|(_, update)| update)
         .collect::<Vec<_>>();
+    sort_updates_by_entity_id(&mut batch);
     let count = batch.len();
-    persistence.persist_world_delta(&batch, tick)?;
+    if let Err(err) = persistence.persist_world_delta(&batch, tick) {
+        // Keep the batch queued for the next attempt; the pending cap bounds
+        // how much piles up while the database is down.
+        for update in batch {
+            pending_updates
+                .entry(update.entity_id.clone())
+                .or_insert(update);
+        }
+        return Err(err);
+    }
     Ok(count)
 }
+
+/// Drops the oldest pending updates until at most `max` remain. Age is the
+/// tick an entity's update was last queued on (`queued_at_tick`; a missing
+/// tick counts as oldest), ties broken by entity id. Entity and component
+/// removals are never dropped, since no later update would repeat them, so
+/// more than `max` may remain. Returns the dropped ids, oldest first. Ticks of
+/// entities no longer pending are forgotten.
+pub fn evict_oldest_pending_updates(
+    pending_updates: &mut HashMap<String, WorldDeltaEntity>,
+    queued_at_tick: &mut HashMap<String, u64>,
+    max: usize,
+) -> Vec<String> {
+    queued_at_tick.retain(|entity_id, _| pending_updates.contains_key(entity_id));
+    if pending_updates.len() <= max {
+        return Vec::new();
+    }
+    let mut by_age = pending_updates
+        .iter()
+        .filter(|(_, update)| !update.removed && update.removed_component_ids.is_empty())
+        .map(|(entity_id, _)| {
+            (
+                queued_at_tick.get(entity_id).copied().unwrap_or(0),
+                entity_id.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+    by_age.sort();
+    let evicted = by_age
+        .into_iter()
+        .take(pending_updates.len() - max)
+        .map(|(_, entity_id)| entity_id)
+        .collect::<Vec<_>>();
+    for entity_id in &evicted {
+        pending_updates.remove(entity_id);
+        queued_at_tick.remove(entity_id);
+    }
+    evicted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sidereal_net::WorldComponentDelta;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    /// Reader whose load blocks until the test releases it.
+    struct BlockingReader {
+        started: mpsc::Sender<()>,
+        release: mpsc::Receiver<()>,
+    }
+
+    impl GraphReader for BlockingReader {
+        fn load_graph_records(
+            &mut self,
+        ) -> std::result::Result<Vec<GraphEntityRecord>, PersistenceError> {
+            let _ = self.started.send(());
+            let _ = self.release.recv();
+            Ok(Vec::new())
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingWriter {
+        persisted: Vec<(u64, usize)>,
+        persisted_ids: Vec<Vec<String>>,
+    }
+
+    impl GraphWriter for RecordingWriter {
+        fn persist_world_delta(
+            &mut self,
+            updates: &[WorldDeltaEntity],
+            tick: u64,
+        ) -> std::result::Result<(), PersistenceError> {
+            self.persisted.push((tick, updates.len()));
+            self.persisted_ids.push(
+                updates
+                    .iter()
+                    .map(|update| update.entity_id.clone())
+                    .collect(),
+            );
+            Ok(())
+        }
+
+        fn persist_snapshot_marker(
+            &mut self,
+            _snapshot_tick: u64,
+            _entity_count: usize,
+        ) -> std::result::Result<(), PersistenceError> {
+            Ok(())
+        }
+    }
+
+    struct FailingWriter;
+
+    impl GraphWriter for FailingWriter {
+        fn persist_world_delta(
+            &mut self,
+            _updates: &[WorldDeltaEntity],
+            _tick: u64,
+        ) -> std::result::Result<(), PersistenceError> {
+            Err(PersistenceError::database("connection refused".to_string()))
+        }
+
+        fn persist_snapshot_marker(
+            &mut self,
+            _snapshot_tick: u64,
+            _entity_count: usize,
+        ) -> std::result::Result<(), PersistenceError> {
+            Err(PersistenceError::database("connection refused".to_string()))
+        }
+    }
+
+    #[test]
+    fn long_running_read_does_not_block_write_handle() {
+        let (started_tx, started_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel();
+        let ReplicationPersistence {
+            mut reader,
+            mut writer,
+        } = ReplicationPersistence {
+            reader: BlockingReader {
+                started: started_tx,
+                release: release_rx,
+            },
+            writer: RecordingWriter::default(),
+        };
+
+        let read = thread::spawn(move || hydrate_known_entity_ids(&mut reader));
+        started_rx
+            .recv_timeout(Duration::from_secs(5))
+            .expect("read should start");
+
+        let mut pending = HashMap::new();
+        pending.insert(
+            "ship:1".to_string(),
+            WorldDeltaEntity {
+                entity_id: "ship:1".to_string(),
+                labels: vec!["Entity".to_string()],
+                properties: serde_json::json!({}),
+                components: Vec::new(),
+                removed: false,
+                removed_component_ids: Vec::new(),
+                removal_reason: None,
+            },
+        );
+        let flushed = flush_pending_updates(&mut writer, &mut pending, 9).expect("flush");
+        assert_eq!(flushed, 1);
+        assert_eq!(writer.persisted, vec![(9, 1)]);
+        assert!(!read.is_finished());
+
+        release_tx.send(()).expect("release read");
+        let known = read.join().expect("read thread").expect("read result");
+        assert!(known.is_empty());
+    }
+
+    #[test]
+    fn component_removal_folds_into_pending_update() {
+        let component = |kind: &str| WorldComponentDelta {
+            component_id: format!("ship:1:{kind}"),
+            component_kind: kind.to_string(),
+            properties: serde_json::json!({}),
+        };
+        let mut known = HashSet::new();
+        let mut pending = HashMap::new();
+        ingest_world_delta(
+            &mut known,
+            &mut pending,
+            WorldStateDelta {
+                updates: vec![WorldDeltaEntity {
+                    entity_id: "ship:1".to_string(),
+                    labels: vec!["Entity".to_string()],
+                    properties: serde_json::json!({"name": "ship"}),
+                    components: vec![component("display_name"), component("flight_computer")],
+                    removed: false,
+                    removed_component_ids: Vec::new(),
+                    removal_reason: None,
+                }],
+            },
+        );
+        let has_removals = ingest_world_delta(
+            &mut known,
+            &mut pending,
+            WorldStateDelta {
+                updates: vec![WorldDeltaEntity {
+                    entity_id: "ship:1".to_string(),
+                    labels: Vec::new(),
+                    properties: serde_json::json!({}),
+                    components: Vec::new(),
+                    removed: false,
+                    removed_component_ids: vec!["ship:1:flight_computer".to_string()],
+                    removal_reason: None,
+                }],
+            },
+        );
+
+        assert!(!has_removals);
+        assert!(known.contains("ship:1"));
+        let merged = &pending["ship:1"];
+        assert_eq!(merged.properties["name"], "ship");
+        assert_eq!(merged.components, vec![component("display_name")]);
+        assert_eq!(merged.removed_component_ids, vec!["ship:1:flight_computer"]);
+    }
+
+    #[test]
+    fn full_update_keeps_a_queued_component_removal() {
+        let component = |kind: &str| WorldComponentDelta {
+            component_id: format!("ship:1:{kind}"),
+            component_kind: kind.to_string(),
+            properties: serde_json::json!({}),
+        };
+        let mut known = HashSet::new();
+        let mut pending = HashMap::new();
+        let mut removal = entity("ship:1");
+        removal.components = vec![component("display_name")];
+        removal.removed_component_ids =
+            vec!["ship:1:fuel_tank".to_string(), "ship:1:engine".to_string()];
+        let mut next = entity("ship:1");
+        next.components = vec![component("display_name"), component("engine")];
+        ingest_world_delta(
+            &mut known,
+            &mut pending,
+            WorldStateDelta {
+                updates: vec![removal, next],
+            },
+        );
+
+        let merged = &pending["ship:1"];
+        assert_eq!(merged.components.len(), 2);
+        assert_eq!(merged.removed_component_ids, vec!["ship:1:fuel_tank"]);
+    }
+
+    #[test]
+    fn collected_component_ids_report_what_went_away() {
+        let component = |kind: &str| WorldComponentDelta {
+            component_id: format!("ship:1:{kind}"),
+            component_kind: kind.to_string(),
+            properties: serde_json::json!({}),
+        };
+        let mut collected = CollectedComponentIds::default();
+        let mut first = entity("ship:1");
+        first.components = vec![
+            component("health_pool"),
+            component("scanner_component"),
+            component("inventory"),
+        ];
+        collected.mark_removed(&mut first);
+        assert!(first.removed_component_ids.is_empty());
+
+        let mut second = entity("ship:1");
+        second.components = vec![component("health_pool")];
+        collected.mark_removed(&mut second);
+        assert_eq!(
+            second.removed_component_ids,
+            vec!["ship:1:inventory", "ship:1:scanner_component"]
+        );
+
+        let mut third = entity("ship:1");
+        third.components = vec![component("health_pool")];
+        collected.mark_removed(&mut third);
+        assert!(third.removed_component_ids.is_empty());
+
+        collected.retain_live(&HashSet::new());
+        let mut respawned = entity("ship:1");
+        collected.mark_removed(&mut respawned);
+        assert!(respawned.removed_component_ids.is_empty());
+    }
+
+    fn entity(entity_id: &str) -> WorldDeltaEntity {
+        WorldDeltaEntity {
+            entity_id: entity_id.to_string(),
+            labels: vec!["Entity".to_string()],
+            properties: serde_json::json!({}),
+            components: Vec::new(),
+            removed: false,
+            removed_component_ids: Vec::new(),
+            removal_reason: None,
+        }
+    }
+
+    #[test]
+    fn update_order_is_independent_of_collection_order() {
+        let mut first = vec![entity("ship:2"), entity("module:1"), entity("ship:1")];
+        let mut second = vec![entity("ship:1"), entity("ship:2"), entity("module:1")];
+        sort_updates_by_entity_id(&mut first);
+        sort_updates_by_entity_id(&mut second);
+        assert_eq!(first, second);
+        assert_eq!(
+            first
+                .iter()
+                .map(|e| e.entity_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["module:1", "ship:1", "ship:2"]
+        );
+    }
+
+    #[test]
+    fn flush_persists_pending_updates_in_entity_id_order() {
+        let mut writer = RecordingWriter::default();
+        let mut known = HashSet::new();
+        let mut pending = HashMap::new();
+        ingest_world_delta(
+            &mut known,
+            &mut pending,
+            WorldStateDelta {
+                updates: vec![entity("c"), entity("a"), entity("b")],
+            },
+        );
+        flush_pending_updates(&mut writer, &mut pending, 7).expect("flush");
+        assert_eq!(writer.persisted_ids, vec![vec!["a", "b", "c"]]);
+    }
+
+    #[test]
+    fn failed_flush_keeps_updates_pending() {
+        let mut pending = HashMap::new();
+        for entity_id in ["a", "b"] {
+            pending.insert(entity_id.to_string(), entity(entity_id));
+        }
+        assert!(flush_pending_updates(&mut FailingWriter, &mut pending, 3).is_err());
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending["a"], entity("a"));
+    }
+
+    #[test]
+    fn eviction_keeps_the_newest_updates_within_the_cap() {
+        let mut pending = HashMap::new();
+        let mut queued_at_tick = HashMap::new();
+        for (entity_id, tick) in [("old", 1), ("mid_b", 5), ("mid_a", 5), ("new", 9)] {
+            pending.insert(entity_id.to_string(), entity(entity_id));
+            queued_at_tick.insert(entity_id.to_string(), tick);
+        }
+        pending.insert("untracked".to_string(), entity("untracked"));
+        queued_at_tick.insert("flushed".to_string(), 2);
+
+        let evicted = evict_oldest_pending_updates(&mut pending, &mut queued_at_tick, 2);
+        assert_eq!(evicted, vec!["untracked", "old", "mid_a"]);
+        let mut kept = pending.keys().cloned().collect::<Vec<_>>();
+        kept.sort();
+        assert_eq!(kept, vec!["mid_b", "new"]);
+        assert_eq!(queued_at_tick.len(), 2);
+
+        assert!(evict_oldest_pending_updates(&mut pending, &mut queued_at_tick, 2).is_empty());
+        assert_eq!(pending.len(), 2);
+    }
+
+    #[test]
+    fn eviction_never_drops_removals() {
+        let mut removed = entity("gone");
+        removed.removed = true;
+        let mut component_removal = entity("stripped");
+        component_removal.removed_component_ids = vec!["stripped:fuel_tank".to_string()];
+        let mut pending = HashMap::new();
+        let mut queued_at_tick = HashMap::new();
+        for (update, tick) in [(removed, 1), (component_removal, 2), (entity("moved"), 3)] {
+            queued_at_tick.insert(update.entity_id.clone(), tick);
+            pending.insert(update.entity_id.clone(), update);
+        }
+
+        let evicted = evict_oldest_pending_updates(&mut pending, &mut queued_at_tick, 1);
+        assert_eq!(evicted, vec!["moved"]);
+        assert!(pending["gone"].removed);
+        assert_eq!(
+            pending["stripped"].removed_component_ids,
+            vec!["stripped:fuel_tank"]
+        );
+    }
+
+    fn mixed_delta() -> WorldStateDelta {
+        let mut removed = entity("ship:gone");
+        removed.removed = true;
+        let mut component_removal = entity("ship:known");
+        component_removal.removed_component_ids = vec!["ship:known:fuel_tank".to_string()];
+        WorldStateDelta {
+            updates: vec![
+                entity("ship:new"),
+                entity("ship:known"),
+                removed,
+                component_removal,
+                entity("module:new"),
+            ],
+        }
+    }
+
+    #[test]
+    fn batched_ingest_matches_single_entity_ingest() {
+        let known_before = ["ship:known", "ship:gone"]
+            .into_iter()
+            .map(ToString::to_string)
+            .collect::<HashSet<_>>();
+        let delta = mixed_delta();
+
+        let mut expected = IngestStats::default();
+        let mut single_known = known_before.clone();
+        let mut single_pending = HashMap::new();
+        for update in delta.updates.clone() {
+            if update.removed {
+                expected.removed += 1;
+            } else if single_known.contains(&update.entity_id) {
+                expected.updated += 1;
+            } else {
+                expected.added += 1;
+            }
+            ingest_world_delta(
+                &mut single_known,
+                &mut single_pending,
+                WorldStateDelta {
+                    updates: vec![update],
+                },
+            );
+        }
+
+        let mut batched_known = known_before;
+        let mut batched_pending = HashMap::new();
+        let stats = ingest_world_delta_batched(&mut batched_known, &mut batched_pending, delta);
+
+        assert_eq!(
+            stats,
+            IngestStats {
+                added: 2,
+                updated: 2,
+                removed: 1,
+            }
+        );
+        assert_eq!(stats, expected);
+        assert!(stats.has_removals());
+        assert_eq!(batched_known, single_known);
+        assert_eq!(batched_pending, single_pending);
+    }
+
+    #[test]
+    fn batched_ingest_keeps_sequential_order_for_update_and_removal() {
+        let mut removed = entity("ship:1");
+        removed.removed = true;
+        let deltas = [
+            vec![entity("ship:1"), removed.clone()],
+            vec![removed, entity("ship:1")],
+        ];
+        for updates in deltas {
+            let delta = WorldStateDelta { updates };
+            let mut single_known = HashSet::new();
+            let mut single_pending = HashMap::new();
+            ingest_world_delta(&mut single_known, &mut single_pending, delta.clone());
+            let mut batched_known = HashSet::new();
+            let mut batched_pending = HashMap::new();
+            ingest_world_delta_batched(&mut batched_known, &mut batched_pending, delta);
+            assert_eq!(batched_known, single_known);
+            assert_eq!(batched_pending, single_pending);
+        }
+    }
+}
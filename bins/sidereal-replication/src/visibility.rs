@@ -2,9 +2,14 @@ use bevy::prelude::*;
 use lightyear::prelude::NetworkTarget;
 use std::collections::{HashMap, HashSet};
 
-use sidereal_net::WorldStateDelta;
+use sidereal_core::visibility::PUBLIC_PROPERTIES;
+use sidereal_net::{RemovalReason, SubscriptionFilter, WorldDeltaEntity, WorldStateDelta};
 
 pub const DEFAULT_VIEW_RANGE_M: f32 = 300.0;
+pub const DEFAULT_REMOVAL_GRACE_TICKS: u32 = 5;
+/// Property an entity detail response adds: the modules mounted on the
+/// entity. Allies and owners only.
+pub const LOADOUT_PROPERTY: &str = "loadout";
 
 #[derive(Resource, Default)]
 pub struct ClientVisibilityRegistry {
@@ -48,9 +53,131 @@ impl ClientControlledEntityPositionMap {
     }
 }
 
+/// Entities last delivered to each client. An entity that stops being
+/// delivered is only reported as removed after it has been missing for more
+/// than `removal_grace_ticks` consecutive ticks, so entities hovering at the
+/// range boundary do not flicker in and out.
 #[derive(Resource, Default)]
 pub struct ClientVisibilityHistory {
-    pub visible_entities_by_client: HashMap<Entity, HashSet<String>>,
+    /// Entity id -> consecutive ticks it has been missing (0 = delivered this tick).
+    pub visible_entities_by_client: HashMap<Entity, HashMap<String, u32>>,
+    pub removal_grace_ticks: u32,
+}
+
+impl ClientVisibilityHistory {
+    pub fn with_removal_grace_ticks(removal_grace_ticks: u32) -> Self {
+        Self {
+            visible_entities_by_client: HashMap::new(),
+            removal_grace_ticks,
+        }
+    }
+
+    /// Reads `REPLICATION_VISIBILITY_REMOVAL_GRACE_TICKS`.
+    pub fn from_env() -> Self {
+        let grace = std::env::var("REPLICATION_VISIBILITY_REMOVAL_GRACE_TICKS")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(DEFAULT_REMOVAL_GRACE_TICKS);
+        Self::with_removal_grace_ticks(grace)
+    }
+
+    /// Records what `client` received this tick and returns the entity ids
+    /// whose grace period just ran out. Entities the world itself removed are
+    /// forgotten without a second removal marker.
+    pub fn record_delivery(&mut self, client: Entity, world: &WorldStateDelta) -> Vec<String> {
+        let history = self.visible_entities_by_client.entry(client).or_default();
+        let mut delivered = HashSet::new();
+        for update in &world.updates {
+            if update.removed {
+                history.remove(&update.entity_id);
+            } else {
+                delivered.insert(update.entity_id.as_str());
+            }
+        }
+        for (entity_id, missing_ticks) in history.iter_mut() {
+            if delivered.contains(entity_id.as_str()) {
+                *missing_ticks = 0;
+            } else {
+                *missing_ticks = missing_ticks.saturating_add(1);
+            }
+        }
+        for entity_id in delivered {
+            history.entry(entity_id.to_string()).or_insert(0);
+        }
+
+        let grace = self.removal_grace_ticks;
+        let mut expired = history
+            .iter()
+            .filter(|(_, missing_ticks)| **missing_ticks > grace)
+            .map(|(entity_id, _)| entity_id.clone())
+            .collect::<Vec<_>>();
+        expired.sort();
+        for entity_id in &expired {
+            history.remove(entity_id);
+        }
+        expired
+    }
+
+    /// Starts `client` off as if it had already been sent `entity_ids`.
+    pub fn seed_client(&mut self, client: Entity, entity_ids: impl IntoIterator<Item = String>) {
+        self.visible_entities_by_client
+            .insert(client, entity_ids.into_iter().map(|id| (id, 0)).collect());
+    }
+
+    pub fn forget_client(&mut self, client: Entity) {
+        self.visible_entities_by_client.remove(&client);
+    }
+
+    /// Every entity id `client` currently holds, sorted.
+    pub fn visible_entity_ids(&self, client: Entity) -> Vec<String> {
+        let mut entity_ids = self
+            .visible_entities_by_client
+            .get(&client)
+            .map(|history| history.keys().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+        entity_ids.sort();
+        entity_ids
+    }
+}
+
+/// Marker telling a client `entity_id` is gone and why.
+pub fn removal_update(entity_id: String, reason: RemovalReason) -> WorldDeltaEntity {
+    WorldDeltaEntity {
+        entity_id,
+        labels: Vec::new(),
+        properties: serde_json::json!({}),
+        components: Vec::new(),
+        removed: true,
+        removed_component_ids: Vec::new(),
+        removal_reason: Some(reason),
+    }
+}
+
+/// Removal markers for the ids `record_delivery` expired. An id still among
+/// `live_entity_ids` (the tick's world before any per-client filtering) was
+/// only filtered out for this client; any other id left the simulation.
+pub fn expired_removals(
+    expired: Vec<String>,
+    live_entity_ids: &HashSet<&str>,
+) -> Vec<WorldDeltaEntity> {
+    expired
+        .into_iter()
+        .map(|entity_id| {
+            let reason = if live_entity_ids.contains(entity_id.as_str()) {
+                RemovalReason::OutOfRange
+            } else {
+                RemovalReason::Destroyed
+            };
+            removal_update(entity_id, reason)
+        })
+        .collect()
+}
+
+/// Entity-kind subscriptions sent by clients. Clients without an entry receive
+/// every kind.
+#[derive(Resource, Default)]
+pub struct ClientSubscriptionRegistry {
+    pub filter_by_client: HashMap<Entity, SubscriptionFilter>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -65,6 +192,8 @@ pub struct VisibilityContext {
     pub player_entity_id: Option<String>,
     pub observer_position: Option<Vec3>,
     pub view_range_m: f32,
+    /// Players whose entities this viewer sees at `RelationLevel::Ally`.
+    pub ally_player_entity_ids: HashSet<String>,
 }
 
 impl VisibilityContext {
@@ -74,6 +203,7 @@ impl VisibilityContext {
             player_entity_id: Some(player_entity_id),
             observer_position,
             view_range_m: DEFAULT_VIEW_RANGE_M,
+            ally_player_entity_ids: HashSet::new(),
         }
     }
 
@@ -83,28 +213,103 @@ impl VisibilityContext {
             player_entity_id: None,
             observer_position: None,
             view_range_m: 0.0,
+            ally_player_entity_ids: HashSet::new(),
         }
     }
 }
 
-const ALWAYS_VISIBLE_PROPERTIES: &[&str] = &[
-    "entity_id",
-    "position_m",
-    "velocity_mps",
-    "heading_rad",
-    "display_name",
-    "ship_tag",
-    "module_tag",
-    "mounted_on_entity_id",
-    "parent_entity_id",
-    "size_m",
-    "collision_aabb_m",
-    "mass_kg",
-    "asset_id",
-    "starfield_shader_asset_id",
-];
+/// Relation between the viewing player and an entity, ordered from least to
+/// most trusted. A field is delivered when the viewer's relation is at least
+/// the level the policy requires for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RelationLevel {
+    Public,
+    Ally,
+    /// The viewer owns the entity.
+    Owner,
+}
+
+/// Field-level redaction rules applied to every non-removal update before it is
+/// sent. Property keys and component kinds not listed fall back to
+/// `default_level`.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct VisibilityPolicy {
+    pub property_levels: HashMap<String, RelationLevel>,
+    pub component_levels: HashMap<String, RelationLevel>,
+    pub default_level: RelationLevel,
+}
+
+impl Default for VisibilityPolicy {
+    /// Physical/render-safe properties are public; everything else, including
+    /// all components, stays with the owner.
+    fn default() -> Self {
+        let mut policy = Self::owner_only();
+        for key in PUBLIC_PROPERTIES {
+            policy.set_property_level(*key, RelationLevel::Public);
+        }
+        for key in OWNER_ONLY_PROPERTIES {
+            policy.set_property_level(*key, RelationLevel::Owner);
+        }
+        policy.set_property_level(LOADOUT_PROPERTY, RelationLevel::Ally);
+        policy
+    }
+}
+
+impl VisibilityPolicy {
+    /// Policy that reveals nothing to non-owners until levels are added.
+    pub fn owner_only() -> Self {
+        Self {
+            property_levels: HashMap::new(),
+            component_levels: HashMap::new(),
+            default_level: RelationLevel::Owner,
+        }
+    }
+
+    pub fn set_property_level(&mut self, key: impl Into<String>, level: RelationLevel) {
+        self.property_levels.insert(key.into(), level);
+    }
+
+    #[allow(dead_code)]
+    pub fn set_component_level(&mut self, component_kind: impl Into<String>, level: RelationLevel) {
+        self.component_levels.insert(component_kind.into(), level);
+    }
+
+    pub fn property_visible(&self, key: &str, relation: RelationLevel) -> bool {
+        relation
+            >= self
+                .property_levels
+                .get(key)
+                .copied()
+                .unwrap_or(self.default_level)
+    }
+
+    pub fn component_visible(&self, component_kind: &str, relation: RelationLevel) -> bool {
+        relation
+            >= self
+                .component_levels
+                .get(component_kind)
+                .copied()
+                .unwrap_or(self.default_level)
+    }
+
+    /// Strips every property and component the viewer's relation does not
+    /// reach. Removed component ids are matched by their `<entity_id>:<kind>`
+    /// suffix.
+    pub fn redact(&self, update: &mut WorldDeltaEntity, relation: RelationLevel) {
+        if let Some(obj) = update.properties.as_object_mut() {
+            obj.retain(|key, _| self.property_visible(key, relation));
+        }
+        update
+            .components
+            .retain(|component| self.component_visible(&component.component_kind, relation));
+        update.removed_component_ids.retain(|component_id| {
+            component_id
+                .rsplit_once(':')
+                .is_some_and(|(_, kind)| self.component_visible(kind, relation))
+        });
+    }
+}
 
-#[allow(dead_code)]
 const OWNER_ONLY_PROPERTIES: &[&str] = &[
     "health",
     "owner_entity_id",
@@ -119,34 +324,146 @@ pub fn visibility_context_for_client(
     client_entity: Entity,
     registry: &ClientVisibilityRegistry,
     positions: &ClientControlledEntityPositionMap,
+) -> VisibilityContext {
+    match registry.get_player_id(client_entity) {
+        Some(player_id) => visibility_context_for_player(player_id, positions),
+        None => VisibilityContext::none(),
+    }
+}
+
+/// Context for `player_entity_id` observing from its controlled entity's
+/// last known position.
+pub fn visibility_context_for_player(
+    player_entity_id: &str,
+    positions: &ClientControlledEntityPositionMap,
 ) -> VisibilityContext {
     if std::env::var("REPLICATION_VISIBILITY_MODE")
         .is_ok_and(|mode| mode.eq_ignore_ascii_case("none"))
     {
         return VisibilityContext::none();
     }
+    VisibilityContext::authenticated(
+        player_entity_id.to_string(),
+        positions.get_position(player_entity_id),
+    )
+}
 
-    if let Some(player_id) = registry.get_player_id(client_entity) {
-        let obs_pos = positions.get_position(player_id);
-        VisibilityContext::authenticated(player_id.to_string(), obs_pos)
-    } else {
-        VisibilityContext::none()
+/// Ids of the entities in `world` that `player_entity_id` would currently be
+/// sent, sorted. Runs the same context and filter as delivery, so culling can
+/// be checked without a connected client.
+pub fn visible_entity_ids_for(
+    player_entity_id: &str,
+    world: &WorldStateDelta,
+    positions: &ClientControlledEntityPositionMap,
+    policy: &VisibilityPolicy,
+) -> Vec<String> {
+    let ctx = visibility_context_for_player(player_entity_id, positions);
+    let Some(filtered) = apply_visibility_filter(world, &ctx, policy) else {
+        return Vec::new();
+    };
+    let mut entity_ids = filtered
+        .updates
+        .into_iter()
+        .filter(|update| !update.removed && !update.is_component_removal())
+        .map(|update| update.entity_id)
+        .collect::<Vec<_>>();
+    entity_ids.sort();
+    entity_ids.dedup();
+    entity_ids
+}
+
+/// Expanded one-off view of `entity_id` for a `RequestEntityDetail`. The
+/// entity must be in what the viewer is currently streamed; the response is
+/// its full update plus a `loadout` summary of the modules mounted on it,
+/// directly or through one of its hardpoints (which the stream never delivers
+/// to non-owners), redacted for the viewer's
+/// relation exactly as the stream is. `None` when the viewer cannot see it.
+pub fn entity_detail_for(
+    world: &WorldStateDelta,
+    entity_id: &str,
+    ctx: &VisibilityContext,
+    policy: &VisibilityPolicy,
+) -> Option<WorldDeltaEntity> {
+    let streamed = apply_visibility_filter(world, ctx, policy)?;
+    if !streamed
+        .updates
+        .iter()
+        .any(|update| update.entity_id == entity_id && !update.removed)
+    {
+        return None;
+    }
+    let mut detail = world
+        .updates
+        .iter()
+        .find(|update| update.entity_id == entity_id && !update.removed)?
+        .clone();
+
+    // Modules are usually mounted on a hardpoint, whose parent is the entity.
+    let mount_points = world
+        .updates
+        .iter()
+        .filter(|update| {
+            !update.removed
+                && update.labels.iter().any(|label| label == "Hardpoint")
+                && property_str(update, "parent_entity_id") == Some(entity_id)
+        })
+        .map(|hardpoint| hardpoint.entity_id.as_str())
+        .chain(std::iter::once(entity_id))
+        .collect::<HashSet<_>>();
+    let mut loadout = world
+        .updates
+        .iter()
+        .filter(|update| {
+            !update.removed
+                && property_str(update, "mounted_on_entity_id")
+                    .is_some_and(|mount| mount_points.contains(mount))
+        })
+        .map(|module| {
+            let mut component_kinds = module
+                .components
+                .iter()
+                .map(|component| component.component_kind.as_str())
+                .collect::<Vec<_>>();
+            component_kinds.sort_unstable();
+            serde_json::json!({
+                "entity_id": module.entity_id,
+                "hardpoint_id": module.properties.get("hardpoint_id"),
+                "component_kinds": component_kinds,
+            })
+        })
+        .collect::<Vec<_>>();
+    loadout.sort_by(|a, b| a["entity_id"].as_str().cmp(&b["entity_id"].as_str()));
+    if let Some(obj) = detail.properties.as_object_mut() {
+        obj.insert(LOADOUT_PROPERTY.to_string(), serde_json::json!(loadout));
     }
+
+    let relation = relation_to(&detail, ctx);
+    policy.redact(&mut detail, relation);
+    Some(detail)
 }
 
 pub fn apply_visibility_filter(
     world: &WorldStateDelta,
     ctx: &VisibilityContext,
+    policy: &VisibilityPolicy,
 ) -> Option<WorldStateDelta> {
     match ctx.scope {
         VisibilityScope::None => None,
         VisibilityScope::Authenticated => {
             let player_id = ctx.player_entity_id.as_ref()?;
-            Some(filter_world_for_client(world, player_id, ctx))
+            Some(filter_world_for_client(world, player_id, ctx, policy))
         }
     }
 }
 
+/// Drops updates whose derived kind the client is not subscribed to. Removals
+/// always pass so the client can drop entities it already holds.
+pub fn apply_subscription_filter(world: &mut WorldStateDelta, filter: &SubscriptionFilter) {
+    world.updates.retain(|update| {
+        update.removed || update.is_component_removal() || filter.includes(update.derived_kind())
+    });
+}
+
 /// Extract position from entity properties JSON
 fn extract_position(properties: &serde_json::Value) -> Option<Vec3> {
     let arr = properties.get("position_m")?.as_array()?;
@@ -165,6 +482,7 @@ fn filter_world_for_client(
     world: &WorldStateDelta,
     player_entity_id: &str,
     ctx: &VisibilityContext,
+    policy: &VisibilityPolicy,
 ) -> WorldStateDelta {
     let mut filtered_updates = Vec::new();
     let ownership = world
@@ -191,7 +509,8 @@ fn filter_world_for_client(
     }
 
     for update in &world.updates {
-        if update.removed {
+        // Component removals carry no state beyond ids; clients ignore ids they never held.
+        if update.removed || update.is_component_removal() {
             filtered_updates.push(update.clone());
             continue;
         }
@@ -227,18 +546,14 @@ fn filter_world_for_client(
 
         if is_owned {
             filtered_updates.push(update.clone());
-        } else {
-            let mut redacted = update.clone();
-            if let Some(obj) = redacted.properties.as_object_mut() {
-                obj.retain(|key, _| is_property_always_visible(key));
-            }
-            redacted.components.clear();
-
-            if let Some(obj) = redacted.properties.as_object()
-                && !obj.is_empty()
-            {
-                filtered_updates.push(redacted);
-            }
+            continue;
+        }
+        let mut redacted = update.clone();
+        policy.redact(&mut redacted, relation_to(update, ctx));
+        if let Some(obj) = redacted.properties.as_object()
+            && !obj.is_empty()
+        {
+            filtered_updates.push(redacted);
         }
     }
 
@@ -247,16 +562,31 @@ fn filter_world_for_client(
     }
 }
 
-fn entity_is_owned_by(update: &sidereal_net::WorldDeltaEntity, player_entity_id: &str) -> bool {
-    update.components.iter().any(|comp| {
-        comp.component_kind == "owner_id"
-            && owner_id_from_component_properties(&comp.properties)
-                .map(|owner_id| owner_id == player_entity_id)
-                .unwrap_or(false)
-    })
+fn property_str<'a>(update: &'a WorldDeltaEntity, key: &str) -> Option<&'a str> {
+    update.properties.get(key).and_then(|value| value.as_str())
+}
+
+fn relation_to(update: &WorldDeltaEntity, ctx: &VisibilityContext) -> RelationLevel {
+    match entity_owner_id(update) {
+        Some(owner) if ctx.player_entity_id.as_deref() == Some(owner) => RelationLevel::Owner,
+        Some(owner) if ctx.ally_player_entity_ids.contains(owner) => RelationLevel::Ally,
+        _ => RelationLevel::Public,
+    }
+}
+
+fn entity_is_owned_by(update: &WorldDeltaEntity, player_entity_id: &str) -> bool {
+    entity_owner_id(update) == Some(player_entity_id)
+}
+
+fn entity_owner_id(update: &WorldDeltaEntity) -> Option<&str> {
+    update
+        .components
+        .iter()
+        .filter(|comp| comp.component_kind == "owner_id")
+        .find_map(|comp| owner_id_from_component_properties(&comp.properties))
 }
 
-fn scanner_extension_m(update: &sidereal_net::WorldDeltaEntity) -> f32 {
+fn scanner_extension_m(update: &WorldDeltaEntity) -> f32 {
     // Scanner components are not yet fully wired in v3, but keep this hook now so
     // authorization range can immediately expand once scanner_range_m is persisted.
     update
@@ -266,10 +596,6 @@ fn scanner_extension_m(update: &sidereal_net::WorldDeltaEntity) -> f32 {
         .unwrap_or(0.0) as f32
 }
 
-fn is_property_always_visible(property_name: &str) -> bool {
-    ALWAYS_VISIBLE_PROPERTIES.contains(&property_name)
-}
-
 fn owner_id_from_component_properties(props: &serde_json::Value) -> Option<&str> {
     if let Some(raw) = props.as_str() {
         return Some(raw);
@@ -302,7 +628,9 @@ pub fn delivery_target_for_session(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use sidereal_game::Engine;
     use sidereal_net::{WorldComponentDelta, WorldDeltaEntity};
+    use sidereal_testkit::{HardpointBuilder, ModuleBuilder, ShipBuilder, WorldDeltaBuilder};
 
     fn make_test_entity(
         entity_id: &str,
@@ -335,6 +663,8 @@ mod tests {
             properties,
             components,
             removed: false,
+            removed_component_ids: Vec::new(),
+            removal_reason: None,
         }
     }
 
@@ -348,7 +678,7 @@ mod tests {
         };
 
         let ctx = VisibilityContext::authenticated("player:alice".to_string(), Some(Vec3::ZERO));
-        let filtered = apply_visibility_filter(&world, &ctx).unwrap();
+        let filtered = apply_visibility_filter(&world, &ctx, &VisibilityPolicy::default()).unwrap();
 
         let own_ship = filtered
             .updates
@@ -377,7 +707,7 @@ mod tests {
         };
 
         let ctx = VisibilityContext::authenticated("player:alice".to_string(), Some(Vec3::ZERO));
-        let filtered = apply_visibility_filter(&world, &ctx).unwrap();
+        let filtered = apply_visibility_filter(&world, &ctx, &VisibilityPolicy::default()).unwrap();
 
         assert!(
             filtered.updates.iter().any(|e| e.entity_id == "ship:1"),
@@ -405,7 +735,7 @@ mod tests {
         };
 
         let ctx = VisibilityContext::authenticated("player:alice".to_string(), Some(Vec3::ZERO));
-        let filtered = apply_visibility_filter(&world, &ctx).unwrap();
+        let filtered = apply_visibility_filter(&world, &ctx, &VisibilityPolicy::default()).unwrap();
         assert!(filtered.updates.is_empty());
     }
 
@@ -427,7 +757,7 @@ mod tests {
         };
 
         let ctx = VisibilityContext::authenticated("player:alice".to_string(), Some(Vec3::ZERO));
-        let filtered = apply_visibility_filter(&world, &ctx).unwrap();
+        let filtered = apply_visibility_filter(&world, &ctx, &VisibilityPolicy::default()).unwrap();
         assert!(
             !filtered
                 .updates
@@ -449,7 +779,7 @@ mod tests {
         };
 
         let ctx = VisibilityContext::none();
-        let filtered = apply_visibility_filter(&world, &ctx);
+        let filtered = apply_visibility_filter(&world, &ctx, &VisibilityPolicy::default());
 
         assert!(filtered.is_none());
     }
@@ -468,12 +798,199 @@ mod tests {
 
     #[test]
     fn always_visible_properties_are_recognized() {
-        assert!(is_property_always_visible("entity_id"));
-        assert!(is_property_always_visible("position_m"));
-        assert!(is_property_always_visible("heading_rad"));
-        assert!(is_property_always_visible("display_name"));
-        assert!(!is_property_always_visible("health"));
-        assert!(!is_property_always_visible("fuel"));
+        let policy = VisibilityPolicy::default();
+        let public = |key: &str| policy.property_visible(key, RelationLevel::Public);
+        assert!(public("entity_id"));
+        assert!(public("position_m"));
+        assert!(public("heading_rad"));
+        assert!(public("display_name"));
+        assert!(!public("health"));
+        assert!(!public("fuel"));
+    }
+
+    #[test]
+    fn policy_reveals_position_publicly_and_health_only_to_owner() {
+        let mut policy = VisibilityPolicy::owner_only();
+        policy.set_property_level("entity_id", RelationLevel::Public);
+        policy.set_property_level("position_m", RelationLevel::Public);
+        policy.set_property_level("health", RelationLevel::Owner);
+        let world = WorldStateDelta {
+            updates: vec![
+                make_test_entity("ship:1", Some("player:alice"), true, [0.0, 0.0, 0.0]),
+                make_test_entity("ship:2", Some("player:bob"), true, [10.0, 0.0, 0.0]),
+            ],
+        };
+
+        let ctx = VisibilityContext::authenticated("player:alice".to_string(), Some(Vec3::ZERO));
+        let filtered = apply_visibility_filter(&world, &ctx, &policy).unwrap();
+
+        let own_ship = filtered
+            .updates
+            .iter()
+            .find(|e| e.entity_id == "ship:1")
+            .unwrap();
+        assert_eq!(own_ship.properties, world.updates[0].properties);
+
+        let other_ship = filtered
+            .updates
+            .iter()
+            .find(|e| e.entity_id == "ship:2")
+            .unwrap();
+        assert_eq!(
+            other_ship.properties,
+            serde_json::json!({
+                "entity_id": "ship:2",
+                "position_m": [10.0, 0.0, 0.0],
+            })
+        );
+        assert!(other_ship.components.is_empty());
+    }
+
+    #[test]
+    fn ally_level_fields_reach_allies_but_not_public() {
+        let mut policy = VisibilityPolicy::default();
+        policy.set_property_level("shield_status", RelationLevel::Ally);
+        policy.set_component_level("owner_id", RelationLevel::Ally);
+        let mut ally_ship = make_test_entity("ship:2", Some("player:bob"), true, [10.0, 0.0, 0.0]);
+        ally_ship.properties["shield_status"] = serde_json::json!("up");
+        let mut stranger_ship =
+            make_test_entity("ship:3", Some("player:carol"), true, [20.0, 0.0, 0.0]);
+        stranger_ship.properties["shield_status"] = serde_json::json!("up");
+        let world = WorldStateDelta {
+            updates: vec![
+                make_test_entity("ship:1", Some("player:alice"), true, [0.0, 0.0, 0.0]),
+                ally_ship,
+                stranger_ship,
+            ],
+        };
+
+        let mut ctx =
+            VisibilityContext::authenticated("player:alice".to_string(), Some(Vec3::ZERO));
+        ctx.ally_player_entity_ids.insert("player:bob".to_string());
+        let filtered = apply_visibility_filter(&world, &ctx, &policy).unwrap();
+        let find = |id: &str| filtered.updates.iter().find(|e| e.entity_id == id).unwrap();
+
+        let ally = find("ship:2");
+        assert!(ally.properties.get("shield_status").is_some());
+        assert!(ally.properties.get("health").is_none());
+        assert_eq!(ally.components.len(), 1);
+
+        let stranger = find("ship:3");
+        assert!(stranger.properties.get("shield_status").is_none());
+        assert!(stranger.components.is_empty());
+    }
+
+    #[test]
+    fn ship_subscription_excludes_modules_and_hardpoints() {
+        let labeled = |entity_id: &str, kind: &str| {
+            let mut entity = make_test_entity(entity_id, Some("player:alice"), true, [0.0; 3]);
+            entity.labels.push(kind.to_string());
+            entity
+        };
+        let mut world = WorldStateDelta {
+            updates: vec![
+                labeled("ship:1", "Ship"),
+                labeled("module:1", "Module"),
+                labeled("hardpoint:1", "Hardpoint"),
+            ],
+        };
+        let mut gone = labeled("module:2", "Module");
+        gone.removed = true;
+        world.updates.push(gone);
+
+        let mut everything = world.clone();
+        apply_subscription_filter(&mut everything, &SubscriptionFilter::default());
+        assert_eq!(everything, world);
+
+        apply_subscription_filter(
+            &mut world,
+            &SubscriptionFilter {
+                include_kinds: vec!["Ship".to_string()],
+            },
+        );
+        let ids = world
+            .updates
+            .iter()
+            .map(|e| e.entity_id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["ship:1", "module:2"]);
+    }
+
+    #[test]
+    fn boundary_oscillation_within_grace_does_not_emit_removals() {
+        let client = Entity::from_bits(7);
+        let mut history = ClientVisibilityHistory::with_removal_grace_ticks(3);
+        let present = WorldStateDelta {
+            updates: vec![make_test_entity("ship:2", None, false, [0.0; 3])],
+        };
+        let absent = WorldStateDelta::default();
+
+        let mut removals = Vec::new();
+        for tick in 0..=18u32 {
+            let world = if tick.is_multiple_of(3) {
+                &present
+            } else {
+                &absent
+            };
+            removals.extend(history.record_delivery(client, world));
+        }
+        assert!(removals.is_empty(), "unexpected removals {removals:?}");
+
+        for _ in 0..3 {
+            assert!(history.record_delivery(client, &absent).is_empty());
+        }
+        assert_eq!(history.record_delivery(client, &absent), vec!["ship:2"]);
+        assert!(history.record_delivery(client, &absent).is_empty());
+    }
+
+    #[test]
+    fn zero_grace_removes_immediately_and_world_removals_are_not_repeated() {
+        let client = Entity::from_bits(7);
+        let mut history = ClientVisibilityHistory::with_removal_grace_ticks(0);
+        let world = WorldStateDelta {
+            updates: vec![
+                make_test_entity("ship:1", None, false, [0.0; 3]),
+                make_test_entity("ship:2", None, false, [0.0; 3]),
+            ],
+        };
+        assert!(history.record_delivery(client, &world).is_empty());
+
+        let mut removed = make_test_entity("ship:1", None, false, [0.0; 3]);
+        removed.removed = true;
+        let next = WorldStateDelta {
+            updates: vec![removed],
+        };
+        assert_eq!(history.record_delivery(client, &next), vec!["ship:2"]);
+    }
+
+    #[test]
+    fn expired_entities_are_out_of_range_only_while_they_still_exist() {
+        let live = HashSet::from(["ship:far"]);
+        let removals =
+            expired_removals(vec!["ship:far".to_string(), "ship:gone".to_string()], &live);
+        let reasons = removals
+            .iter()
+            .map(|update| {
+                assert!(update.removed);
+                (update.entity_id.as_str(), update.removal_reason)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            reasons,
+            vec![
+                ("ship:far", Some(RemovalReason::OutOfRange)),
+                ("ship:gone", Some(RemovalReason::Destroyed)),
+            ]
+        );
+    }
+
+    #[test]
+    fn visible_entity_ids_lists_what_the_client_holds() {
+        let client = Entity::from_bits(7);
+        let mut history = ClientVisibilityHistory::with_removal_grace_ticks(3);
+        assert!(history.visible_entity_ids(client).is_empty());
+        history.seed_client(client, ["ship:2".to_string(), "ship:1".to_string()]);
+        assert_eq!(history.visible_entity_ids(client), vec!["ship:1", "ship:2"]);
     }
 
     #[test]
@@ -507,4 +1024,174 @@ mod tests {
         );
         assert_eq!(map.get_position("player:bob"), None);
     }
+    #[test]
+    fn visible_entity_ids_are_the_in_range_permitted_entities() {
+        let world = WorldStateDelta {
+            updates: vec![
+                make_test_entity("ship:2", Some("player:bob"), true, [120.0, 0.0, 0.0]),
+                make_test_entity("ship:1", Some("player:alice"), true, [1000.0, 0.0, 0.0]),
+                make_test_entity("ship:3", Some("player:carol"), true, [1500.0, 0.0, 0.0]),
+                make_test_entity("asteroid:1", None, false, [40.0, 0.0, 0.0]),
+                WorldDeltaEntity {
+                    entity_id: "ship:gone".to_string(),
+                    labels: Vec::new(),
+                    properties: serde_json::json!({}),
+                    components: Vec::new(),
+                    removed: true,
+                    removed_component_ids: Vec::new(),
+                    removal_reason: None,
+                },
+            ],
+        };
+        let mut positions = ClientControlledEntityPositionMap::default();
+        positions.update_position("player:alice", Vec3::new(1000.0, 0.0, 0.0));
+        let policy = VisibilityPolicy::default();
+
+        // Everything else is beyond Alice's 300 m range, and removals are not
+        // visible entities.
+        assert_eq!(
+            visible_entity_ids_for("player:alice", &world, &positions, &policy),
+            vec!["ship:1"]
+        );
+
+        positions.update_position("player:bob", Vec3::new(120.0, 0.0, 0.0));
+        assert_eq!(
+            visible_entity_ids_for("player:bob", &world, &positions, &policy),
+            vec!["asteroid:1", "ship:2"]
+        );
+    }
+
+    struct DetailFixture {
+        world: WorldStateDelta,
+        own: ShipBuilder,
+        ally: ShipBuilder,
+        hostile: ShipBuilder,
+        /// The ally's modules with the hardpoint each sits on, sorted by
+        /// entity id as the loadout summary is.
+        ally_loadout: Vec<(String, &'static str)>,
+    }
+
+    fn detail_fixture() -> DetailFixture {
+        let engine = Engine {
+            thrust_n: 75_000.0,
+            burn_rate_kg_s: 1.0,
+            thrust_dir: Vec3::Y,
+        };
+        let own = ShipBuilder::new().owned_by("player:alice");
+        let ally = ShipBuilder::new()
+            .owned_by("player:bob")
+            .at(Vec3::new(40.0, 0.0, 0.0));
+        let hostile = ShipBuilder::new()
+            .owned_by("player:carol")
+            .at(Vec3::new(60.0, 0.0, 0.0));
+        let ally_dorsal = HardpointBuilder::on(&ally, "dorsal");
+        let hostile_dorsal = HardpointBuilder::on(&hostile, "dorsal");
+        let ally_aft_module = ModuleBuilder::mounted_on(&ally, "aft").with_engine(engine.clone());
+        let ally_dorsal_module =
+            ModuleBuilder::mounted_on_hardpoint(&ally_dorsal).with_engine(engine.clone());
+        let world = WorldDeltaBuilder::new()
+            .ship(&own)
+            .ship(&ally)
+            .ship(&hostile)
+            .hardpoint(&ally_dorsal)
+            .hardpoint(&hostile_dorsal)
+            .module(&ally_aft_module)
+            .module(&ally_dorsal_module)
+            .module(&ModuleBuilder::mounted_on(&hostile, "aft").with_engine(engine.clone()))
+            .module(&ModuleBuilder::mounted_on_hardpoint(&hostile_dorsal).with_engine(engine))
+            .build();
+        let mut ally_loadout = vec![
+            (ally_aft_module.entity_id(), "aft"),
+            (ally_dorsal_module.entity_id(), "dorsal"),
+        ];
+        ally_loadout.sort();
+        DetailFixture {
+            world,
+            own,
+            ally,
+            hostile,
+            ally_loadout,
+        }
+    }
+
+    #[test]
+    fn allied_detail_carries_the_loadout_beyond_the_stream() {
+        let fixture = detail_fixture();
+        let world = &fixture.world;
+        let ally_id = fixture.ally.entity_id();
+        let policy = VisibilityPolicy::default();
+        let mut ctx =
+            VisibilityContext::authenticated("player:alice".to_string(), Some(Vec3::ZERO));
+        ctx.ally_player_entity_ids.insert("player:bob".to_string());
+
+        let streamed = apply_visibility_filter(world, &ctx, &policy).unwrap();
+        let streamed_ally = streamed
+            .updates
+            .iter()
+            .find(|update| update.entity_id == ally_id)
+            .unwrap();
+        let detail = entity_detail_for(world, &ally_id, &ctx, &policy).unwrap();
+
+        assert!(streamed_ally.properties.get(LOADOUT_PROPERTY).is_none());
+        assert!(
+            detail.properties.as_object().unwrap().len()
+                > streamed_ally.properties.as_object().unwrap().len()
+        );
+        let expected_loadout = fixture
+            .ally_loadout
+            .iter()
+            .map(|(entity_id, hardpoint_id)| {
+                serde_json::json!({
+                    "entity_id": entity_id,
+                    "hardpoint_id": hardpoint_id,
+                    "component_kinds": ["engine"],
+                })
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            detail.properties[LOADOUT_PROPERTY],
+            serde_json::json!(expected_loadout)
+        );
+        assert!(detail.properties.get("health").is_none());
+    }
+
+    #[test]
+    fn hostile_detail_has_only_public_fields() {
+        let fixture = detail_fixture();
+        let world = &fixture.world;
+        let policy = VisibilityPolicy::default();
+        let ctx = VisibilityContext::authenticated("player:alice".to_string(), Some(Vec3::ZERO));
+
+        let detail = entity_detail_for(world, &fixture.hostile.entity_id(), &ctx, &policy).unwrap();
+        assert!(
+            detail
+                .properties
+                .as_object()
+                .unwrap()
+                .keys()
+                .all(|key| PUBLIC_PROPERTIES.contains(&key.as_str()))
+        );
+        assert!(detail.components.is_empty());
+
+        let own = entity_detail_for(world, &fixture.own.entity_id(), &ctx, &policy).unwrap();
+        assert!(own.properties.get("health").is_some());
+        assert!(own.properties.get(LOADOUT_PROPERTY).is_some());
+    }
+
+    #[test]
+    fn detail_is_refused_outside_the_viewer_range() {
+        let fixture = detail_fixture();
+        let world = &fixture.world;
+        let hostile_id = fixture.hostile.entity_id();
+        let policy = VisibilityPolicy::default();
+        let ctx = VisibilityContext::authenticated(
+            "player:alice".to_string(),
+            Some(Vec3::new(5000.0, 0.0, 0.0)),
+        );
+        assert!(entity_detail_for(world, &hostile_id, &ctx, &policy).is_none());
+        assert!(entity_detail_for(world, "ship:404", &ctx, &policy).is_none());
+        assert!(
+            entity_detail_for(world, &hostile_id, &VisibilityContext::none(), &policy).is_none()
+        );
+    }
 }
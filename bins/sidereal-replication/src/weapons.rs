@@ -0,0 +1,193 @@
+//! Server-side resolution of primary fire.
+//!
+//! A `FirePrimary` input fires at the shooter's locked target. The range check
+//! uses where the target was on the tick the shooter last saw
+//! (`seen_state_tick`), looked up in `PositionHistory`, so a player who hit
+//! what was on their screen is not denied by latency. The history window
+//! bounds how far back that lookup can reach.
+
+use bevy::math::Vec3;
+use std::collections::{HashMap, HashSet};
+
+use crate::rewind::PositionHistory;
+
+pub const PRIMARY_RANGE_M: f32 = 400.0;
+pub const PRIMARY_DAMAGE: f32 = 10.0;
+/// A third of a second at the 30 Hz sim rate.
+pub const PRIMARY_COOLDOWN_TICKS: u64 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShotOutcome {
+    /// The target was within range on the rewound tick.
+    Hit {
+        target_position_m: Vec3,
+    },
+    OutOfRange,
+    /// No position history for the target, e.g. it already despawned.
+    UnknownTarget,
+    CoolingDown,
+}
+
+/// Last tick each shooter fired on, keyed by the shooter's entity id.
+#[derive(Debug, Clone, Default)]
+pub struct PrimaryFireCooldowns {
+    last_fired_tick_by_shooter: HashMap<String, u64>,
+}
+
+impl PrimaryFireCooldowns {
+    pub fn is_ready(&self, shooter_entity_id: &str, now_tick: u64) -> bool {
+        self.last_fired_tick_by_shooter
+            .get(shooter_entity_id)
+            .is_none_or(|last| now_tick.saturating_sub(*last) >= PRIMARY_COOLDOWN_TICKS)
+    }
+
+    pub fn mark_fired(&mut self, shooter_entity_id: &str, now_tick: u64) {
+        self.last_fired_tick_by_shooter
+            .insert(shooter_entity_id.to_string(), now_tick);
+    }
+
+    /// Forgets shooters not in `live`.
+    pub fn retain_live(&mut self, live: &HashSet<String>) {
+        self.last_fired_tick_by_shooter
+            .retain(|entity_id, _| live.contains(entity_id));
+    }
+}
+
+/// Resolves one shot from `shooter_entity_id`, currently at
+/// `shooter_position_m`, at `target_entity_id` as the shooter saw it on
+/// `seen_state_tick`. Any shot that gets past the cooldown starts a new one,
+/// hit or miss.
+pub fn resolve_primary_shot(
+    history: &PositionHistory,
+    cooldowns: &mut PrimaryFireCooldowns,
+    shooter_entity_id: &str,
+    shooter_position_m: Vec3,
+    target_entity_id: &str,
+    seen_state_tick: u64,
+    now_tick: u64,
+) -> ShotOutcome {
+    if !cooldowns.is_ready(shooter_entity_id, now_tick) {
+        return ShotOutcome::CoolingDown;
+    }
+    cooldowns.mark_fired(shooter_entity_id, now_tick);
+    // A client claiming to have seen a tick from the future gets the present.
+    let Some(target_position_m) =
+        history.position_at(target_entity_id, seen_state_tick.min(now_tick))
+    else {
+        return ShotOutcome::UnknownTarget;
+    };
+    if shooter_position_m.distance(target_position_m) > PRIMARY_RANGE_M {
+        return ShotOutcome::OutOfRange;
+    }
+    ShotOutcome::Hit { target_position_m }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: f32) -> Vec3 {
+        Vec3::new(x, 0.0, 0.0)
+    }
+
+    /// Target flying away from the origin at 10 m per tick.
+    fn receding_target() -> PositionHistory {
+        let mut history = PositionHistory::new(15);
+        for tick in 0..=50 {
+            history.record("ship:target", tick, at(tick as f32 * 10.0));
+        }
+        history
+    }
+
+    #[test]
+    fn shot_hits_where_the_shooter_saw_the_target() {
+        let history = receding_target();
+        let mut cooldowns = PrimaryFireCooldowns::default();
+        // Now at 500 m, but at 390 m on tick 39 which the shooter last saw.
+        let outcome = resolve_primary_shot(
+            &history,
+            &mut cooldowns,
+            "ship:shooter",
+            Vec3::ZERO,
+            "ship:target",
+            39,
+            50,
+        );
+        assert_eq!(
+            outcome,
+            ShotOutcome::Hit {
+                target_position_m: at(390.0)
+            }
+        );
+    }
+
+    #[test]
+    fn shot_is_judged_at_the_present_without_a_rewind() {
+        let history = receding_target();
+        let mut cooldowns = PrimaryFireCooldowns::default();
+        let outcome = resolve_primary_shot(
+            &history,
+            &mut cooldowns,
+            "ship:shooter",
+            Vec3::ZERO,
+            "ship:target",
+            50,
+            50,
+        );
+        assert_eq!(outcome, ShotOutcome::OutOfRange);
+        let outcome = resolve_primary_shot(
+            &history,
+            &mut cooldowns,
+            "ship:other",
+            Vec3::ZERO,
+            "ship:missing",
+            50,
+            50,
+        );
+        assert_eq!(outcome, ShotOutcome::UnknownTarget);
+    }
+
+    #[test]
+    fn rewind_cannot_reach_past_the_window() {
+        let history = receding_target();
+        let mut cooldowns = PrimaryFireCooldowns::default();
+        // Tick 5 is outside the 15-tick window, so the oldest kept sample
+        // (tick 35, 350 m) is used instead of the 50 m on tick 5.
+        let outcome = resolve_primary_shot(
+            &history,
+            &mut cooldowns,
+            "ship:shooter",
+            at(-100.0),
+            "ship:target",
+            5,
+            50,
+        );
+        assert_eq!(outcome, ShotOutcome::OutOfRange);
+    }
+
+    #[test]
+    fn shots_inside_the_cooldown_are_dropped() {
+        let history = receding_target();
+        let mut cooldowns = PrimaryFireCooldowns::default();
+        let fire = |cooldowns: &mut PrimaryFireCooldowns, now_tick| {
+            resolve_primary_shot(
+                &history,
+                cooldowns,
+                "ship:shooter",
+                Vec3::ZERO,
+                "ship:target",
+                38,
+                now_tick,
+            )
+        };
+        assert!(matches!(fire(&mut cooldowns, 40), ShotOutcome::Hit { .. }));
+        assert_eq!(
+            fire(&mut cooldowns, 40 + PRIMARY_COOLDOWN_TICKS - 1),
+            ShotOutcome::CoolingDown
+        );
+        assert!(matches!(
+            fire(&mut cooldowns, 40 + PRIMARY_COOLDOWN_TICKS),
+            ShotOutcome::Hit { .. }
+        ));
+    }
+}
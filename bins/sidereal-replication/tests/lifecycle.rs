@@ -73,6 +73,8 @@ fn replication_ingest_persist_hydrate_lifecycle() {
                 },
             ],
             removed: false,
+            removed_component_ids: Vec::new(),
+            removal_reason: None,
         },
         WorldDeltaEntity {
             entity_id: hardpoint_id.clone(),
@@ -89,6 +91,8 @@ fn replication_ingest_persist_hydrate_lifecycle() {
                 properties: serde_json::json!({"hardpoint_id": "engine_main", "offset_m": [0.0, 0.0, -2.5]}),
             }],
             removed: false,
+            removed_component_ids: Vec::new(),
+            removal_reason: None,
         },
         WorldDeltaEntity {
             entity_id: engine_id.clone(),
@@ -104,10 +108,12 @@ fn replication_ingest_persist_hydrate_lifecycle() {
                 properties: serde_json::json!({
                     "thrust_n": 180000.0,
                     "burn_rate_kg_s": 14.0,
-                    "thrust_dir": [0.0, 0.0, 1.0]
+                    "thrust_dir": [0.0, 1.0, 0.0]
                 }),
             }],
             removed: false,
+            removed_component_ids: Vec::new(),
+            removal_reason: None,
         },
     ];
 
@@ -134,6 +140,8 @@ fn replication_ingest_persist_hydrate_lifecycle() {
         properties: serde_json::json!({}),
         components: Vec::new(),
         removed: true,
+        removed_component_ids: Vec::new(),
+        removal_reason: None,
     };
     let encoded = encode_envelope_json(&make_envelope(501, vec![removal_update]))
         .expect("encode removal envelope");
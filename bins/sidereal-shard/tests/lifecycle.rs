@@ -142,6 +142,8 @@ fn world_to_deltas(app: &mut App) -> Vec<WorldDeltaEntity> {
             properties: props,
             components,
             removed: false,
+            removed_component_ids: Vec::new(),
+            removal_reason: None,
         });
     }
     out
@@ -381,7 +383,7 @@ fn shard_avian_hydration_persistence_roundtrip() {
             Engine {
                 thrust_n: 250_000.0,
                 burn_rate_kg_s: 16.0,
-                thrust_dir: Vec3::Z,
+                thrust_dir: Vec3::Y,
             },
         ))
         .id();
@@ -0,0 +1,202 @@
+//! Graph/wire entity ids of the form `<kind>:<uuid>` (for example
+//! `ship:6f1c...`). Build them here instead of with `format!` so a malformed id
+//! is rejected where it enters rather than silently becoming a new uuid.
+use std::fmt;
+use std::str::FromStr;
+
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntityIdKind {
+    Player,
+    Ship,
+    Module,
+    Hardpoint,
+    /// An engine mounted on a hull, persisted as its own graph entity.
+    Engine,
+    Asteroid,
+    /// Generic entity without a more specific kind.
+    Entity,
+}
+
+impl EntityIdKind {
+    pub const ALL: [Self; 7] = [
+        Self::Player,
+        Self::Ship,
+        Self::Module,
+        Self::Hardpoint,
+        Self::Engine,
+        Self::Asteroid,
+        Self::Entity,
+    ];
+
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Self::Player => "player",
+            Self::Ship => "ship",
+            Self::Module => "module",
+            Self::Hardpoint => "hardpoint",
+            Self::Engine => "engine",
+            Self::Asteroid => "asteroid",
+            Self::Entity => "entity",
+        }
+    }
+
+    pub fn from_prefix(prefix: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.prefix() == prefix)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntityIdError {
+    MissingSeparator(String),
+    UnknownPrefix(String),
+    InvalidUuid(String),
+    /// The id parsed but is not of the kind the caller required.
+    WrongKind {
+        expected: EntityIdKind,
+        found: EntityIdKind,
+    },
+}
+
+impl fmt::Display for EntityIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingSeparator(raw) => write!(f, "entity id '{raw}' is not <kind>:<uuid>"),
+            Self::UnknownPrefix(raw) => write!(f, "entity id '{raw}' has an unknown kind prefix"),
+            Self::InvalidUuid(raw) => write!(f, "entity id '{raw}' has an invalid uuid"),
+            Self::WrongKind { expected, found } => write!(
+                f,
+                "expected a {} id, found a {} id",
+                expected.prefix(),
+                found.prefix()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EntityIdError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TypedEntityId {
+    pub kind: EntityIdKind,
+    pub uuid: Uuid,
+}
+
+impl TypedEntityId {
+    pub fn new(kind: EntityIdKind, uuid: Uuid) -> Self {
+        Self { kind, uuid }
+    }
+
+    pub fn player(uuid: Uuid) -> Self {
+        Self::new(EntityIdKind::Player, uuid)
+    }
+
+    pub fn ship(uuid: Uuid) -> Self {
+        Self::new(EntityIdKind::Ship, uuid)
+    }
+
+    pub fn module(uuid: Uuid) -> Self {
+        Self::new(EntityIdKind::Module, uuid)
+    }
+
+    pub fn hardpoint(uuid: Uuid) -> Self {
+        Self::new(EntityIdKind::Hardpoint, uuid)
+    }
+
+    pub fn engine(uuid: Uuid) -> Self {
+        Self::new(EntityIdKind::Engine, uuid)
+    }
+
+    pub fn parse(raw: &str) -> Result<Self, EntityIdError> {
+        let (prefix, uuid) = raw
+            .split_once(':')
+            .ok_or_else(|| EntityIdError::MissingSeparator(raw.to_string()))?;
+        let kind = EntityIdKind::from_prefix(prefix)
+            .ok_or_else(|| EntityIdError::UnknownPrefix(raw.to_string()))?;
+        let uuid =
+            Uuid::parse_str(uuid).map_err(|_| EntityIdError::InvalidUuid(raw.to_string()))?;
+        Ok(Self { kind, uuid })
+    }
+
+    /// Parses `raw` and requires it to be of `kind`.
+    pub fn parse_kind(raw: &str, kind: EntityIdKind) -> Result<Self, EntityIdError> {
+        let id = Self::parse(raw)?;
+        if id.kind != kind {
+            return Err(EntityIdError::WrongKind {
+                expected: kind,
+                found: id.kind,
+            });
+        }
+        Ok(id)
+    }
+}
+
+impl fmt::Display for TypedEntityId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.kind.prefix(), self.uuid)
+    }
+}
+
+impl FromStr for TypedEntityId {
+    type Err = EntityIdError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        Self::parse(raw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_kind_roundtrips_through_its_string_form() {
+        let uuid = Uuid::new_v4();
+        for kind in EntityIdKind::ALL {
+            let id = TypedEntityId::new(kind, uuid);
+            let raw = id.to_string();
+            assert_eq!(raw, format!("{}:{uuid}", kind.prefix()));
+            assert_eq!(TypedEntityId::parse(&raw), Ok(id));
+            assert_eq!(raw.parse::<TypedEntityId>(), Ok(id));
+        }
+    }
+
+    #[test]
+    fn malformed_ids_are_rejected() {
+        assert!(matches!(
+            TypedEntityId::parse("ship:not-a-uuid"),
+            Err(EntityIdError::InvalidUuid(_))
+        ));
+        assert!(matches!(
+            TypedEntityId::parse(&format!("starbase:{}", Uuid::nil())),
+            Err(EntityIdError::UnknownPrefix(_))
+        ));
+        assert!(matches!(
+            TypedEntityId::parse("ship"),
+            Err(EntityIdError::MissingSeparator(_))
+        ));
+    }
+
+    #[test]
+    fn parse_kind_rejects_other_kinds() {
+        let raw = TypedEntityId::module(Uuid::nil()).to_string();
+        assert_eq!(
+            TypedEntityId::parse_kind(&raw, EntityIdKind::Ship),
+            Err(EntityIdError::WrongKind {
+                expected: EntityIdKind::Ship,
+                found: EntityIdKind::Module,
+            })
+        );
+        assert!(TypedEntityId::parse_kind(&raw, EntityIdKind::Module).is_ok());
+    }
+
+    #[test]
+    fn engine_ids_used_by_ship_fixtures_parse() {
+        let uuid = Uuid::new_v4();
+        assert_eq!(
+            TypedEntityId::parse(&format!("engine:{uuid}")),
+            Ok(TypedEntityId::engine(uuid))
+        );
+    }
+}
@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 
+pub mod entity_ids;
+pub mod physics;
 pub mod remote_inspect;
+pub mod visibility;
+pub mod world_seed;
 
 pub const PROTOCOL_VERSION: u16 = 1;
 pub const SIM_TICK_HZ: u16 = 30;
@@ -0,0 +1,92 @@
+use std::env;
+
+/// Avian's own default substep count.
+pub const DEFAULT_PHYSICS_SUBSTEPS: u32 = 6;
+/// Upper bound on substeps; each one reruns the solver, so large values cost
+/// far more tick time than they buy in collision accuracy.
+pub const MAX_PHYSICS_SUBSTEPS: u32 = 64;
+/// Open space: no global gravity.
+pub const DEFAULT_PHYSICS_GRAVITY_MPS2: [f32; 3] = [0.0, 0.0, 0.0];
+/// World units per metre. Avian scales its contact tolerances by this.
+pub const DEFAULT_PHYSICS_LENGTH_UNIT: f32 = 1.0;
+
+/// Physics settings shared by replication and the client. Both read the same
+/// `SIDEREAL_PHYSICS_*` vars so client prediction steps the world exactly like
+/// the authority does.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhysicsConfig {
+    pub substeps: u32,
+    pub gravity_mps2: [f32; 3],
+    pub length_unit: f32,
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            substeps: DEFAULT_PHYSICS_SUBSTEPS,
+            gravity_mps2: DEFAULT_PHYSICS_GRAVITY_MPS2,
+            length_unit: DEFAULT_PHYSICS_LENGTH_UNIT,
+        }
+    }
+}
+
+impl PhysicsConfig {
+    pub fn from_env() -> Result<Self, String> {
+        let read = |name: &str| env::var(name).ok().filter(|raw| !raw.trim().is_empty());
+        let defaults = Self::default();
+        Ok(Self {
+            substeps: read("SIDEREAL_PHYSICS_SUBSTEPS")
+                .map(|raw| parse_substeps(&raw))
+                .transpose()?
+                .unwrap_or(defaults.substeps),
+            gravity_mps2: read("SIDEREAL_PHYSICS_GRAVITY_MPS2")
+                .map(|raw| parse_gravity(&raw))
+                .transpose()?
+                .unwrap_or(defaults.gravity_mps2),
+            length_unit: read("SIDEREAL_PHYSICS_LENGTH_UNIT")
+                .map(|raw| parse_length_unit(&raw))
+                .transpose()?
+                .unwrap_or(defaults.length_unit),
+        })
+    }
+}
+
+/// Parses a substep count in `1..=MAX_PHYSICS_SUBSTEPS`.
+pub fn parse_substeps(raw: &str) -> Result<u32, String> {
+    let substeps = raw
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| format!("SIDEREAL_PHYSICS_SUBSTEPS must be a whole number, got {raw:?}"))?;
+    if !(1..=MAX_PHYSICS_SUBSTEPS).contains(&substeps) {
+        return Err(format!(
+            "SIDEREAL_PHYSICS_SUBSTEPS must be between 1 and {MAX_PHYSICS_SUBSTEPS}, got {substeps}"
+        ));
+    }
+    Ok(substeps)
+}
+
+/// Parses gravity as three comma-separated components, e.g. `0,-9.81,0`.
+pub fn parse_gravity(raw: &str) -> Result<[f32; 3], String> {
+    let invalid =
+        || format!("SIDEREAL_PHYSICS_GRAVITY_MPS2 must be three finite numbers x,y,z, got {raw:?}");
+    let components = raw
+        .split(',')
+        .map(|part| part.trim().parse::<f32>().ok().filter(|v| v.is_finite()))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(invalid)?;
+    <[f32; 3]>::try_from(components).map_err(|_| invalid())
+}
+
+/// Parses a length unit; it must be finite and greater than zero.
+pub fn parse_length_unit(raw: &str) -> Result<f32, String> {
+    let unit = raw
+        .trim()
+        .parse::<f32>()
+        .map_err(|_| format!("SIDEREAL_PHYSICS_LENGTH_UNIT must be a number, got {raw:?}"))?;
+    if !unit.is_finite() || unit <= 0.0 {
+        return Err(format!(
+            "SIDEREAL_PHYSICS_LENGTH_UNIT must be finite and greater than 0, got {unit}"
+        ));
+    }
+    Ok(unit)
+}
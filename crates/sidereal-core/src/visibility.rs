@@ -0,0 +1,44 @@
+//! Property keys any player may see on an entity they do not own. The
+//! replication filter and gateway public lookups both read this list so the
+//! two cannot drift apart.
+
+pub const PUBLIC_PROPERTIES: &[&str] = &[
+    "entity_id",
+    "position_m",
+    "velocity_mps",
+    "heading_rad",
+    "display_name",
+    "ship_tag",
+    "module_tag",
+    "mounted_on_entity_id",
+    "parent_entity_id",
+    "size_m",
+    "collision_aabb_m",
+    "mass_kg",
+    "asset_id",
+    "starfield_shader_asset_id",
+];
+
+/// Public keys that replication only delivers while the entity is within the
+/// viewer's view range. A viewer with no position, such as a profile lookup,
+/// never gets them.
+pub const SPATIAL_PROPERTIES: &[&str] = &["position_m", "velocity_mps", "heading_rad"];
+
+/// Whether `key` is visible to a non-owner with no position in the world.
+pub fn is_public_without_position(key: &str) -> bool {
+    PUBLIC_PROPERTIES.contains(&key) && !SPATIAL_PROPERTIES.contains(&key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spatial_keys_are_public_only_with_a_position() {
+        assert!(is_public_without_position("display_name"));
+        assert!(is_public_without_position("asset_id"));
+        assert!(!is_public_without_position("position_m"));
+        assert!(!is_public_without_position("heading_rad"));
+        assert!(!is_public_without_position("health"));
+    }
+}
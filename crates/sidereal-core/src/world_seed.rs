@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+/// Root of every deterministic random stream in a world. Stored once in the
+/// graph and handed to clients in `/world/me`, so procedural content derived
+/// from it (asteroid layouts, spawn jitter, cosmetic noise) comes out the same
+/// on both sides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct WorldSeed(pub u64);
+
+impl WorldSeed {
+    /// A new random seed, for a world that has none yet.
+    pub fn fresh() -> Self {
+        let (high, low) = uuid::Uuid::new_v4().as_u64_pair();
+        Self(high ^ low)
+    }
+
+    /// Seed for one independent stream, named by `purpose` (e.g.
+    /// `"asteroid_field"`). Distinct purposes give unrelated streams; the
+    /// same seed and purpose always give the same value on every platform.
+    pub fn derive(self, purpose: &str) -> u64 {
+        const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+        let purpose_hash = purpose.bytes().fold(FNV_OFFSET, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+        });
+        splitmix64(self.0 ^ purpose_hash)
+    }
+}
+
+/// SplitMix64's state increment (2^64 / golden ratio).
+pub const SPLITMIX64_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// SplitMix64 step: adds the golden-ratio increment to `value` and mixes the
+/// result, spreading nearby inputs across the whole range.
+pub fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(SPLITMIX64_GAMMA);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
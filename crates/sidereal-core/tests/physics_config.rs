@@ -0,0 +1,52 @@
+use sidereal_core::physics::{
+    DEFAULT_PHYSICS_GRAVITY_MPS2, DEFAULT_PHYSICS_LENGTH_UNIT, DEFAULT_PHYSICS_SUBSTEPS,
+    PhysicsConfig, parse_gravity, parse_length_unit, parse_substeps,
+};
+
+#[test]
+fn valid_substep_count_is_applied() {
+    assert_eq!(parse_substeps("12"), Ok(12));
+    assert_eq!(parse_substeps(" 1 "), Ok(1));
+    assert_eq!(PhysicsConfig::default().substeps, DEFAULT_PHYSICS_SUBSTEPS);
+}
+
+#[test]
+fn invalid_substep_count_is_rejected_with_the_reason() {
+    let zero = parse_substeps("0").expect_err("zero substeps should be rejected");
+    assert!(zero.contains("between 1 and 64"), "{zero}");
+    let huge = parse_substeps("500").expect_err("500 substeps should be rejected");
+    assert!(huge.contains("got 500"), "{huge}");
+    let text = parse_substeps("fast").expect_err("non-numeric substeps should be rejected");
+    assert!(text.contains("whole number"), "{text}");
+    assert!(parse_substeps("-3").is_err());
+}
+
+#[test]
+fn defaults_keep_open_space_at_metre_scale() {
+    let config = PhysicsConfig::default();
+    assert_eq!(config.gravity_mps2, DEFAULT_PHYSICS_GRAVITY_MPS2);
+    assert_eq!(config.gravity_mps2, [0.0, 0.0, 0.0]);
+    assert_eq!(config.length_unit, DEFAULT_PHYSICS_LENGTH_UNIT);
+}
+
+#[test]
+fn gravity_parses_three_components() {
+    assert_eq!(parse_gravity("0,-9.81,0"), Ok([0.0, -9.81, 0.0]));
+    assert_eq!(parse_gravity(" 1.5 , 2 , -3 "), Ok([1.5, 2.0, -3.0]));
+    let short = parse_gravity("0,-9.81").expect_err("two components should be rejected");
+    assert!(short.contains("x,y,z"), "{short}");
+    assert!(parse_gravity("0,-9.81,0,1").is_err());
+    assert!(parse_gravity("0,down,0").is_err());
+    assert!(parse_gravity("0,NaN,0").is_err());
+}
+
+#[test]
+fn invalid_length_unit_is_rejected_with_the_reason() {
+    assert_eq!(parse_length_unit("0.5"), Ok(0.5));
+    let zero = parse_length_unit("0").expect_err("zero length unit should be rejected");
+    assert!(zero.contains("greater than 0"), "{zero}");
+    assert!(parse_length_unit("-2").is_err());
+    assert!(parse_length_unit("inf").is_err());
+    let text = parse_length_unit("metre").expect_err("non-numeric length unit should be rejected");
+    assert!(text.contains("must be a number"), "{text}");
+}
@@ -0,0 +1,27 @@
+use sidereal_core::world_seed::{SPLITMIX64_GAMMA, WorldSeed, splitmix64};
+
+#[test]
+fn derived_streams_are_stable_and_independent() {
+    let seed = WorldSeed(0x5eed);
+    assert_eq!(
+        seed.derive("asteroid_field"),
+        WorldSeed(0x5eed).derive("asteroid_field")
+    );
+    assert_ne!(seed.derive("asteroid_field"), seed.derive("spawn_jitter"));
+    assert_ne!(
+        seed.derive("asteroid_field"),
+        WorldSeed(0x5eee).derive("asteroid_field")
+    );
+}
+
+#[test]
+fn fresh_seeds_differ() {
+    assert_ne!(WorldSeed::fresh(), WorldSeed::fresh());
+}
+
+#[test]
+fn splitmix64_matches_the_reference_sequence() {
+    // First outputs of the reference SplitMix64 generator seeded with 0.
+    assert_eq!(splitmix64(0), 0xe220_a839_7b1d_cdaf);
+    assert_eq!(splitmix64(SPLITMIX64_GAMMA), 0x6e78_9e6a_a1b9_65f4);
+}
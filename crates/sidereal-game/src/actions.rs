@@ -89,7 +89,7 @@ impl ActionQueue {
 }
 
 /// Component that declares which actions an entity can handle
-#[derive(Component, Clone, Reflect)]
+#[derive(Debug, Component, Clone, PartialEq, Reflect, Serialize, Deserialize)]
 #[reflect(Component)]
 pub struct ActionCapabilities {
     /// Set of actions this entity can process
@@ -0,0 +1,200 @@
+// Asteroid Field Generator
+// Deterministic placement of asteroid entities around a field center
+// Used by the replication server at startup to seed the persisted world
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use sidereal_core::world_seed::{SPLITMIX64_GAMMA, splitmix64};
+use uuid::Uuid;
+
+use crate::{DisplayName, EntityGuid, HealthPool, MassKg, PositionM, VelocityMps};
+
+const ASTEROID_MIN_MASS_KG: f32 = 5_000.0;
+const ASTEROID_MAX_MASS_KG: f32 = 250_000.0;
+const ASTEROID_MIN_RADIUS_M: f32 = 4.0;
+const ASTEROID_MAX_RADIUS_M: f32 = 24.0;
+const ASTEROID_HEALTH_PER_RADIUS_M: f32 = 50.0;
+
+/// Describes a field of asteroids scattered uniformly over a disc in the XY plane
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AsteroidFieldSpec {
+    /// Field center in world space (meters)
+    pub center: Vec3,
+    /// Field radius (meters)
+    pub radius: f32,
+    /// Number of asteroids in the field
+    pub count: u32,
+    /// PRNG seed; the same seed always yields the same field
+    pub seed: u64,
+}
+
+impl AsteroidFieldSpec {
+    /// Stable identifier stored on every generated asteroid so the field can be
+    /// recognised on later startups
+    pub fn field_id(&self) -> String {
+        format!("asteroid_field:{:016x}", self.seed)
+    }
+}
+
+/// One generated asteroid, ready to be persisted or spawned
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AsteroidSpawn {
+    pub entity_guid: Uuid,
+    pub position_m: Vec3,
+    pub radius_m: f32,
+    pub mass_kg: f32,
+    pub health: f32,
+}
+
+impl AsteroidSpawn {
+    pub fn entity_id(&self) -> String {
+        format!("asteroid:{}", self.entity_guid)
+    }
+}
+
+/// Small SplitMix64 generator; stable across platforms and releases so the
+/// same seed always reproduces the same field
+#[derive(Debug, Clone)]
+pub struct DeterministicRng {
+    state: u64,
+}
+
+impl DeterministicRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let value = splitmix64(self.state);
+        self.state = self.state.wrapping_add(SPLITMIX64_GAMMA);
+        value
+    }
+
+    /// Uniform value in [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub fn next_uuid(&mut self) -> Uuid {
+        let mut bytes = [0_u8; 16];
+        bytes[..8].copy_from_slice(&self.next_u64().to_le_bytes());
+        bytes[8..].copy_from_slice(&self.next_u64().to_le_bytes());
+        uuid::Builder::from_random_bytes(bytes).into_uuid()
+    }
+}
+
+/// Generates the asteroids for a field. Pure and deterministic for a given spec.
+pub fn generate_asteroid_field(spec: &AsteroidFieldSpec) -> Vec<AsteroidSpawn> {
+    let mut rng = DeterministicRng::new(spec.seed);
+    let radius = spec.radius.max(0.0);
+    (0..spec.count)
+        .map(|_| {
+            let entity_guid = rng.next_uuid();
+            // sqrt keeps the distribution uniform over the disc area
+            let distance = radius * rng.next_f32().sqrt();
+            let angle = rng.next_f32() * std::f32::consts::TAU;
+            let position_m =
+                spec.center + Vec3::new(distance * angle.cos(), distance * angle.sin(), 0.0);
+            let size = rng.next_f32();
+            let radius_m =
+                ASTEROID_MIN_RADIUS_M + (ASTEROID_MAX_RADIUS_M - ASTEROID_MIN_RADIUS_M) * size;
+            let mass_kg =
+                ASTEROID_MIN_MASS_KG + (ASTEROID_MAX_MASS_KG - ASTEROID_MIN_MASS_KG) * size * size;
+            AsteroidSpawn {
+                entity_guid,
+                position_m,
+                radius_m,
+                mass_kg,
+                health: radius_m * ASTEROID_HEALTH_PER_RADIUS_M,
+            }
+        })
+        .collect()
+}
+
+/// Spawns a single asteroid as a dynamic rigid body
+pub fn spawn_asteroid(commands: &mut Commands, asteroid: &AsteroidSpawn) -> Entity {
+    commands
+        .spawn((
+            Name::new(asteroid.entity_id()),
+            EntityGuid(asteroid.entity_guid),
+            DisplayName("Asteroid".to_string()),
+            PositionM(asteroid.position_m),
+            VelocityMps(Vec3::ZERO),
+            MassKg(asteroid.mass_kg),
+            HealthPool {
+                current: asteroid.health,
+                maximum: asteroid.health,
+            },
+            Transform::from_translation(asteroid.position_m),
+        ))
+        .insert((
+            RigidBody::Dynamic,
+            Collider::sphere(asteroid.radius_m),
+            Mass(asteroid.mass_kg),
+            Position(asteroid.position_m),
+            Rotation::default(),
+            LinearVelocity::default(),
+            AngularVelocity::default(),
+        ))
+        .id()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(seed: u64) -> AsteroidFieldSpec {
+        AsteroidFieldSpec {
+            center: Vec3::new(1_000.0, -500.0, 0.0),
+            radius: 800.0,
+            count: 32,
+            seed,
+        }
+    }
+
+    #[test]
+    fn same_seed_produces_identical_field() {
+        assert_eq!(
+            generate_asteroid_field(&spec(42)),
+            generate_asteroid_field(&spec(42))
+        );
+    }
+
+    #[test]
+    fn different_seeds_produce_different_fields() {
+        assert_ne!(
+            generate_asteroid_field(&spec(1)),
+            generate_asteroid_field(&spec(2))
+        );
+    }
+
+    #[test]
+    fn asteroids_stay_inside_field_with_sane_properties() {
+        let field = spec(7);
+        let asteroids = generate_asteroid_field(&field);
+        assert_eq!(asteroids.len(), field.count as usize);
+        for asteroid in &asteroids {
+            assert!(asteroid.position_m.distance(field.center) <= field.radius + 1e-3);
+            assert_eq!(asteroid.position_m.z, field.center.z);
+            assert!((ASTEROID_MIN_MASS_KG..=ASTEROID_MAX_MASS_KG).contains(&asteroid.mass_kg));
+            assert!((ASTEROID_MIN_RADIUS_M..=ASTEROID_MAX_RADIUS_M).contains(&asteroid.radius_m));
+            assert!(asteroid.health > 0.0);
+        }
+    }
+
+    #[test]
+    fn asteroid_guids_are_unique_and_valid() {
+        let asteroids = generate_asteroid_field(&spec(99));
+        let mut seen = std::collections::HashSet::new();
+        for asteroid in &asteroids {
+            assert!(!asteroid.entity_guid.is_nil());
+            assert!(seen.insert(asteroid.entity_guid));
+        }
+    }
+
+    #[test]
+    fn field_id_is_stable_for_seed() {
+        assert_eq!(spec(255).field_id(), "asteroid_field:00000000000000ff");
+    }
+}
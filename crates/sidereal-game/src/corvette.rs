@@ -11,7 +11,7 @@ use uuid::Uuid;
 use crate::{
     BaseMassKg, CargoMassKg, CollisionAabbM, DisplayName, Engine, EntityGuid, FlightComputer,
     FuelTank, Hardpoint, HealthPool, Inventory, MassDirty, MassKg, ModuleMassKg, MountedOn,
-    OwnerId, PositionM, ShardAssignment, ShipTag, SizeM, TotalMassKg, VelocityMps,
+    OwnerId, PositionM, ShardAssignment, ShipTag, SizeM, TotalMassKg, VelocityMps, VisualAssets,
 };
 
 /// Complete component bundle for the Prospector-class corvette
@@ -38,6 +38,9 @@ pub struct CorvetteBundle {
     pub size: SizeM,
     pub collision: CollisionAabbM,
 
+    // Presentation
+    pub visual_assets: VisualAssets,
+
     // Health/combat
     pub health: HealthPool,
 
@@ -129,6 +132,7 @@ pub fn spawn_corvette(
             collision: CollisionAabbM {
                 half_extents: Vec3::new(12.5, 6.0, 4.0),
             },
+            visual_assets: VisualAssets::default(),
             health: HealthPool {
                 current: 1000.0,
                 maximum: 1000.0,
@@ -216,9 +220,9 @@ fn spawn_corvette_modules(
         EntityGuid(engine_left_guid),
         DisplayName("Engine Port".to_string()),
         Engine {
-            thrust_n: 50000.0,                    // 50kN thrust
-            burn_rate_kg_s: 0.5,                  // 0.5 kg/s fuel consumption
-            thrust_dir: Vec3::new(0.0, 0.0, 1.0), // Forward thrust
+            thrust_n: 50000.0,   // 50kN thrust
+            burn_rate_kg_s: 0.5, // 0.5 kg/s fuel consumption
+            thrust_dir: Vec3::Y, // Hull forward
         },
         MountedOn {
             parent_entity_id: ship_guid,
@@ -252,7 +256,7 @@ fn spawn_corvette_modules(
         Engine {
             thrust_n: 50000.0,
             burn_rate_kg_s: 0.5,
-            thrust_dir: Vec3::new(0.0, 0.0, 1.0),
+            thrust_dir: Vec3::Y,
         },
         MountedOn {
             parent_entity_id: ship_guid,
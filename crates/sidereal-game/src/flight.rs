@@ -17,7 +17,7 @@ use uuid::Uuid;
 
 use crate::actions::{ActionQueue, EntityAction};
 use crate::generated::components::{
-    Engine, EntityGuid, FlightComputer, FuelTank, MountedOn, TotalMassKg,
+    Engine, EntityGuid, FlightComputer, FlightDamping, FuelTank, MountedOn, TotalMassKg,
 };
 
 const BRAKE_SENTINEL_THROTTLE: f32 = 2.0;
@@ -28,48 +28,422 @@ const PASSIVE_LINEAR_BRAKE_ACCEL_MPS2: f32 = 1.5;
 const ACTIVE_LINEAR_BRAKE_ACCEL_MPS2: f32 = 8.0;
 const PASSIVE_ANGULAR_DAMP_GAIN: f32 = 4_500.0;
 const ACTIVE_ANGULAR_DAMP_GAIN: f32 = 9_000.0;
+/// Torque per rad/s of yaw rate still missing from the commanded turn rate.
+const YAW_RATE_GAIN: f32 = 4_000.0;
+
+/// Applies one action to a flight computer's control state. Returns false for
+/// actions the flight computer does not handle.
+pub fn apply_flight_action(computer: &mut FlightComputer, action: EntityAction) -> bool {
+    match action {
+        EntityAction::ThrustForward => computer.throttle = 1.0,
+        EntityAction::ThrustReverse => computer.throttle = -0.7, // Reverse is typically weaker
+        EntityAction::ThrustNeutral => computer.throttle = 0.0,
+        EntityAction::Brake => {
+            computer.throttle = BRAKE_SENTINEL_THROTTLE;
+            computer.yaw_input = 0.0;
+        }
+        EntityAction::YawLeft => computer.yaw_input = 1.0,
+        EntityAction::YawRight => computer.yaw_input = -1.0,
+        EntityAction::YawNeutral => computer.yaw_input = 0.0,
+        _ => return false,
+    }
+    true
+}
+
+/// How `process_flight_actions` settles flight actions queued in the same
+/// tick that set the same control: the thrust axis (`ThrustForward`,
+/// `ThrustReverse`, `ThrustNeutral`, `Brake`) or the yaw axis (`YawLeft`,
+/// `YawRight`, `YawNeutral`). Client prediction and the server must use the
+/// same rule or they diverge on input churn.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FlightActionTieBreak {
+    /// Independent of queue order. Thrust: `Brake` beats `ThrustForward`,
+    /// which beats `ThrustReverse`, which beats `ThrustNeutral`. Yaw: `YawLeft`
+    /// and `YawRight` together cancel to `YawNeutral`; either alone beats
+    /// `YawNeutral`. The yaw winner is applied first, so a winning `Brake`
+    /// still zeroes yaw.
+    #[default]
+    Priority,
+    /// The last action queued on each axis wins, and the winners apply in
+    /// queue order.
+    LatestWins,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FlightAxis {
+    Thrust,
+    Yaw,
+}
+
+fn flight_axis(action: EntityAction) -> Option<FlightAxis> {
+    match action {
+        EntityAction::ThrustForward
+        | EntityAction::ThrustReverse
+        | EntityAction::ThrustNeutral
+        | EntityAction::Brake => Some(FlightAxis::Thrust),
+        EntityAction::YawLeft | EntityAction::YawRight | EntityAction::YawNeutral => {
+            Some(FlightAxis::Yaw)
+        }
+        _ => None,
+    }
+}
+
+fn thrust_priority(action: EntityAction) -> u8 {
+    match action {
+        EntityAction::Brake => 3,
+        EntityAction::ThrustForward => 2,
+        EntityAction::ThrustReverse => 1,
+        _ => 0,
+    }
+}
+
+/// Reduces one tick's actions to at most one thrust-axis and one yaw-axis
+/// action, in the order they should be applied. Non-flight actions are
+/// dropped.
+pub fn resolve_flight_actions(
+    actions: &[EntityAction],
+    tie_break: FlightActionTieBreak,
+) -> Vec<EntityAction> {
+    match tie_break {
+        FlightActionTieBreak::Priority => {
+            let on_axis = |axis| {
+                actions
+                    .iter()
+                    .copied()
+                    .filter(move |action| flight_axis(*action) == Some(axis))
+            };
+            let has_yaw = |yaw| actions.contains(&yaw);
+            let yaw = match (
+                has_yaw(EntityAction::YawLeft),
+                has_yaw(EntityAction::YawRight),
+            ) {
+                (true, true) => Some(EntityAction::YawNeutral),
+                (true, false) => Some(EntityAction::YawLeft),
+                (false, true) => Some(EntityAction::YawRight),
+                (false, false) => on_axis(FlightAxis::Yaw).next(),
+            };
+            let thrust = on_axis(FlightAxis::Thrust).max_by_key(|action| thrust_priority(*action));
+            yaw.into_iter().chain(thrust).collect()
+        }
+        FlightActionTieBreak::LatestWins => {
+            let last_on_axis = |axis| {
+                actions
+                    .iter()
+                    .rposition(|action| flight_axis(*action) == Some(axis))
+            };
+            let winners = [
+                last_on_axis(FlightAxis::Thrust),
+                last_on_axis(FlightAxis::Yaw),
+            ];
+            actions
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| winners.contains(&Some(*index)))
+                .map(|(_, action)| *action)
+                .collect()
+        }
+    }
+}
 
 /// System that processes actions and updates FlightComputer state
 pub fn process_flight_actions(
+    tie_break: Option<Res<FlightActionTieBreak>>,
     mut query: Query<(&mut ActionQueue, &mut FlightComputer, Option<&MountedOn>)>,
 ) {
+    let tie_break = tie_break.map(|tie_break| *tie_break).unwrap_or_default();
     for (mut queue, mut computer, mounted_on) in &mut query {
         if queue.pending.is_empty() {
             continue;
         }
 
-        for action in queue.drain() {
-            match action {
-                EntityAction::ThrustForward => computer.throttle = 1.0,
-                EntityAction::ThrustReverse => computer.throttle = -0.7, // Reverse is typically weaker
-                EntityAction::ThrustNeutral => computer.throttle = 0.0,
-                EntityAction::Brake => {
-                    computer.throttle = BRAKE_SENTINEL_THROTTLE;
-                    computer.yaw_input = 0.0;
-                }
-                EntityAction::YawLeft => computer.yaw_input = 1.0,
-                EntityAction::YawRight => computer.yaw_input = -1.0,
-                EntityAction::YawNeutral => computer.yaw_input = 0.0,
-                _ => {
-                    // Flight computer doesn't handle this action
-                    if mounted_on.is_some() {
-                        debug!(action = ?action, "FlightComputer module ignoring non-flight action");
-                    }
-                }
+        let actions = queue.drain().collect::<Vec<_>>();
+        for action in &actions {
+            // Flight computer doesn't handle this action
+            if flight_axis(*action).is_none() && mounted_on.is_some() {
+                debug!(action = ?action, "FlightComputer module ignoring non-flight action");
             }
         }
+        for action in resolve_flight_actions(&actions, tie_break) {
+            apply_flight_action(&mut computer, action);
+        }
+    }
+}
+
+/// Control state read from a `FlightComputer` for one physics step.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FlightControl {
+    pub throttle: f32,
+    pub yaw_input: f32,
+    pub turn_rate_deg_s: f32,
+    pub brake_active: bool,
+}
+
+impl FlightControl {
+    pub fn from_computer(computer: &FlightComputer) -> Self {
+        Self {
+            throttle: computer.throttle,
+            yaw_input: computer.yaw_input,
+            turn_rate_deg_s: computer.turn_rate_deg_s,
+            brake_active: computer.throttle >= BRAKE_SENTINEL_THROTTLE,
+        }
+    }
+
+    /// Whether the engines are asked for forward or reverse thrust.
+    pub fn is_thrusting(&self) -> bool {
+        !self.brake_active && self.throttle != 0.0
+    }
+}
+
+/// Force and torque one body receives from its flight controls in one step.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FlightForces {
+    pub force: Vec3,
+    pub torque: Vec3,
+}
+
+/// Body state the flight model reads.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlightBody {
+    pub rotation: Quat,
+    pub velocity: Vec3,
+    pub angular_velocity: Vec3,
+    pub mass_kg: f32,
+}
+
+/// Forces the flight model applies to `body` for a step of `dt` seconds under
+/// `control`, with `thrust` combined from the body's engines. Pure, so the
+/// flight model can be stepped and tested without a world.
+pub fn flight_forces(
+    control: &FlightControl,
+    thrust: &EngineThrustBudget,
+    body: &FlightBody,
+    dt: f32,
+) -> FlightForces {
+    let mut forces = FlightForces::default();
+    let FlightBody {
+        rotation,
+        velocity,
+        angular_velocity,
+        mass_kg,
+    } = *body;
+    let speed = velocity.length();
+    // No engines thrusting leaves a zero direction, which falls back to hull forward.
+    let forward_axis_world = hull_thrust_axis(rotation, thrust.thrust_dir);
+
+    if control.is_thrusting() {
+        let throttle = control.throttle;
+        let accel_cap = engine_accel_cap_mps2(throttle, thrust.thrust_n, mass_kg);
+
+        let current_forward_speed = velocity.dot(forward_axis_world);
+        let target_forward_speed = MAX_LINEAR_SPEED_MPS * throttle.abs() * throttle.signum();
+        let speed_delta = target_forward_speed - current_forward_speed;
+        if dt > 0.0 && accel_cap > 0.0 {
+            let max_speed_step = accel_cap * dt;
+            let applied_step = speed_delta.clamp(-max_speed_step, max_speed_step);
+            let required_accel = applied_step / dt;
+            forces.force += forward_axis_world * (required_accel * mass_kg);
+        }
+
+        // Hard speed governor to prevent runaway values.
+        if speed > MAX_LINEAR_SPEED_MPS {
+            let overspeed = speed - MAX_LINEAR_SPEED_MPS;
+            let governor_accel = (overspeed / dt.max(1e-6)).min(MAX_LINEAR_ACCEL_MPS2 * 2.0);
+            forces.force += -(velocity / speed) * governor_accel * mass_kg;
+        }
+    } else if speed > 0.01 {
+        let decel_accel = linear_decel_accel_mps2(
+            control.brake_active,
+            speed,
+            dt,
+            thrust.brake_thrust_n,
+            mass_kg,
+        );
+        forces.force += -(velocity / speed) * decel_accel * mass_kg;
+    }
+
+    if control.yaw_input != 0.0 {
+        // Drive the yaw rate toward the commanded rate rather than applying a
+        // fixed torque, so a light hull cannot spin past its rated turn rate.
+        let yaw_rate_rad_s = control.yaw_input * control.turn_rate_deg_s.to_radians();
+        forces.torque = Vec3::new(
+            0.0,
+            0.0,
+            (yaw_rate_rad_s - angular_velocity.z) * YAW_RATE_GAIN,
+        );
+    } else {
+        let angular_z = angular_velocity.z;
+        if angular_z.abs() > 0.001 {
+            let gain = if control.brake_active {
+                ACTIVE_ANGULAR_DAMP_GAIN
+            } else {
+                PASSIVE_ANGULAR_DAMP_GAIN
+            };
+            forces.torque = Vec3::new(0.0, 0.0, -angular_z * gain);
+        }
+    }
+    forces
+}
+
+/// Rotates an engine's hull-local thrust direction into world space.
+/// Falls back to hull forward (+Y) when the direction is degenerate.
+pub fn hull_thrust_axis(hull_rotation: Quat, local_thrust_dir: Vec3) -> Vec3 {
+    let local = local_thrust_dir.try_normalize().unwrap_or(Vec3::Y);
+    (hull_rotation * local).try_normalize().unwrap_or(Vec3::Y)
+}
+
+/// Engines persisted before thrust directions became hull-local stored hull
+/// forward as `+Z`. In the hull frame `+Z` points out of the flight plane and
+/// would push nowhere, so a direction with no planar part is read as `+Y`
+/// (`-Z` as `-Y`). Anything else is returned unchanged.
+pub fn migrate_legacy_thrust_dir(thrust_dir: Vec3) -> Vec3 {
+    if thrust_dir.truncate().length_squared() > 1e-6 || thrust_dir.z == 0.0 {
+        return thrust_dir;
+    }
+    Vec3::new(0.0, thrust_dir.z, 0.0)
+}
+
+/// Rewrites legacy engine directions as engines appear (hydration, bootstrap),
+/// so old ships thrust along the hull and the next flush stores the new axis.
+pub fn migrate_legacy_engine_thrust_dirs(mut engines: Query<&mut Engine, Added<Engine>>) {
+    for mut engine in &mut engines {
+        let migrated = migrate_legacy_thrust_dir(engine.thrust_dir);
+        if migrated != engine.thrust_dir {
+            engine.thrust_dir = migrated;
+        }
+    }
+}
+
+/// Deceleration applied when not thrusting. Coasting (`brake_active == false`)
+/// only bleeds speed at the passive rate; active braking uses the engines' brake
+/// thrust up to `ACTIVE_LINEAR_BRAKE_ACCEL_MPS2`. Never overshoots zero in one step.
+pub fn linear_decel_accel_mps2(
+    brake_active: bool,
+    speed: f32,
+    dt: f32,
+    brake_thrust_n: f32,
+    mass_kg: f32,
+) -> f32 {
+    let mut target_accel = PASSIVE_LINEAR_BRAKE_ACCEL_MPS2;
+    if brake_active {
+        let engine_limited_accel = if brake_thrust_n > 0.0 {
+            brake_thrust_n / mass_kg
+        } else {
+            0.0
+        };
+        target_accel = ACTIVE_LINEAR_BRAKE_ACCEL_MPS2.min(engine_limited_accel.max(target_accel));
+    }
+    let no_overshoot_accel = if dt > 0.0 { speed / dt } else { target_accel };
+    target_accel.min(no_overshoot_accel)
+}
+
+/// Most linear acceleration `flight_forces` can command for a body of
+/// `mass_kg` moving at `speed_mps` whose engines total `thrust_n`: the larger
+/// of engine thrust and the (passive or active) brake, plus the overspeed
+/// governor once the body is past `MAX_LINEAR_SPEED_MPS`. Thrusting and
+/// braking are exclusive; the governor only acts while thrusting.
+pub fn max_commanded_accel_mps2(thrust_n: f32, mass_kg: f32, speed_mps: f32) -> f32 {
+    let mass_kg = mass_kg.max(1.0);
+    let engine_accel = engine_accel_cap_mps2(1.0, thrust_n, mass_kg);
+    let brake_accel = ACTIVE_LINEAR_BRAKE_ACCEL_MPS2
+        .min((thrust_n / mass_kg).max(PASSIVE_LINEAR_BRAKE_ACCEL_MPS2));
+    let governor_accel = if speed_mps > MAX_LINEAR_SPEED_MPS {
+        MAX_LINEAR_ACCEL_MPS2 * 2.0
+    } else {
+        0.0
+    };
+    engine_accel.max(brake_accel) + governor_accel
+}
+
+/// Combined output of the engines mounted on one parent for a single step.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EngineThrustBudget {
+    /// Forward thrust in newtons, after fuel limits.
+    pub thrust_n: f32,
+    /// Thrust-weighted sum of hull-local engine directions.
+    pub thrust_dir: Vec3,
+    /// Thrust in newtons available to active braking.
+    pub brake_thrust_n: f32,
+    /// Engines that contributed nothing because their tank is empty.
+    pub fuel_exhausted: usize,
+}
+
+impl EngineThrustBudget {
+    /// Adds one engine's contribution for a step of `dt` seconds, burning
+    /// from that engine's own `fuel_tank`. An engine short on fuel delivers
+    /// thrust in proportion to the fuel it could burn.
+    pub fn add_engine(
+        &mut self,
+        engine: &Engine,
+        fuel_tank: &mut FuelTank,
+        throttle: f32,
+        brake_active: bool,
+        dt: f32,
+    ) {
+        if fuel_tank.fuel_kg <= 0.0 {
+            self.fuel_exhausted += 1;
+            return;
+        }
+        // Active braking uses the full engine budget opposite current velocity.
+        let burn_fraction = if brake_active { 1.0 } else { throttle.abs() };
+        if burn_fraction == 0.0 {
+            return;
+        }
+        let requested_burn_kg = engine.burn_rate_kg_s * burn_fraction * dt;
+        let actual_burn_kg = requested_burn_kg.min(fuel_tank.fuel_kg);
+        let thrust_scale = if requested_burn_kg > 0.0 {
+            actual_burn_kg / requested_burn_kg
+        } else {
+            1.0
+        };
+        fuel_tank.fuel_kg -= actual_burn_kg;
+
+        let thrust_n = engine.thrust_n.abs() * thrust_scale;
+        if brake_active {
+            self.brake_thrust_n += thrust_n;
+        } else {
+            self.thrust_n += thrust_n;
+            self.thrust_dir += engine.thrust_dir.normalize_or_zero() * thrust_n;
+        }
+    }
+}
+
+/// Forward acceleration the combined engine thrust allows at `throttle`,
+/// capped by the flight model's `MAX_LINEAR_ACCEL_MPS2`. Below the cap it
+/// scales with total thrust, so more engines or less mass accelerate harder.
+pub fn engine_accel_cap_mps2(throttle: f32, thrust_n: f32, mass_kg: f32) -> f32 {
+    let engine_accel = if thrust_n > 0.0 {
+        thrust_n / mass_kg
+    } else {
+        0.0
+    };
+    (MAX_LINEAR_ACCEL_MPS2 * throttle.abs()).min(engine_accel)
+}
+
+/// System that feeds `FlightDamping` into Avian's damping whenever it changes
+/// (spawn, hydration, damage, upgrades), so physics always runs on the
+/// persisted values
+pub fn apply_flight_damping(
+    mut commands: Commands,
+    bodies: Query<(Entity, &FlightDamping), Changed<FlightDamping>>,
+) {
+    for (entity, damping) in &bodies {
+        commands.entity(entity).insert((
+            LinearDamping(damping.linear_per_s),
+            AngularDamping(damping.angular_per_s),
+        ));
     }
 }
 
 /// System that applies engine thrust based on FlightComputer state
 /// Uses Avian's Forces query helper for proper force integration
+/// `Engine.thrust_dir` is in the hull's local frame and follows the parent's Rotation
 pub fn apply_engine_thrust(
     time: Res<Time>,
     // Parent entities with flight computers (by GUID)
     computers: Query<(&EntityGuid, &FlightComputer, Option<&MountedOn>)>,
     // Parent entities that can receive forces (Avian Forces query helper)
     mut body_queries: ParamSet<(
-        Query<(&EntityGuid, &Transform, Option<&TotalMassKg>, Forces)>,
+        Query<(&EntityGuid, &Rotation, Option<&TotalMassKg>, Forces)>,
         Query<(&EntityGuid, &LinearVelocity, &AngularVelocity)>,
     )>,
     // Engine modules
@@ -78,7 +452,7 @@ pub fn apply_engine_thrust(
     let dt = time.delta_secs();
 
     // Build map of control state by parent entity GUID
-    let mut control_by_parent = HashMap::<Uuid, (f32, f32, f32, bool)>::new();
+    let mut control_by_parent = HashMap::<Uuid, FlightControl>::new();
     for (guid, computer, mounted_on) in &computers {
         let parent_guid = if let Some(mount) = mounted_on {
             // FlightComputer is a module, use parent GUID
@@ -87,186 +461,466 @@ pub fn apply_engine_thrust(
             // FlightComputer is built-in to the entity
             guid.0
         };
-        let brake_active = computer.throttle >= BRAKE_SENTINEL_THROTTLE;
-
-        control_by_parent.entry(parent_guid).or_insert((
-            computer.throttle,
-            computer.yaw_input,
-            computer.turn_rate_deg_s,
-            brake_active,
-        ));
+        control_by_parent
+            .entry(parent_guid)
+            .or_insert_with(|| FlightControl::from_computer(computer));
     }
 
-    // Aggregate engine thrust budget by parent GUID
-    let mut thrust_budget_by_parent = HashMap::<Uuid, f32>::new();
-    let mut brake_thrust_budget_by_parent = HashMap::<Uuid, f32>::new();
-    let mut fuel_exhausted_count = HashMap::<Uuid, usize>::new();
-
+    // Aggregate engine thrust by parent GUID; every mounted engine adds to
+    // its parent's budget and burns its own tank.
+    let mut thrust_by_parent = HashMap::<Uuid, EngineThrustBudget>::new();
     for (mounted_on, engine, mut fuel_tank) in &mut engines {
-        let Some((throttle, _, _, brake_active)) = control_by_parent.get(&mounted_on.parent_entity_id) else {
+        let Some(control) = control_by_parent.get(&mounted_on.parent_entity_id) else {
             // No flight computer on this parent, engine idle
             continue;
         };
+        thrust_by_parent
+            .entry(mounted_on.parent_entity_id)
+            .or_default()
+            .add_engine(
+                engine,
+                &mut fuel_tank,
+                control.throttle,
+                control.brake_active,
+                dt,
+            );
+    }
 
-        // Check fuel availability
-        if fuel_tank.fuel_kg <= 0.0 {
-            *fuel_exhausted_count
-                .entry(mounted_on.parent_entity_id)
-                .or_insert(0) += 1;
-            continue;
-        }
+    let mut kinematics_by_guid = HashMap::<Uuid, (Vec3, Vec3)>::new();
+    for (guid, linear_velocity, angular_velocity) in &body_queries.p1() {
+        kinematics_by_guid.insert(guid.0, (linear_velocity.0, angular_velocity.0));
+    }
 
-        if *brake_active {
-            // Active braking uses available engine thrust budget opposite current velocity.
-            let requested_burn_kg = engine.burn_rate_kg_s * dt;
-            let actual_burn_kg = requested_burn_kg.min(fuel_tank.fuel_kg);
-            let thrust_scale = if requested_burn_kg > 0.0 {
-                actual_burn_kg / requested_burn_kg
-            } else {
-                1.0
-            };
-            fuel_tank.fuel_kg -= actual_burn_kg;
-            brake_thrust_budget_by_parent
-                .entry(mounted_on.parent_entity_id)
-                .and_modify(|v| *v += engine.thrust_n.abs() * thrust_scale)
-                .or_insert(engine.thrust_n.abs() * thrust_scale);
+    // Apply aggregated forces to parent bodies using Avian's Forces helper
+    for (guid, rotation, total_mass, mut forces) in &mut body_queries.p0() {
+        let Some(control) = control_by_parent.get(&guid.0) else {
             continue;
+        };
+        let (velocity, angular_velocity) = kinematics_by_guid
+            .get(&guid.0)
+            .copied()
+            .unwrap_or((Vec3::ZERO, Vec3::ZERO));
+        let body = FlightBody {
+            rotation: rotation.0,
+            velocity,
+            angular_velocity,
+            mass_kg: total_mass.map(|mass| mass.0.max(1.0)).unwrap_or(15_000.0),
+        };
+        let thrust = thrust_by_parent.get(&guid.0).copied().unwrap_or_default();
+        let step = flight_forces(control, &thrust, &body, dt);
+        if step.force != Vec3::ZERO {
+            forces.apply_force(step.force);
+        }
+        if step.torque != Vec3::ZERO {
+            forces.apply_torque(step.torque);
         }
 
-        if *throttle == 0.0 {
-            continue;
+        // Log if throttle was applied but no thrust budget was available (fuel exhausted path).
+        if control.is_thrusting() && thrust.thrust_n <= 0.0 && thrust.fuel_exhausted > 0 {
+            debug!(
+                entity_guid = %guid.0,
+                exhausted_engines = thrust.fuel_exhausted,
+                "throttle applied but all engines out of fuel"
+            );
         }
+    }
+}
 
-        // Compute fuel burn
-        let requested_burn_kg = engine.burn_rate_kg_s * throttle.abs() * dt;
-        let actual_burn_kg = requested_burn_kg.min(fuel_tank.fuel_kg);
-        let thrust_scale = if requested_burn_kg > 0.0 {
-            actual_burn_kg / requested_burn_kg
-        } else {
-            1.0
-        };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use std::f32::consts::FRAC_PI_2;
 
-        fuel_tank.fuel_kg -= actual_burn_kg;
+    #[test]
+    fn thrust_dir_follows_hull_after_yaw() {
+        let yawed = Quat::from_rotation_z(FRAC_PI_2);
+        let facing = yawed * Vec3::Y;
+        let axis = hull_thrust_axis(yawed, Vec3::Y);
 
-        thrust_budget_by_parent
-            .entry(mounted_on.parent_entity_id)
-            .and_modify(|v| *v += engine.thrust_n.abs() * thrust_scale)
-            .or_insert(engine.thrust_n.abs() * thrust_scale);
+        assert!(axis.distance(facing) < 1e-5);
+        assert!(axis.dot(Vec3::Y).abs() < 1e-5);
+        assert!(axis.distance(Vec3::NEG_X) < 1e-5);
     }
 
-    let mut kinematics_by_guid = HashMap::<Uuid, (Vec3, Vec3)>::new();
-    for (guid, linear_velocity, angular_velocity) in &body_queries.p1() {
-        kinematics_by_guid.insert(guid.0, (linear_velocity.0, angular_velocity.0));
+    #[test]
+    fn legacy_z_forward_engines_are_migrated_to_hull_forward() {
+        assert_eq!(migrate_legacy_thrust_dir(Vec3::Z), Vec3::Y);
+        assert_eq!(
+            migrate_legacy_thrust_dir(Vec3::new(0.0, 0.0, -2.0)),
+            Vec3::NEG_Y * 2.0
+        );
+        let angled = Vec3::new(1.0, 0.0, 1.0);
+        assert_eq!(migrate_legacy_thrust_dir(angled), angled);
+        assert_eq!(migrate_legacy_thrust_dir(Vec3::ZERO), Vec3::ZERO);
+
+        let mut world = World::new();
+        let engine = world
+            .spawn(Engine {
+                thrust_n: 50_000.0,
+                burn_rate_kg_s: 0.5,
+                thrust_dir: Vec3::Z,
+            })
+            .id();
+        world
+            .run_system_once(migrate_legacy_engine_thrust_dirs)
+            .expect("migration runs");
+        assert_eq!(world.get::<Engine>(engine).unwrap().thrust_dir, Vec3::Y);
     }
 
-    // Apply aggregated forces to parent bodies using Avian's Forces helper
-    for (guid, transform, total_mass, mut forces) in &mut body_queries.p0() {
-        let mass_kg = total_mass.map(|mass| mass.0.max(1.0)).unwrap_or(15_000.0);
-        let control = control_by_parent.get(&guid.0).copied();
-
-        if let Some((throttle, yaw_input, turn_rate_deg_s, brake_active)) = control {
-            let (velocity, angular_velocity) = kinematics_by_guid
-                .get(&guid.0)
-                .copied()
-                .unwrap_or((Vec3::ZERO, Vec3::ZERO));
-            let speed = velocity.length();
-            let forward_axis_world = {
-                let axis = transform.rotation * Vec3::Y;
-                let len_sq = axis.length_squared();
-                if len_sq > 1e-6 {
-                    axis / len_sq.sqrt()
-                } else {
-                    Vec3::Y
-                }
-            };
+    #[test]
+    fn degenerate_thrust_dir_falls_back_to_hull_forward() {
+        let yawed = Quat::from_rotation_z(-FRAC_PI_2);
+        assert!(hull_thrust_axis(yawed, Vec3::ZERO).distance(Vec3::X) < 1e-5);
+    }
 
-            if !brake_active && throttle != 0.0 {
-                let available_thrust = thrust_budget_by_parent.get(&guid.0).copied().unwrap_or(0.0);
-                let engine_accel_cap = if available_thrust > 0.0 {
-                    available_thrust / mass_kg
-                } else {
-                    0.0
-                };
-                let accel_target = MAX_LINEAR_ACCEL_MPS2 * throttle.abs();
-                let accel_cap = accel_target.min(engine_accel_cap.max(0.0));
-
-                let current_forward_speed = velocity.dot(forward_axis_world);
-                let target_forward_speed =
-                    MAX_LINEAR_SPEED_MPS * throttle.abs() * throttle.signum();
-                let speed_delta = target_forward_speed - current_forward_speed;
-                if dt > 0.0 && accel_cap > 0.0 {
-                    let max_speed_step = accel_cap * dt;
-                    let applied_step = speed_delta.clamp(-max_speed_step, max_speed_step);
-                    let required_accel = applied_step / dt;
-                    let required_force = forward_axis_world * (required_accel * mass_kg);
-                    forces.apply_force(required_force);
-                }
-
-                // Hard speed governor to prevent runaway values.
-                if speed > MAX_LINEAR_SPEED_MPS {
-                    let overspeed = speed - MAX_LINEAR_SPEED_MPS;
-                    let governor_accel = (overspeed / dt.max(1e-6)).min(MAX_LINEAR_ACCEL_MPS2 * 2.0);
-                    let governor_force = -(velocity / speed) * governor_accel * mass_kg;
-                    forces.apply_force(governor_force);
-                }
-            } else {
-                if speed > 0.01 {
-                    let mut target_accel = PASSIVE_LINEAR_BRAKE_ACCEL_MPS2;
-                    if brake_active {
-                        let available_thrust = brake_thrust_budget_by_parent
-                            .get(&guid.0)
-                            .copied()
-                            .unwrap_or(0.0);
-                        let engine_limited_accel = if available_thrust > 0.0 {
-                            available_thrust / mass_kg
-                        } else {
-                            0.0
-                        };
-                        target_accel = ACTIVE_LINEAR_BRAKE_ACCEL_MPS2.min(engine_limited_accel.max(target_accel));
-                    }
-                    let no_overshoot_accel = if dt > 0.0 { speed / dt } else { target_accel };
-                    let decel_accel = target_accel.min(no_overshoot_accel);
-                    let braking_force = -(velocity / speed) * decel_accel * mass_kg;
-                    forces.apply_force(braking_force);
-                }
-            }
+    #[test]
+    fn brake_action_engages_active_braking_unlike_neutral_thrust() {
+        let mut world = World::new();
+        let computer = FlightComputer {
+            profile: "basic_fly_by_wire".to_string(),
+            throttle: 1.0,
+            yaw_input: 1.0,
+            turn_rate_deg_s: 45.0,
+        };
+        let braking = world
+            .spawn((
+                ActionQueue {
+                    pending: vec![EntityAction::Brake],
+                },
+                computer.clone(),
+            ))
+            .id();
+        let coasting = world
+            .spawn((
+                ActionQueue {
+                    pending: vec![EntityAction::ThrustNeutral],
+                },
+                computer,
+            ))
+            .id();
+        world
+            .run_system_once(process_flight_actions)
+            .expect("flight actions system should run");
 
-            if yaw_input != 0.0 {
-                let yaw_rate_rad_s = yaw_input * turn_rate_deg_s.to_radians();
-                // TODO: Proper torque calculation based on inertia tensor
-                let torque = Vec3::new(0.0, 0.0, yaw_rate_rad_s * 4000.0);
-                forces.apply_torque(torque);
-            } else {
-                let angular_z = angular_velocity.z;
-                if angular_z.abs() > 0.001 {
-                    let gain = if brake_active {
-                        ACTIVE_ANGULAR_DAMP_GAIN
-                    } else {
-                        PASSIVE_ANGULAR_DAMP_GAIN
-                    };
-                    forces.apply_torque(Vec3::new(0.0, 0.0, -angular_z * gain));
-                }
+        let braking = world.get::<FlightComputer>(braking).expect("computer");
+        assert!(braking.throttle >= BRAKE_SENTINEL_THROTTLE);
+        assert_eq!(braking.yaw_input, 0.0);
+        let coasting = world.get::<FlightComputer>(coasting).expect("computer");
+        assert_eq!(coasting.throttle, 0.0);
+        assert_eq!(coasting.yaw_input, 1.0);
+    }
+
+    #[test]
+    fn priority_tie_break_resolves_each_conflicting_pair() {
+        use EntityAction::*;
+        let cases = [
+            ([ThrustForward, ThrustReverse], vec![ThrustForward]),
+            ([ThrustForward, ThrustNeutral], vec![ThrustForward]),
+            ([ThrustReverse, ThrustNeutral], vec![ThrustReverse]),
+            ([Brake, ThrustForward], vec![Brake]),
+            ([Brake, ThrustReverse], vec![Brake]),
+            ([Brake, ThrustNeutral], vec![Brake]),
+            ([YawLeft, YawRight], vec![YawNeutral]),
+            ([YawLeft, YawNeutral], vec![YawLeft]),
+            ([YawRight, YawNeutral], vec![YawRight]),
+            ([Brake, YawLeft], vec![YawLeft, Brake]),
+        ];
+        for (pair, expected) in cases {
+            let reversed = [pair[1], pair[0]];
+            for actions in [pair, reversed] {
+                assert_eq!(
+                    resolve_flight_actions(&actions, FlightActionTieBreak::Priority),
+                    expected,
+                    "{actions:?}"
+                );
             }
         }
+    }
 
-        // Log if throttle was applied but no thrust budget was available (fuel exhausted path).
-        if let Some((throttle, _, _, brake_active)) = control_by_parent.get(&guid.0)
-            && !*brake_active
-            && *throttle != 0.0
-            && thrust_budget_by_parent
-                .get(&guid.0)
-                .copied()
-                .unwrap_or(0.0)
-                <= 0.0
-        {
-            let exhausted = fuel_exhausted_count.get(&guid.0).copied().unwrap_or(0);
-            if exhausted > 0 {
-                debug!(
-                    entity_guid = %guid.0,
-                    exhausted_engines = exhausted,
-                    "throttle applied but all engines out of fuel"
-                );
+    #[test]
+    fn latest_wins_tie_break_keeps_the_last_action_per_axis() {
+        use EntityAction::*;
+        let resolve = |actions: &[EntityAction]| {
+            resolve_flight_actions(actions, FlightActionTieBreak::LatestWins)
+        };
+        assert_eq!(
+            resolve(&[ThrustForward, ThrustReverse]),
+            vec![ThrustReverse]
+        );
+        assert_eq!(
+            resolve(&[ThrustReverse, ThrustForward]),
+            vec![ThrustForward]
+        );
+        assert_eq!(resolve(&[Brake, ThrustNeutral]), vec![ThrustNeutral]);
+        assert_eq!(resolve(&[YawRight, YawLeft]), vec![YawLeft]);
+        assert_eq!(
+            resolve(&[ThrustForward, YawLeft, FirePrimary, Brake, YawRight]),
+            vec![Brake, YawRight]
+        );
+    }
+
+    #[test]
+    fn flight_actions_system_applies_the_configured_tie_break() {
+        let computer = FlightComputer {
+            profile: "basic_fly_by_wire".to_string(),
+            throttle: 0.0,
+            yaw_input: 0.0,
+            turn_rate_deg_s: 45.0,
+        };
+        let churn = vec![
+            EntityAction::ThrustReverse,
+            EntityAction::ThrustForward,
+            EntityAction::YawRight,
+            EntityAction::YawLeft,
+        ];
+        let throttle_and_yaw = |tie_break: Option<FlightActionTieBreak>| {
+            let mut world = World::new();
+            if let Some(tie_break) = tie_break {
+                world.insert_resource(tie_break);
             }
+            let ship = world
+                .spawn((
+                    ActionQueue {
+                        pending: churn.clone(),
+                    },
+                    computer.clone(),
+                ))
+                .id();
+            world
+                .run_system_once(process_flight_actions)
+                .expect("flight actions system should run");
+            let computer = world.get::<FlightComputer>(ship).expect("computer");
+            (computer.throttle, computer.yaw_input)
+        };
+
+        assert_eq!(throttle_and_yaw(None), (1.0, 0.0));
+        assert_eq!(
+            throttle_and_yaw(Some(FlightActionTieBreak::LatestWins)),
+            (1.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn two_engines_double_the_acceleration_of_one() {
+        let engine = Engine {
+            thrust_n: 75_000.0,
+            burn_rate_kg_s: 0.4,
+            thrust_dir: Vec3::Y,
+        };
+        let dt = 1.0 / 30.0;
+        let mass_kg = 15_000.0;
+
+        let mut single = EngineThrustBudget::default();
+        single.add_engine(&engine, &mut FuelTank { fuel_kg: 100.0 }, 1.0, false, dt);
+        let mut twin = EngineThrustBudget::default();
+        let mut tanks = [FuelTank { fuel_kg: 100.0 }, FuelTank { fuel_kg: 100.0 }];
+        for tank in &mut tanks {
+            twin.add_engine(&engine, tank, 1.0, false, dt);
+        }
+
+        let one = engine_accel_cap_mps2(1.0, single.thrust_n, mass_kg);
+        let two = engine_accel_cap_mps2(1.0, twin.thrust_n, mass_kg);
+        assert!((one - 5.0).abs() < 1e-4);
+        assert!((two - 2.0 * one).abs() < 1e-4);
+        assert!(hull_thrust_axis(Quat::IDENTITY, twin.thrust_dir).distance(Vec3::Y) < 1e-5);
+        // Each engine burns from its own tank.
+        for tank in &tanks {
+            assert!((tank.fuel_kg - (100.0 - 0.4 * dt)).abs() < 1e-5);
+        }
+    }
+
+    const DT: f32 = 1.0 / 30.0;
+    const MASS_KG: f32 = 15_000.0;
+
+    fn control_after(actions: &[EntityAction]) -> FlightControl {
+        let mut computer = FlightComputer {
+            profile: "basic_fly_by_wire".to_string(),
+            throttle: 0.0,
+            yaw_input: 0.0,
+            turn_rate_deg_s: 45.0,
+        };
+        for action in actions {
+            apply_flight_action(&mut computer, *action);
+        }
+        FlightControl::from_computer(&computer)
+    }
+
+    fn budget(control: &FlightControl) -> EngineThrustBudget {
+        let engine = Engine {
+            thrust_n: 75_000.0,
+            burn_rate_kg_s: 0.4,
+            thrust_dir: Vec3::Y,
+        };
+        let mut budget = EngineThrustBudget::default();
+        budget.add_engine(
+            &engine,
+            &mut FuelTank { fuel_kg: 100.0 },
+            control.throttle,
+            control.brake_active,
+            DT,
+        );
+        budget
+    }
+
+    fn body(velocity: Vec3, angular_velocity_z: f32) -> FlightBody {
+        FlightBody {
+            rotation: Quat::IDENTITY,
+            velocity,
+            angular_velocity: Vec3::new(0.0, 0.0, angular_velocity_z),
+            mass_kg: MASS_KG,
+        }
+    }
+
+    fn forces_after(actions: &[EntityAction], body: &FlightBody) -> FlightForces {
+        let control = control_after(actions);
+        flight_forces(&control, &budget(&control), body, DT)
+    }
+
+    #[test]
+    fn non_flight_actions_leave_the_computer_alone() {
+        let control = control_after(&[EntityAction::ThrustForward, EntityAction::FirePrimary]);
+        assert_eq!(control.throttle, 1.0);
+        let mut computer = FlightComputer {
+            profile: "basic_fly_by_wire".to_string(),
+            throttle: 0.5,
+            yaw_input: 0.0,
+            turn_rate_deg_s: 45.0,
+        };
+        assert!(!apply_flight_action(
+            &mut computer,
+            EntityAction::FirePrimary
+        ));
+        assert_eq!(computer.throttle, 0.5);
+    }
+
+    #[test]
+    fn forward_thrust_accelerates_along_the_hull_at_the_engine_cap() {
+        let mut ship = body(Vec3::ZERO, 0.0);
+        for _ in 0..30 {
+            let step = forces_after(&[EntityAction::ThrustForward], &ship);
+            assert_eq!(step.torque, Vec3::ZERO);
+            ship.velocity += step.force / MASS_KG * DT;
         }
+        // 75 kN on 15 t is 5 m/s² for one second.
+        assert!(ship.velocity.distance(Vec3::new(0.0, 5.0, 0.0)) < 1e-3);
+    }
+
+    #[test]
+    fn reverse_after_forward_pushes_against_forward_motion() {
+        let moving = body(Vec3::new(0.0, 50.0, 0.0), 0.0);
+        let control = control_after(&[EntityAction::ThrustForward, EntityAction::ThrustReverse]);
+        assert_eq!(control.throttle, -0.7);
+        assert!(!control.brake_active);
+        let step = forces_after(
+            &[EntityAction::ThrustForward, EntityAction::ThrustReverse],
+            &moving,
+        );
+        assert!(step.force.y < 0.0);
+        assert!(step.force.x.abs() < 1e-3);
+        // Reverse is still limited by the engines.
+        assert!(step.force.length() <= 75_000.0 + 1e-2);
+    }
+
+    #[test]
+    fn brake_overrides_thrust_and_yaw_and_opposes_velocity() {
+        let sequence = [
+            EntityAction::ThrustForward,
+            EntityAction::YawLeft,
+            EntityAction::Brake,
+        ];
+        let control = control_after(&sequence);
+        assert!(control.brake_active);
+        assert_eq!(control.yaw_input, 0.0);
+
+        let step = forces_after(&sequence, &body(Vec3::new(100.0, 0.0, 0.0), 0.5));
+        let decel = -step.force / MASS_KG;
+        // One 75 kN engine limits braking to 5 m/s², below the active cap.
+        assert!(decel.distance(Vec3::new(75_000.0 / MASS_KG, 0.0, 0.0)) < 1e-3);
+        assert_eq!(
+            step.torque,
+            Vec3::new(0.0, 0.0, -0.5 * ACTIVE_ANGULAR_DAMP_GAIN)
+        );
+
+        // Thrusting again releases the brake.
+        let control = control_after(&[EntityAction::Brake, EntityAction::ThrustForward]);
+        assert!(!control.brake_active);
+    }
+
+    #[test]
+    fn yaw_actions_torque_then_damp_once_neutral() {
+        let left = forces_after(&[EntityAction::YawLeft], &body(Vec3::ZERO, 0.0));
+        assert!(left.torque.z > 0.0);
+        let right = forces_after(
+            &[EntityAction::YawLeft, EntityAction::YawRight],
+            &body(Vec3::ZERO, 0.0),
+        );
+        assert_eq!(right.torque, -left.torque);
+
+        let spinning = body(Vec3::ZERO, 0.8);
+        let released = forces_after(
+            &[EntityAction::YawLeft, EntityAction::YawNeutral],
+            &spinning,
+        );
+        assert_eq!(
+            released.torque,
+            Vec3::new(0.0, 0.0, -0.8 * PASSIVE_ANGULAR_DAMP_GAIN)
+        );
+        // Nothing to damp and no input: no torque at all.
+        assert_eq!(
+            forces_after(&[EntityAction::YawNeutral], &body(Vec3::ZERO, 0.0)).torque,
+            Vec3::ZERO
+        );
+    }
+
+    #[test]
+    fn held_yaw_settles_at_the_turn_rate() {
+        let turn_rate = 45.0_f32.to_radians();
+        let below = forces_after(&[EntityAction::YawLeft], &body(Vec3::ZERO, 0.5 * turn_rate));
+        assert!(below.torque.z > 0.0);
+        let at_rate = forces_after(&[EntityAction::YawLeft], &body(Vec3::ZERO, turn_rate));
+        assert!(at_rate.torque.z.abs() < 1e-2);
+        // Past the rated turn rate the yaw input pulls the spin back.
+        let past = forces_after(&[EntityAction::YawLeft], &body(Vec3::ZERO, 2.0 * turn_rate));
+        assert!(past.torque.z < 0.0);
+    }
+
+    #[test]
+    fn commanded_accel_bound_covers_brakes_and_governor() {
+        // An engineless hull still coasts down at the passive rate.
+        assert_eq!(
+            max_commanded_accel_mps2(0.0, 4_000.0, 10.0),
+            PASSIVE_LINEAR_BRAKE_ACCEL_MPS2
+        );
+        // Engines bound both thrust and braking; strong ones thrust harder
+        // than the active brake cap allows.
+        assert_eq!(max_commanded_accel_mps2(75_000.0, MASS_KG, 10.0), 5.0);
+        assert_eq!(max_commanded_accel_mps2(600_000.0, MASS_KG, 10.0), 40.0);
+        assert_eq!(
+            max_commanded_accel_mps2(75_000.0, MASS_KG, MAX_LINEAR_SPEED_MPS + 1.0),
+            5.0 + MAX_LINEAR_ACCEL_MPS2 * 2.0
+        );
+
+        let moving = body(Vec3::new(0.0, 100.0, 0.0), 0.0);
+        for actions in [
+            &[EntityAction::ThrustForward][..],
+            &[EntityAction::ThrustReverse],
+            &[EntityAction::Brake],
+            &[EntityAction::ThrustNeutral],
+        ] {
+            let accel = forces_after(actions, &moving).force.length() / MASS_KG;
+            assert!(accel <= max_commanded_accel_mps2(75_000.0, MASS_KG, 100.0) + 1e-3);
+        }
+    }
+
+    #[test]
+    fn active_brake_decelerates_harder_than_coasting() {
+        let coast = linear_decel_accel_mps2(false, 100.0, 1.0 / 30.0, 200_000.0, 15_000.0);
+        let brake = linear_decel_accel_mps2(true, 100.0, 1.0 / 30.0, 200_000.0, 15_000.0);
+        assert_eq!(coast, PASSIVE_LINEAR_BRAKE_ACCEL_MPS2);
+        assert_eq!(brake, ACTIVE_LINEAR_BRAKE_ACCEL_MPS2);
+        // Near standstill braking only removes the remaining speed.
+        let settle = linear_decel_accel_mps2(true, 0.1, 1.0 / 30.0, 200_000.0, 15_000.0);
+        assert!((settle - 3.0).abs() < 1e-4);
     }
 }
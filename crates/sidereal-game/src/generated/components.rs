@@ -75,6 +75,16 @@ pub struct FlightComputer {
     pub turn_rate_deg_s: f32,
 }
 
+#[derive(Debug, Clone, Copy, Component, Reflect, Serialize, Deserialize, PartialEq)]
+#[reflect(Component, Serialize, Deserialize)]
+#[require(EntityGuid)]
+pub struct FlightDamping {
+    /// Linear velocity damping per second (Avian `LinearDamping`)
+    pub linear_per_s: f32,
+    /// Angular velocity damping per second (Avian `AngularDamping`)
+    pub angular_per_s: f32,
+}
+
 #[derive(Debug, Clone, Component, Reflect, Serialize, Deserialize, PartialEq)]
 #[reflect(Component, Serialize, Deserialize)]
 #[require(EntityGuid)]
@@ -104,6 +114,25 @@ pub struct CollisionAabbM {
     pub half_extents: Vec3,
 }
 
+/// Assets a client renders an entity with: its model and the starfield
+/// shader drawn behind it. Both are ids from the gateway's asset stream.
+#[derive(Debug, Clone, Component, Reflect, Serialize, Deserialize, PartialEq, Eq)]
+#[reflect(Component, Serialize, Deserialize)]
+#[require(EntityGuid)]
+pub struct VisualAssets {
+    pub model_asset_id: String,
+    pub starfield_shader_asset_id: String,
+}
+
+impl Default for VisualAssets {
+    fn default() -> Self {
+        Self {
+            model_asset_id: "corvette_01".to_string(),
+            starfield_shader_asset_id: "starfield_wgsl".to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Component, Reflect, Serialize, Deserialize, PartialEq, Eq)]
 #[reflect(Component, Serialize, Deserialize)]
 #[require(EntityGuid)]
@@ -175,6 +204,13 @@ pub struct BaseMassKg(pub f32);
 #[require(EntityGuid)]
 pub struct CargoMassKg(pub f32);
 
+/// Cargo mass the entity's class can carry. Set by the server from the class,
+/// never persisted; `recompute_total_mass` clamps `CargoMassKg` to it.
+#[derive(Debug, Clone, Copy, Component, Reflect, Serialize, Deserialize, PartialEq)]
+#[reflect(Component, Serialize, Deserialize)]
+#[require(EntityGuid)]
+pub struct MaxCargoMassKg(pub f32);
+
 #[derive(Debug, Clone, Copy, Component, Reflect, Serialize, Deserialize, PartialEq, Default)]
 #[reflect(Component, Serialize, Deserialize)]
 #[require(EntityGuid)]
@@ -196,6 +232,9 @@ pub struct MassDirty;
 pub struct ComponentRegistryEntry {
     pub component_kind: &'static str,
     pub type_path: &'static str,
+    /// Written to graph persistence. Transient components (markers, caches)
+    /// are still replicated to clients but never persisted.
+    pub persistable: bool,
 }
 
 #[derive(Debug, Resource, Clone)]
@@ -203,6 +242,17 @@ pub struct GeneratedComponentRegistry {
     pub entries: Vec<ComponentRegistryEntry>,
 }
 
+impl GeneratedComponentRegistry {
+    /// Unknown kinds are persisted, matching the registry's persist-by-default
+    /// contract.
+    pub fn is_persistable(&self, component_kind: &str) -> bool {
+        self.entries
+            .iter()
+            .find(|entry| entry.component_kind == component_kind)
+            .is_none_or(|entry| entry.persistable)
+    }
+}
+
 pub fn register_generated_components(app: &mut App) {
     app.register_type::<EntityGuid>()
         .register_type::<DisplayName>()
@@ -214,10 +264,12 @@ pub fn register_generated_components(app: &mut App) {
         .register_type::<Engine>()
         .register_type::<FuelTank>()
         .register_type::<FlightComputer>()
+        .register_type::<FlightDamping>()
         .register_type::<HealthPool>()
         .register_type::<MassKg>()
         .register_type::<SizeM>()
         .register_type::<CollisionAabbM>()
+        .register_type::<VisualAssets>()
         .register_type::<ShipTag>()
         .register_type::<ModuleTag>()
         .register_type::<OwnerKind>()
@@ -228,6 +280,7 @@ pub fn register_generated_components(app: &mut App) {
         .register_type::<Inventory>()
         .register_type::<BaseMassKg>()
         .register_type::<CargoMassKg>()
+        .register_type::<MaxCargoMassKg>()
         .register_type::<ModuleMassKg>()
         .register_type::<TotalMassKg>()
         .register_type::<MassDirty>()
@@ -249,10 +302,12 @@ pub fn generated_component_registry() -> Vec<ComponentRegistryEntry> {
         entry::<Engine>("engine"),
         entry::<FuelTank>("fuel_tank"),
         entry::<FlightComputer>("flight_computer"),
+        entry::<FlightDamping>("flight_damping"),
         entry::<HealthPool>("health_pool"),
         entry::<MassKg>("mass_kg"),
         entry::<SizeM>("size_m"),
         entry::<CollisionAabbM>("collision_aabb_m"),
+        entry::<VisualAssets>("visual_assets"),
         entry::<ShipTag>("ship_tag"),
         entry::<ModuleTag>("module_tag"),
         entry::<ScannerRangeM>("scanner_range_m"),
@@ -261,9 +316,10 @@ pub fn generated_component_registry() -> Vec<ComponentRegistryEntry> {
         entry::<Inventory>("inventory"),
         entry::<BaseMassKg>("base_mass_kg"),
         entry::<CargoMassKg>("cargo_mass_kg"),
+        transient::<MaxCargoMassKg>("max_cargo_mass_kg"),
         entry::<ModuleMassKg>("module_mass_kg"),
         entry::<TotalMassKg>("total_mass_kg"),
-        entry::<MassDirty>("mass_dirty"),
+        transient::<MassDirty>("mass_dirty"),
         entry::<OwnerId>("owner_id"),
     ]
 }
@@ -272,5 +328,13 @@ fn entry<T>(component_kind: &'static str) -> ComponentRegistryEntry {
     ComponentRegistryEntry {
         component_kind,
         type_path: std::any::type_name::<T>(),
+        persistable: true,
+    }
+}
+
+fn transient<T>(component_kind: &'static str) -> ComponentRegistryEntry {
+    ComponentRegistryEntry {
+        persistable: false,
+        ..entry::<T>(component_kind)
     }
 }
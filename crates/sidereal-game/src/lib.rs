@@ -1,6 +1,7 @@
 use bevy::prelude::*;
 
 pub mod actions;
+pub mod asteroid_field;
 pub mod corvette;
 pub mod flight;
 pub mod generated;
@@ -8,12 +9,16 @@ pub mod mass;
 
 // Re-export commonly used items
 pub use actions::*;
+pub use asteroid_field::*;
 pub use corvette::*;
 pub use generated::components::*;
 pub use mass::recompute_total_mass;
 
 // Re-export flight systems (not components, those come from generated)
-pub use flight::{apply_engine_thrust, process_flight_actions};
+pub use flight::{
+    FlightActionTieBreak, apply_engine_thrust, apply_flight_damping, max_commanded_accel_mps2,
+    migrate_legacy_engine_thrust_dirs, process_flight_actions, resolve_flight_actions,
+};
 
 pub struct SiderealGamePlugin;
 
@@ -26,11 +31,15 @@ impl Plugin for SiderealGamePlugin {
             .register_type::<ActionQueue>()
             .register_type::<ActionCapabilities>();
 
+        app.init_resource::<FlightActionTieBreak>();
+
         // Register action system (runs in FixedUpdate for determinism)
         app.add_systems(
             FixedUpdate,
             (
+                migrate_legacy_engine_thrust_dirs,
                 validate_action_capabilities,
+                apply_flight_damping,
                 process_flight_actions,
                 recompute_total_mass,
                 apply_engine_thrust,
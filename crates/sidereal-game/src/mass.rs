@@ -4,8 +4,8 @@ use std::collections::HashMap;
 use uuid::Uuid;
 
 use crate::generated::components::{
-    BaseMassKg, CargoMassKg, EntityGuid, Inventory, MassDirty, MassKg, ModuleMassKg, MountedOn,
-    TotalMassKg,
+    BaseMassKg, CargoMassKg, EntityGuid, Inventory, MassDirty, MassKg, MaxCargoMassKg,
+    ModuleMassKg, MountedOn, TotalMassKg,
 };
 
 fn inventory_mass_kg(inventory: Option<&Inventory>) -> f32 {
@@ -19,6 +19,15 @@ fn inventory_mass_kg(inventory: Option<&Inventory>) -> f32 {
         .unwrap_or(0.0)
 }
 
+/// Cargo mass counted toward physics: `cargo_kg` capped at the class limit,
+/// when there is one.
+pub fn clamp_cargo_mass_kg(cargo_kg: f32, max_cargo: Option<&MaxCargoMassKg>) -> f32 {
+    match max_cargo {
+        Some(max_cargo) => cargo_kg.min(max_cargo.0.max(0.0)),
+        None => cargo_kg,
+    }
+}
+
 fn module_tree_mass(
     root_guid: Uuid,
     module_mass_by_guid: &HashMap<Uuid, f32>,
@@ -70,6 +79,7 @@ pub fn recompute_total_mass(
             Option<&BaseMassKg>,
             Option<&Inventory>,
             &mut CargoMassKg,
+            Option<&MaxCargoMassKg>,
             &mut ModuleMassKg,
             &mut TotalMassKg,
             Option<&MassDirty>,
@@ -112,6 +122,7 @@ pub fn recompute_total_mass(
         base_mass,
         inventory,
         mut cargo_mass,
+        max_cargo,
         mut module_mass,
         mut total_mass,
         mass_dirty,
@@ -132,7 +143,18 @@ pub fn recompute_total_mass(
             &inventory_mass_by_entity,
             &children_by_parent_entity,
         );
-        let cargo_total = own_inventory + child_inventory;
+        let carried = own_inventory + child_inventory;
+        let cargo_total = clamp_cargo_mass_kg(carried, max_cargo);
+        // Recompute runs every tick while dirty; only log when the clamp
+        // first takes effect.
+        if cargo_total < carried && cargo_mass.0 != cargo_total {
+            warn!(
+                entity_guid = %guid.0,
+                carried_kg = carried,
+                max_cargo_kg = cargo_total,
+                "cargo mass over class limit; clamped"
+            );
+        }
         let module_total = module_tree_mass(
             guid.0,
             &module_mass_by_guid,
@@ -148,3 +170,62 @@ pub fn recompute_total_mass(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generated::components::InventoryEntry;
+    use bevy::ecs::system::RunSystemOnce;
+
+    fn ship_with_cargo(world: &mut World, cargo_kg: f32, max_cargo: Option<f32>) -> Entity {
+        let mut ship = world.spawn((
+            EntityGuid(Uuid::new_v4()),
+            BaseMassKg(15_000.0),
+            Inventory {
+                entries: vec![InventoryEntry {
+                    item_entity_id: Uuid::new_v4(),
+                    quantity: 10,
+                    unit_mass_kg: cargo_kg / 10.0,
+                }],
+            },
+            CargoMassKg(0.0),
+            ModuleMassKg(0.0),
+            TotalMassKg(0.0),
+            MassDirty,
+        ));
+        if let Some(max_cargo) = max_cargo {
+            ship.insert(MaxCargoMassKg(max_cargo));
+        }
+        ship.id()
+    }
+
+    #[test]
+    fn cargo_clamps_to_the_class_limit() {
+        let limit = MaxCargoMassKg(20_000.0);
+        assert_eq!(clamp_cargo_mass_kg(5_000.0, Some(&limit)), 5_000.0);
+        assert_eq!(clamp_cargo_mass_kg(1.0e9, Some(&limit)), 20_000.0);
+        assert_eq!(clamp_cargo_mass_kg(1.0e9, None), 1.0e9);
+        assert_eq!(clamp_cargo_mass_kg(10.0, Some(&MaxCargoMassKg(-5.0))), 0.0);
+    }
+
+    #[test]
+    fn mass_recompute_uses_the_clamped_cargo() {
+        let mut world = World::new();
+        let overloaded = ship_with_cargo(&mut world, 1.0e9, Some(20_000.0));
+        let within = ship_with_cargo(&mut world, 5_000.0, Some(20_000.0));
+        let unlimited = ship_with_cargo(&mut world, 50_000.0, None);
+        world
+            .run_system_once(recompute_total_mass)
+            .expect("mass recompute should run");
+
+        let masses = |entity| {
+            (
+                world.get::<CargoMassKg>(entity).expect("cargo").0,
+                world.get::<TotalMassKg>(entity).expect("total").0,
+            )
+        };
+        assert_eq!(masses(overloaded), (20_000.0, 35_000.0));
+        assert_eq!(masses(within), (5_000.0, 20_000.0));
+        assert_eq!(masses(unlimited), (50_000.0, 65_000.0));
+    }
+}
@@ -45,6 +45,16 @@ fn flight_computer_mapping_is_stable() {
     );
 }
 
+#[test]
+fn transient_components_are_not_persistable() {
+    let registry = GeneratedComponentRegistry {
+        entries: generated_component_registry(),
+    };
+    assert!(!registry.is_persistable("mass_dirty"));
+    assert!(registry.is_persistable("health_pool"));
+    assert!(registry.is_persistable("not_a_registered_kind"));
+}
+
 #[test]
 fn sidereal_game_plugin_inserts_generated_registry_resource() {
     let mut app = App::new();
@@ -6,6 +6,7 @@ pub struct RawInputState {
     pub down: bool,
     pub left: bool,
     pub right: bool,
+    pub brake: bool,
 }
 
 pub fn map_raw_input(raw: RawInputState) -> InputSnapshot {
@@ -14,5 +15,116 @@ pub fn map_raw_input(raw: RawInputState) -> InputSnapshot {
         thrust_reverse: raw.down,
         yaw_left: raw.left,
         yaw_right: raw.right,
+        brake: raw.brake,
+        ..Default::default()
+    }
+}
+
+/// Axis values for a snapshot: `(thrust, turn, brake)`. Brake overrides thrust;
+/// reverse thrust is 70% of forward.
+pub fn snapshot_axes(snapshot: &InputSnapshot) -> (f32, f32, bool) {
+    let thrust = if snapshot.brake {
+        0.0
+    } else if snapshot.thrust_forward {
+        1.0
+    } else if snapshot.thrust_reverse {
+        -0.7
+    } else {
+        0.0
+    };
+    let turn = if snapshot.yaw_left {
+        1.0
+    } else if snapshot.yaw_right {
+        -1.0
+    } else {
+        0.0
+    };
+    (thrust, turn, snapshot.brake)
+}
+
+/// Decides when a snapshot needs to go out: on any change, and otherwise once
+/// every `keepalive_frames` frames so an unreliable channel recovers from loss.
+#[derive(Debug, Clone)]
+pub struct InputChangeGate {
+    last_sent: Option<InputSnapshot>,
+    frames_since_send: u32,
+    keepalive_frames: u32,
+}
+
+impl InputChangeGate {
+    pub fn new(keepalive_frames: u32) -> Self {
+        Self {
+            last_sent: None,
+            frames_since_send: 0,
+            keepalive_frames: keepalive_frames.max(1),
+        }
+    }
+
+    pub fn should_send(&mut self, snapshot: InputSnapshot) -> bool {
+        self.frames_since_send = self.frames_since_send.saturating_add(1);
+        if self.last_sent == Some(snapshot) && self.frames_since_send < self.keepalive_frames {
+            return false;
+        }
+        self.last_sent = Some(snapshot);
+        self.frames_since_send = 0;
+        true
+    }
+
+    pub fn reset(&mut self) {
+        self.last_sent = None;
+        self.frames_since_send = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gate_skips_unchanged_frames_until_keepalive() {
+        let mut gate = InputChangeGate::new(4);
+        let forward = map_raw_input(RawInputState {
+            up: true,
+            ..Default::default()
+        });
+
+        assert!(gate.should_send(forward));
+        assert!(!gate.should_send(forward));
+        assert!(!gate.should_send(forward));
+        assert!(!gate.should_send(forward));
+        assert!(gate.should_send(forward), "keepalive frame");
+        assert!(!gate.should_send(forward));
+    }
+
+    #[test]
+    fn gate_sends_on_change_and_after_reset() {
+        let mut gate = InputChangeGate::new(100);
+        let neutral = InputSnapshot::default();
+        let left = map_raw_input(RawInputState {
+            left: true,
+            ..Default::default()
+        });
+
+        assert!(gate.should_send(neutral));
+        assert!(gate.should_send(left));
+        assert!(gate.should_send(neutral));
+        gate.reset();
+        assert!(gate.should_send(neutral));
+    }
+
+    #[test]
+    fn brake_overrides_thrust_axis() {
+        let snapshot = map_raw_input(RawInputState {
+            up: true,
+            right: true,
+            brake: true,
+            ..Default::default()
+        });
+        assert_eq!(snapshot_axes(&snapshot), (0.0, -1.0, true));
+        let reverse = map_raw_input(RawInputState {
+            down: true,
+            ..Default::default()
+        });
+        assert_eq!(snapshot_axes(&reverse), (-0.7, 0.0, false));
     }
 }
@@ -1,19 +1,163 @@
-use serde::de::DeserializeOwned;
+use serde::de::{DeserializeOwned, IgnoredAny};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
+use sidereal_core::PROTOCOL_VERSION;
+use std::collections::{HashMap, HashSet};
+use std::io;
 
 #[cfg(feature = "lightyear_protocol")]
 mod lightyear_protocol;
 #[cfg(feature = "lightyear_protocol")]
 pub use lightyear_protocol::*;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ChannelClass {
     Input,
     State,
     Control,
 }
 
+impl ChannelClass {
+    pub const ALL: [Self; 3] = [Self::Input, Self::State, Self::Control];
+
+    /// Upper-case name used in the `SIDEREAL_CHANNEL_<CLASS>_*` env vars.
+    pub fn env_name(self) -> &'static str {
+        match self {
+            Self::Input => "INPUT",
+            Self::State => "STATE",
+            Self::Control => "CONTROL",
+        }
+    }
+}
+
+/// How a channel orders and retransmits its messages. Sequenced channels
+/// drop a message that arrives after a newer one; ordered channels hold it
+/// back until the gap is filled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelDelivery {
+    UnorderedUnreliable,
+    SequencedUnreliable,
+    UnorderedReliable,
+    SequencedReliable,
+    OrderedReliable,
+}
+
+impl ChannelDelivery {
+    /// Parses the snake_case name, e.g. `ordered_reliable`, ignoring case.
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "unordered_unreliable" => Some(Self::UnorderedUnreliable),
+            "sequenced_unreliable" => Some(Self::SequencedUnreliable),
+            "unordered_reliable" => Some(Self::UnorderedReliable),
+            "sequenced_reliable" => Some(Self::SequencedReliable),
+            "ordered_reliable" => Some(Self::OrderedReliable),
+            _ => None,
+        }
+    }
+
+    pub fn is_reliable(self) -> bool {
+        matches!(
+            self,
+            Self::UnorderedReliable | Self::SequencedReliable | Self::OrderedReliable
+        )
+    }
+}
+
+/// Delivery and send priority of one channel class.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ChannelConfig {
+    pub delivery: ChannelDelivery,
+    pub priority: f32,
+}
+
+/// Channel settings for every `ChannelClass`. Defaults: control (auth,
+/// subscriptions, chat-style requests) is ordered and reliable, state
+/// snapshots are sequenced so a late snapshot never overwrites a newer one,
+/// and input is fire-and-forget because the next tick's input supersedes it.
+///
+/// Both ends of a connection must agree, so replication and the client read
+/// the same `SIDEREAL_CHANNEL_<CLASS>_DELIVERY` and
+/// `SIDEREAL_CHANNEL_<CLASS>_PRIORITY` vars.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ChannelConfigs {
+    pub input: ChannelConfig,
+    pub state: ChannelConfig,
+    pub control: ChannelConfig,
+}
+
+impl Default for ChannelConfigs {
+    fn default() -> Self {
+        Self {
+            input: ChannelConfig {
+                delivery: ChannelDelivery::UnorderedUnreliable,
+                priority: 10.0,
+            },
+            state: ChannelConfig {
+                delivery: ChannelDelivery::SequencedUnreliable,
+                priority: 10.0,
+            },
+            control: ChannelConfig {
+                delivery: ChannelDelivery::OrderedReliable,
+                priority: 8.0,
+            },
+        }
+    }
+}
+
+impl ChannelConfigs {
+    pub fn get(&self, class: ChannelClass) -> ChannelConfig {
+        match class {
+            ChannelClass::Input => self.input,
+            ChannelClass::State => self.state,
+            ChannelClass::Control => self.control,
+        }
+    }
+
+    pub fn get_mut(&mut self, class: ChannelClass) -> &mut ChannelConfig {
+        match class {
+            ChannelClass::Input => &mut self.input,
+            ChannelClass::State => &mut self.state,
+            ChannelClass::Control => &mut self.control,
+        }
+    }
+
+    pub fn from_env() -> Result<Self, String> {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// `from_env` over an arbitrary variable source; unset or blank
+    /// variables keep the default.
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+        let mut configs = Self::default();
+        for class in ChannelClass::ALL {
+            let read = |suffix: &str| {
+                let name = format!("SIDEREAL_CHANNEL_{}_{suffix}", class.env_name());
+                lookup(&name)
+                    .filter(|raw| !raw.trim().is_empty())
+                    .map(|raw| (name, raw))
+            };
+            let config = configs.get_mut(class);
+            if let Some((name, raw)) = read("DELIVERY") {
+                config.delivery = ChannelDelivery::parse(&raw).ok_or_else(|| {
+                    format!("{name} must be a channel delivery mode, got {raw:?}")
+                })?;
+            }
+            if let Some((name, raw)) = read("PRIORITY") {
+                config.priority = raw
+                    .trim()
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|priority| priority.is_finite() && *priority > 0.0)
+                    .ok_or_else(|| {
+                        format!("{name} must be a number greater than 0, got {raw:?}")
+                    })?;
+            }
+        }
+        Ok(configs)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetEnvelope<T> {
     pub protocol_version: u16,
@@ -32,6 +176,59 @@ pub struct WorldComponentDelta {
     pub properties: JsonValue,
 }
 
+/// The `component_id` of `entity_id`'s `component_kind` component:
+/// `"{entity_id}:{component_kind}"`. Broadcast and persistence both key
+/// components by this id, and orphan deletion compares it, so every path
+/// that builds a component must produce exactly this shape.
+pub fn component_id_for(entity_id: &str, component_kind: &str) -> String {
+    format!("{entity_id}:{component_kind}")
+}
+
+/// A component whose id does not match the `entity_id:kind` shape.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentIdMismatch {
+    pub entity_id: String,
+    pub component_kind: String,
+    pub component_id: String,
+}
+
+impl std::fmt::Display for ComponentIdMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "component_id {:?} of {} does not match expected {:?}",
+            self.component_id,
+            self.entity_id,
+            component_id_for(&self.entity_id, &self.component_kind)
+        )
+    }
+}
+
+impl WorldComponentDelta {
+    /// Length in bytes of this component's JSON encoding.
+    pub fn encoded_size(&self) -> usize {
+        encoded_json_len(self)
+    }
+
+    /// Checks that `component_id` is `component_id_for(entity_id, kind)`.
+    pub fn validate_id(&self, entity_id: &str) -> Result<(), ComponentIdMismatch> {
+        let well_formed = self
+            .component_id
+            .strip_prefix(entity_id)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .is_some_and(|kind| kind == self.component_kind);
+        if well_formed {
+            Ok(())
+        } else {
+            Err(ComponentIdMismatch {
+                entity_id: entity_id.to_string(),
+                component_kind: self.component_kind.clone(),
+                component_id: self.component_id.clone(),
+            })
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct WorldDeltaEntity {
     pub entity_id: String,
@@ -43,6 +240,68 @@ pub struct WorldDeltaEntity {
     pub components: Vec<WorldComponentDelta>,
     #[serde(default)]
     pub removed: bool,
+    /// Components dropped from an entity that otherwise remains. An update that
+    /// carries only removals (no `components`) leaves every other component as is.
+    #[serde(default)]
+    pub removed_component_ids: Vec<String>,
+    /// Why a `removed` entity went away, so the client can tell a destroyed
+    /// ship from one that merely left scanner range. `None` when unknown,
+    /// including from servers that predate it.
+    #[serde(default)]
+    pub removal_reason: Option<RemovalReason>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemovalReason {
+    /// Still exists, but is no longer visible to this client.
+    OutOfRange,
+    /// Despawned by the simulation.
+    Destroyed,
+    /// The client lost the right to see it, e.g. its session was taken over.
+    Unauthorized,
+    /// The server is going away.
+    ServerShutdown,
+}
+
+impl WorldDeltaEntity {
+    /// True when the update only drops components and should not be treated as
+    /// a full component snapshot of the entity.
+    pub fn is_component_removal(&self) -> bool {
+        !self.removed && self.components.is_empty() && !self.removed_component_ids.is_empty()
+    }
+
+    /// Rewrites every component id that does not match the `entity_id:kind`
+    /// shape and returns what was wrong, so the caller can log it. Two
+    /// components of the same kind collapse onto one id; the graph keys
+    /// components by id, so it could only ever have stored one of them.
+    pub fn normalize_component_ids(&mut self) -> Vec<ComponentIdMismatch> {
+        let mut mismatches = Vec::new();
+        for component in &mut self.components {
+            if let Err(mismatch) = component.validate_id(&self.entity_id) {
+                component.component_id =
+                    component_id_for(&self.entity_id, &component.component_kind);
+                mismatches.push(mismatch);
+            }
+        }
+        mismatches
+    }
+
+    /// Most specific graph label (`Ship`, `Module`, `Hardpoint`, ...), or
+    /// `Entity` when the update carries no other label.
+    pub fn derived_kind(&self) -> &str {
+        self.labels
+            .iter()
+            .map(String::as_str)
+            .find(|label| *label != "Entity")
+            .unwrap_or("Entity")
+    }
+
+    /// Length in bytes of this update's JSON encoding, as it appears inside a
+    /// `WorldStateDelta`. Counts the bytes without buffering them.
+    pub fn encoded_size(&self) -> usize {
+        encoded_json_len(self)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
@@ -51,14 +310,484 @@ pub struct WorldStateDelta {
     pub updates: Vec<WorldDeltaEntity>,
 }
 
+/// Grid positions and velocities are rounded to before `state_hash`, so a
+/// float that survives a JSON round trip slightly differently cannot change it.
+pub const STATE_HASH_RESOLUTION: f64 = 0.01;
+
+impl WorldStateDelta {
+    /// `state_hash_with` at the default heading step.
+    pub fn state_hash(&self) -> u64 {
+        self.state_hash_with(&HeadingQuantizer::default())
+    }
+
+    /// Checksum of every live entity's id and spatial state (`position_m`,
+    /// `velocity_mps` rounded to `STATE_HASH_RESOLUTION`, `heading_rad` to
+    /// `heading`'s step), independent of update order. Components are not
+    /// covered. Uses FNV-1a so the value is identical across builds and
+    /// platforms, which the client relies on to detect desync. Both sides must
+    /// use the same heading step.
+    pub fn state_hash_with(&self, heading: &HeadingQuantizer) -> u64 {
+        let mut entities = self
+            .updates
+            .iter()
+            .filter(|update| !update.removed && !update.is_component_removal())
+            .collect::<Vec<_>>();
+        entities.sort_by(|a, b| a.entity_id.cmp(&b.entity_id));
+
+        let mut hash = Fnv1a::default();
+        for entity in entities {
+            hash.write(entity.entity_id.as_bytes());
+            hash.write(&[0]);
+            for key in ["position_m", "velocity_mps"] {
+                match entity.properties.get(key).and_then(JsonValue::as_array) {
+                    Some(axes) => {
+                        hash.write(&[1]);
+                        for axis in axes {
+                            let value = axis.as_f64().unwrap_or(0.0);
+                            let cells = (value / STATE_HASH_RESOLUTION).round() as i64;
+                            hash.write(&cells.to_le_bytes());
+                        }
+                    }
+                    None => hash.write(&[0]),
+                }
+            }
+            match entity
+                .properties
+                .get("heading_rad")
+                .and_then(JsonValue::as_f64)
+            {
+                Some(heading_rad) => {
+                    let quantized = heading.quantize(heading_rad as f32);
+                    hash.write(&[1]);
+                    hash.write(&quantized.to_bits().to_le_bytes());
+                }
+                None => hash.write(&[0]),
+            }
+        }
+        hash.0
+    }
+}
+
+impl WorldStateDelta {
+    /// What a peer holding `prev` needs to reach `self`, both full states:
+    /// entities new since `prev` in full; for changed entities, only the
+    /// top-level properties that changed (a dropped key as `null`), the
+    /// components that changed, the ids of components that went away, and the
+    /// labels only if they changed; a `removed` update for every entity of
+    /// `prev` that `self` no longer has. Unchanged entities are left out.
+    /// `apply_delta` reverses it.
+    pub fn diff_against(&self, prev: &WorldStateDelta) -> WorldStateDelta {
+        let prev_by_id = prev
+            .updates
+            .iter()
+            .map(|update| (update.entity_id.as_str(), update))
+            .collect::<HashMap<_, _>>();
+        let mut updates = Vec::new();
+        for next in &self.updates {
+            match prev_by_id.get(next.entity_id.as_str()) {
+                Some(prev) if !prev.removed && !next.removed => {
+                    if let Some(changed) = diff_entity(prev, next) {
+                        updates.push(changed);
+                    }
+                }
+                _ => updates.push(next.clone()),
+            }
+        }
+
+        let next_ids = self
+            .updates
+            .iter()
+            .map(|update| update.entity_id.as_str())
+            .collect::<HashSet<_>>();
+        for gone in prev
+            .updates
+            .iter()
+            .filter(|update| !update.removed && !next_ids.contains(update.entity_id.as_str()))
+        {
+            updates.push(WorldDeltaEntity {
+                entity_id: gone.entity_id.clone(),
+                labels: Vec::new(),
+                properties: JsonValue::Null,
+                components: Vec::new(),
+                removed: true,
+                removed_component_ids: Vec::new(),
+                removal_reason: None,
+            });
+        }
+        WorldStateDelta { updates }
+    }
+
+    /// Brings this full state up to date with a `diff_against` result.
+    /// Updated entities keep their place and new ones are appended; removed
+    /// entities are dropped. A property the delta sets to `null` is dropped,
+    /// so a full state that holds `null` properties does not round-trip them.
+    pub fn apply_delta(&mut self, delta: &WorldStateDelta) {
+        for change in &delta.updates {
+            let position = self
+                .updates
+                .iter()
+                .position(|update| update.entity_id == change.entity_id);
+            match (position, change.removed) {
+                (Some(index), true) => {
+                    self.updates.remove(index);
+                }
+                (None, true) => {}
+                (None, false) => self.updates.push(change.clone()),
+                (Some(index), false) => apply_entity_delta(&mut self.updates[index], change),
+            }
+        }
+    }
+}
+
+fn diff_entity(prev: &WorldDeltaEntity, next: &WorldDeltaEntity) -> Option<WorldDeltaEntity> {
+    let labels = if next.labels == prev.labels {
+        Vec::new()
+    } else {
+        next.labels.clone()
+    };
+    let properties = match (&prev.properties, &next.properties) {
+        (prev_properties, next_properties) if prev_properties == next_properties => JsonValue::Null,
+        (JsonValue::Object(prev_properties), JsonValue::Object(next_properties)) => {
+            let mut changed = next_properties
+                .iter()
+                .filter(|(key, value)| prev_properties.get(*key) != Some(*value))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect::<serde_json::Map<_, _>>();
+            for key in prev_properties.keys() {
+                if !next_properties.contains_key(key) {
+                    changed.insert(key.clone(), JsonValue::Null);
+                }
+            }
+            JsonValue::Object(changed)
+        }
+        (_, next_properties) => next_properties.clone(),
+    };
+    let components = next
+        .components
+        .iter()
+        .filter(|component| {
+            !prev
+                .components
+                .iter()
+                .any(|previous| previous == *component)
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    let removed_component_ids = prev
+        .components
+        .iter()
+        .filter(|previous| {
+            !next
+                .components
+                .iter()
+                .any(|component| component.component_id == previous.component_id)
+        })
+        .map(|previous| previous.component_id.clone())
+        .collect::<Vec<_>>();
+
+    let unchanged = labels.is_empty()
+        && properties.is_null()
+        && components.is_empty()
+        && removed_component_ids.is_empty();
+    (!unchanged).then(|| WorldDeltaEntity {
+        entity_id: next.entity_id.clone(),
+        labels,
+        properties,
+        components,
+        removed: false,
+        removed_component_ids,
+        removal_reason: None,
+    })
+}
+
+fn apply_entity_delta(entity: &mut WorldDeltaEntity, change: &WorldDeltaEntity) {
+    if !change.labels.is_empty() {
+        entity.labels = change.labels.clone();
+    }
+    match (&mut entity.properties, &change.properties) {
+        (_, JsonValue::Null) => {}
+        (JsonValue::Object(properties), JsonValue::Object(changed)) => {
+            for (key, value) in changed {
+                if value.is_null() {
+                    properties.remove(key);
+                } else {
+                    properties.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        (properties, changed) => *properties = changed.clone(),
+    }
+    entity.components.retain(|component| {
+        !change
+            .removed_component_ids
+            .contains(&component.component_id)
+    });
+    for component in &change.components {
+        match entity
+            .components
+            .iter_mut()
+            .find(|existing| existing.component_id == component.component_id)
+        {
+            Some(existing) => *existing = component.clone(),
+            None => entity.components.push(component.clone()),
+        }
+    }
+}
+
+/// An update `WorldStateDelta::decode_lenient` could not decode and dropped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedEntity {
+    /// Position of the update in the encoded `updates` array.
+    pub index: usize,
+    /// `None` when the update has no readable `entity_id`.
+    pub entity_id: Option<String>,
+    pub error: String,
+}
+
+/// A world decoded update by update, with the updates that were dropped.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DecodedWorld {
+    pub world: WorldStateDelta,
+    pub skipped: Vec<SkippedEntity>,
+}
+
+impl DecodedWorld {
+    pub fn skipped_entity_ids(&self) -> Vec<&str> {
+        self.skipped
+            .iter()
+            .filter_map(|skipped| skipped.entity_id.as_deref())
+            .collect()
+    }
+}
+
+/// The encoded world as a whole is unreadable, so no update could be decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeWorldError {
+    /// Not valid JSON.
+    Json(String),
+    /// Valid JSON, but not an object with an `updates` array.
+    Shape(String),
+}
+
+impl std::fmt::Display for DecodeWorldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Json(err) => write!(f, "world is not valid JSON: {err}"),
+            Self::Shape(err) => write!(f, "world has an unexpected shape: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeWorldError {}
+
+impl WorldStateDelta {
+    /// Decodes `bytes` one update at a time, so a single malformed entity is
+    /// dropped and reported in `DecodedWorld::skipped` instead of failing the
+    /// whole world. Only an unreadable envelope is an error.
+    pub fn decode_lenient(bytes: &[u8]) -> Result<DecodedWorld, DecodeWorldError> {
+        let value = serde_json::from_slice::<JsonValue>(bytes)
+            .map_err(|err| DecodeWorldError::Json(err.to_string()))?;
+        let JsonValue::Object(mut object) = value else {
+            return Err(DecodeWorldError::Shape("expected an object".to_string()));
+        };
+        let updates = match object.remove("updates") {
+            None | Some(JsonValue::Null) => Vec::new(),
+            Some(JsonValue::Array(updates)) => updates,
+            Some(_) => {
+                return Err(DecodeWorldError::Shape(
+                    "`updates` is not an array".to_string(),
+                ));
+            }
+        };
+
+        let mut decoded = DecodedWorld::default();
+        for (index, update) in updates.into_iter().enumerate() {
+            let entity_id = update
+                .get("entity_id")
+                .and_then(JsonValue::as_str)
+                .map(str::to_string);
+            match serde_json::from_value::<WorldDeltaEntity>(update) {
+                Ok(update) => decoded.world.updates.push(update),
+                Err(err) => decoded.skipped.push(SkippedEntity {
+                    index,
+                    entity_id,
+                    error: err.to_string(),
+                }),
+            }
+        }
+        Ok(decoded)
+    }
+}
+
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
+/// Headings per full turn used when nothing is configured, about 0.09 deg per
+/// step: finer than anything visible, coarse enough to absorb solver jitter.
+pub const DEFAULT_HEADING_STEPS: u32 = 4096;
+
+/// Snaps `heading_rad` to one of `steps` evenly spaced angles before it goes
+/// on the wire, so sub-step jitter in the simulated angle sends the same value
+/// every tick instead of a stream of tiny rotation corrections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeadingQuantizer {
+    /// Zero sends headings unquantized.
+    pub steps: u32,
+}
+
+impl Default for HeadingQuantizer {
+    fn default() -> Self {
+        Self {
+            steps: DEFAULT_HEADING_STEPS,
+        }
+    }
+}
+
+impl HeadingQuantizer {
+    pub fn new(steps: u32) -> Self {
+        Self { steps }
+    }
+
+    /// Angle of one step in radians; zero when quantization is off.
+    pub fn step_rad(&self) -> f32 {
+        if self.steps == 0 {
+            0.0
+        } else {
+            std::f32::consts::TAU / self.steps as f32
+        }
+    }
+
+    /// Nearest step to `heading_rad`, in `[0, TAU)`. Angles that differ by
+    /// whole turns give the same value.
+    pub fn quantize(&self, heading_rad: f32) -> f32 {
+        if self.steps == 0 || !heading_rad.is_finite() {
+            return heading_rad;
+        }
+        let step = self.step_rad();
+        let index = (heading_rad.rem_euclid(std::f32::consts::TAU) / step).round() as u32;
+        (index % self.steps) as f32 * step
+    }
+
+    /// Whether two headings are within one step of each other (across the
+    /// wrap), i.e. the difference is below what the wire can express.
+    pub fn within_step(&self, a_rad: f32, b_rad: f32) -> bool {
+        let diff = (a_rad - b_rad).rem_euclid(std::f32::consts::TAU);
+        diff.min(std::f32::consts::TAU - diff) < self.step_rad()
+    }
+}
+
+/// `io::Write` sink that only counts what is written to it.
+struct ByteCounter(usize);
+
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn encoded_json_len<T: Serialize>(value: &T) -> usize {
+    let mut counter = ByteCounter(0);
+    serde_json::to_writer(&mut counter, value)
+        .expect("delta types have string keys and an infallible sink");
+    counter.0
+}
+
 pub fn encode_envelope_json<T: Serialize>(
     envelope: &NetEnvelope<T>,
 ) -> serde_json::Result<Vec<u8>> {
     serde_json::to_vec(envelope)
 }
 
-pub fn decode_envelope_json<T: DeserializeOwned>(
+/// Why a received envelope was not accepted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NetError {
+    /// The sender speaks a different protocol version. Reported before the
+    /// payload is decoded, since a payload from another version may not parse.
+    ProtocolMismatch { expected: u16, got: u16 },
+    /// The bytes are not an envelope in this codec.
+    Decode(String),
+}
+
+impl std::fmt::Display for NetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ProtocolMismatch { expected, got } => write!(
+                f,
+                "protocol version mismatch: expected {expected}, got {got}; update required"
+            ),
+            Self::Decode(err) => write!(f, "envelope could not be decoded: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for NetError {}
+
+/// Checks a peer's `protocol_version` against this build's `PROTOCOL_VERSION`.
+pub fn check_protocol_version(got: u16) -> Result<(), NetError> {
+    if got == PROTOCOL_VERSION {
+        Ok(())
+    } else {
+        Err(NetError::ProtocolMismatch {
+            expected: PROTOCOL_VERSION,
+            got,
+        })
+    }
+}
+
+impl<T> NetEnvelope<T> {
+    pub fn validate_version(&self) -> Result<(), NetError> {
+        check_protocol_version(self.protocol_version)
+    }
+}
+
+/// Decodes an envelope, rejecting one from another protocol version with
+/// `NetError::ProtocolMismatch` rather than whatever its payload fails with.
+pub fn decode_envelope_json<T: DeserializeOwned>(bytes: &[u8]) -> Result<NetEnvelope<T>, NetError> {
+    let header = serde_json::from_slice::<NetEnvelope<IgnoredAny>>(bytes)
+        .map_err(|err| NetError::Decode(err.to_string()))?;
+    header.validate_version()?;
+    serde_json::from_slice(bytes).map_err(|err| NetError::Decode(err.to_string()))
+}
+
+/// Compact binary encoding of an envelope, for links where JSON's size
+/// matters. MessagePack rather than bincode or postcard: delta properties are
+/// free-form `serde_json::Value`s, which only a self-describing format can
+/// decode. Structs are written as arrays, so both ends must agree on field
+/// order; the envelope's `protocol_version` is what guards that.
+#[cfg(feature = "msgpack_codec")]
+pub fn encode_envelope_msgpack<T: Serialize>(
+    envelope: &NetEnvelope<T>,
+) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+    rmp_serde::to_vec(envelope)
+}
+
+/// MessagePack counterpart of `decode_envelope_json`, with the same version
+/// check.
+#[cfg(feature = "msgpack_codec")]
+pub fn decode_envelope_msgpack<T: DeserializeOwned>(
     bytes: &[u8],
-) -> serde_json::Result<NetEnvelope<T>> {
-    serde_json::from_slice(bytes)
+) -> Result<NetEnvelope<T>, NetError> {
+    let header = rmp_serde::from_slice::<NetEnvelope<IgnoredAny>>(bytes)
+        .map_err(|err| NetError::Decode(err.to_string()))?;
+    header.validate_version()?;
+    rmp_serde::from_slice(bytes).map_err(|err| NetError::Decode(err.to_string()))
 }
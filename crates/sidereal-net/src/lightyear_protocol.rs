@@ -6,7 +6,10 @@ use lightyear::prelude::{
 use serde::{Deserialize, Serialize};
 use sidereal_game::EntityAction;
 
-use crate::WorldStateDelta;
+use crate::{
+    ChannelConfig, ChannelConfigs, ChannelDelivery, DecodeWorldError, DecodedWorld,
+    HeadingQuantizer, NetError, WorldStateDelta, check_protocol_version, encoded_json_len,
+};
 
 /// Client sends input actions to replication server
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -14,6 +17,45 @@ pub struct ClientInputMessage {
     pub player_entity_id: String,
     pub actions: Vec<EntityAction>,
     pub tick: u64,
+    /// Player is holding brake (active deceleration), as opposed to releasing
+    /// thrust and coasting. Overrides any thrust action in `actions`.
+    #[serde(default)]
+    pub brake: bool,
+    /// Input of the ticks sent just before `tick`, oldest first, repeated so
+    /// a lost message loses no input tick. Replication skips the ticks it has
+    /// already applied.
+    #[serde(default)]
+    pub earlier_ticks: Vec<TickInput>,
+    /// Tick of the newest `ReplicationStateMessage` the client had applied
+    /// when it sent this input, i.e. the world the player was looking at.
+    /// Replication rewinds shot targets to it; zero when unknown.
+    #[serde(default)]
+    pub seen_state_tick: u64,
+}
+
+/// One sim tick's input, coalesced from every frame the client rendered
+/// during that tick.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TickInput {
+    pub tick: u64,
+    pub actions: Vec<EntityAction>,
+    #[serde(default)]
+    pub brake: bool,
+}
+
+impl TickInput {
+    pub fn from_axis_inputs(tick: u64, thrust: f32, turn: f32, brake: bool) -> Self {
+        Self {
+            tick,
+            actions: actions_from_axis_inputs(thrust, turn, brake),
+            brake,
+        }
+    }
+
+    /// Same brake resolution as `ClientInputMessage::resolved_actions`.
+    pub fn resolved_actions(&self) -> Vec<EntityAction> {
+        resolve_brake(&self.actions, self.brake)
+    }
 }
 
 /// Client authenticates replication session and binds transport identity.
@@ -21,6 +63,103 @@ pub struct ClientInputMessage {
 pub struct ClientAuthMessage {
     pub player_entity_id: String,
     pub access_token: String,
+    /// The client's `PROTOCOL_VERSION`; zero from clients that predate it,
+    /// which replication rejects like any other mismatch.
+    #[serde(default)]
+    pub protocol_version: u16,
+}
+
+/// Replication refuses a client's `ClientAuthMessage`, sent on the control
+/// channel before anything else so the client can tell the player why (e.g.
+/// `NetError::ProtocolMismatch` means the client must be updated).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ClientAuthRejectedMessage {
+    pub error: NetError,
+}
+
+/// Client narrows the entity kinds replication streams to it (see
+/// `WorldDeltaEntity::derived_kind`). An empty `include_kinds` subscribes to
+/// everything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SubscriptionFilter {
+    pub include_kinds: Vec<String>,
+}
+
+impl SubscriptionFilter {
+    pub fn includes(&self, kind: &str) -> bool {
+        self.include_kinds.is_empty()
+            || self
+                .include_kinds
+                .iter()
+                .any(|included| included.eq_ignore_ascii_case(kind))
+    }
+}
+
+/// Replication tells a client which replicated entity it controls, so the
+/// client reconciles that entity into its local ship instead of spawning it a
+/// second time as a remote ship. `None` means the player controls nothing.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ControlledEntityMessage {
+    pub player_entity_id: String,
+    pub controlled_entity_id: Option<String>,
+}
+
+/// Client asks for an expanded, one-off view of one entity (e.g. a ship it
+/// just targeted): class, loadout summary and anything else its relation to
+/// the entity allows, beyond what the culled stream carries.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RequestEntityDetail {
+    pub entity_id: String,
+}
+
+/// Replication's answer to `RequestEntityDetail`: a delta holding only the
+/// requested entity, redacted for the requesting player. The delta is empty
+/// when the player cannot currently see the entity.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EntityDetailMessage {
+    pub tick: u64,
+    pub entity_id: String,
+    pub world_json: Vec<u8>,
+}
+
+impl EntityDetailMessage {
+    pub fn from_world(
+        tick: u64,
+        entity_id: String,
+        world: &WorldStateDelta,
+    ) -> serde_json::Result<Self> {
+        Ok(Self {
+            tick,
+            entity_id,
+            world_json: serde_json::to_vec(world)?,
+        })
+    }
+
+    pub fn decode_world(&self) -> Result<DecodedWorld, DecodeWorldError> {
+        WorldStateDelta::decode_lenient(&self.world_json)
+    }
+}
+
+/// Client probe replication echoes straight back as a `PongMessage`, so the
+/// client can measure round-trip time.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PingMessage {
+    pub nonce: u64,
+}
+
+/// Replication's echo of a `PingMessage`, carrying the same nonce.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PongMessage {
+    pub nonce: u64,
+}
+
+/// Client reports the entity it has locked as its target, or `None` after
+/// releasing it. Replication keeps the latest per player for server-side
+/// targeting such as weapon aim-off.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TargetLockMessage {
+    pub player_entity_id: String,
+    pub target_entity_id: Option<String>,
 }
 
 /// Replication sends state to clients
@@ -28,22 +167,71 @@ pub struct ClientAuthMessage {
 pub struct ReplicationStateMessage {
     pub tick: u64,
     pub world_json: Vec<u8>,
+    /// `WorldStateDelta::state_hash_with` of the world as the server sent it, at
+    /// the server's heading step; zero from servers that predate it.
+    #[serde(default)]
+    pub state_hash: u64,
+    /// Position of this message in the stream replication sends one client,
+    /// from 1, so the client can drop one that arrives after a newer one.
+    /// Zero from servers that predate it, or when not numbered.
+    #[serde(default)]
+    pub seq: u64,
 }
 
 impl ReplicationStateMessage {
     pub fn from_world(tick: u64, world: &WorldStateDelta) -> serde_json::Result<Self> {
+        Self::from_world_with_heading(tick, world, &HeadingQuantizer::default())
+    }
+
+    /// Like `from_world`, hashing headings at `heading`'s step.
+    pub fn from_world_with_heading(
+        tick: u64,
+        world: &WorldStateDelta,
+        heading: &HeadingQuantizer,
+    ) -> serde_json::Result<Self> {
         Ok(Self {
             tick,
             world_json: serde_json::to_vec(world)?,
+            state_hash: world.state_hash_with(heading),
+            seq: 0,
         })
     }
 
-    pub fn decode_world(&self) -> serde_json::Result<WorldStateDelta> {
-        serde_json::from_slice(&self.world_json)
+    /// Decodes the world, dropping (and reporting) malformed updates rather
+    /// than the whole tick.
+    pub fn decode_world(&self) -> Result<DecodedWorld, DecodeWorldError> {
+        WorldStateDelta::decode_lenient(&self.world_json)
+    }
+
+    /// Whether `world`, this side's own state for the message's tick, hashes
+    /// to what the server sent at the same `heading` step. Always true when
+    /// the server sent no hash.
+    pub fn state_hash_matches(&self, world: &WorldStateDelta, heading: &HeadingQuantizer) -> bool {
+        self.state_hash == 0 || self.state_hash == world.state_hash_with(heading)
+    }
+}
+
+impl ClientAuthMessage {
+    /// Rejects clients built against another protocol version before their
+    /// token is looked at.
+    pub fn validate_version(&self) -> Result<(), NetError> {
+        check_protocol_version(self.protocol_version)
+    }
+
+    /// Length in bytes of this message's JSON encoding, the size replication
+    /// counts toward the sending client's received bytes.
+    pub fn encoded_size(&self) -> usize {
+        encoded_json_len(self)
     }
 }
 
 impl ClientInputMessage {
+    /// Length in bytes of this message's JSON encoding, the size replication
+    /// counts toward the sending client's received bytes.
+    pub fn encoded_size(&self) -> usize {
+        encoded_json_len(self)
+    }
+
     pub fn from_axis_inputs(
         player_entity_id: String,
         tick: u64,
@@ -51,39 +239,102 @@ impl ClientInputMessage {
         turn: f32,
         brake: bool,
     ) -> Self {
-        let mut actions = Vec::new();
-        if brake {
-            actions.push(EntityAction::Brake);
-        } else if thrust > 0.0 {
-            actions.push(EntityAction::ThrustForward);
-        } else if thrust < 0.0 {
-            actions.push(EntityAction::ThrustReverse);
-        } else {
-            actions.push(EntityAction::ThrustNeutral);
-        }
-
-        if turn > 0.0 {
-            actions.push(EntityAction::YawLeft);
-        } else if turn < 0.0 {
-            actions.push(EntityAction::YawRight);
-        } else {
-            actions.push(EntityAction::YawNeutral);
-        }
-
         Self {
             player_entity_id,
-            actions,
+            actions: actions_from_axis_inputs(thrust, turn, brake),
             tick,
+            brake,
+            earlier_ticks: Vec::new(),
+            seen_state_tick: 0,
         }
     }
+
+    /// Every tick this message carries that is newer than `last_applied`, in
+    /// tick order, with its actions resolved.
+    pub fn ticks_after(&self, last_applied: Option<u64>) -> Vec<TickInput> {
+        let mut ticks = self
+            .earlier_ticks
+            .iter()
+            .cloned()
+            .chain(std::iter::once(TickInput {
+                tick: self.tick,
+                actions: self.actions.clone(),
+                brake: self.brake,
+            }))
+            .filter(|input| last_applied.is_none_or(|last| input.tick > last))
+            .map(|input| TickInput {
+                actions: input.resolved_actions(),
+                ..input
+            })
+            .collect::<Vec<_>>();
+        ticks.sort_by_key(|input| input.tick);
+        ticks.dedup_by_key(|input| input.tick);
+        ticks
+    }
+
+    /// Actions the server should enqueue. With `brake` set, thrust actions are
+    /// replaced by a single `Brake` so a stale or tampered thrust action cannot
+    /// turn braking into coasting or acceleration.
+    pub fn resolved_actions(&self) -> Vec<EntityAction> {
+        resolve_brake(&self.actions, self.brake)
+    }
+}
+
+fn resolve_brake(actions: &[EntityAction], brake: bool) -> Vec<EntityAction> {
+    if !brake {
+        return actions.to_vec();
+    }
+    let mut resolved = vec![EntityAction::Brake];
+    resolved.extend(actions.iter().copied().filter(|action| {
+        !matches!(
+            action,
+            EntityAction::Brake
+                | EntityAction::ThrustForward
+                | EntityAction::ThrustReverse
+                | EntityAction::ThrustNeutral
+        )
+    }));
+    resolved
+}
+
+/// Maps axis inputs to the thrust and yaw actions shared by local prediction
+/// and the network input message.
+pub fn actions_from_axis_inputs(thrust: f32, turn: f32, brake: bool) -> Vec<EntityAction> {
+    let mut actions = Vec::new();
+    if brake {
+        actions.push(EntityAction::Brake);
+    } else if thrust > 0.0 {
+        actions.push(EntityAction::ThrustForward);
+    } else if thrust < 0.0 {
+        actions.push(EntityAction::ThrustReverse);
+    } else {
+        actions.push(EntityAction::ThrustNeutral);
+    }
+
+    if turn > 0.0 {
+        actions.push(EntityAction::YawLeft);
+    } else if turn < 0.0 {
+        actions.push(EntityAction::YawRight);
+    } else {
+        actions.push(EntityAction::YawNeutral);
+    }
+    actions
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "kind", content = "payload", rename_all = "snake_case")]
 pub enum LightyearWireMessage {
     ClientAuth(ClientAuthMessage),
+    ClientAuthRejected(ClientAuthRejectedMessage),
     ClientInput(ClientInputMessage),
+    SubscriptionFilter(SubscriptionFilter),
+    ControlledEntity(ControlledEntityMessage),
     ReplicationState(ReplicationStateMessage),
+    RequestEntityDetail(RequestEntityDetail),
+    EntityDetail(EntityDetailMessage),
+    Ping(PingMessage),
+    Pong(PongMessage),
+    TargetLock(TargetLockMessage),
 }
 
 #[derive(Debug)]
@@ -93,32 +344,63 @@ pub struct InputChannel;
 #[derive(Debug)]
 pub struct StateChannel;
 
+/// Lightyear settings for one channel class.
+pub fn channel_settings(config: ChannelConfig) -> ChannelSettings {
+    let mode = match config.delivery {
+        ChannelDelivery::UnorderedUnreliable => ChannelMode::UnorderedUnreliable,
+        ChannelDelivery::SequencedUnreliable => ChannelMode::SequencedUnreliable,
+        ChannelDelivery::UnorderedReliable => {
+            ChannelMode::UnorderedReliable(ReliableSettings::default())
+        }
+        ChannelDelivery::SequencedReliable => {
+            ChannelMode::SequencedReliable(ReliableSettings::default())
+        }
+        ChannelDelivery::OrderedReliable => {
+            ChannelMode::OrderedReliable(ReliableSettings::default())
+        }
+    };
+    ChannelSettings {
+        mode,
+        send_frequency: Duration::default(),
+        priority: config.priority,
+    }
+}
+
+/// Registers messages and channels with the default `ChannelConfigs`.
 pub fn register_lightyear_protocol(app: &mut App) {
+    register_lightyear_protocol_with_channels(app, &ChannelConfigs::default());
+}
+
+pub fn register_lightyear_protocol_with_channels(app: &mut App, channels: &ChannelConfigs) {
     app.register_message::<ClientAuthMessage>()
         .add_direction(NetworkDirection::Bidirectional);
+    app.register_message::<ClientAuthRejectedMessage>()
+        .add_direction(NetworkDirection::Bidirectional);
     app.register_message::<ClientInputMessage>()
         .add_direction(NetworkDirection::Bidirectional);
+    app.register_message::<SubscriptionFilter>()
+        .add_direction(NetworkDirection::Bidirectional);
+    app.register_message::<ControlledEntityMessage>()
+        .add_direction(NetworkDirection::Bidirectional);
     app.register_message::<ReplicationStateMessage>()
         .add_direction(NetworkDirection::Bidirectional);
+    app.register_message::<RequestEntityDetail>()
+        .add_direction(NetworkDirection::Bidirectional);
+    app.register_message::<EntityDetailMessage>()
+        .add_direction(NetworkDirection::Bidirectional);
+    app.register_message::<PingMessage>()
+        .add_direction(NetworkDirection::Bidirectional);
+    app.register_message::<PongMessage>()
+        .add_direction(NetworkDirection::Bidirectional);
+    app.register_message::<TargetLockMessage>()
+        .add_direction(NetworkDirection::Bidirectional);
 
-    app.add_channel::<ControlChannel>(ChannelSettings {
-        mode: ChannelMode::UnorderedReliable(ReliableSettings::default()),
-        send_frequency: Duration::default(),
-        priority: 8.0,
-    })
-    .add_direction(NetworkDirection::Bidirectional);
-    app.add_channel::<InputChannel>(ChannelSettings {
-        mode: ChannelMode::UnorderedUnreliable,
-        send_frequency: Duration::default(),
-        priority: 10.0,
-    })
-    .add_direction(NetworkDirection::Bidirectional);
-    app.add_channel::<StateChannel>(ChannelSettings {
-        mode: ChannelMode::UnorderedUnreliable,
-        send_frequency: Duration::default(),
-        priority: 10.0,
-    })
-    .add_direction(NetworkDirection::Bidirectional);
+    app.add_channel::<ControlChannel>(channel_settings(channels.control))
+        .add_direction(NetworkDirection::Bidirectional);
+    app.add_channel::<InputChannel>(channel_settings(channels.input))
+        .add_direction(NetworkDirection::Bidirectional);
+    app.add_channel::<StateChannel>(channel_settings(channels.state))
+        .add_direction(NetworkDirection::Bidirectional);
 }
 
 pub fn encode_wire_message(message: &LightyearWireMessage) -> serde_json::Result<Vec<u8>> {
@@ -0,0 +1,58 @@
+use sidereal_net::{ChannelClass, ChannelConfigs, ChannelDelivery};
+use std::collections::HashMap;
+
+fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars = vars
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect::<HashMap<_, _>>();
+    move |name| vars.get(name).cloned()
+}
+
+#[test]
+fn default_channels_match_intended_reliability() {
+    let channels = ChannelConfigs::default();
+    assert_eq!(
+        channels.get(ChannelClass::Control).delivery,
+        ChannelDelivery::OrderedReliable
+    );
+    assert_eq!(
+        channels.get(ChannelClass::State).delivery,
+        ChannelDelivery::SequencedUnreliable
+    );
+    assert_eq!(
+        channels.get(ChannelClass::Input).delivery,
+        ChannelDelivery::UnorderedUnreliable
+    );
+    assert!(channels.control.delivery.is_reliable());
+    assert!(!channels.state.delivery.is_reliable());
+}
+
+#[test]
+fn env_overrides_one_class_and_keeps_the_rest() {
+    let channels = ChannelConfigs::from_lookup(lookup(&[
+        ("SIDEREAL_CHANNEL_STATE_DELIVERY", " Unordered_Unreliable "),
+        ("SIDEREAL_CHANNEL_INPUT_PRIORITY", "12.5"),
+        ("SIDEREAL_CHANNEL_CONTROL_DELIVERY", ""),
+    ]))
+    .expect("valid overrides should parse");
+    assert_eq!(
+        channels.state.delivery,
+        ChannelDelivery::UnorderedUnreliable
+    );
+    assert_eq!(channels.input.priority, 12.5);
+    assert_eq!(channels.control, ChannelConfigs::default().control);
+}
+
+#[test]
+fn invalid_channel_overrides_are_rejected() {
+    let err = ChannelConfigs::from_lookup(lookup(&[(
+        "SIDEREAL_CHANNEL_CONTROL_DELIVERY",
+        "reliable-ish",
+    )]))
+    .unwrap_err();
+    assert!(err.contains("SIDEREAL_CHANNEL_CONTROL_DELIVERY"));
+    assert!(
+        ChannelConfigs::from_lookup(lookup(&[("SIDEREAL_CHANNEL_STATE_PRIORITY", "0")])).is_err()
+    );
+}
@@ -0,0 +1,73 @@
+use sidereal_net::{ComponentIdMismatch, WorldComponentDelta, WorldDeltaEntity, component_id_for};
+
+fn component(component_id: &str, component_kind: &str) -> WorldComponentDelta {
+    WorldComponentDelta {
+        component_id: component_id.to_string(),
+        component_kind: component_kind.to_string(),
+        properties: serde_json::json!({}),
+    }
+}
+
+fn ship(components: Vec<WorldComponentDelta>) -> WorldDeltaEntity {
+    WorldDeltaEntity {
+        entity_id: "ship:1".to_string(),
+        labels: vec!["Entity".to_string(), "Ship".to_string()],
+        properties: serde_json::json!({}),
+        components,
+        removed: false,
+        removed_component_ids: Vec::new(),
+        removal_reason: None,
+    }
+}
+
+#[test]
+fn well_formed_id_validates() {
+    assert_eq!(
+        component_id_for("ship:1", "health_pool"),
+        "ship:1:health_pool"
+    );
+    assert_eq!(
+        component("ship:1:health_pool", "health_pool").validate_id("ship:1"),
+        Ok(())
+    );
+}
+
+#[test]
+fn mismatched_ids_are_flagged() {
+    for malformed in [
+        "ship:2:health_pool",
+        "ship:1:flight_computer",
+        "ship:1health_pool",
+        "health_pool",
+        "ship:1:health_pool:extra",
+    ] {
+        let err = component(malformed, "health_pool")
+            .validate_id("ship:1")
+            .unwrap_err();
+        assert_eq!(err.component_id, malformed);
+        assert!(err.to_string().contains("\"ship:1:health_pool\""));
+    }
+}
+
+#[test]
+fn normalize_rewrites_only_malformed_ids() {
+    let mut update = ship(vec![
+        component("ship:1:health_pool", "health_pool"),
+        component("flight_computer", "flight_computer"),
+    ]);
+    assert_eq!(
+        update.normalize_component_ids(),
+        vec![ComponentIdMismatch {
+            entity_id: "ship:1".to_string(),
+            component_kind: "flight_computer".to_string(),
+            component_id: "flight_computer".to_string(),
+        }]
+    );
+    let ids = update
+        .components
+        .iter()
+        .map(|c| c.component_id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, ["ship:1:health_pool", "ship:1:flight_computer"]);
+    assert!(update.normalize_component_ids().is_empty());
+}
@@ -0,0 +1,57 @@
+use sidereal_net::{DecodeWorldError, WorldDeltaEntity, WorldStateDelta};
+
+fn ship(entity_id: &str, x: f64) -> WorldDeltaEntity {
+    WorldDeltaEntity {
+        entity_id: entity_id.to_string(),
+        labels: vec!["Entity".to_string(), "Ship".to_string()],
+        properties: serde_json::json!({ "position_m": [x, 0.0, 0.0] }),
+        components: Vec::new(),
+        removed: false,
+        removed_component_ids: Vec::new(),
+        removal_reason: None,
+    }
+}
+
+#[test]
+fn one_corrupt_entity_is_skipped_and_the_rest_delivered() {
+    let encoded = serde_json::json!({
+        "updates": [
+            serde_json::to_value(ship("ship:a", 1.0)).expect("encode"),
+            { "entity_id": "ship:corrupt", "labels": "Ship", "components": 7 },
+            serde_json::to_value(ship("ship:b", 2.0)).expect("encode"),
+        ]
+    });
+    let decoded = WorldStateDelta::decode_lenient(&serde_json::to_vec(&encoded).expect("bytes"))
+        .expect("envelope should decode");
+
+    assert_eq!(
+        decoded.world.updates,
+        vec![ship("ship:a", 1.0), ship("ship:b", 2.0)]
+    );
+    assert_eq!(decoded.skipped_entity_ids(), vec!["ship:corrupt"]);
+    assert_eq!(decoded.skipped[0].index, 1);
+}
+
+#[test]
+fn update_without_an_id_is_reported_by_index() {
+    let encoded = br#"{"updates":[{"labels":["Ship"]},{"entity_id":"ship:a"}]}"#;
+    let decoded = WorldStateDelta::decode_lenient(encoded).expect("envelope should decode");
+    assert_eq!(decoded.world.updates.len(), 1);
+    assert_eq!(decoded.skipped.len(), 1);
+    assert_eq!(decoded.skipped[0].index, 0);
+    assert!(decoded.skipped[0].entity_id.is_none());
+}
+
+#[test]
+fn unreadable_envelope_is_an_error() {
+    assert!(matches!(
+        WorldStateDelta::decode_lenient(b"not json"),
+        Err(DecodeWorldError::Json(_))
+    ));
+    assert!(matches!(
+        WorldStateDelta::decode_lenient(br#"{"updates":{}}"#),
+        Err(DecodeWorldError::Shape(_))
+    ));
+    let empty = WorldStateDelta::decode_lenient(b"{}").expect("no updates is an empty world");
+    assert!(empty.world.updates.is_empty() && empty.skipped.is_empty());
+}
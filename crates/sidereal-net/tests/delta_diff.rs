@@ -0,0 +1,119 @@
+use sidereal_net::{WorldComponentDelta, WorldDeltaEntity, WorldStateDelta};
+
+fn component(entity_id: &str, kind: &str, properties: serde_json::Value) -> WorldComponentDelta {
+    WorldComponentDelta {
+        component_id: format!("{entity_id}:{kind}"),
+        component_kind: kind.to_string(),
+        properties,
+    }
+}
+
+fn ship(entity_id: &str, x: f64, health: f64) -> WorldDeltaEntity {
+    WorldDeltaEntity {
+        entity_id: entity_id.to_string(),
+        labels: vec!["Entity".to_string(), "Ship".to_string()],
+        properties: serde_json::json!({
+            "position_m": [x, 0.0, 0.0],
+            "velocity_mps": [1.0, 0.0, 0.0],
+            "name": entity_id,
+        }),
+        components: vec![
+            component(
+                entity_id,
+                "health_pool",
+                serde_json::json!({"current": health, "maximum": 100.0}),
+            ),
+            component(entity_id, "engine", serde_json::json!({"thrust_n": 5000.0})),
+        ],
+        removed: false,
+        removed_component_ids: Vec::new(),
+        removal_reason: None,
+    }
+}
+
+#[test]
+fn diff_then_apply_reproduces_the_next_state() {
+    let prev = WorldStateDelta {
+        updates: vec![
+            ship("ship:a", 0.0, 100.0),
+            ship("ship:b", 5.0, 100.0),
+            ship("ship:gone", 9.0, 100.0),
+        ],
+    };
+
+    let mut moved = ship("ship:a", 1.0, 100.0);
+    moved.properties["velocity_mps"] = serde_json::json!([2.0, 0.0, 0.0]);
+    let mut damaged = ship("ship:b", 5.0, 60.0);
+    damaged.components.pop();
+    damaged.labels.push("Flagship".to_string());
+    damaged
+        .properties
+        .as_object_mut()
+        .expect("object")
+        .remove("name");
+    let next = WorldStateDelta {
+        updates: vec![moved, damaged, ship("ship:new", 20.0, 100.0)],
+    };
+
+    let delta = next.diff_against(&prev);
+    let mut reconstructed = prev.clone();
+    reconstructed.apply_delta(&delta);
+
+    assert_eq!(reconstructed, next);
+    assert_eq!(reconstructed.state_hash(), next.state_hash());
+}
+
+#[test]
+fn diff_carries_only_what_changed() {
+    let prev = WorldStateDelta {
+        updates: vec![ship("ship:a", 0.0, 100.0), ship("ship:still", 3.0, 100.0)],
+    };
+    let next = WorldStateDelta {
+        updates: vec![ship("ship:a", 1.0, 100.0), ship("ship:still", 3.0, 100.0)],
+    };
+
+    let delta = next.diff_against(&prev);
+
+    assert_eq!(delta.updates.len(), 1);
+    let update = &delta.updates[0];
+    assert_eq!(update.entity_id, "ship:a");
+    assert!(update.labels.is_empty());
+    assert!(update.components.is_empty());
+    assert_eq!(
+        update.properties,
+        serde_json::json!({"position_m": [1.0, 0.0, 0.0]})
+    );
+    let size = |world: &WorldStateDelta| serde_json::to_vec(world).expect("encode").len();
+    assert!(size(&delta) * 4 < size(&next));
+}
+
+#[test]
+fn appeared_and_vanished_entities_are_represented() {
+    let prev = WorldStateDelta {
+        updates: vec![ship("ship:gone", 0.0, 100.0)],
+    };
+    let next = WorldStateDelta {
+        updates: vec![ship("ship:new", 0.0, 100.0)],
+    };
+
+    let delta = next.diff_against(&prev);
+
+    assert_eq!(delta.updates.len(), 2);
+    assert_eq!(delta.updates[0], ship("ship:new", 0.0, 100.0));
+    assert_eq!(delta.updates[1].entity_id, "ship:gone");
+    assert!(delta.updates[1].removed);
+}
+
+#[test]
+fn diff_against_an_empty_state_is_the_full_state() {
+    let next = WorldStateDelta {
+        updates: vec![ship("ship:a", 0.0, 100.0)],
+    };
+
+    let delta = next.diff_against(&WorldStateDelta::default());
+    assert_eq!(delta, next);
+
+    let mut reconstructed = WorldStateDelta::default();
+    reconstructed.apply_delta(&delta);
+    assert_eq!(reconstructed, next);
+}
@@ -0,0 +1,76 @@
+use sidereal_net::{WorldComponentDelta, WorldDeltaEntity};
+
+fn component(entity_id: &str, kind: &str, properties: serde_json::Value) -> WorldComponentDelta {
+    WorldComponentDelta {
+        component_id: format!("{entity_id}:{kind}"),
+        component_kind: kind.to_string(),
+        properties,
+    }
+}
+
+fn entity(components: Vec<WorldComponentDelta>) -> WorldDeltaEntity {
+    WorldDeltaEntity {
+        entity_id: "ship:size".to_string(),
+        labels: vec!["Entity".to_string(), "Ship".to_string()],
+        properties: serde_json::json!({"position_m": [1.0, 2.0, 0.0]}),
+        components,
+        removed: false,
+        removed_component_ids: Vec::new(),
+        removal_reason: None,
+    }
+}
+
+#[test]
+fn encoded_size_matches_serialized_length() {
+    let delta = entity(vec![component(
+        "ship:size",
+        "health_pool",
+        serde_json::json!({"current": 80.0, "maximum": 100.0, "name": "Ωmega"}),
+    )]);
+    assert_eq!(
+        delta.encoded_size(),
+        serde_json::to_vec(&delta).expect("encode").len()
+    );
+    assert_eq!(
+        delta.components[0].encoded_size(),
+        serde_json::to_vec(&delta.components[0])
+            .expect("encode")
+            .len()
+    );
+}
+
+#[test]
+fn minimal_delta_is_smaller_than_one_with_many_components() {
+    let minimal = entity(Vec::new());
+    let many = entity(
+        (0..16)
+            .map(|i| {
+                component(
+                    "ship:size",
+                    &format!("kind_{i}"),
+                    serde_json::json!({"v": i}),
+                )
+            })
+            .collect(),
+    );
+    assert!(minimal.encoded_size() < many.encoded_size());
+}
+
+#[test]
+fn adding_a_component_increases_the_size() {
+    let mut delta = entity(vec![component(
+        "ship:size",
+        "display_name",
+        serde_json::json!({"value": "ISS Size"}),
+    )]);
+    let before = delta.encoded_size();
+    let added = component(
+        "ship:size",
+        "fuel_tank",
+        serde_json::json!({"fuel_kg": 500.0}),
+    );
+    let added_size = added.encoded_size();
+    delta.components.push(added);
+    // The component's own bytes plus the separating comma.
+    assert_eq!(delta.encoded_size(), before + added_size + 1);
+}
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
-use sidereal_net::{ChannelClass, NetEnvelope, decode_envelope_json, encode_envelope_json};
+use sidereal_core::PROTOCOL_VERSION;
+use sidereal_net::{
+    ChannelClass, NetEnvelope, NetError, decode_envelope_json, encode_envelope_json,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 struct PayloadV1 {
@@ -57,3 +60,57 @@ fn backward_compatible_decode_missing_new_payload_field_uses_default() {
     assert!(decoded.payload.thrust_forward);
     assert!(!decoded.payload.stop_requested);
 }
+
+fn envelope_with_version(protocol_version: u16) -> NetEnvelope<PayloadV1> {
+    NetEnvelope {
+        protocol_version,
+        channel: ChannelClass::Input,
+        source_shard_id: 1,
+        lease_epoch: 1,
+        seq: 1,
+        tick: 1,
+        payload: PayloadV1 {
+            player_id: "player:abc".to_string(),
+            thrust_forward: false,
+            stop_requested: false,
+        },
+    }
+}
+
+#[test]
+fn matching_protocol_version_is_accepted() {
+    let envelope = envelope_with_version(PROTOCOL_VERSION);
+    assert_eq!(envelope.validate_version(), Ok(()));
+
+    let bytes = encode_envelope_json(&envelope).expect("encode should succeed");
+    assert!(decode_envelope_json::<PayloadV1>(&bytes).is_ok());
+}
+
+#[test]
+fn mismatched_protocol_version_is_rejected_before_the_payload_is_read() {
+    let newer = envelope_with_version(PROTOCOL_VERSION + 1);
+    let expected = NetError::ProtocolMismatch {
+        expected: PROTOCOL_VERSION,
+        got: PROTOCOL_VERSION + 1,
+    };
+    assert_eq!(newer.validate_version(), Err(expected.clone()));
+
+    // A payload from another version may not parse at all; the mismatch is
+    // still what gets reported.
+    let incompatible_payload = format!(
+        r#"{{"protocol_version":{},"channel":"Input","source_shard_id":1,"lease_epoch":1,"seq":1,"tick":1,"payload":{{"renamed_field":true}}}}"#,
+        PROTOCOL_VERSION + 1
+    );
+    assert_eq!(
+        decode_envelope_json::<PayloadV1>(incompatible_payload.as_bytes()).unwrap_err(),
+        expected
+    );
+}
+
+#[test]
+fn malformed_envelope_is_a_decode_error() {
+    assert!(matches!(
+        decode_envelope_json::<PayloadV1>(b"not json"),
+        Err(NetError::Decode(_))
+    ));
+}
@@ -0,0 +1,90 @@
+#![cfg(feature = "msgpack_codec")]
+
+use sidereal_core::PROTOCOL_VERSION;
+use sidereal_net::{
+    ChannelClass, NetEnvelope, NetError, WorldComponentDelta, WorldDeltaEntity, WorldStateDelta,
+    decode_envelope_msgpack, encode_envelope_json, encode_envelope_msgpack,
+};
+
+fn envelope(payload: WorldStateDelta) -> NetEnvelope<WorldStateDelta> {
+    NetEnvelope {
+        protocol_version: PROTOCOL_VERSION,
+        channel: ChannelClass::State,
+        source_shard_id: 3,
+        lease_epoch: 9,
+        seq: 1200,
+        tick: 48_000,
+        payload,
+    }
+}
+
+fn delta(entity_count: usize) -> WorldStateDelta {
+    WorldStateDelta {
+        updates: (0..entity_count)
+            .map(|i| {
+                let entity_id = format!("ship:{i:04}");
+                WorldDeltaEntity {
+                    labels: vec!["Entity".to_string(), "Ship".to_string()],
+                    properties: serde_json::json!({
+                        "position_m": [i as f64 * 12.5, -40.25, 0.0],
+                        "velocity_mps": [3.5, 0.0, 0.0],
+                        "heading_rad": 1.25,
+                        "owner_account_id": "account:7f3c",
+                    }),
+                    components: vec![WorldComponentDelta {
+                        component_id: format!("{entity_id}:health_pool"),
+                        component_kind: "health_pool".to_string(),
+                        properties: serde_json::json!({"current": 80.0, "maximum": 100.0}),
+                    }],
+                    removed: false,
+                    removed_component_ids: Vec::new(),
+                    removal_reason: None,
+                    entity_id,
+                }
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn msgpack_roundtrip_preserves_the_delta() {
+    let original = envelope(delta(3));
+    let bytes = encode_envelope_msgpack(&original).expect("encode should succeed");
+    let decoded: NetEnvelope<WorldStateDelta> =
+        decode_envelope_msgpack(&bytes).expect("decode should succeed");
+
+    assert_eq!(decoded.protocol_version, original.protocol_version);
+    assert_eq!(decoded.channel, original.channel);
+    assert_eq!(decoded.seq, original.seq);
+    assert_eq!(decoded.tick, original.tick);
+    assert_eq!(decoded.payload, original.payload);
+}
+
+#[test]
+fn msgpack_is_meaningfully_smaller_than_json_for_a_50_entity_delta() {
+    let original = envelope(delta(50));
+    let json = encode_envelope_json(&original).expect("json encode");
+    let msgpack = encode_envelope_msgpack(&original).expect("msgpack encode");
+
+    assert!(
+        msgpack.len() * 4 < json.len() * 3,
+        "msgpack {} bytes vs json {} bytes",
+        msgpack.len(),
+        json.len()
+    );
+}
+
+#[test]
+fn msgpack_decode_rejects_another_protocol_version() {
+    let mut newer = envelope(delta(1));
+    newer.protocol_version = PROTOCOL_VERSION + 1;
+    let bytes = encode_envelope_msgpack(&newer).expect("encode should succeed");
+
+    assert_eq!(
+        decode_envelope_msgpack::<WorldStateDelta>(&bytes).unwrap_err(),
+        NetError::ProtocolMismatch {
+            expected: PROTOCOL_VERSION,
+            got: PROTOCOL_VERSION + 1,
+        }
+    );
+}
@@ -0,0 +1,39 @@
+use sidereal_net::HeadingQuantizer;
+use std::f32::consts::{PI, TAU};
+
+#[test]
+fn headings_within_one_step_share_a_wire_value() {
+    let quantizer = HeadingQuantizer::new(1024);
+    let step = quantizer.step_rad();
+    let center = 300.0 * step;
+    let wire = quantizer.quantize(center);
+    assert_eq!(quantizer.quantize(center + step * 0.3), wire);
+    assert_eq!(quantizer.quantize(center - step * 0.3), wire);
+    assert_ne!(quantizer.quantize(center + step), wire);
+}
+
+#[test]
+fn whole_turns_and_the_wrap_map_to_the_same_value() {
+    let quantizer = HeadingQuantizer::default();
+    let step = quantizer.step_rad();
+    assert_eq!(quantizer.quantize(-PI), quantizer.quantize(PI));
+    assert_eq!(quantizer.quantize(1.0 + TAU), quantizer.quantize(1.0));
+    assert_eq!(quantizer.quantize(TAU - step * 0.2), 0.0);
+    assert_eq!(quantizer.quantize(-step * 0.2), 0.0);
+}
+
+#[test]
+fn sub_step_differences_count_as_no_change() {
+    let quantizer = HeadingQuantizer::new(360);
+    let step = quantizer.step_rad();
+    assert!(quantizer.within_step(1.0, 1.0 + step * 0.5));
+    assert!(quantizer.within_step(TAU - step * 0.25, step * 0.25));
+    assert!(!quantizer.within_step(1.0, 1.0 + step * 1.5));
+}
+
+#[test]
+fn zero_steps_leaves_headings_untouched() {
+    let quantizer = HeadingQuantizer::new(0);
+    assert_eq!(quantizer.quantize(-0.123_456), -0.123_456);
+    assert!(!quantizer.within_step(1.0, 1.0 + 1e-6));
+}
@@ -1,9 +1,18 @@
 #![cfg(feature = "lightyear_protocol")]
 
 use bevy::prelude::App;
-use lightyear::prelude::AppMessageExt;
 use lightyear::prelude::server::ServerPlugins;
-use sidereal_net::{ClientInputMessage, ReplicationStateMessage, register_lightyear_protocol};
+use lightyear::prelude::{AppMessageExt, ChannelMode};
+use sidereal_core::PROTOCOL_VERSION;
+use sidereal_game::EntityAction;
+use sidereal_net::{
+    ChannelClass, ChannelConfigs, ClientAuthMessage, ClientAuthRejectedMessage, ClientInputMessage,
+    ControlledEntityMessage, EntityDetailMessage, HeadingQuantizer, LightyearWireMessage, NetError,
+    PingMessage, PongMessage, ReplicationStateMessage, RequestEntityDetail, SubscriptionFilter,
+    TargetLockMessage, TickInput, WorldDeltaEntity, WorldStateDelta, channel_settings,
+    decode_wire_message, encode_wire_message, register_lightyear_protocol,
+    register_lightyear_protocol_with_channels,
+};
 
 #[test]
 fn lightyear_protocol_registration_registers_messages() {
@@ -11,6 +20,165 @@ fn lightyear_protocol_registration_registers_messages() {
     app.add_plugins(ServerPlugins::default());
     register_lightyear_protocol(&mut app);
 
+    assert!(app.is_message_registered::<ClientAuthRejectedMessage>());
     assert!(app.is_message_registered::<ClientInputMessage>());
+    assert!(app.is_message_registered::<SubscriptionFilter>());
+    assert!(app.is_message_registered::<ControlledEntityMessage>());
     assert!(app.is_message_registered::<ReplicationStateMessage>());
+    assert!(app.is_message_registered::<RequestEntityDetail>());
+    assert!(app.is_message_registered::<EntityDetailMessage>());
+    assert!(app.is_message_registered::<PingMessage>());
+    assert!(app.is_message_registered::<PongMessage>());
+    assert!(app.is_message_registered::<TargetLockMessage>());
+}
+
+#[test]
+fn channel_settings_match_each_class_reliability() {
+    let channels = ChannelConfigs::default();
+    let control = channel_settings(channels.get(ChannelClass::Control));
+    assert!(matches!(control.mode, ChannelMode::OrderedReliable(_)));
+    assert_eq!(control.priority, 8.0);
+    assert!(matches!(
+        channel_settings(channels.get(ChannelClass::State)).mode,
+        ChannelMode::SequencedUnreliable
+    ));
+    assert!(matches!(
+        channel_settings(channels.get(ChannelClass::Input)).mode,
+        ChannelMode::UnorderedUnreliable
+    ));
+
+    let mut app = App::new();
+    app.add_plugins(ServerPlugins::default());
+    register_lightyear_protocol_with_channels(&mut app, &channels);
+    assert!(app.is_message_registered::<ClientInputMessage>());
+}
+
+#[test]
+fn brake_input_sets_flag_and_resolves_to_brake_action() {
+    let braking = ClientInputMessage::from_axis_inputs("player:p".to_string(), 3, 0.0, 1.0, true);
+    assert!(braking.brake);
+    assert_eq!(
+        braking.resolved_actions(),
+        vec![EntityAction::Brake, EntityAction::YawLeft]
+    );
+
+    let coasting = ClientInputMessage::from_axis_inputs("player:p".to_string(), 3, 0.0, 0.0, false);
+    assert!(!coasting.brake);
+    assert_eq!(
+        coasting.resolved_actions(),
+        vec![EntityAction::ThrustNeutral, EntityAction::YawNeutral]
+    );
+}
+
+#[test]
+fn brake_flag_overrides_thrust_actions() {
+    let message = ClientInputMessage {
+        player_entity_id: "player:p".to_string(),
+        actions: vec![EntityAction::ThrustForward, EntityAction::YawRight],
+        tick: 9,
+        brake: true,
+        earlier_ticks: Vec::new(),
+        seen_state_tick: 0,
+    };
+    assert_eq!(
+        message.resolved_actions(),
+        vec![EntityAction::Brake, EntityAction::YawRight]
+    );
+
+    let legacy: ClientInputMessage = serde_json::from_value(serde_json::json!({
+        "player_entity_id": "player:p",
+        "actions": ["ThrustForward"],
+        "tick": 1,
+    }))
+    .expect("messages without a brake flag should decode");
+    assert!(!legacy.brake);
+}
+
+#[test]
+fn state_message_carries_the_world_hash() {
+    let world = WorldStateDelta {
+        updates: vec![WorldDeltaEntity {
+            entity_id: "ship:hash".to_string(),
+            labels: vec!["Entity".to_string(), "Ship".to_string()],
+            properties: serde_json::json!({"position_m": [1.0, 2.0, 0.0], "heading_rad": 0.3}),
+            components: Vec::new(),
+            removed: false,
+            removed_component_ids: Vec::new(),
+            removal_reason: None,
+        }],
+    };
+    let heading = HeadingQuantizer::new(64);
+    let message =
+        ReplicationStateMessage::from_world_with_heading(9, &world, &heading).expect("encode");
+    assert_eq!(message.state_hash, world.state_hash_with(&heading));
+    let decoded = message.decode_world().expect("decode").world;
+    assert!(message.state_hash_matches(&decoded, &heading));
+
+    let mut drifted = decoded.clone();
+    drifted.updates[0].properties["position_m"] = serde_json::json!([1.0, 3.0, 0.0]);
+    assert!(!message.state_hash_matches(&drifted, &heading));
+
+    // Within one 64-step heading increment at the server's step, but not at
+    // the default one.
+    let mut turned = decoded;
+    turned.updates[0].properties["heading_rad"] = serde_json::json!(0.32);
+    assert!(message.state_hash_matches(&turned, &heading));
+    assert!(!message.state_hash_matches(&turned, &HeadingQuantizer::default()));
+}
+
+#[test]
+fn auth_handshake_rejects_other_protocol_versions() {
+    let auth = |protocol_version| ClientAuthMessage {
+        player_entity_id: "player:abc".to_string(),
+        access_token: "token".to_string(),
+        protocol_version,
+    };
+
+    assert_eq!(auth(PROTOCOL_VERSION).validate_version(), Ok(()));
+    assert_eq!(
+        auth(PROTOCOL_VERSION + 1).validate_version(),
+        Err(NetError::ProtocolMismatch {
+            expected: PROTOCOL_VERSION,
+            got: PROTOCOL_VERSION + 1,
+        })
+    );
+
+    // Clients that predate the field send no version at all.
+    let legacy: ClientAuthMessage =
+        serde_json::from_str(r#"{"player_entity_id":"player:abc","access_token":"token"}"#)
+            .expect("legacy auth should decode");
+    assert!(legacy.validate_version().is_err());
+
+    let rejection = LightyearWireMessage::ClientAuthRejected(ClientAuthRejectedMessage {
+        error: auth(PROTOCOL_VERSION + 1)
+            .validate_version()
+            .expect_err("mismatched version"),
+    });
+    let bytes = encode_wire_message(&rejection).expect("encode rejection");
+    assert_eq!(
+        decode_wire_message(&bytes).expect("decode rejection"),
+        rejection
+    );
+}
+
+#[test]
+fn batched_input_ticks_apply_in_order_and_skip_applied_ones() {
+    let mut message =
+        ClientInputMessage::from_axis_inputs("player:p".to_string(), 12, 1.0, 0.0, false);
+    message.earlier_ticks = vec![
+        TickInput::from_axis_inputs(11, 0.0, 0.0, true),
+        TickInput::from_axis_inputs(10, 1.0, 0.0, false),
+    ];
+
+    let ticks = message.ticks_after(Some(10));
+    assert_eq!(
+        ticks.iter().map(|input| input.tick).collect::<Vec<_>>(),
+        vec![11, 12]
+    );
+    assert_eq!(
+        ticks[0].actions,
+        vec![EntityAction::Brake, EntityAction::YawNeutral]
+    );
+    assert_eq!(message.ticks_after(None).len(), 3);
+    assert!(message.ticks_after(Some(12)).is_empty());
 }
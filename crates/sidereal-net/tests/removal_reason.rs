@@ -0,0 +1,44 @@
+use sidereal_net::{RemovalReason, WorldDeltaEntity, WorldStateDelta};
+
+#[test]
+fn removal_without_a_reason_from_an_older_server_decodes_as_none() {
+    let legacy = r#"{"updates":[{"entity_id":"ship:gone","removed":true}]}"#;
+
+    let world: WorldStateDelta = serde_json::from_str(legacy).expect("legacy world should decode");
+    assert!(world.updates[0].removed);
+    assert_eq!(world.updates[0].removal_reason, None);
+
+    let decoded = WorldStateDelta::decode_lenient(legacy.as_bytes()).expect("lenient decode");
+    assert!(decoded.skipped.is_empty());
+    assert_eq!(decoded.world.updates[0].removal_reason, None);
+}
+
+#[test]
+fn removal_reason_round_trips_in_snake_case() {
+    let removal = WorldDeltaEntity {
+        entity_id: "ship:gone".to_string(),
+        labels: Vec::new(),
+        properties: serde_json::json!({}),
+        components: Vec::new(),
+        removed: true,
+        removed_component_ids: Vec::new(),
+        removal_reason: Some(RemovalReason::Destroyed),
+    };
+
+    let encoded = serde_json::to_value(&removal).expect("encode");
+    assert_eq!(encoded["removal_reason"], "destroyed");
+    let decoded: WorldDeltaEntity = serde_json::from_value(encoded).expect("decode");
+    assert_eq!(decoded, removal);
+
+    for reason in [
+        RemovalReason::OutOfRange,
+        RemovalReason::Unauthorized,
+        RemovalReason::ServerShutdown,
+    ] {
+        let json = serde_json::to_string(&reason).expect("encode");
+        assert_eq!(
+            serde_json::from_str::<RemovalReason>(&json).expect("decode"),
+            reason
+        );
+    }
+}
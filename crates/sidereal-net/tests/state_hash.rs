@@ -0,0 +1,79 @@
+use sidereal_net::{HeadingQuantizer, WorldDeltaEntity, WorldStateDelta};
+
+fn ship(entity_id: &str, position_m: [f32; 3], heading_rad: f32) -> WorldDeltaEntity {
+    WorldDeltaEntity {
+        entity_id: entity_id.to_string(),
+        labels: vec!["Entity".to_string(), "Ship".to_string()],
+        properties: serde_json::json!({
+            "position_m": position_m,
+            "velocity_mps": [4.0, -1.5, 0.0],
+            "heading_rad": heading_rad,
+            "health": 100.0,
+        }),
+        components: Vec::new(),
+        removed: false,
+        removed_component_ids: Vec::new(),
+        removal_reason: None,
+    }
+}
+
+fn world(updates: Vec<WorldDeltaEntity>) -> WorldStateDelta {
+    WorldStateDelta { updates }
+}
+
+#[test]
+fn identical_worlds_hash_equal_regardless_of_order() {
+    let a = ship("ship:a", [10.0, 20.0, 0.0], 0.5);
+    let b = ship("ship:b", [-3.25, 7.0, 0.0], 2.0);
+    let first = world(vec![a.clone(), b.clone()]);
+    let second = world(vec![b, a]);
+    assert_eq!(first.state_hash(), second.state_hash());
+    assert_eq!(first.state_hash(), first.clone().state_hash());
+}
+
+#[test]
+fn a_changed_position_changes_the_hash() {
+    let before = world(vec![
+        ship("ship:a", [10.0, 20.0, 0.0], 0.5),
+        ship("ship:b", [-3.25, 7.0, 0.0], 2.0),
+    ]);
+    let after = world(vec![
+        ship("ship:a", [10.0, 20.5, 0.0], 0.5),
+        ship("ship:b", [-3.25, 7.0, 0.0], 2.0),
+    ]);
+    assert_ne!(before.state_hash(), after.state_hash());
+}
+
+#[test]
+fn hash_survives_a_json_round_trip() {
+    let original = world(vec![ship("ship:a", [0.1, 1.0 / 3.0, 0.0], 1.234_567)]);
+    let bytes = serde_json::to_vec(&original).expect("encode");
+    let decoded: WorldStateDelta = serde_json::from_slice(&bytes).expect("decode");
+    assert_eq!(original.state_hash(), decoded.state_hash());
+}
+
+#[test]
+fn removals_and_non_spatial_fields_do_not_affect_the_hash() {
+    let base = world(vec![ship("ship:a", [1.0, 2.0, 0.0], 0.0)]);
+    let mut damaged = base.clone();
+    damaged.updates[0].properties["health"] = serde_json::json!(40.0);
+    let mut with_removal = base.clone();
+    with_removal.updates.push(WorldDeltaEntity {
+        removed: true,
+        ..ship("ship:gone", [5.0, 5.0, 0.0], 0.0)
+    });
+    assert_eq!(base.state_hash(), damaged.state_hash());
+    assert_eq!(base.state_hash(), with_removal.state_hash());
+}
+
+#[test]
+fn heading_is_hashed_at_the_given_step() {
+    let coarse = HeadingQuantizer::new(64);
+    let before = world(vec![ship("ship:a", [1.0, 2.0, 0.0], 0.30)]);
+    let after = world(vec![ship("ship:a", [1.0, 2.0, 0.0], 0.32)]);
+    assert_eq!(
+        before.state_hash_with(&coarse),
+        after.state_hash_with(&coarse)
+    );
+    assert_ne!(before.state_hash(), after.state_hash());
+}
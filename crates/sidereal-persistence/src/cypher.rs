@@ -0,0 +1,119 @@
+//! Helpers for the parts of Apache AGE Cypher text that cannot be parameters.
+//!
+//! Values are bound as `$name` parameters through AGE's
+//! `cypher(graph, $$ ... $$, $1)` form (see `CypherSink::execute_cypher_with_params`),
+//! so ids and other caller strings never reach the query text. Labels and
+//! property keys cannot be parameters and are reduced to `[A-Za-z0-9_]`; the
+//! graph name is escaped into its single-quoted SQL argument.
+//! `cypher_literal`/`cypher_set_clauses` render values inline for one-off
+//! query text; the graph writer binds parameters instead.
+
+use serde_json::Value as JsonValue;
+
+/// Escapes `value` for use inside a single-quoted Cypher string literal.
+/// Backslashes and single quotes are escaped; everything else (including
+/// non-ASCII text) is kept as is.
+pub fn escape_cypher_string(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+/// Reduces `raw` to the characters allowed in labels and property keys
+/// (`[A-Za-z0-9_]`). May return an empty string.
+pub fn sanitize_identifier(raw: &str) -> String {
+    raw.chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect()
+}
+
+/// Sanitizes each label, dropping labels that are empty after sanitizing.
+pub fn sanitize_labels(labels: &[String]) -> Vec<String> {
+    labels
+        .iter()
+        .map(|label| sanitize_identifier(label))
+        .filter(|label| !label.is_empty())
+        .collect()
+}
+
+/// Renders a JSON value as a Cypher literal. Map keys are sanitized; keys that
+/// sanitize to nothing are dropped.
+pub fn cypher_literal(value: &JsonValue) -> String {
+    match value {
+        JsonValue::Null => "null".to_string(),
+        JsonValue::Bool(v) => v.to_string(),
+        JsonValue::Number(v) => v.to_string(),
+        JsonValue::String(v) => format!("'{}'", escape_cypher_string(v)),
+        JsonValue::Array(values) => {
+            let rendered = values.iter().map(cypher_literal).collect::<Vec<_>>();
+            format!("[{}]", rendered.join(","))
+        }
+        JsonValue::Object(map) => {
+            let rendered = map
+                .iter()
+                .filter_map(|(k, v)| {
+                    let clean_key = sanitize_identifier(k);
+                    (!clean_key.is_empty()).then(|| format!("{clean_key}:{}", cypher_literal(v)))
+                })
+                .collect::<Vec<_>>();
+            format!("{{{}}}", rendered.join(","))
+        }
+    }
+}
+
+/// Renders one `prefix.key=literal` assignment per entry of a JSON object, for
+/// use after `SET`. Non-objects yield no clauses; keys that sanitize to nothing
+/// are dropped.
+pub fn cypher_set_clauses(prefix: &str, value: &JsonValue) -> Vec<String> {
+    let Some(obj) = value.as_object() else {
+        return Vec::new();
+    };
+    obj.iter()
+        .filter_map(|(key, val)| {
+            let clean_key = sanitize_identifier(key);
+            (!clean_key.is_empty()).then(|| format!("{prefix}.{clean_key}={}", cypher_literal(val)))
+        })
+        .collect()
+}
+
+/// Copies a JSON value with every map key sanitized the way `cypher_literal`
+/// renders it, for property maps bound as parameters instead of rendered.
+/// Keys that sanitize to nothing are dropped.
+pub fn sanitize_property_keys(value: &JsonValue) -> JsonValue {
+    match value {
+        JsonValue::Array(values) => values.iter().map(sanitize_property_keys).collect(),
+        JsonValue::Object(map) => map
+            .iter()
+            .filter_map(|(key, val)| {
+                let clean_key = sanitize_identifier(key);
+                (!clean_key.is_empty()).then(|| (clean_key, sanitize_property_keys(val)))
+            })
+            .collect(),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cypher_literal_renders_nested_maps_and_arrays() {
+        let value = serde_json::json!({"a": 1, "b": [true, "x"], "c": {"k": "v"}});
+        let out = cypher_literal(&value);
+        assert!(out.contains("a:1"));
+        assert!(out.contains("b:[true,'x']"));
+        assert!(out.contains("c:{k:'v'}"));
+    }
+
+    #[test]
+    fn sanitized_property_keys_match_rendered_literals() {
+        let value = serde_json::json!({"max-hp": 1, "::": 2, "slots": [{"a b": "x"}]});
+        assert_eq!(
+            sanitize_property_keys(&value),
+            serde_json::json!({"maxhp": 1, "slots": [{"ab": "x"}]})
+        );
+        assert_eq!(
+            cypher_literal(&sanitize_property_keys(&value)),
+            cypher_literal(&value)
+        );
+    }
+}
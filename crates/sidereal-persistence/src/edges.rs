@@ -0,0 +1,80 @@
+//! Relationship edges derived from entity properties.
+//!
+//! An entity that names another entity in a property (`parent_entity_id`,
+//! `mounted_on_entity_id`, ...) gets a graph edge to it on persist, so the
+//! relationship can be walked in Cypher. Each `EdgeRule` maps one such
+//! property to one edge label; `default_edge_rules` are the relationships the
+//! game writes today, and a deployment can add its own with
+//! `GraphPersistence::set_edge_rules`.
+
+/// Which way the edge points, relative to the entity being persisted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeDirection {
+    /// From the entity named by the property to the record, e.g.
+    /// `(parent)-[:HAS_CHILD]->(child)`.
+    FromProperty,
+    /// From the record to the entity named by the property, e.g.
+    /// `(module)-[:MOUNTED_ON]->(hardpoint)`.
+    ToProperty,
+}
+
+/// Writes an `edge_label` edge for every persisted entity whose string
+/// property `property_key` names another entity, optionally only for entities
+/// carrying `required_label`. Edges are MERGEd, so rewriting a record does not
+/// duplicate them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EdgeRule {
+    pub property_key: String,
+    /// Reduced to `[A-Za-z0-9_]` when rendered; a rule whose label reduces to
+    /// nothing writes no edge.
+    pub edge_label: String,
+    pub direction: EdgeDirection,
+    pub required_label: Option<String>,
+}
+
+impl EdgeRule {
+    pub fn new(
+        property_key: impl Into<String>,
+        edge_label: impl Into<String>,
+        direction: EdgeDirection,
+    ) -> Self {
+        Self {
+            property_key: property_key.into(),
+            edge_label: edge_label.into(),
+            direction,
+            required_label: None,
+        }
+    }
+
+    /// Applies the rule only to entities with `label`.
+    pub fn requiring_label(mut self, label: impl Into<String>) -> Self {
+        self.required_label = Some(label.into());
+        self
+    }
+
+    /// Whether the rule applies to an entity with `labels`.
+    pub fn applies_to(&self, labels: &[String]) -> bool {
+        self.required_label
+            .as_ref()
+            .is_none_or(|required| labels.contains(required))
+    }
+}
+
+/// `parent_entity_id -> HAS_CHILD`, `owner_entity_id -> HAS_HARDPOINT` on
+/// hardpoints, and `mounted_on_entity_id -> MOUNTED_ON`.
+pub fn default_edge_rules() -> Vec<EdgeRule> {
+    vec![
+        EdgeRule::new("parent_entity_id", "HAS_CHILD", EdgeDirection::FromProperty),
+        EdgeRule::new(
+            "owner_entity_id",
+            "HAS_HARDPOINT",
+            EdgeDirection::FromProperty,
+        )
+        .requiring_label("Hardpoint"),
+        EdgeRule::new(
+            "mounted_on_entity_id",
+            "MOUNTED_ON",
+            EdgeDirection::ToProperty,
+        ),
+    ]
+}
@@ -0,0 +1,166 @@
+//! The graph labels persistence expects, and how strictly records are checked
+//! against them.
+//!
+//! `sanitize_labels` only makes a label safe to render into Cypher; it accepts
+//! any spelling. A typo such as `"Shp"` would persist fine and then miss the
+//! `label == "Ship"` checks hydration classifies entities with. Checking labels
+//! at persist time reports the write that introduced the typo.
+
+use std::collections::HashSet;
+
+/// Labels written by the gateway, replication and shard today.
+pub const KNOWN_LABELS: &[&str] = &[
+    "Entity",
+    "Player",
+    "Ship",
+    "Drone",
+    "Turret",
+    "Hardpoint",
+    "Module",
+    "Engine",
+    "Asteroid",
+];
+
+/// What persisting a record with an unknown label does.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LabelValidation {
+    /// Persist without checking.
+    Off,
+    /// Persist, and print a warning naming the entity and its unknown labels.
+    #[default]
+    Warn,
+    /// Fail the whole batch before anything is written.
+    Reject,
+}
+
+impl LabelValidation {
+    /// Parses `off`, `warn` or `reject`, ignoring case.
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "warn" => Some(Self::Warn),
+            "reject" => Some(Self::Reject),
+            _ => None,
+        }
+    }
+}
+
+/// `KNOWN_LABELS` plus any extra labels a deployment adds, and the validation
+/// mode applied to them.
+#[derive(Debug, Clone, Default)]
+pub struct LabelValidator {
+    mode: LabelValidation,
+    extra_labels: HashSet<String>,
+}
+
+impl LabelValidator {
+    pub fn new(mode: LabelValidation) -> Self {
+        Self {
+            mode,
+            extra_labels: HashSet::new(),
+        }
+    }
+
+    /// Accepts `labels` in addition to `KNOWN_LABELS`.
+    pub fn with_extra_labels<I, S>(mut self, labels: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.extra_labels.extend(labels.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn mode(&self) -> LabelValidation {
+        self.mode
+    }
+
+    /// Labels are compared exactly; `"ship"` is not `"Ship"`.
+    pub fn is_known(&self, label: &str) -> bool {
+        KNOWN_LABELS.contains(&label) || self.extra_labels.contains(label)
+    }
+
+    /// The labels in `labels` that are not known, in their original order.
+    pub fn unknown_labels<'a>(&self, labels: &'a [String]) -> Vec<&'a str> {
+        labels
+            .iter()
+            .map(String::as_str)
+            .filter(|label| !self.is_known(label))
+            .collect()
+    }
+
+    /// Applies the mode to one record's labels. `Err` carries the message
+    /// for a rejected record; warnings are printed here.
+    pub fn check(&self, entity_id: &str, labels: &[String]) -> Result<(), String> {
+        if self.mode == LabelValidation::Off {
+            return Ok(());
+        }
+        let unknown = self.unknown_labels(labels);
+        if unknown.is_empty() {
+            return Ok(());
+        }
+        let message = format!("entity {entity_id} has unknown graph labels {unknown:?}");
+        match self.mode {
+            LabelValidation::Reject => Err(message),
+            _ => {
+                eprintln!("WARNING persisting {message}");
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(raw: &[&str]) -> Vec<String> {
+        raw.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn known_labels_pass_and_typos_are_flagged() {
+        let validator = LabelValidator::new(LabelValidation::Reject);
+        let known = labels(&["Entity", "Ship"]);
+        assert!(validator.check("ship:1", &known).is_ok());
+        assert!(validator.unknown_labels(&known).is_empty());
+
+        let typo = labels(&["Entity", "Shp", "ship"]);
+        assert_eq!(validator.unknown_labels(&typo), vec!["Shp", "ship"]);
+        let err = validator.check("ship:2", &typo).unwrap_err();
+        assert!(err.contains("ship:2") && err.contains("Shp"));
+    }
+
+    #[test]
+    fn warn_and_off_modes_never_reject() {
+        let typo = labels(&["Entity", "Shp"]);
+        assert!(
+            LabelValidator::new(LabelValidation::Warn)
+                .check("ship:1", &typo)
+                .is_ok()
+        );
+        assert!(
+            LabelValidator::new(LabelValidation::Off)
+                .check("ship:1", &typo)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn extra_labels_extend_the_known_set() {
+        let validator = LabelValidator::new(LabelValidation::Reject).with_extra_labels(["Station"]);
+        assert!(validator.is_known("Station"));
+        assert!(validator.is_known("Asteroid"));
+        assert!(!validator.is_known("Statoin"));
+    }
+
+    #[test]
+    fn validation_mode_parses_case_insensitively() {
+        assert_eq!(
+            LabelValidation::parse(" Reject "),
+            Some(LabelValidation::Reject)
+        );
+        assert_eq!(LabelValidation::parse("off"), Some(LabelValidation::Off));
+        assert_eq!(LabelValidation::parse("loud"), None);
+    }
+}
@@ -1,18 +1,72 @@
-use postgres::{Client, NoTls};
+use postgres::{Client, NoTls, Portal, Row, Transaction};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map as JsonMap, Value as JsonValue};
-use sidereal_net::WorldDeltaEntity;
+use sidereal_core::world_seed::WorldSeed;
+use sidereal_net::{WorldDeltaEntity, component_id_for};
 use std::collections::HashMap;
 use thiserror::Error;
 
+pub mod cypher;
+pub mod edges;
+pub mod labels;
+pub mod pool;
+pub mod sink;
+pub mod tls;
+
+use cypher::{escape_cypher_string, sanitize_identifier, sanitize_labels, sanitize_property_keys};
+use edges::{EdgeDirection, EdgeRule, default_edge_rules};
+use labels::LabelValidator;
+use sink::{AgtypeParams, CypherSink};
+use tls::TlsConfig;
+
 const DEFAULT_GRAPH_NAME: &str = "sidereal";
+const PROPERTIES_JSON_KEY: &str = "properties_json";
+/// Entity property holding the unix time of the entity's first persist.
+pub const CREATED_AT_KEY: &str = "created_at_epoch_s";
+/// Player property: whether the player has an authenticated client session.
+pub const ONLINE_KEY: &str = "online";
+/// Player property holding the unix time `ONLINE_KEY` last changed.
+pub const LAST_SEEN_KEY: &str = "last_seen_epoch_s";
+/// Property of the singleton `WorldConfig` node holding the `WorldSeed`,
+/// stored as a decimal string because agtype integers are signed 64-bit.
+pub const WORLD_SEED_KEY: &str = "world_seed";
 
 #[derive(Debug, Error)]
 pub enum PersistenceError {
-    #[error("database error: {0}")]
-    Database(String),
+    /// `rolled_back` is set when the failure undid a whole
+    /// `persist_world_delta` batch, so none of it was written.
+    #[error(
+        "database error: {message}{}",
+        if *rolled_back { " (batch rolled back)" } else { "" }
+    )]
+    Database { message: String, rolled_back: bool },
     #[error("serialization error: {0}")]
     Serialization(String),
+    #[error("entity not found: {0}")]
+    NotFound(String),
+    #[error("invalid labels: {0}")]
+    InvalidLabels(String),
+    #[error("tls error: {0}")]
+    Tls(String),
+}
+
+impl PersistenceError {
+    pub fn database(message: impl Into<String>) -> Self {
+        Self::Database {
+            message: message.into(),
+            rolled_back: false,
+        }
+    }
+
+    fn rolled_back(self, rolled_back: bool) -> Self {
+        match self {
+            Self::Database { message, .. } => Self::Database {
+                message,
+                rolled_back,
+            },
+            other => other,
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, PersistenceError>;
@@ -45,63 +99,235 @@ pub struct GraphEntityRecord {
     pub components: Vec<GraphComponentRecord>,
 }
 
-pub struct GraphPersistence {
-    client: Client,
-    graph_name: String,
+impl GraphEntityRecord {
+    /// When the entity was first persisted. `None` for records that have not
+    /// been loaded back from the graph.
+    pub fn created_at_epoch_s(&self) -> Option<u64> {
+        self.properties
+            .get(CREATED_AT_KEY)
+            .and_then(JsonValue::as_u64)
+    }
+
+    /// Seconds since the entity was first persisted, saturating at zero.
+    pub fn age_s(&self, now_epoch_s: u64) -> Option<u64> {
+        self.created_at_epoch_s()
+            .map(|created_at| now_epoch_s.saturating_sub(created_at))
+    }
+
+    /// A player's online flag; `false` for players never marked online.
+    pub fn is_online(&self) -> bool {
+        self.properties
+            .get(ONLINE_KEY)
+            .and_then(JsonValue::as_bool)
+            .unwrap_or(false)
+    }
+
+    /// When the player last came online or went offline.
+    pub fn last_seen_epoch_s(&self) -> Option<u64> {
+        self.properties
+            .get(LAST_SEEN_KEY)
+            .and_then(JsonValue::as_u64)
+    }
+
+    /// Sorts and dedups labels and sorts components by `component_id`, the
+    /// order every load returns and every persist writes, so records can be
+    /// compared or diffed regardless of how they were assembled.
+    pub fn normalize(&mut self) {
+        self.labels.sort();
+        self.labels.dedup();
+        self.components
+            .sort_by(|a, b| a.component_id.cmp(&b.component_id));
+    }
+}
+
+/// One inventory entry persisted as an `:Item` node under its container.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GraphItemRecord {
+    pub item_entity_id: String,
+    pub quantity: u64,
+    pub unit_mass_kg: f64,
+}
+
+/// A row of `replication_snapshot_markers`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotMarker {
+    pub snapshot_id: i64,
+    pub snapshot_tick: u64,
+    pub entity_count: u64,
+    pub created_at_epoch_s: u64,
+}
+
+/// What `restore_to_snapshot` deleted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SnapshotRestore {
+    pub snapshot_tick: u64,
+    pub entities_removed: u64,
+    pub components_removed: u64,
+}
+
+/// A Postgres session `GraphPersistence` runs on: an owned `Client`, or a
+/// connection checked out of a `GraphPersistencePool`.
+pub trait PgSession {
+    fn client(&mut self) -> &mut Client;
+}
+
+impl PgSession for Client {
+    fn client(&mut self) -> &mut Client {
+        self
+    }
+}
+
+pub struct GraphPersistence<S = Client> {
+    session: S,
+    writer: CypherWriter,
 }
 
 impl GraphPersistence {
+    /// Connects without TLS; see `connect_with_tls` for servers that
+    /// require it.
     pub fn connect(database_url: &str) -> Result<Self> {
         Self::connect_with_graph(database_url, DEFAULT_GRAPH_NAME)
     }
 
     pub fn connect_with_graph(database_url: &str, graph_name: impl Into<String>) -> Result<Self> {
         let client = Client::connect(database_url, NoTls)
-            .map_err(|err| PersistenceError::Database(format!("postgres connect failed: {err}")))?;
+            .map_err(|err| PersistenceError::database(format!("postgres connect failed: {err}")))?;
+        Ok(Self::from_client(client, graph_name))
+    }
+
+    /// Runs on an already open `client`, keeping whatever session state the
+    /// caller set up on it (e.g. temporary tables).
+    pub fn from_client(client: Client, graph_name: impl Into<String>) -> Self {
+        Self {
+            session: client,
+            writer: CypherWriter::new(graph_name),
+        }
+    }
+
+    /// Connects over TLS as the URL's `sslmode` (or `tls`'s default mode)
+    /// asks; `sslmode=disable` still connects in plain text.
+    pub fn connect_with_tls(
+        database_url: &str,
+        graph_name: impl Into<String>,
+        tls: TlsConfig,
+    ) -> Result<Self> {
+        let (config, connector) = tls.resolve(database_url)?;
+        let client = config
+            .connect(connector)
+            .map_err(|err| PersistenceError::database(format!("postgres connect failed: {err}")))?;
         Ok(Self {
-            client,
-            graph_name: graph_name.into(),
+            session: client,
+            writer: CypherWriter::new(graph_name),
         })
     }
+}
+
+impl<S: PgSession> GraphPersistence<S> {
+    fn client(&mut self) -> &mut Client {
+        self.session.client()
+    }
+
+    /// Also persists inventories with at least `min_entries` entries as `:Item`
+    /// nodes linked by `HAS_ITEM`, so they can be found with
+    /// `find_containers_with_item`. The JSON inventory component is still written
+    /// and remains the hydration source; smaller inventories are stored as JSON only.
+    pub fn with_structured_inventory(mut self, min_entries: usize) -> Self {
+        self.writer = self.writer.with_structured_inventory(min_entries);
+        self
+    }
+
+    /// Stores components whose serialized payload is at least `min_bytes` as a
+    /// single `properties_json` string instead of one `SET` per key. Loading
+    /// restores the original payload from that string.
+    pub fn with_component_json_threshold(mut self, min_bytes: usize) -> Self {
+        self.writer = self.writer.with_component_json_threshold(min_bytes);
+        self
+    }
+
+    /// Checks record labels against `validator` on persist. Defaults to
+    /// warning about unknown labels.
+    pub fn with_label_validator(mut self, validator: LabelValidator) -> Self {
+        self.writer = self.writer.with_label_validator(validator);
+        self
+    }
+
+    /// Replaces the property-to-edge rules applied on persist. Defaults to
+    /// `edges::default_edge_rules`; extend that list to keep the built-in
+    /// relationships.
+    pub fn set_edge_rules(&mut self, rules: Vec<EdgeRule>) {
+        self.writer.set_edge_rules(rules);
+    }
 
     pub fn graph_name(&self) -> &str {
-        &self.graph_name
+        self.writer.graph_name()
+    }
+
+    /// Postgres backend process id of this session; two handles reporting
+    /// the same id share one connection.
+    pub fn backend_pid(&mut self) -> Result<i32> {
+        let row = self
+            .client()
+            .query_one("SELECT pg_backend_pid()", &[])
+            .map_err(db_err("query backend pid"))?;
+        Ok(row.get(0))
+    }
+
+    /// Cheap readiness check: one `SELECT 1` round trip.
+    pub fn ping(&mut self) -> Result<()> {
+        self.client()
+            .query_one("SELECT 1", &[])
+            .map_err(db_err("ping"))?;
+        Ok(())
+    }
+
+    /// Whether the server has the `age` extension installed, i.e. whether
+    /// `ensure_schema` can create and load it. Changes nothing.
+    pub fn age_available(&mut self) -> Result<bool> {
+        let row = self
+            .client()
+            .query_one(
+                "SELECT EXISTS (SELECT 1 FROM pg_available_extensions WHERE name = 'age')",
+                &[],
+            )
+            .map_err(db_err("query age availability"))?;
+        Ok(row.get(0))
     }
 
     pub fn ensure_schema(&mut self) -> Result<()> {
-        self.client
+        self.client()
             .batch_execute("CREATE EXTENSION IF NOT EXISTS age;")
             .map_err(db_err("create age extension"))?;
-        self.client
+        self.client()
             .batch_execute("LOAD 'age';")
             .map_err(db_err("load age extension"))?;
-        self.client
+        self.client()
             .batch_execute("SET search_path = ag_catalog, \"$user\", public;")
             .map_err(db_err("set age search_path"))?;
 
         let graph_exists = self
-            .client
+            .session
+            .client()
             .query_opt(
                 "SELECT 1 FROM ag_catalog.ag_graph WHERE name = $1 LIMIT 1",
-                &[&self.graph_name],
+                &[&self.writer.graph_name],
             )
             .map_err(db_err("query graph existence"))?
             .is_some();
         if !graph_exists {
             let query = format!(
                 "SELECT * FROM ag_catalog.create_graph('{}');",
-                escape_cypher_string(&self.graph_name)
+                escape_cypher_string(self.writer.graph_name())
             );
-            self.client
+            self.client()
                 .batch_execute(&query)
                 .map_err(db_err("create graph"))?;
         }
 
-        self.client
+        self.client()
             .batch_execute("SET search_path = public;")
             .map_err(db_err("reset search_path"))?;
 
-        self.client
+        self.client()
             .batch_execute(
                 "
                 CREATE TABLE IF NOT EXISTS replication_snapshot_markers (
@@ -114,42 +340,27 @@ impl GraphPersistence {
             )
             .map_err(db_err("create snapshot marker table"))?;
 
+        self.client()
+            .batch_execute(
+                "
+                CREATE TABLE IF NOT EXISTS replication_session_snapshots (
+                    snapshot_id BIGSERIAL PRIMARY KEY,
+                    snapshot_tick BIGINT NOT NULL,
+                    payload TEXT NOT NULL,
+                    created_at_epoch_s BIGINT NOT NULL
+                );
+                ",
+            )
+            .map_err(db_err("create session snapshot table"))?;
+
         Ok(())
     }
 
+    /// All-or-nothing: a failure rolls back the whole batch and reports
+    /// `PersistenceError::Database { rolled_back: true, .. }`.
     pub fn persist_world_delta(&mut self, updates: &[WorldDeltaEntity], tick: u64) -> Result<()> {
-        let removed_entity_ids = updates
-            .iter()
-            .filter(|u| u.removed)
-            .map(|u| u.entity_id.clone())
-            .collect::<Vec<_>>();
-
-        let records = updates
-            .iter()
-            .filter(|u| !u.removed)
-            .map(|u| GraphEntityRecord {
-                entity_id: u.entity_id.clone(),
-                labels: if u.labels.is_empty() {
-                    vec!["Entity".to_string()]
-                } else {
-                    u.labels.clone()
-                },
-                properties: u.properties.clone(),
-                components: u
-                    .components
-                    .iter()
-                    .map(|c| GraphComponentRecord {
-                        component_id: c.component_id.clone(),
-                        component_kind: c.component_kind.clone(),
-                        properties: c.properties.clone(),
-                    })
-                    .collect::<Vec<_>>(),
-            })
-            .collect::<Vec<_>>();
-
-        self.persist_graph_records(&records, tick)?;
-        self.remove_graph_entities(&removed_entity_ids)?;
-        Ok(())
+        self.writer
+            .persist_world_delta(self.session.client(), updates, tick)
     }
 
     pub fn persist_graph_records(
@@ -157,105 +368,20 @@ impl GraphPersistence {
         records: &[GraphEntityRecord],
         tick: u64,
     ) -> Result<()> {
-        if records.is_empty() {
-            return Ok(());
-        }
-        self.client
-            .batch_execute("LOAD 'age'; SET search_path = ag_catalog, \"$user\", public;")
-            .map_err(db_err("prep age for graph persist"))?;
-
-        for record in records {
-            let labels = sanitize_labels(&record.labels);
-            let mut set_parts = vec![format!("e.last_tick={tick}")];
-            set_parts.push(format!(
-                "e.sidereal_labels={}",
-                cypher_literal(&JsonValue::Array(
-                    labels
-                        .iter()
-                        .cloned()
-                        .map(JsonValue::String)
-                        .collect::<Vec<_>>()
-                ))
-            ));
-            set_parts.extend(cypher_set_clauses("e", &record.properties));
-
-            let query = format!(
-                "MERGE (e:Entity {{entity_id:'{}'}}) SET {}",
-                escape_cypher_string(&record.entity_id),
-                set_parts.join(", "),
-            );
-            self.run_cypher(&query)?;
-
-            let incoming_component_ids = JsonValue::Array(
-                record
-                    .components
-                    .iter()
-                    .map(|c| JsonValue::String(c.component_id.clone()))
-                    .collect::<Vec<_>>(),
-            );
-            self.run_cypher(&format!(
-                "MATCH (e:Entity {{entity_id:'{}'}}) \
-                 OPTIONAL MATCH (e)-[:HAS_COMPONENT]->(c:Component) \
-                 WHERE c IS NOT NULL AND NOT c.component_id IN {} \
-                 DETACH DELETE c",
-                escape_cypher_string(&record.entity_id),
-                cypher_literal(&incoming_component_ids),
-            ))?;
-
-            for component in &record.components {
-                let mut comp_set = vec![
-                    format!("c.last_tick={tick}"),
-                    format!(
-                        "c.component_id={}",
-                        cypher_literal(&JsonValue::String(component.component_id.clone()))
-                    ),
-                    format!(
-                        "c.component_kind={}",
-                        cypher_literal(&JsonValue::String(component.component_kind.clone()))
-                    ),
-                ];
-                comp_set.extend(cypher_set_clauses("c", &component.properties));
-                self.run_cypher(&format!(
-                    "MERGE (c:Component {{component_id:'{}'}}) SET {}",
-                    escape_cypher_string(&component.component_id),
-                    comp_set.join(", ")
-                ))?;
-                self.run_cypher(&format!(
-                    "MATCH (e:Entity {{entity_id:'{}'}}), (c:Component {{component_id:'{}'}}) MERGE (e)-[:HAS_COMPONENT]->(c)",
-                    escape_cypher_string(&record.entity_id),
-                    escape_cypher_string(&component.component_id),
-                ))?;
-            }
-
-            self.persist_relationship_edges(record)?;
-        }
-
-        self.client
-            .batch_execute("SET search_path = public;")
-            .map_err(db_err("reset search_path after graph persist"))?;
-
-        Ok(())
+        self.writer
+            .persist_graph_records(self.session.client(), records, tick)
     }
 
     pub fn remove_graph_entities(&mut self, entity_ids: &[String]) -> Result<()> {
-        if entity_ids.is_empty() {
-            return Ok(());
-        }
-        self.client
-            .batch_execute("LOAD 'age'; SET search_path = ag_catalog, \"$user\", public;")
-            .map_err(db_err("prep age for graph remove"))?;
-
-        for entity_id in entity_ids {
-            self.run_cypher(&format!(
-                "MATCH (e:Entity {{entity_id:'{}'}}) OPTIONAL MATCH (e)-[:HAS_COMPONENT]->(c:Component) DETACH DELETE c, e",
-                escape_cypher_string(entity_id),
-            ))?;
-        }
+        self.writer
+            .remove_graph_entities(self.session.client(), entity_ids)
+    }
 
-        self.client
-            .batch_execute("SET search_path = public;")
-            .map_err(db_err("reset search_path after graph remove"))?;
-        Ok(())
+    /// Deletes only the named components of each entity, leaving the entity and
+    /// its remaining components in place.
+    pub fn remove_graph_components(&mut self, removals: &[(String, Vec<String>)]) -> Result<()> {
+        self.writer
+            .remove_graph_components(self.session.client(), removals)
     }
 
     pub fn persist_snapshot_marker(
@@ -264,7 +390,7 @@ impl GraphPersistence {
         entity_count: usize,
     ) -> Result<()> {
         let now = now_epoch_s() as i64;
-        self.client
+        self.client()
             .execute(
                 "INSERT INTO replication_snapshot_markers (snapshot_tick, entity_count, created_at_epoch_s) VALUES ($1, $2, $3)",
                 &[&(snapshot_tick as i64), &(entity_count as i64), &now],
@@ -273,220 +399,1323 @@ impl GraphPersistence {
         Ok(())
     }
 
+    /// Every snapshot marker, oldest first.
+    pub fn load_snapshot_markers(&mut self) -> Result<Vec<SnapshotMarker>> {
+        let rows = self
+            .client()
+            .query(
+                "SELECT snapshot_id, snapshot_tick, entity_count, created_at_epoch_s \
+                 FROM replication_snapshot_markers ORDER BY snapshot_id",
+                &[],
+            )
+            .map_err(db_err("load snapshot markers"))?;
+        Ok(rows
+            .iter()
+            .map(|row| SnapshotMarker {
+                snapshot_id: row.get(0),
+                snapshot_tick: row.get::<_, i64>(1).max(0) as u64,
+                entity_count: row.get::<_, i64>(2).max(0) as u64,
+                created_at_epoch_s: row.get::<_, i64>(3).max(0) as u64,
+            })
+            .collect())
+    }
+
+    /// Deletes all but the `keep_latest` newest snapshot markers and returns
+    /// how many were removed.
+    pub fn prune_snapshot_markers(&mut self, keep_latest: usize) -> Result<u64> {
+        let keep_latest = i64::try_from(keep_latest).unwrap_or(i64::MAX);
+        self.client()
+            .execute(
+                "DELETE FROM replication_snapshot_markers WHERE snapshot_id NOT IN \
+                 (SELECT snapshot_id FROM replication_snapshot_markers \
+                  ORDER BY snapshot_id DESC LIMIT $1)",
+                &[&keep_latest],
+            )
+            .map_err(db_err("prune snapshot markers"))
+    }
+
+    /// Deletes snapshot markers created before `epoch_s` and returns how many
+    /// were removed.
+    pub fn prune_snapshot_markers_older_than(&mut self, epoch_s: i64) -> Result<u64> {
+        self.client()
+            .execute(
+                "DELETE FROM replication_snapshot_markers WHERE created_at_epoch_s < $1",
+                &[&epoch_s],
+            )
+            .map_err(db_err("prune snapshot markers by age"))
+    }
+
+    /// Rewinds the graph to a snapshot marker by deleting every entity and
+    /// component node persisted after the marker's tick, in one transaction.
+    /// This is a coarse rollback for a corrupted tick range: an entity that
+    /// existed at the snapshot but was written again afterwards is deleted
+    /// too, not reverted, and is restored by the next delta that persists it.
+    /// Fails with `NotFound` for an unknown marker.
+    pub fn restore_to_snapshot(&mut self, snapshot_id: i64) -> Result<SnapshotRestore> {
+        let graph_name = escape_cypher_string(self.writer.graph_name());
+        let mut tx = self
+            .client()
+            .transaction()
+            .map_err(db_err("begin snapshot restore transaction"))?;
+        let snapshot_tick = tx
+            .query_opt(
+                "SELECT snapshot_tick FROM replication_snapshot_markers WHERE snapshot_id = $1",
+                &[&snapshot_id],
+            )
+            .map_err(db_err("load snapshot marker"))?
+            .map(|row| row.get::<_, i64>(0))
+            .ok_or_else(|| PersistenceError::NotFound(format!("snapshot marker {snapshot_id}")))?;
+        tx.batch_execute("LOAD 'age'; SET search_path = ag_catalog, \"$user\", public;")
+            .map_err(db_err("prep age for snapshot restore"))?;
+
+        let params = serde_json::json!({ "tick": snapshot_tick });
+        let mut removed = [0u64; 2];
+        for (label, count) in ["Entity", "Component"].into_iter().zip(&mut removed) {
+            // AGE cannot return a count from the same clause that deletes.
+            let count_query = format!(
+                "SELECT n::text FROM ag_catalog.cypher('{graph_name}', $$ \
+                    MATCH (n:{label}) WHERE n.last_tick > $tick RETURN count(n) \
+                 $$, $1) AS (n agtype);"
+            );
+            let row = tx
+                .query_one(&count_query, &[&AgtypeParams(&params)])
+                .map_err(db_err("count nodes after snapshot"))?;
+            *count = parse_agtype_json(row.get::<_, String>(0))
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0);
+            let delete_query = format!(
+                "SELECT * FROM ag_catalog.cypher('{graph_name}', $$ \
+                    MATCH (n:{label}) WHERE n.last_tick > $tick DETACH DELETE n \
+                 $$, $1) AS (v agtype);"
+            );
+            tx.query(&delete_query, &[&AgtypeParams(&params)])
+                .map_err(db_err("delete nodes after snapshot"))?;
+        }
+
+        tx.batch_execute("SET search_path = public;")
+            .map_err(db_err("reset search_path after snapshot restore"))?;
+        tx.commit()
+            .map_err(db_err("commit snapshot restore transaction"))?;
+        let [entities_removed, components_removed] = removed;
+        Ok(SnapshotRestore {
+            snapshot_tick: snapshot_tick.max(0) as u64,
+            entities_removed,
+            components_removed,
+        })
+    }
+
+    /// Stores replication's serialized client session state. Only the newest
+    /// payload is kept; it is read back once on the next boot.
+    pub fn persist_session_snapshot(&mut self, snapshot_tick: u64, payload: &str) -> Result<()> {
+        let now = now_epoch_s() as i64;
+        let mut tx = self
+            .client()
+            .transaction()
+            .map_err(db_err("begin session snapshot transaction"))?;
+        tx.execute("DELETE FROM replication_session_snapshots", &[])
+            .map_err(db_err("clear session snapshots"))?;
+        tx.execute(
+            "INSERT INTO replication_session_snapshots (snapshot_tick, payload, created_at_epoch_s) VALUES ($1, $2, $3)",
+            &[&(snapshot_tick as i64), &payload, &now],
+        )
+        .map_err(db_err("insert session snapshot"))?;
+        tx.commit()
+            .map_err(db_err("commit session snapshot transaction"))?;
+        Ok(())
+    }
+
+    /// The payload last written by `persist_session_snapshot`, if any.
+    pub fn load_session_snapshot(&mut self) -> Result<Option<String>> {
+        let row = self
+            .client()
+            .query_opt(
+                "SELECT payload FROM replication_session_snapshots ORDER BY snapshot_id DESC LIMIT 1",
+                &[],
+            )
+            .map_err(db_err("load session snapshot"))?;
+        Ok(row.map(|row| row.get::<_, String>(0)))
+    }
+
     pub fn drop_graph(mut self) -> Result<()> {
-        self.client
+        self.client()
             .batch_execute("LOAD 'age'; SET search_path = ag_catalog, \"$user\", public;")
             .map_err(db_err("prep age for graph drop"))?;
         let sql = format!(
             "SELECT * FROM ag_catalog.drop_graph('{}', true);",
-            escape_cypher_string(&self.graph_name)
+            escape_cypher_string(self.writer.graph_name())
         );
-        self.client
+        self.client()
             .batch_execute(&sql)
             .map_err(db_err("drop graph"))?;
-        self.client
+        self.client()
             .batch_execute("SET search_path = public;")
             .map_err(db_err("reset search_path after graph drop"))?;
         Ok(())
     }
 
     pub fn load_graph_records(&mut self) -> Result<Vec<GraphEntityRecord>> {
-        self.client
+        self.load_graph_records_where("", &JsonValue::Null)
+    }
+
+    /// Same records as `load_graph_records`, ordered by entity id, but read
+    /// through a cursor `STREAM_BATCH_ROWS` rows at a time, so only one batch
+    /// and one entity are held in memory. The stream owns a read-only
+    /// transaction on this connection until it is dropped.
+    pub fn stream_graph_records(&mut self) -> Result<GraphRecordStream<'_>> {
+        let query = records_query(self.writer.graph_name(), "", false, "ORDER BY e.entity_id");
+        let mut transaction = self
+            .client()
+            .transaction()
+            .map_err(db_err("begin graph stream transaction"))?;
+        transaction
+            .batch_execute("LOAD 'age'; SET LOCAL search_path = ag_catalog, \"$user\", public;")
+            .map_err(db_err("prep age for graph stream"))?;
+        let portal = transaction
+            .bind(query.as_str(), &[])
+            .map_err(db_err("open graph stream cursor"))?;
+        Ok(GraphRecordStream {
+            transaction,
+            portal,
+            rows: Vec::new().into_iter(),
+            pending: None,
+            exhausted: false,
+        })
+    }
+
+    /// Loads every entity persisted at or after `tick`, with its full current
+    /// component set, for delta backups and shard catch-up. Each persist
+    /// stamps the entity's `last_tick` together with its components', so the
+    /// entity tick covers component changes too. Removals leave no node and
+    /// are not reported.
+    pub fn load_graph_records_since(&mut self, tick: u64) -> Result<Vec<GraphEntityRecord>> {
+        self.load_graph_records_filtered(&[], Some(tick))
+    }
+
+    /// Loads only entities carrying at least one of `labels` (all entities
+    /// when empty) and, with `since_tick`, persisted at or after that tick.
+    /// Filtering happens in the database, so skipped entities are never
+    /// transferred.
+    pub fn load_graph_records_filtered(
+        &mut self,
+        labels: &[&str],
+        since_tick: Option<u64>,
+    ) -> Result<Vec<GraphEntityRecord>> {
+        self.load_graph_records_where(&records_filter(labels, since_tick), &JsonValue::Null)
+    }
+
+    /// Loads the player's own node plus every entity bound to it through a
+    /// `player_entity_id` property (its ships), with their components. Empty
+    /// when the player does not exist.
+    pub fn load_player_records(
+        &mut self,
+        player_entity_id: &str,
+    ) -> Result<Vec<GraphEntityRecord>> {
+        self.load_graph_records_where(
+            "WHERE e.entity_id = $player_entity_id OR e.player_entity_id = $player_entity_id",
+            &serde_json::json!({ "player_entity_id": player_entity_id }),
+        )
+    }
+
+    /// Loads one entity with all of its components, e.g. to re-hydrate a
+    /// remounted module. `None` when no entity has that id.
+    pub fn load_graph_record(&mut self, entity_id: &str) -> Result<Option<GraphEntityRecord>> {
+        let mut records = self.load_graph_records_where(
+            "WHERE e.entity_id = $entity_id",
+            &serde_json::json!({ "entity_id": entity_id }),
+        )?;
+        Ok(records.pop())
+    }
+
+    /// Loads entities whose `position_m` lies within `radius_m` of
+    /// `center_m`, nearest first. Distance is full 3D Euclidean over x, y and
+    /// z, computed in the database; entities without a three-element
+    /// `position_m` are never returned. Empty for a negative or non-finite
+    /// radius or center.
+    pub fn load_entities_near(
+        &mut self,
+        center_m: [f32; 3],
+        radius_m: f32,
+    ) -> Result<Vec<GraphEntityRecord>> {
+        let valid =
+            radius_m >= 0.0 && radius_m.is_finite() && center_m.iter().all(|v| v.is_finite());
+        if !valid {
+            return Ok(Vec::new());
+        }
+        let [x, y, z] = center_m.map(f64::from);
+        let mut records = self.load_graph_records_where(
+            "WHERE size(e.position_m) = 3 \
+             AND (e.position_m[0] - $x) * (e.position_m[0] - $x) \
+               + (e.position_m[1] - $y) * (e.position_m[1] - $y) \
+               + (e.position_m[2] - $z) * (e.position_m[2] - $z) <= $radius_sq",
+            &serde_json::json!({
+                "x": x,
+                "y": y,
+                "z": z,
+                "radius_sq": f64::from(radius_m) * f64::from(radius_m),
+            }),
+        )?;
+        let distance_sq = |record: &GraphEntityRecord| {
+            record
+                .properties
+                .get("position_m")
+                .and_then(JsonValue::as_array)
+                .map(|axes| {
+                    axes.iter()
+                        .zip([x, y, z])
+                        .map(|(axis, center)| (axis.as_f64().unwrap_or(center) - center).powi(2))
+                        .sum::<f64>()
+                })
+                .unwrap_or(f64::MAX)
+        };
+        records.sort_by(|a, b| {
+            distance_sq(a)
+                .total_cmp(&distance_sq(b))
+                .then_with(|| a.entity_id.cmp(&b.entity_id))
+        });
+        Ok(records)
+    }
+
+    /// `filter` is a Cypher `WHERE` clause on the entity node `e`, or empty.
+    /// It may read `$name` parameters from `params` unless that is `Null`.
+    fn load_graph_records_where(
+        &mut self,
+        filter: &str,
+        params: &JsonValue,
+    ) -> Result<Vec<GraphEntityRecord>> {
+        self.client()
             .batch_execute("LOAD 'age'; SET search_path = ag_catalog, \"$user\", public;")
             .map_err(db_err("prep age for graph load"))?;
 
-        let query = format!(
-            "SELECT entity_id::text AS entity_id, labels::text AS labels, props::text AS props, component_id::text AS component_id, component_kind::text AS component_kind, component_props::text AS component_props \
-             FROM ag_catalog.cypher('{}', $$ \
-                MATCH (e:Entity) \
-                OPTIONAL MATCH (e)-[:HAS_COMPONENT]->(c:Component) \
-                RETURN e.entity_id, labels(e), properties(e), c.component_id, c.component_kind, properties(c) \
-             $$) AS (entity_id agtype, labels agtype, props agtype, component_id agtype, component_kind agtype, component_props agtype);",
-            escape_cypher_string(&self.graph_name)
-        );
-        let rows = self
-            .client
-            .query(&query, &[])
-            .map_err(db_err("load graph records"))?;
+        let query = records_query(self.writer.graph_name(), filter, !params.is_null(), "");
+        let rows = if params.is_null() {
+            self.client().query(&query, &[])
+        } else {
+            self.client().query(&query, &[&AgtypeParams(params)])
+        }
+        .map_err(db_err("load graph records"))?;
 
-        self.client
+        self.client()
             .batch_execute("SET search_path = public;")
             .map_err(db_err("reset search_path after graph load"))?;
 
         let mut by_entity = HashMap::<String, GraphEntityRecord>::new();
         for row in rows {
-            let Some(entity_id) = parse_agtype_string(row.get::<_, String>("entity_id")) else {
+            let Some((entity, component)) = parse_record_row(&row) else {
                 continue;
             };
-            let mut labels = parse_agtype_json(row.get::<_, String>("labels"))
-                .and_then(|v| serde_json::from_value::<Vec<String>>(v).ok())
-                .unwrap_or_else(|| vec!["Entity".to_string()]);
-            let properties = parse_agtype_json(row.get::<_, String>("props"))
-                .unwrap_or(JsonValue::Object(JsonMap::new()));
-            if let Some(extra_labels) = properties.get("sidereal_labels").and_then(|v| v.as_array())
-            {
-                labels.extend(
-                    extra_labels
-                        .iter()
-                        .filter_map(|v| v.as_str().map(ToString::to_string)),
-                );
-                labels.sort();
-                labels.dedup();
-            }
-            let entry = by_entity
-                .entry(entity_id.clone())
-                .or_insert_with(|| GraphEntityRecord {
-                    entity_id: entity_id.clone(),
-                    labels,
-                    properties,
-                    components: Vec::new(),
-                });
-
-            let component_id = row
-                .try_get::<_, Option<String>>("component_id")
-                .ok()
-                .flatten()
-                .and_then(parse_agtype_string);
-            let component_kind = row
-                .try_get::<_, Option<String>>("component_kind")
-                .ok()
-                .flatten()
-                .and_then(parse_agtype_string);
-            if let (Some(component_id), Some(component_kind)) = (component_id, component_kind) {
-                let component_props = row
-                    .try_get::<_, Option<String>>("component_props")
-                    .ok()
-                    .flatten()
-                    .and_then(parse_agtype_json)
-                    .unwrap_or(JsonValue::Object(JsonMap::new()));
-                if !entry
-                    .components
-                    .iter()
-                    .any(|c| c.component_id == component_id)
-                {
-                    entry.components.push(GraphComponentRecord {
-                        component_id,
-                        component_kind,
-                        properties: component_props,
-                    });
-                }
+            let entry = by_entity.entry(entity.entity_id.clone()).or_insert(entity);
+            if let Some(component) = component {
+                add_component(entry, component);
             }
         }
 
         let mut out = by_entity.into_values().collect::<Vec<_>>();
+        for record in &mut out {
+            record.normalize();
+        }
         out.sort_by(|a, b| a.entity_id.cmp(&b.entity_id));
         Ok(out)
     }
 
-    fn persist_relationship_edges(&mut self, record: &GraphEntityRecord) -> Result<()> {
-        if let Some(parent_id) = record
-            .properties
-            .get("parent_entity_id")
-            .and_then(JsonValue::as_str)
-        {
-            self.run_cypher(&format!(
-                "MATCH (p:Entity {{entity_id:'{}'}}), (e:Entity {{entity_id:'{}'}}) MERGE (p)-[:HAS_CHILD]->(e)",
-                escape_cypher_string(parent_id),
-                escape_cypher_string(&record.entity_id),
-            ))?;
-        }
+    /// Returns the ids of entities whose structured inventory holds `item_entity_id`.
+    pub fn find_containers_with_item(&mut self, item_entity_id: &str) -> Result<Vec<String>> {
+        self.client()
+            .batch_execute("LOAD 'age'; SET search_path = ag_catalog, \"$user\", public;")
+            .map_err(db_err("prep age for item query"))?;
 
-        if record.labels.iter().any(|l| l == "Hardpoint")
-            && let Some(owner_id) = record
-                .properties
-                .get("owner_entity_id")
-                .and_then(JsonValue::as_str)
-        {
-            self.run_cypher(&format!(
-                "MATCH (s:Entity {{entity_id:'{}'}}), (h:Entity {{entity_id:'{}'}}) MERGE (s)-[:HAS_HARDPOINT]->(h)",
-                escape_cypher_string(owner_id),
-                escape_cypher_string(&record.entity_id),
-            ))?;
-        }
+        let query = format!(
+            "SELECT entity_id::text AS entity_id \
+             FROM ag_catalog.cypher('{}', $$ \
+                MATCH (e:Entity)-[:HAS_ITEM]->(i:Item {{item_entity_id: $item_entity_id}}) \
+                RETURN DISTINCT e.entity_id \
+             $$, $1) AS (entity_id agtype);",
+            escape_cypher_string(self.writer.graph_name()),
+        );
+        let params = serde_json::json!({ "item_entity_id": item_entity_id });
+        let rows = self
+            .client()
+            .query(&query, &[&AgtypeParams(&params)])
+            .map_err(db_err("find containers with item"))?;
 
-        if let Some(mounted_on) = record
-            .properties
-            .get("mounted_on_entity_id")
-            .and_then(JsonValue::as_str)
-        {
-            self.run_cypher(&format!(
-                "MATCH (m:Entity {{entity_id:'{}'}}), (h:Entity {{entity_id:'{}'}}) MERGE (m)-[:MOUNTED_ON]->(h)",
-                escape_cypher_string(&record.entity_id),
-                escape_cypher_string(mounted_on),
-            ))?;
-        }
+        self.client()
+            .batch_execute("SET search_path = public;")
+            .map_err(db_err("reset search_path after item query"))?;
 
-        Ok(())
+        let mut out = rows
+            .into_iter()
+            .filter_map(|row| parse_agtype_string(row.get::<_, String>("entity_id")))
+            .collect::<Vec<_>>();
+        out.sort();
+        Ok(out)
     }
 
-    fn run_cypher(&mut self, cypher: &str) -> Result<()> {
-        let sql = format!(
-            "SELECT * FROM ag_catalog.cypher('{}', $$ {cypher} $$) AS (v agtype);",
-            escape_cypher_string(&self.graph_name)
+    /// Returns the ids of entities first persisted before `cutoff_epoch_s`,
+    /// sorted. Entities persisted before creation timestamps existed have none
+    /// and are never returned.
+    pub fn find_entities_created_before(&mut self, cutoff_epoch_s: u64) -> Result<Vec<String>> {
+        self.client()
+            .batch_execute("LOAD 'age'; SET search_path = ag_catalog, \"$user\", public;")
+            .map_err(db_err("prep age for age query"))?;
+
+        let query = format!(
+            "SELECT entity_id::text AS entity_id \
+             FROM ag_catalog.cypher('{}', $$ \
+                MATCH (e:Entity) \
+                WHERE e.{CREATED_AT_KEY} < {cutoff_epoch_s} \
+                RETURN e.entity_id \
+             $$) AS (entity_id agtype);",
+            escape_cypher_string(self.writer.graph_name()),
         );
-        self.client.query(&sql, &[]).map_err(|err| {
-            PersistenceError::Database(format!("cypher execution failed: {err}; query={cypher}"))
-        })?;
-        Ok(())
+        let rows = self
+            .client()
+            .query(&query, &[])
+            .map_err(db_err("find entities created before cutoff"))?;
+
+        self.client()
+            .batch_execute("SET search_path = public;")
+            .map_err(db_err("reset search_path after age query"))?;
+
+        let mut out = rows
+            .into_iter()
+            .filter_map(|row| parse_agtype_string(row.get::<_, String>("entity_id")))
+            .collect::<Vec<_>>();
+        out.sort();
+        Ok(out)
     }
-}
 
-fn sanitize_labels(labels: &[String]) -> Vec<String> {
-    labels
-        .iter()
-        .filter_map(|label| {
-            let cleaned = label
-                .chars()
-                .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
-                .collect::<String>();
-            if cleaned.is_empty() {
-                None
-            } else {
-                Some(cleaned)
-            }
-        })
-        .collect::<Vec<_>>()
-}
+    /// Moves a persisted entity by setting `position_m`/`velocity_mps` in place,
+    /// leaving its other properties and components untouched. Meant for
+    /// entities that are not live in the simulation (a live entity's next
+    /// delta would overwrite the warp). Fails with `NotFound` if the entity
+    /// does not exist.
+    pub fn set_entity_position(
+        &mut self,
+        entity_id: &str,
+        position_m: [f32; 3],
+        velocity_mps: [f32; 3],
+    ) -> Result<()> {
+        self.client()
+            .batch_execute("LOAD 'age'; SET search_path = ag_catalog, \"$user\", public;")
+            .map_err(db_err("prep age for entity warp"))?;
 
-fn cypher_set_clauses(prefix: &str, value: &JsonValue) -> Vec<String> {
-    let Some(obj) = value.as_object() else {
-        return Vec::new();
-    };
-    obj.iter()
-        .map(|(key, val)| {
-            let clean_key = key
-                .chars()
-                .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
-                .collect::<String>();
-            format!("{prefix}.{clean_key}={}", cypher_literal(val))
-        })
-        .collect::<Vec<_>>()
-}
+        let query = format!(
+            "SELECT entity_id::text AS entity_id \
+             FROM ag_catalog.cypher('{}', $$ \
+                MATCH (e:Entity {{entity_id: $entity_id}}) \
+                SET e.position_m = $position_m, e.velocity_mps = $velocity_mps \
+                RETURN e.entity_id \
+             $$, $1) AS (entity_id agtype);",
+            escape_cypher_string(self.writer.graph_name()),
+        );
+        let params = serde_json::json!({
+            "entity_id": entity_id,
+            "position_m": position_m,
+            "velocity_mps": velocity_mps,
+        });
+        let rows = self
+            .client()
+            .query(&query, &[&AgtypeParams(&params)])
+            .map_err(db_err("warp entity"))?;
 
-fn cypher_literal(value: &JsonValue) -> String {
-    match value {
-        JsonValue::Null => "null".to_string(),
-        JsonValue::Bool(v) => v.to_string(),
-        JsonValue::Number(v) => v.to_string(),
-        JsonValue::String(v) => format!("'{}'", escape_cypher_string(v)),
-        JsonValue::Array(values) => {
-            let rendered = values.iter().map(cypher_literal).collect::<Vec<_>>();
-            format!("[{}]", rendered.join(","))
-        }
-        JsonValue::Object(map) => {
-            let rendered = map
-                .iter()
-                .map(|(k, v)| {
-                    let clean_key = k
-                        .chars()
-                        .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
-                        .collect::<String>();
-                    format!("{clean_key}:{}", cypher_literal(v))
-                })
-                .collect::<Vec<_>>();
-            format!("{{{}}}", rendered.join(","))
+        self.client()
+            .batch_execute("SET search_path = public;")
+            .map_err(db_err("reset search_path after entity warp"))?;
+
+        if rows.is_empty() {
+            return Err(PersistenceError::NotFound(entity_id.to_string()));
         }
+        Ok(())
+    }
+
+    /// Marks a player online or offline and stamps `LAST_SEEN_KEY` with the
+    /// current time. Only those two properties change. Fails with `NotFound`
+    /// if the player does not exist.
+    pub fn set_player_online(&mut self, player_entity_id: &str, online: bool) -> Result<()> {
+        let params = serde_json::json!({
+            "entity_id": player_entity_id,
+            "online": online,
+            "now": now_epoch_s(),
+        });
+        let updated = self.update_player_presence(
+            &format!(
+                "MATCH (e:Entity {{entity_id: $entity_id}}) \
+                 SET e.{ONLINE_KEY} = $online, e.{LAST_SEEN_KEY} = $now \
+                 RETURN e.entity_id"
+            ),
+            &params,
+            "set player presence",
+        )?;
+        if updated == 0 {
+            return Err(PersistenceError::NotFound(player_entity_id.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Marks every player still flagged online as offline, stamping
+    /// `LAST_SEEN_KEY`. Replication calls this at boot: nobody is connected
+    /// yet, and a crash leaves the previous session's players flagged online.
+    /// Returns how many players were cleared.
+    pub fn clear_players_online(&mut self) -> Result<usize> {
+        let params = serde_json::json!({ "now": now_epoch_s() });
+        self.update_player_presence(
+            &format!(
+                "MATCH (e:Player) WHERE e.{ONLINE_KEY} = true \
+                 SET e.{ONLINE_KEY} = false, e.{LAST_SEEN_KEY} = $now \
+                 RETURN e.entity_id"
+            ),
+            &params,
+            "clear player presence",
+        )
+    }
+
+    /// Runs a presence `cypher` that returns one row per updated player.
+    fn update_player_presence(
+        &mut self,
+        cypher: &str,
+        params: &JsonValue,
+        action: &'static str,
+    ) -> Result<usize> {
+        self.client()
+            .batch_execute("LOAD 'age'; SET search_path = ag_catalog, \"$user\", public;")
+            .map_err(db_err("prep age for player presence"))?;
+
+        let query = format!(
+            "SELECT entity_id::text AS entity_id \
+             FROM ag_catalog.cypher('{}', $$ {cypher} $$, $1) AS (entity_id agtype);",
+            escape_cypher_string(self.writer.graph_name()),
+        );
+        let rows = self
+            .client()
+            .query(&query, &[&AgtypeParams(params)])
+            .map_err(db_err(action))?;
+
+        self.client()
+            .batch_execute("SET search_path = public;")
+            .map_err(db_err("reset search_path after player presence"))?;
+        Ok(rows.len())
+    }
+
+    /// The world's seed, or `None` before one has been stored.
+    pub fn load_world_seed(&mut self) -> Result<Option<WorldSeed>> {
+        let query =
+            format!("MATCH (w:WorldConfig {{config_id:'world'}}) RETURN w.{WORLD_SEED_KEY}");
+        self.query_world_seed(&query, "load world seed")
+    }
+
+    /// Stores `seed` as the world's seed, replacing any existing one. Clients
+    /// that already derived content from the old seed will disagree with the
+    /// server until they fetch it again.
+    pub fn store_world_seed(&mut self, seed: WorldSeed) -> Result<()> {
+        let query = format!(
+            "MERGE (w:WorldConfig {{config_id:'world'}}) SET w.{WORLD_SEED_KEY}='{}' RETURN w.{WORLD_SEED_KEY}",
+            seed.0
+        );
+        self.query_world_seed(&query, "store world seed")?;
+        Ok(())
+    }
+
+    /// The world's seed, storing a fresh one first if there is none. AGE's
+    /// `MERGE` is not atomic, so callers hold a Postgres advisory lock keyed
+    /// by the graph while they check and store; gateway and replication
+    /// booting together against an empty graph still agree on one seed.
+    pub fn load_or_init_world_seed(&mut self) -> Result<WorldSeed> {
+        let lock_key = format!("{}:world_seed", self.writer.graph_name());
+        self.client()
+            .execute(
+                "SELECT pg_advisory_lock(hashtextextended($1, 0))",
+                &[&lock_key],
+            )
+            .map_err(db_err("lock world seed"))?;
+        let seed = self.query_world_seed(
+            &world_seed_init_query(WorldSeed::fresh()),
+            "init world seed",
+        );
+        let unlocked = self
+            .client()
+            .execute(
+                "SELECT pg_advisory_unlock(hashtextextended($1, 0))",
+                &[&lock_key],
+            )
+            .map_err(db_err("unlock world seed"));
+        let seed = seed?;
+        unlocked?;
+        seed.ok_or_else(|| PersistenceError::database("world seed missing after init".to_string()))
+    }
+
+    /// Runs a Cypher query returning the seed property as its only column.
+    fn query_world_seed(
+        &mut self,
+        cypher: &str,
+        action: &'static str,
+    ) -> Result<Option<WorldSeed>> {
+        self.client()
+            .batch_execute("LOAD 'age'; SET search_path = ag_catalog, \"$user\", public;")
+            .map_err(db_err("prep age for world seed"))?;
+        let query = format!(
+            "SELECT seed::text AS seed FROM ag_catalog.cypher('{}', $$ {cypher} $$) AS (seed agtype);",
+            escape_cypher_string(self.writer.graph_name()),
+        );
+        let rows = self.client().query(&query, &[]).map_err(db_err(action))?;
+        self.client()
+            .batch_execute("SET search_path = public;")
+            .map_err(db_err("reset search_path after world seed"))?;
+        match rows
+            .first()
+            .and_then(|row| row.try_get::<_, Option<String>>("seed").ok().flatten())
+        {
+            Some(raw) => parse_world_seed(raw),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Renders graph writes (entity, component, item and edge MERGEs, and
+/// removals) as Cypher and sends them to a `CypherSink`. `GraphPersistence`
+/// drives one against its Postgres connection; tests can drive one against a
+/// `RecordingSink` to see the statements without a database.
+#[derive(Debug, Clone)]
+pub struct CypherWriter {
+    graph_name: String,
+    structured_inventory_min_entries: Option<usize>,
+    component_json_min_bytes: Option<usize>,
+    label_validator: LabelValidator,
+    edge_rules: Vec<EdgeRule>,
+}
+
+impl CypherWriter {
+    pub fn new(graph_name: impl Into<String>) -> Self {
+        Self {
+            graph_name: graph_name.into(),
+            structured_inventory_min_entries: None,
+            component_json_min_bytes: None,
+            label_validator: LabelValidator::default(),
+            edge_rules: default_edge_rules(),
+        }
+    }
+
+    /// See `GraphPersistence::with_structured_inventory`.
+    pub fn with_structured_inventory(mut self, min_entries: usize) -> Self {
+        self.structured_inventory_min_entries = Some(min_entries.max(1));
+        self
+    }
+
+    /// See `GraphPersistence::with_component_json_threshold`.
+    pub fn with_component_json_threshold(mut self, min_bytes: usize) -> Self {
+        self.component_json_min_bytes = Some(min_bytes);
+        self
+    }
+
+    pub fn with_label_validator(mut self, validator: LabelValidator) -> Self {
+        self.label_validator = validator;
+        self
+    }
+
+    /// See `GraphPersistence::set_edge_rules`.
+    pub fn set_edge_rules(&mut self, rules: Vec<EdgeRule>) {
+        self.edge_rules = rules;
+    }
+
+    pub fn graph_name(&self) -> &str {
+        &self.graph_name
+    }
+
+    /// Writes the whole batch in one transaction: on any error everything it
+    /// wrote is rolled back, so no entity is left half-persisted.
+    pub fn persist_world_delta(
+        &self,
+        sink: &mut dyn CypherSink,
+        updates: &[WorldDeltaEntity],
+        tick: u64,
+    ) -> Result<()> {
+        sink.batch_execute("BEGIN;", "begin world delta transaction")?;
+        match self.write_world_delta(sink, updates, tick) {
+            Ok(()) => sink.batch_execute("COMMIT;", "commit world delta transaction"),
+            Err(err) => {
+                let rolled_back = sink
+                    .batch_execute("ROLLBACK;", "roll back world delta transaction")
+                    .is_ok();
+                Err(err.rolled_back(rolled_back))
+            }
+        }
+    }
+
+    fn write_world_delta(
+        &self,
+        sink: &mut dyn CypherSink,
+        updates: &[WorldDeltaEntity],
+        tick: u64,
+    ) -> Result<()> {
+        let removed_entity_ids = updates
+            .iter()
+            .filter(|u| u.removed)
+            .map(|u| u.entity_id.clone())
+            .collect::<Vec<_>>();
+
+        let removed_components = updates
+            .iter()
+            .filter(|u| !u.removed && !u.removed_component_ids.is_empty())
+            .map(|u| (u.entity_id.clone(), u.removed_component_ids.clone()))
+            .collect::<Vec<_>>();
+
+        // Removal-only updates must not go through the full record path, which
+        // would treat their empty component list as "drop every component".
+        let records = updates
+            .iter()
+            .filter(|u| !u.removed && !u.is_component_removal())
+            .map(|u| GraphEntityRecord {
+                entity_id: u.entity_id.clone(),
+                labels: if u.labels.is_empty() {
+                    vec!["Entity".to_string()]
+                } else {
+                    u.labels.clone()
+                },
+                properties: u.properties.clone(),
+                components: u
+                    .components
+                    .iter()
+                    .map(|c| GraphComponentRecord {
+                        component_id: c.component_id.clone(),
+                        component_kind: c.component_kind.clone(),
+                        properties: c.properties.clone(),
+                    })
+                    .collect::<Vec<_>>(),
+            })
+            .collect::<Vec<_>>();
+
+        self.persist_graph_records(sink, &records, tick)?;
+        self.remove_graph_components(sink, &removed_components)?;
+        self.remove_graph_entities(sink, &removed_entity_ids)?;
+        Ok(())
+    }
+
+    pub fn persist_graph_records(
+        &self,
+        sink: &mut dyn CypherSink,
+        records: &[GraphEntityRecord],
+        tick: u64,
+    ) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+        // Checked up front so a rejected record fails the batch before any write.
+        for record in records {
+            self.label_validator
+                .check(&record.entity_id, &sanitize_labels(&record.labels))
+                .map_err(PersistenceError::InvalidLabels)?;
+        }
+        sink.batch_execute(
+            "LOAD 'age'; SET search_path = ag_catalog, \"$user\", public;",
+            "prep age for graph persist",
+        )?;
+
+        let created_at = now_epoch_s();
+        let mut inventory_containers = Vec::new();
+        for record in records {
+            self.run_cypher_with_params(
+                sink,
+                ENTITY_MERGE_QUERY,
+                &entity_merge_params(record, tick, created_at),
+            )?;
+
+            // Components are keyed by the normalized `entity_id:kind` id, so
+            // a caller passing a malformed id neither orphans the stored
+            // component nor stores a second copy of it.
+            let component_ids = record
+                .components
+                .iter()
+                .map(|c| component_id_for(&record.entity_id, &c.component_kind))
+                .collect::<Vec<_>>();
+            self.run_cypher_with_params(
+                sink,
+                "MATCH (e:Entity {entity_id: $entity_id}) \
+                 OPTIONAL MATCH (e)-[:HAS_COMPONENT]->(c:Component) \
+                 WHERE c IS NOT NULL AND NOT c.component_id IN $component_ids \
+                 DETACH DELETE c",
+                &serde_json::json!({
+                    "entity_id": record.entity_id,
+                    "component_ids": component_ids,
+                }),
+            )?;
+
+            if !record.components.is_empty() {
+                let mut components = record
+                    .components
+                    .iter()
+                    .zip(&component_ids)
+                    .collect::<Vec<_>>();
+                components.sort_by_key(|(_, component_id)| *component_id);
+                let components = components
+                    .into_iter()
+                    .map(|(component, component_id)| {
+                        serde_json::json!({
+                            "component_id": component_id,
+                            "properties": self.component_node_properties(
+                                component,
+                                component_id,
+                                tick,
+                            ),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                self.run_cypher_with_params(
+                    sink,
+                    COMPONENT_MERGE_QUERY,
+                    &serde_json::json!({
+                        "entity_id": record.entity_id,
+                        "components": components,
+                    }),
+                )?;
+            }
+
+            self.persist_relationship_edges(sink, record)?;
+            if let Some(min_entries) = self.structured_inventory_min_entries {
+                let item_keys = self.persist_inventory_items(sink, record, min_entries, tick)?;
+                inventory_containers.push(serde_json::json!({
+                    "entity_id": record.entity_id,
+                    "item_keys": item_keys,
+                }));
+            }
+        }
+        if !inventory_containers.is_empty() {
+            self.run_cypher_with_params(
+                sink,
+                STALE_ITEM_REMOVAL_QUERY,
+                &serde_json::json!({ "containers": inventory_containers }),
+            )?;
+        }
+
+        sink.batch_execute(
+            "SET search_path = public;",
+            "reset search_path after graph persist",
+        )?;
+
+        Ok(())
+    }
+
+    pub fn remove_graph_entities(
+        &self,
+        sink: &mut dyn CypherSink,
+        entity_ids: &[String],
+    ) -> Result<()> {
+        if entity_ids.is_empty() {
+            return Ok(());
+        }
+        sink.batch_execute(
+            "LOAD 'age'; SET search_path = ag_catalog, \"$user\", public;",
+            "prep age for graph remove",
+        )?;
+
+        for entity_id in entity_ids {
+            self.run_cypher_with_params(
+                sink,
+                "MATCH (e:Entity {entity_id: $entity_id}) \
+                 OPTIONAL MATCH (e)-[:HAS_COMPONENT]->(c:Component) \
+                 OPTIONAL MATCH (e)-[:HAS_ITEM]->(i:Item) \
+                 DETACH DELETE c, i, e",
+                &serde_json::json!({ "entity_id": entity_id }),
+            )?;
+        }
+
+        sink.batch_execute(
+            "SET search_path = public;",
+            "reset search_path after graph remove",
+        )?;
+        Ok(())
+    }
+
+    /// Deletes only the named components of each entity, leaving the entity and
+    /// its remaining components in place.
+    pub fn remove_graph_components(
+        &self,
+        sink: &mut dyn CypherSink,
+        removals: &[(String, Vec<String>)],
+    ) -> Result<()> {
+        if removals
+            .iter()
+            .all(|(_, component_ids)| component_ids.is_empty())
+        {
+            return Ok(());
+        }
+        sink.batch_execute(
+            "LOAD 'age'; SET search_path = ag_catalog, \"$user\", public;",
+            "prep age for component remove",
+        )?;
+
+        for (entity_id, component_ids) in removals {
+            if component_ids.is_empty() {
+                continue;
+            }
+            self.run_cypher_with_params(
+                sink,
+                COMPONENT_REMOVAL_QUERY,
+                &component_removal_params(entity_id, component_ids),
+            )?;
+        }
+
+        sink.batch_execute(
+            "SET search_path = public;",
+            "reset search_path after component remove",
+        )?;
+        Ok(())
+    }
+
+    /// Everything a component node stores, as one map for `SET c += ...`.
+    /// Payload keys come after the bookkeeping keys and win on a clash.
+    fn component_node_properties(
+        &self,
+        component: &GraphComponentRecord,
+        component_id: &str,
+        tick: u64,
+    ) -> JsonValue {
+        let mut properties = JsonMap::new();
+        properties.insert("last_tick".to_string(), tick.into());
+        properties.insert("component_id".to_string(), component_id.into());
+        properties.insert(
+            "component_kind".to_string(),
+            component.component_kind.clone().into(),
+        );
+        match component_json_payload(&component.properties, self.component_json_min_bytes) {
+            Some(raw) => {
+                properties.insert(PROPERTIES_JSON_KEY.to_string(), raw.into());
+            }
+            None => {
+                // A null value removes a `properties_json` left from an
+                // earlier, larger payload.
+                properties.insert(PROPERTIES_JSON_KEY.to_string(), JsonValue::Null);
+                if let JsonValue::Object(payload) = sanitize_property_keys(&component.properties) {
+                    properties.extend(payload);
+                }
+            }
+        }
+        JsonValue::Object(properties)
+    }
+
+    /// Writes the record's inventory entries as `:Item` nodes and returns their
+    /// keys. Items no longer in the inventory are dropped afterwards, by one
+    /// `STALE_ITEM_REMOVAL_QUERY` for the whole batch.
+    fn persist_inventory_items(
+        &self,
+        sink: &mut dyn CypherSink,
+        record: &GraphEntityRecord,
+        min_entries: usize,
+        tick: u64,
+    ) -> Result<Vec<String>> {
+        let items = record
+            .components
+            .iter()
+            .find(|c| c.component_kind == "inventory")
+            .map(inventory_items_from_component)
+            .unwrap_or_default();
+        let items = if items.len() >= min_entries {
+            items
+        } else {
+            Vec::new()
+        };
+
+        let incoming_item_keys = items
+            .iter()
+            .map(|item| item_key(&record.entity_id, item))
+            .collect::<Vec<_>>();
+        for (item, key) in items.iter().zip(&incoming_item_keys) {
+            let item_params = serde_json::json!({
+                "entity_id": record.entity_id,
+                "item_key": key,
+                "item_entity_id": item.item_entity_id,
+                "quantity": item.quantity,
+                "unit_mass_kg": item.unit_mass_kg,
+            });
+            self.run_cypher_with_params(
+                sink,
+                &format!(
+                    "MERGE (i:Item {{item_key: $item_key}}) SET i.last_tick={tick}, \
+                     i.item_entity_id=$item_entity_id, i.container_entity_id=$entity_id, \
+                     i.quantity=$quantity, i.unit_mass_kg=$unit_mass_kg"
+                ),
+                &item_params,
+            )?;
+            self.run_cypher_with_params(
+                sink,
+                "MATCH (e:Entity {entity_id: $entity_id}), (i:Item {item_key: $item_key}) \
+                 MERGE (e)-[:HAS_ITEM]->(i)",
+                &item_params,
+            )?;
+        }
+        Ok(incoming_item_keys)
+    }
+
+    fn persist_relationship_edges(
+        &self,
+        sink: &mut dyn CypherSink,
+        record: &GraphEntityRecord,
+    ) -> Result<()> {
+        // Clear this entity's rule-managed edges first, so a changed or
+        // removed property drops its old edge. Edges of other labels are left
+        // alone.
+        for (direction, pattern) in [
+            (
+                EdgeDirection::ToProperty,
+                "(e:Entity {entity_id: $entity_id})-[r]->(:Entity)",
+            ),
+            (
+                EdgeDirection::FromProperty,
+                "(:Entity)-[r]->(e:Entity {entity_id: $entity_id})",
+            ),
+        ] {
+            let mut edge_labels = self
+                .edge_rules
+                .iter()
+                .filter(|rule| rule.direction == direction)
+                .map(|rule| sanitize_identifier(&rule.edge_label))
+                .filter(|label| !label.is_empty())
+                .collect::<Vec<_>>();
+            edge_labels.sort();
+            edge_labels.dedup();
+            if edge_labels.is_empty() {
+                continue;
+            }
+            self.run_cypher_with_params(
+                sink,
+                &format!("MATCH {pattern} WHERE type(r) IN $edge_labels DELETE r"),
+                &serde_json::json!({
+                    "entity_id": record.entity_id,
+                    "edge_labels": edge_labels,
+                }),
+            )?;
+        }
+
+        for rule in &self.edge_rules {
+            if !rule.applies_to(&record.labels) {
+                continue;
+            }
+            let Some(other_id) = record
+                .properties
+                .get(&rule.property_key)
+                .and_then(JsonValue::as_str)
+            else {
+                continue;
+            };
+            let edge_label = sanitize_identifier(&rule.edge_label);
+            if edge_label.is_empty() {
+                continue;
+            }
+            let (from_id, to_id) = match rule.direction {
+                EdgeDirection::FromProperty => (other_id, record.entity_id.as_str()),
+                EdgeDirection::ToProperty => (record.entity_id.as_str(), other_id),
+            };
+            self.run_cypher_with_params(
+                sink,
+                &format!(
+                    "MATCH (f:Entity {{entity_id: $from_id}}), (t:Entity {{entity_id: $to_id}}) \
+                     MERGE (f)-[:{edge_label}]->(t)"
+                ),
+                &serde_json::json!({ "from_id": from_id, "to_id": to_id }),
+            )?;
+        }
+
+        Ok(())
     }
+
+    fn run_cypher_with_params(
+        &self,
+        sink: &mut dyn CypherSink,
+        cypher: &str,
+        params: &JsonValue,
+    ) -> Result<()> {
+        sink.execute_cypher_with_params(&self.graph_name, cypher, params)
+    }
+}
+
+/// Reads inventory entries from an `inventory` component payload, either bare
+/// (`{"entries": [...]}`) or wrapped in a reflect type-path envelope.
+pub fn inventory_items_from_component(component: &GraphComponentRecord) -> Vec<GraphItemRecord> {
+    let props = &component.properties;
+    let entries = props.get("entries").or_else(|| {
+        props
+            .as_object()
+            .filter(|obj| obj.len() == 1)
+            .and_then(|obj| obj.values().next())
+            .and_then(|inner| inner.get("entries"))
+    });
+    let Some(entries) = entries.and_then(JsonValue::as_array) else {
+        return Vec::new();
+    };
+    entries
+        .iter()
+        .filter_map(|entry| {
+            Some(GraphItemRecord {
+                item_entity_id: entry.get("item_entity_id")?.as_str()?.to_string(),
+                quantity: entry
+                    .get("quantity")
+                    .and_then(JsonValue::as_u64)
+                    .unwrap_or(0),
+                unit_mass_kg: entry
+                    .get("unit_mass_kg")
+                    .and_then(JsonValue::as_f64)
+                    .unwrap_or(0.0),
+            })
+        })
+        .collect()
+}
+
+/// Rows fetched per round trip by `stream_graph_records`.
+pub const STREAM_BATCH_ROWS: i32 = 500;
+
+/// Iterator returned by `GraphPersistence::stream_graph_records`. Yields
+/// each entity once all its component rows have been read; stops after the
+/// first error.
+pub struct GraphRecordStream<'a> {
+    transaction: Transaction<'a>,
+    portal: Portal,
+    rows: std::vec::IntoIter<Row>,
+    pending: Option<GraphEntityRecord>,
+    exhausted: bool,
+}
+
+impl Iterator for GraphRecordStream<'_> {
+    type Item = Result<GraphEntityRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some(row) = self.rows.next() else {
+                if self.exhausted {
+                    return self.pending.take().map(normalized);
+                }
+                match self
+                    .transaction
+                    .query_portal(&self.portal, STREAM_BATCH_ROWS)
+                {
+                    Ok(rows) => {
+                        self.exhausted = rows.len() < STREAM_BATCH_ROWS as usize;
+                        self.rows = rows.into_iter();
+                        continue;
+                    }
+                    Err(err) => {
+                        self.exhausted = true;
+                        self.pending = None;
+                        return Some(Err(db_err("stream graph records")(err)));
+                    }
+                }
+            };
+            let Some((entity, component)) = parse_record_row(&row) else {
+                continue;
+            };
+            // Rows arrive ordered by entity id, so a new id means the
+            // pending entity has all its components.
+            let finished = match &mut self.pending {
+                Some(pending) if pending.entity_id == entity.entity_id => None,
+                pending => pending.replace(entity),
+            };
+            if let (Some(component), Some(pending)) = (component, self.pending.as_mut()) {
+                add_component(pending, component);
+            }
+            if finished.is_some() {
+                return finished.map(normalized);
+            }
+        }
+    }
+}
+
+/// SQL wrapping the Cypher that returns one row per entity and component
+/// (one row with null component columns for an entity without components).
+/// `filter` is a Cypher `WHERE` clause on `e`; `order_by` follows `RETURN`.
+fn records_query(graph_name: &str, filter: &str, with_params: bool, order_by: &str) -> String {
+    let params_arg = if with_params { ", $1" } else { "" };
+    format!(
+        "SELECT entity_id::text AS entity_id, labels::text AS labels, props::text AS props, component_id::text AS component_id, component_kind::text AS component_kind, component_props::text AS component_props \
+         FROM ag_catalog.cypher('{}', $$ \
+            MATCH (e:Entity) {filter} \
+            OPTIONAL MATCH (e)-[:HAS_COMPONENT]->(c:Component) \
+            RETURN e.entity_id, labels(e), properties(e), c.component_id, c.component_kind, properties(c) {order_by} \
+         $${params_arg}) AS (entity_id agtype, labels agtype, props agtype, component_id agtype, component_kind agtype, component_props agtype);",
+        escape_cypher_string(graph_name)
+    )
+}
+
+/// One row of `records_query`: the entity (without components) and the
+/// component on that row, if any.
+fn parse_record_row(row: &Row) -> Option<(GraphEntityRecord, Option<GraphComponentRecord>)> {
+    let entity_id = parse_agtype_string(row.get::<_, String>("entity_id"))?;
+    let mut labels = parse_agtype_json(row.get::<_, String>("labels"))
+        .and_then(|v| serde_json::from_value::<Vec<String>>(v).ok())
+        .unwrap_or_else(|| vec!["Entity".to_string()]);
+    let properties = parse_agtype_json(row.get::<_, String>("props"))
+        .unwrap_or(JsonValue::Object(JsonMap::new()));
+    if let Some(extra_labels) = properties.get("sidereal_labels").and_then(|v| v.as_array()) {
+        labels.extend(
+            extra_labels
+                .iter()
+                .filter_map(|v| v.as_str().map(ToString::to_string)),
+        );
+    }
+    let entity = GraphEntityRecord {
+        entity_id,
+        labels,
+        properties,
+        components: Vec::new(),
+    };
+
+    let component_id = row
+        .try_get::<_, Option<String>>("component_id")
+        .ok()
+        .flatten()
+        .and_then(parse_agtype_string);
+    let component_kind = row
+        .try_get::<_, Option<String>>("component_kind")
+        .ok()
+        .flatten()
+        .and_then(parse_agtype_string);
+    let component = match (component_id, component_kind) {
+        (Some(component_id), Some(component_kind)) => {
+            let properties = row
+                .try_get::<_, Option<String>>("component_props")
+                .ok()
+                .flatten()
+                .and_then(parse_agtype_json)
+                .map(expand_component_properties)
+                .unwrap_or(JsonValue::Object(JsonMap::new()));
+            Some(GraphComponentRecord {
+                component_id,
+                component_kind,
+                properties,
+            })
+        }
+        _ => None,
+    };
+    Some((entity, component))
+}
+
+fn normalized(mut record: GraphEntityRecord) -> Result<GraphEntityRecord> {
+    record.normalize();
+    Ok(record)
+}
+
+fn add_component(record: &mut GraphEntityRecord, component: GraphComponentRecord) {
+    if !record
+        .components
+        .iter()
+        .any(|c| c.component_id == component.component_id)
+    {
+        record.components.push(component);
+    }
+}
+
+/// Serialized component payload when it should be stored as one JSON string.
+fn component_json_payload(properties: &JsonValue, min_bytes: Option<usize>) -> Option<String> {
+    let min_bytes = min_bytes?;
+    let raw = serde_json::to_string(properties).ok()?;
+    (raw.len() >= min_bytes).then_some(raw)
+}
+
+/// Restores a component stored as `properties_json`; per-key components are
+/// returned unchanged.
+fn expand_component_properties(properties: JsonValue) -> JsonValue {
+    properties
+        .get(PROPERTIES_JSON_KEY)
+        .and_then(JsonValue::as_str)
+        .and_then(|raw| serde_json::from_str::<JsonValue>(raw).ok())
+        .unwrap_or(properties)
+}
+
+/// `WHERE` clause for `load_graph_records_filtered`; empty when unfiltered.
+fn records_filter(labels: &[&str], since_tick: Option<u64>) -> String {
+    let mut conditions = Vec::new();
+    if !labels.is_empty() {
+        // Labels are stored sanitized; one that sanitizes to nothing matches
+        // no entity rather than widening the filter.
+        let any_label = labels
+            .iter()
+            .map(|label| sanitize_labels(&[label.to_string()]).pop())
+            .map(|label| match label {
+                Some(label) => format!("'{label}' IN e.sidereal_labels"),
+                None => "false".to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(" OR ");
+        conditions.push(format!("({any_label})"));
+    }
+    if let Some(tick) = since_tick {
+        conditions.push(format!("e.last_tick >= {tick}"));
+    }
+    if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    }
+}
+
+fn item_key(container_entity_id: &str, item: &GraphItemRecord) -> String {
+    format!("{container_entity_id}:item:{}", item.item_entity_id)
+}
+
+/// MERGEs an entity node and sets its properties. `created_at_epoch_s` is set
+/// once on create; AGE lacks `ON CREATE SET`, hence the `coalesce`.
+const ENTITY_MERGE_QUERY: &str = "MERGE (e:Entity {entity_id: $entity_id}) \
+     SET e += $properties, e.last_tick = $tick, e.sidereal_labels = $labels, \
+     e.created_at_epoch_s = coalesce(e.created_at_epoch_s, $created_at)";
+
+/// Parameters for `ENTITY_MERGE_QUERY`. Property keys are sanitized like
+/// labels; `entity_id` (the MERGE key) and the creation time are dropped so a
+/// caller echoing them back cannot overwrite them.
+fn entity_merge_params(record: &GraphEntityRecord, tick: u64, created_at: u64) -> JsonValue {
+    let mut labels = sanitize_labels(&record.labels);
+    labels.sort();
+    labels.dedup();
+    let mut properties = match sanitize_property_keys(&record.properties) {
+        JsonValue::Object(map) => map,
+        _ => JsonMap::new(),
+    };
+    properties.remove("entity_id");
+    properties.remove(CREATED_AT_KEY);
+    serde_json::json!({
+        "entity_id": record.entity_id,
+        "properties": properties,
+        "tick": tick,
+        "labels": labels,
+        "created_at": created_at,
+    })
+}
+
+/// MERGEs all of an entity's components and their `HAS_COMPONENT` edges in
+/// one round trip.
+const COMPONENT_MERGE_QUERY: &str = "MATCH (e:Entity {entity_id: $entity_id}) \
+     UNWIND $components AS component \
+     MERGE (c:Component {component_id: component.component_id}) \
+     SET c += component.properties \
+     MERGE (e)-[:HAS_COMPONENT]->(c)";
+
+const COMPONENT_REMOVAL_QUERY: &str = "MATCH (e:Entity {entity_id: $entity_id})-[:HAS_COMPONENT]->(c:Component) \
+     WHERE c.component_id IN $component_ids \
+     DETACH DELETE c";
+
+/// Drops every item whose key is no longer in its container's inventory, for
+/// all containers of a batch at once.
+const STALE_ITEM_REMOVAL_QUERY: &str = "UNWIND $containers AS container \
+     MATCH (e:Entity {entity_id: container.entity_id})-[:HAS_ITEM]->(i:Item) \
+     WHERE NOT i.item_key IN container.item_keys \
+     DETACH DELETE i";
+
+fn component_removal_params(entity_id: &str, component_ids: &[String]) -> JsonValue {
+    serde_json::json!({ "entity_id": entity_id, "component_ids": component_ids })
+}
+
+/// Keeps an existing seed; AGE lacks `ON CREATE SET`, hence the `coalesce`.
+fn world_seed_init_query(candidate: WorldSeed) -> String {
+    format!(
+        "MERGE (w:WorldConfig {{config_id:'world'}}) \
+         SET w.{WORLD_SEED_KEY}=coalesce(w.{WORLD_SEED_KEY}, '{}') \
+         RETURN w.{WORLD_SEED_KEY}",
+        candidate.0
+    )
+}
+
+/// `None` for a missing (null) seed; an error for one that is not a `u64`.
+fn parse_world_seed(raw: String) -> Result<Option<WorldSeed>> {
+    let Some(seed) = parse_agtype_string(raw) else {
+        return Ok(None);
+    };
+    seed.parse()
+        .map(|seed| Some(WorldSeed(seed)))
+        .map_err(|_| PersistenceError::Serialization(format!("invalid world seed {seed:?}")))
 }
 
 fn parse_agtype_string(raw: String) -> Option<String> {
@@ -524,10 +1753,6 @@ fn strip_trailing_agtype_suffix(raw: &str) -> &str {
     }
 }
 
-fn escape_cypher_string(value: &str) -> String {
-    value.replace('\\', "\\\\").replace('\'', "\\'")
-}
-
 fn now_epoch_s() -> u64 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -536,20 +1761,532 @@ fn now_epoch_s() -> u64 {
 }
 
 fn db_err(action: &'static str) -> impl Fn(postgres::Error) -> PersistenceError {
-    move |err| PersistenceError::Database(format!("{action} failed: {err}"))
+    move |err| PersistenceError::database(format!("{action} failed: {err}"))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use labels::LabelValidation;
+    use sidereal_net::WorldComponentDelta;
+    use sink::{RecordedStatement, RecordingSink};
+
+    fn sample_ship_record() -> GraphEntityRecord {
+        GraphEntityRecord {
+            entity_id: "ship:1".to_string(),
+            labels: vec!["Entity".to_string(), "Ship".to_string()],
+            properties: serde_json::json!({"name": "Kestrel"}),
+            components: vec![GraphComponentRecord {
+                component_id: "ship:1:health_pool".to_string(),
+                component_kind: "health_pool".to_string(),
+                properties: serde_json::json!({"current": 640}),
+            }],
+        }
+    }
+
+    #[test]
+    fn dry_run_records_entity_and_component_merges() {
+        let writer = CypherWriter::new("dry_run");
+        let mut sink = RecordingSink::new();
+        writer
+            .persist_graph_records(&mut sink, &[sample_ship_record()], 7)
+            .expect("recording sink never fails");
+
+        assert_eq!(
+            sink.statements.first(),
+            Some(&RecordedStatement::Sql(
+                "LOAD 'age'; SET search_path = ag_catalog, \"$user\", public;".to_string()
+            ))
+        );
+        assert_eq!(
+            sink.statements.last(),
+            Some(&RecordedStatement::Sql(
+                "SET search_path = public;".to_string()
+            ))
+        );
+        assert!(sink.statements.iter().all(|statement| match statement {
+            RecordedStatement::Cypher { graph_name, .. } => graph_name == "dry_run",
+            RecordedStatement::Sql(_) => true,
+        }));
+
+        let cypher = sink.cypher();
+        let params = sink.cypher_params();
+        assert_eq!(cypher.len(), 5);
+        assert_eq!(cypher[0], ENTITY_MERGE_QUERY);
+        assert_eq!(params[0]["entity_id"], "ship:1");
+        assert_eq!(
+            params[0]["properties"],
+            serde_json::json!({"name": "Kestrel"})
+        );
+        assert_eq!(params[0]["tick"], 7);
+        assert_eq!(params[0]["labels"], serde_json::json!(["Entity", "Ship"]));
+        assert!(params[0]["created_at"].is_u64());
+        assert_eq!(
+            cypher[1],
+            "MATCH (e:Entity {entity_id: $entity_id}) \
+             OPTIONAL MATCH (e)-[:HAS_COMPONENT]->(c:Component) \
+             WHERE c IS NOT NULL AND NOT c.component_id IN $component_ids \
+             DETACH DELETE c"
+        );
+        assert_eq!(
+            params[1],
+            &serde_json::json!({
+                "entity_id": "ship:1",
+                "component_ids": ["ship:1:health_pool"],
+            })
+        );
+        assert_eq!(cypher[2], COMPONENT_MERGE_QUERY);
+        assert_eq!(
+            params[2],
+            &serde_json::json!({
+                "entity_id": "ship:1",
+                "components": [{
+                    "component_id": "ship:1:health_pool",
+                    "properties": {
+                        "last_tick": 7,
+                        "component_id": "ship:1:health_pool",
+                        "component_kind": "health_pool",
+                        "properties_json": null,
+                        "current": 640,
+                    },
+                }],
+            })
+        );
+        assert_eq!(
+            cypher[3],
+            "MATCH (e:Entity {entity_id: $entity_id})-[r]->(:Entity) \
+             WHERE type(r) IN $edge_labels DELETE r"
+        );
+        assert_eq!(
+            params[3],
+            &serde_json::json!({"entity_id": "ship:1", "edge_labels": ["MOUNTED_ON"]})
+        );
+        assert_eq!(
+            cypher[4],
+            "MATCH (:Entity)-[r]->(e:Entity {entity_id: $entity_id}) \
+             WHERE type(r) IN $edge_labels DELETE r"
+        );
+        assert_eq!(
+            params[4],
+            &serde_json::json!({
+                "entity_id": "ship:1",
+                "edge_labels": ["HAS_CHILD", "HAS_HARDPOINT"],
+            })
+        );
+    }
 
     #[test]
-    fn cypher_literal_renders_nested_maps_and_arrays() {
-        let value = serde_json::json!({"a": 1, "b": [true, "x"], "c": {"k": "v"}});
-        let out = cypher_literal(&value);
-        assert!(out.contains("a:1"));
-        assert!(out.contains("b:[true,'x']"));
-        assert!(out.contains("c:{k:'v'}"));
+    fn entity_properties_are_bound_not_rendered() {
+        let mut record = sample_ship_record();
+        record.entity_id = "ship:'$$\\".to_string();
+        record.properties = serde_json::json!({
+            "name": "it's $$ done",
+            "max-hp": 10,
+            "entity_id": "ship:spoofed",
+            CREATED_AT_KEY: 1,
+        });
+        let params = entity_merge_params(&record, 3, 900);
+        assert_eq!(params["entity_id"], "ship:'$$\\");
+        assert_eq!(
+            params["properties"],
+            serde_json::json!({"name": "it's $$ done", "maxhp": 10})
+        );
+        assert_eq!(params["created_at"], 900);
+    }
+
+    #[test]
+    fn all_components_of_an_entity_merge_in_one_round_trip() {
+        let mut record = sample_ship_record();
+        record.components = (0..10)
+            .map(|index| GraphComponentRecord {
+                component_id: format!("ship:1:part_{index}"),
+                component_kind: format!("part_{index}"),
+                properties: serde_json::json!({"index": index}),
+            })
+            .collect();
+        let mut sink = RecordingSink::new();
+        CypherWriter::new("dry_run")
+            .persist_graph_records(&mut sink, &[record], 7)
+            .expect("recording sink never fails");
+
+        // Entity MERGE, orphan cleanup, one component UNWIND and the two
+        // edge-rule cleanups; previously 2 more per component.
+        assert_eq!(sink.cypher().len(), 5);
+        let params = sink.cypher_params();
+        let components = params[2]["components"]
+            .as_array()
+            .expect("components are bound as a list");
+        assert_eq!(components.len(), 10);
+        assert_eq!(components[9]["component_id"], "ship:1:part_9");
+        assert_eq!(components[9]["properties"]["index"], 9);
+    }
+
+    #[test]
+    fn persist_writes_labels_and_components_in_normalized_order() {
+        let mut record = sample_ship_record();
+        record.labels = vec!["Ship".to_string(), "Entity".to_string(), "Ship".to_string()];
+        record.components = ["weapon", "engine", "armor"]
+            .into_iter()
+            .map(|kind| GraphComponentRecord {
+                component_id: format!("ship:1:{kind}"),
+                component_kind: kind.to_string(),
+                properties: serde_json::json!({}),
+            })
+            .collect();
+        let mut sink = RecordingSink::new();
+        CypherWriter::new("dry_run")
+            .persist_graph_records(&mut sink, std::slice::from_ref(&record), 7)
+            .expect("recording sink never fails");
+
+        let params = sink.cypher_params();
+        assert_eq!(params[0]["labels"], serde_json::json!(["Entity", "Ship"]));
+        let merged = params[2]["components"]
+            .as_array()
+            .expect("components are bound as a list")
+            .iter()
+            .map(|component| component["component_id"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(merged, ["ship:1:armor", "ship:1:engine", "ship:1:weapon"]);
+
+        record.normalize();
+        assert_eq!(record.labels, ["Entity", "Ship"]);
+        assert_eq!(record.components[0].component_kind, "armor");
+    }
+
+    #[test]
+    fn ids_are_bound_as_parameters_and_never_reach_the_query_text() {
+        let hostile = "ship:o'brien$$\\x";
+        let mut record = sample_ship_record();
+        record.entity_id = hostile.to_string();
+        record.components[0].component_id = format!("{hostile}:health_pool");
+        let writer = CypherWriter::new("dry_run");
+        let mut sink = RecordingSink::new();
+        writer
+            .persist_graph_records(&mut sink, &[record], 7)
+            .expect("recording sink never fails");
+        writer
+            .remove_graph_components(
+                &mut sink,
+                &[(hostile.to_string(), vec![format!("{hostile}:health_pool")])],
+            )
+            .expect("recording sink never fails");
+        writer
+            .remove_graph_entities(&mut sink, &[hostile.to_string()])
+            .expect("recording sink never fails");
+
+        assert!(sink.cypher().iter().all(|query| !query.contains("brien")));
+        let params = sink.cypher_params();
+        assert_eq!(params.len(), 7);
+        assert!(params.iter().all(|params| params["entity_id"] == hostile));
+    }
+
+    #[test]
+    fn malformed_component_ids_are_normalized_before_orphan_deletion() {
+        let writer = CypherWriter::new("dry_run");
+        let mut malformed = sample_ship_record();
+        malformed.components[0].component_id = "health_pool:ship:1".to_string();
+        let mut well_formed_sink = RecordingSink::new();
+        let mut malformed_sink = RecordingSink::new();
+        writer
+            .persist_graph_records(&mut well_formed_sink, &[sample_ship_record()], 7)
+            .expect("recording sink never fails");
+        writer
+            .persist_graph_records(&mut malformed_sink, &[malformed], 7)
+            .expect("recording sink never fails");
+
+        // Same statements either way, so the stored component is kept rather
+        // than deleted as an orphan and replaced by a differently keyed copy.
+        let params = malformed_sink.cypher_params();
+        assert_eq!(malformed_sink.cypher()[1..], well_formed_sink.cypher()[1..]);
+        assert_eq!(params, well_formed_sink.cypher_params());
+        assert_eq!(
+            params[1]["component_ids"],
+            serde_json::json!(["ship:1:health_pool"])
+        );
+        assert!(
+            params
+                .iter()
+                .all(|params| !params.to_string().contains("health_pool:ship:1"))
+        );
+    }
+
+    /// Fails every component MERGE, as a database error would.
+    struct FailingComponentSink(RecordingSink);
+
+    impl CypherSink for FailingComponentSink {
+        fn batch_execute(&mut self, sql: &str, action: &'static str) -> Result<()> {
+            self.0.batch_execute(sql, action)
+        }
+
+        fn execute_cypher(&mut self, graph_name: &str, cypher: &str) -> Result<()> {
+            self.execute_cypher_with_params(graph_name, cypher, &JsonValue::Null)
+        }
+
+        fn execute_cypher_with_params(
+            &mut self,
+            graph_name: &str,
+            cypher: &str,
+            params: &JsonValue,
+        ) -> Result<()> {
+            if cypher == COMPONENT_MERGE_QUERY {
+                return Err(PersistenceError::database("injected failure"));
+            }
+            self.0
+                .execute_cypher_with_params(graph_name, cypher, params)
+        }
+    }
+
+    fn sample_delta() -> Vec<WorldDeltaEntity> {
+        vec![WorldDeltaEntity {
+            entity_id: "ship:1".to_string(),
+            labels: vec!["Entity".to_string(), "Ship".to_string()],
+            properties: serde_json::json!({"name": "Kestrel"}),
+            components: vec![WorldComponentDelta {
+                component_id: "ship:1:health_pool".to_string(),
+                component_kind: "health_pool".to_string(),
+                properties: serde_json::json!({"current": 640}),
+            }],
+            removed: false,
+            removed_component_ids: Vec::new(),
+            removal_reason: None,
+        }]
+    }
+
+    #[test]
+    fn world_delta_commits_as_one_transaction() {
+        let mut sink = RecordingSink::new();
+        CypherWriter::new("dry_run")
+            .persist_world_delta(&mut sink, &sample_delta(), 7)
+            .expect("recording sink never fails");
+        assert_eq!(
+            sink.statements.first(),
+            Some(&RecordedStatement::Sql("BEGIN;".to_string()))
+        );
+        assert_eq!(
+            sink.statements.last(),
+            Some(&RecordedStatement::Sql("COMMIT;".to_string()))
+        );
+        assert_eq!(sink.cypher().len(), 5);
+    }
+
+    #[test]
+    fn failed_world_delta_rolls_back_and_says_so() {
+        let mut sink = FailingComponentSink(RecordingSink::new());
+        let err = CypherWriter::new("dry_run")
+            .persist_world_delta(&mut sink, &sample_delta(), 7)
+            .unwrap_err();
+        assert!(matches!(
+            &err,
+            PersistenceError::Database {
+                rolled_back: true,
+                ..
+            }
+        ));
+        assert!(err.to_string().ends_with("(batch rolled back)"));
+        let statements = &sink.0.statements;
+        assert_eq!(
+            statements.last(),
+            Some(&RecordedStatement::Sql("ROLLBACK;".to_string()))
+        );
+        assert!(!statements.contains(&RecordedStatement::Sql("COMMIT;".to_string())));
+    }
+
+    #[test]
+    fn dry_run_rejected_batch_records_nothing() {
+        let writer = CypherWriter::new("dry_run")
+            .with_label_validator(LabelValidator::new(LabelValidation::Reject));
+        let mut record = sample_ship_record();
+        record.labels.push("Shp".to_string());
+        let mut sink = RecordingSink::new();
+        let err = writer
+            .persist_graph_records(&mut sink, &[sample_ship_record(), record], 7)
+            .unwrap_err();
+        assert!(matches!(err, PersistenceError::InvalidLabels(_)));
+        assert!(sink.statements.is_empty());
+    }
+
+    #[test]
+    fn records_filter_combines_labels_and_tick() {
+        assert_eq!(records_filter(&[], None), "");
+        assert_eq!(records_filter(&[], Some(12)), "WHERE e.last_tick >= 12");
+        assert_eq!(
+            records_filter(&["Ship", "Hard'point"], Some(12)),
+            "WHERE ('Ship' IN e.sidereal_labels OR 'Hardpoint' IN e.sidereal_labels) \
+             AND e.last_tick >= 12"
+        );
+        assert_eq!(records_filter(&["'"], None), "WHERE (false)");
+    }
+
+    #[test]
+    fn custom_edge_rules_add_to_the_default_relationships() {
+        let mut writer = CypherWriter::new("dry_run");
+        let mut rules = edges::default_edge_rules();
+        rules.push(edges::EdgeRule::new(
+            "docked_to_entity_id",
+            "DOCKED_AT",
+            EdgeDirection::ToProperty,
+        ));
+        writer.set_edge_rules(rules);
+        let mut record = sample_ship_record();
+        record.properties = serde_json::json!({
+            "parent_entity_id": "fleet:1",
+            "owner_entity_id": "player:1",
+            "docked_to_entity_id": "station:1",
+        });
+        let mut sink = RecordingSink::new();
+        writer
+            .persist_graph_records(&mut sink, &[record], 7)
+            .expect("recording sink never fails");
+
+        let edges = sink
+            .cypher()
+            .into_iter()
+            .zip(sink.cypher_params())
+            .filter(|(query, _)| query.contains("$from_id"))
+            .map(|(query, params)| {
+                (
+                    query.rsplit("-[:").next().unwrap().to_string(),
+                    params.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            edges,
+            vec![
+                (
+                    "HAS_CHILD]->(t)".to_string(),
+                    serde_json::json!({"from_id": "fleet:1", "to_id": "ship:1"}),
+                ),
+                (
+                    "DOCKED_AT]->(t)".to_string(),
+                    serde_json::json!({"from_id": "ship:1", "to_id": "station:1"}),
+                ),
+            ],
+            "HAS_HARDPOINT only applies to hardpoints"
+        );
+    }
+
+    #[test]
+    fn component_removal_query_targets_only_named_components() {
+        let params = component_removal_params(
+            "ship:1",
+            &[
+                "ship:1:flight_computer".to_string(),
+                "ship:1:o'brien".to_string(),
+            ],
+        );
+        assert_eq!(params["entity_id"], "ship:1");
+        assert_eq!(
+            params["component_ids"],
+            serde_json::json!(["ship:1:flight_computer", "ship:1:o'brien"])
+        );
+        assert!(COMPONENT_REMOVAL_QUERY.contains("c.component_id IN $component_ids"));
+        assert!(!COMPONENT_REMOVAL_QUERY.contains("NOT c.component_id"));
+    }
+
+    #[test]
+    fn inventory_items_read_bare_and_wrapped_payloads() {
+        let entries = serde_json::json!({"entries": [
+            {"item_entity_id": "11111111-1111-1111-1111-111111111111", "quantity": 3, "unit_mass_kg": 2.5},
+            {"quantity": 1},
+        ]});
+        let bare = GraphComponentRecord {
+            component_id: "ship:1:inventory".to_string(),
+            component_kind: "inventory".to_string(),
+            properties: entries.clone(),
+        };
+        let wrapped = GraphComponentRecord {
+            properties: encode_reflect_component("sidereal_game::Inventory", entries),
+            ..bare.clone()
+        };
+
+        let expected = vec![GraphItemRecord {
+            item_entity_id: "11111111-1111-1111-1111-111111111111".to_string(),
+            quantity: 3,
+            unit_mass_kg: 2.5,
+        }];
+        assert_eq!(inventory_items_from_component(&bare), expected);
+        assert_eq!(inventory_items_from_component(&wrapped), expected);
+    }
+
+    #[test]
+    fn stale_items_are_dropped_once_per_batch() {
+        let records = ["ship:1", "ship:2"]
+            .into_iter()
+            .map(|entity_id| GraphEntityRecord {
+                entity_id: entity_id.to_string(),
+                components: vec![GraphComponentRecord {
+                    component_id: format!("{entity_id}:inventory"),
+                    component_kind: "inventory".to_string(),
+                    properties: serde_json::json!({"entries": [
+                        {"item_entity_id": "11111111-1111-1111-1111-111111111111", "quantity": 3},
+                    ]}),
+                }],
+                ..sample_ship_record()
+            })
+            .collect::<Vec<_>>();
+        let mut sink = RecordingSink::new();
+        CypherWriter::new("dry_run")
+            .with_structured_inventory(1)
+            .persist_graph_records(&mut sink, &records, 7)
+            .expect("recording sink never fails");
+
+        let cypher = sink.cypher();
+        let removals = cypher
+            .iter()
+            .filter(|statement| statement.contains("DETACH DELETE i"))
+            .count();
+        assert_eq!(removals, 1);
+        assert_eq!(*cypher.last().unwrap(), STALE_ITEM_REMOVAL_QUERY);
+        let containers = sink.cypher_params().last().unwrap()["containers"].clone();
+        assert_eq!(containers[1]["entity_id"], "ship:2");
+        assert_eq!(containers[1]["item_keys"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn component_json_payload_respects_threshold() {
+        let props = serde_json::json!({"a": {"b": [1, 2, 3]}});
+        assert_eq!(component_json_payload(&props, None), None);
+        assert_eq!(component_json_payload(&props, Some(1_000)), None);
+        assert_eq!(
+            component_json_payload(&props, Some(1)).as_deref(),
+            Some(r#"{"a":{"b":[1,2,3]}}"#)
+        );
+    }
+
+    #[test]
+    fn expand_component_properties_prefers_json_payload() {
+        let stored = serde_json::json!({
+            "component_id": "ship:1:loadout",
+            "last_tick": 4,
+            "stale_key": 1,
+            "properties_json": r#"{"sidereal_game::Loadout":{"racks":[{"slots":[{"id":"a"}]}]}}"#,
+        });
+        assert_eq!(
+            expand_component_properties(stored),
+            serde_json::json!({"sidereal_game::Loadout": {"racks": [{"slots": [{"id": "a"}]}]}})
+        );
+
+        let per_key = serde_json::json!({"hp": 10.0});
+        assert_eq!(expand_component_properties(per_key.clone()), per_key);
+    }
+
+    #[test]
+    fn record_age_comes_from_created_at_property() {
+        let mut record = GraphEntityRecord {
+            entity_id: "ship:a".to_string(),
+            labels: vec!["Entity".to_string()],
+            properties: serde_json::json!({}),
+            components: Vec::new(),
+        };
+        assert_eq!(record.created_at_epoch_s(), None);
+        assert_eq!(record.age_s(1_000), None);
+
+        record.properties = serde_json::json!({ CREATED_AT_KEY: 900 });
+        assert_eq!(record.created_at_epoch_s(), Some(900));
+        assert_eq!(record.age_s(1_000), Some(100));
+        // Clock skew never yields a negative age.
+        assert_eq!(record.age_s(800), Some(0));
     }
 
     #[test]
@@ -560,6 +2297,25 @@ mod tests {
         assert_eq!(json["x"], 1);
     }
 
+    #[test]
+    fn world_seed_init_keeps_an_existing_seed_and_parses_back() {
+        assert_eq!(
+            world_seed_init_query(WorldSeed(42)),
+            "MERGE (w:WorldConfig {config_id:'world'}) \
+             SET w.world_seed=coalesce(w.world_seed, '42') \
+             RETURN w.world_seed"
+        );
+        assert_eq!(
+            parse_world_seed(format!("\"{}\"::agtype", u64::MAX)).ok(),
+            Some(Some(WorldSeed(u64::MAX)))
+        );
+        assert_eq!(parse_world_seed("null".to_string()).ok(), Some(None));
+        assert!(matches!(
+            parse_world_seed("\"not a seed\"".to_string()),
+            Err(PersistenceError::Serialization(_))
+        ));
+    }
+
     #[test]
     fn reflect_envelope_roundtrip() {
         let payload = serde_json::json!({"fuel_kg": 42.0});
@@ -0,0 +1,134 @@
+//! Pooled graph sessions.
+//!
+//! `GraphPersistence::connect` opens a new Postgres connection per call. A
+//! `GraphPersistencePool` keeps up to `max_size` connections open and hands
+//! them out as `PooledGraphPersistence` handles, which have the same methods
+//! and return their connection to the pool when dropped.
+
+use std::time::Duration;
+
+use postgres::{Client, NoTls};
+use r2d2::{Pool, PooledConnection};
+use r2d2_postgres::PostgresConnectionManager;
+
+use crate::{
+    CypherWriter, DEFAULT_GRAPH_NAME, GraphPersistence, PersistenceError, PgSession, Result, db_err,
+};
+
+type Manager = PostgresConnectionManager<NoTls>;
+
+/// A pooled connection; back in the pool once the handle is dropped.
+pub type PooledGraphPersistence = GraphPersistence<PooledConnection<Manager>>;
+
+impl PgSession for PooledConnection<Manager> {
+    fn client(&mut self) -> &mut Client {
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphPersistencePoolBuilder {
+    graph_name: String,
+    max_size: u32,
+    connection_timeout: Duration,
+}
+
+impl Default for GraphPersistencePoolBuilder {
+    fn default() -> Self {
+        Self {
+            graph_name: DEFAULT_GRAPH_NAME.to_string(),
+            max_size: 8,
+            connection_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl GraphPersistencePoolBuilder {
+    pub fn graph_name(mut self, graph_name: impl Into<String>) -> Self {
+        self.graph_name = graph_name.into();
+        self
+    }
+
+    /// Most connections open at once (default 8). `get` waits for a free one
+    /// when all are checked out.
+    pub fn max_size(mut self, max_size: u32) -> Self {
+        self.max_size = max_size.max(1);
+        self
+    }
+
+    /// How long `get` waits for a connection before failing (default 10s).
+    pub fn connection_timeout(mut self, connection_timeout: Duration) -> Self {
+        self.connection_timeout = connection_timeout;
+        self
+    }
+
+    /// Opens the pool and runs `ensure_schema` once, so handles from `get`
+    /// are ready to use. A server without the `age` extension is reported as
+    /// such rather than as a failed `CREATE EXTENSION`.
+    pub fn build(self, database_url: &str) -> Result<GraphPersistencePool> {
+        let config = database_url
+            .parse()
+            .map_err(|err| PersistenceError::database(format!("invalid database url: {err}")))?;
+        let pool = Pool::builder()
+            .max_size(self.max_size)
+            .min_idle(Some(0))
+            .connection_timeout(self.connection_timeout)
+            .build(PostgresConnectionManager::new(config, NoTls))
+            .map_err(|err| PersistenceError::database(format!("postgres pool failed: {err}")))?;
+        let pool = GraphPersistencePool {
+            pool,
+            writer: CypherWriter::new(self.graph_name),
+        };
+        // Straight from r2d2: `get` loads AGE, which is what is checked here.
+        let mut first = GraphPersistence {
+            session: pool.pool.get().map_err(|err| {
+                PersistenceError::database(format!("postgres pool checkout failed: {err}"))
+            })?,
+            writer: pool.writer.clone(),
+        };
+        if !first.age_available()? {
+            return Err(PersistenceError::database(
+                "age extension is not installed on the database server",
+            ));
+        }
+        first.ensure_schema()?;
+        Ok(pool)
+    }
+}
+
+/// Shared, thread-safe source of `GraphPersistence` sessions. Cloning shares
+/// the same connections.
+#[derive(Clone)]
+pub struct GraphPersistencePool {
+    pool: Pool<Manager>,
+    writer: CypherWriter,
+}
+
+impl GraphPersistencePool {
+    pub fn builder() -> GraphPersistencePoolBuilder {
+        GraphPersistencePoolBuilder::default()
+    }
+
+    /// Checks out a connection with AGE loaded and the AGE search_path set.
+    pub fn get(&self) -> Result<PooledGraphPersistence> {
+        let mut client = self.pool.get().map_err(|err| {
+            PersistenceError::database(format!("postgres pool checkout failed: {err}"))
+        })?;
+        client
+            .batch_execute("LOAD 'age'; SET search_path = ag_catalog, \"$user\", public;")
+            .map_err(db_err("prep age on checkout"))?;
+        Ok(GraphPersistence {
+            session: client,
+            writer: self.writer.clone(),
+        })
+    }
+
+    pub fn graph_name(&self) -> &str {
+        self.writer.graph_name()
+    }
+
+    /// Connections currently open, idle or checked out.
+    pub fn open_connections(&self) -> u32 {
+        self.pool.state().connections
+    }
+}
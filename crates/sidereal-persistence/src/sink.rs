@@ -0,0 +1,168 @@
+//! Where the graph writer's statements go.
+//!
+//! `CypherWriter` only builds query text; a `CypherSink` decides what happens
+//! to it. `postgres::Client` executes it against Apache AGE, which is what
+//! `GraphPersistence` uses. `RecordingSink` keeps every statement instead, so
+//! tests can check the exact Cypher a record set produces without a database.
+
+use std::error::Error;
+
+use postgres::Client;
+use postgres::types::private::BytesMut;
+use postgres::types::{IsNull, ToSql, Type, to_sql_checked};
+use serde_json::Value as JsonValue;
+
+use crate::cypher::escape_cypher_string;
+use crate::{PersistenceError, Result, db_err};
+
+pub trait CypherSink {
+    /// Runs plain SQL, e.g. the `LOAD 'age'` / `search_path` setup around a
+    /// batch of Cypher. `action` names the step in error messages.
+    fn batch_execute(&mut self, sql: &str, action: &'static str) -> Result<()>;
+
+    /// Runs one Cypher statement against `graph_name`.
+    fn execute_cypher(&mut self, graph_name: &str, cypher: &str) -> Result<()>;
+
+    /// Runs one Cypher statement that reads `$name` parameters from `params`,
+    /// a JSON object. The values travel outside the query text, so ids and
+    /// other caller strings need no escaping.
+    fn execute_cypher_with_params(
+        &mut self,
+        graph_name: &str,
+        cypher: &str,
+        params: &JsonValue,
+    ) -> Result<()>;
+}
+
+impl CypherSink for Client {
+    fn batch_execute(&mut self, sql: &str, action: &'static str) -> Result<()> {
+        Client::batch_execute(self, sql).map_err(db_err(action))
+    }
+
+    fn execute_cypher(&mut self, graph_name: &str, cypher: &str) -> Result<()> {
+        let sql = format!(
+            "SELECT * FROM ag_catalog.cypher('{}', $$ {cypher} $$) AS (v agtype);",
+            escape_cypher_string(graph_name)
+        );
+        self.query(&sql, &[]).map_err(|err| {
+            PersistenceError::database(format!("cypher execution failed: {err}; query={cypher}"))
+        })?;
+        Ok(())
+    }
+
+    fn execute_cypher_with_params(
+        &mut self,
+        graph_name: &str,
+        cypher: &str,
+        params: &JsonValue,
+    ) -> Result<()> {
+        // AGE only accepts the parameter map as a bound `$1`, never inline.
+        let sql = format!(
+            "SELECT * FROM ag_catalog.cypher('{}', $$ {cypher} $$, $1) AS (v agtype);",
+            escape_cypher_string(graph_name)
+        );
+        self.query(&sql, &[&AgtypeParams(params)]).map_err(|err| {
+            PersistenceError::database(format!("cypher execution failed: {err}; query={cypher}"))
+        })?;
+        Ok(())
+    }
+}
+
+/// A JSON object bound as an `agtype` parameter. agtype's binary input is a
+/// version byte followed by the text form, and JSON is valid agtype text.
+#[derive(Debug)]
+pub(crate) struct AgtypeParams<'a>(pub(crate) &'a JsonValue);
+
+impl ToSql for AgtypeParams<'_> {
+    fn to_sql(
+        &self,
+        _ty: &Type,
+        out: &mut BytesMut,
+    ) -> std::result::Result<IsNull, Box<dyn Error + Sync + Send>> {
+        out.extend_from_slice(&[1]);
+        out.extend_from_slice(serde_json::to_string(self.0)?.as_bytes());
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        ty.name() == "agtype"
+    }
+
+    to_sql_checked!();
+}
+
+/// One statement a `RecordingSink` received.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordedStatement {
+    Sql(String),
+    /// `params` is `Null` for a statement run without parameters.
+    Cypher {
+        graph_name: String,
+        cypher: String,
+        params: JsonValue,
+    },
+}
+
+/// Dry-run sink: records statements in order and executes nothing.
+#[derive(Debug, Clone, Default)]
+pub struct RecordingSink {
+    pub statements: Vec<RecordedStatement>,
+}
+
+impl RecordingSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The recorded Cypher statements, without the SQL around them.
+    pub fn cypher(&self) -> Vec<&str> {
+        self.statements
+            .iter()
+            .filter_map(|statement| match statement {
+                RecordedStatement::Cypher { cypher, .. } => Some(cypher.as_str()),
+                RecordedStatement::Sql(_) => None,
+            })
+            .collect()
+    }
+
+    /// The parameters of each recorded Cypher statement, in `cypher()` order.
+    pub fn cypher_params(&self) -> Vec<&JsonValue> {
+        self.statements
+            .iter()
+            .filter_map(|statement| match statement {
+                RecordedStatement::Cypher { params, .. } => Some(params),
+                RecordedStatement::Sql(_) => None,
+            })
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.statements.clear();
+    }
+}
+
+impl CypherSink for RecordingSink {
+    fn batch_execute(&mut self, sql: &str, _action: &'static str) -> Result<()> {
+        self.statements
+            .push(RecordedStatement::Sql(sql.to_string()));
+        Ok(())
+    }
+
+    fn execute_cypher(&mut self, graph_name: &str, cypher: &str) -> Result<()> {
+        self.execute_cypher_with_params(graph_name, cypher, &JsonValue::Null)
+    }
+
+    fn execute_cypher_with_params(
+        &mut self,
+        graph_name: &str,
+        cypher: &str,
+        params: &JsonValue,
+    ) -> Result<()> {
+        self.statements.push(RecordedStatement::Cypher {
+            graph_name: graph_name.to_string(),
+            cypher: cypher.to_string(),
+            params: params.clone(),
+        });
+        Ok(())
+    }
+}
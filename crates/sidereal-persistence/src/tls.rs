@@ -0,0 +1,230 @@
+//! TLS for Postgres connections.
+//!
+//! `GraphPersistence::connect_with_tls` connects through native-tls. The
+//! `sslmode` in the database URL picks the behaviour with libpq's meaning,
+//! including `verify-ca` and `verify-full`, which the `postgres` crate itself
+//! does not parse.
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use native_tls::{Certificate, TlsConnector};
+use postgres::Config;
+use postgres::config::SslMode as PgSslMode;
+use postgres_native_tls::MakeTlsConnector;
+
+use crate::{PersistenceError, Result};
+
+/// libpq `sslmode` values. `allow` is treated as `prefer`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SslMode {
+    /// Never encrypt.
+    Disable,
+    /// Encrypt when the server supports it, without verifying it.
+    Prefer,
+    /// Always encrypt, without verifying the server unless a CA file is set.
+    Require,
+    /// Always encrypt and verify the certificate chain.
+    VerifyCa,
+    /// Always encrypt and verify the chain and the server host name.
+    VerifyFull,
+}
+
+impl FromStr for SslMode {
+    type Err = PersistenceError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "disable" => Ok(Self::Disable),
+            "allow" | "prefer" => Ok(Self::Prefer),
+            "require" => Ok(Self::Require),
+            "verify-ca" => Ok(Self::VerifyCa),
+            "verify-full" => Ok(Self::VerifyFull),
+            other => Err(PersistenceError::Tls(format!(
+                "unsupported sslmode {other:?}"
+            ))),
+        }
+    }
+}
+
+impl SslMode {
+    fn postgres_mode(self) -> PgSslMode {
+        match self {
+            Self::Disable => PgSslMode::Disable,
+            Self::Prefer => PgSslMode::Prefer,
+            Self::Require | Self::VerifyCa | Self::VerifyFull => PgSslMode::Require,
+        }
+    }
+}
+
+/// How `connect_with_tls` sets up TLS. The URL's `sslmode` wins over
+/// `default_mode`, which applies to URLs without one (default `Require`).
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    ca_file: Option<PathBuf>,
+    default_mode: SslMode,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self {
+            ca_file: None,
+            default_mode: SslMode::Require,
+        }
+    }
+}
+
+impl TlsConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trusts the PEM certificate(s) in `ca_file` in addition to the system
+    /// roots, e.g. a managed Postgres provider's CA. As with libpq's
+    /// `sslrootcert`, `require` then verifies the chain too.
+    pub fn with_ca_file(mut self, ca_file: impl Into<PathBuf>) -> Self {
+        self.ca_file = Some(ca_file.into());
+        self
+    }
+
+    pub fn with_default_mode(mut self, mode: SslMode) -> Self {
+        self.default_mode = mode;
+        self
+    }
+
+    /// Parses `database_url` into a connection config and a connector for
+    /// the `sslmode` it asks for. Fails on an unknown `sslmode` or an
+    /// unreadable CA file; nothing is contacted.
+    pub fn resolve(&self, database_url: &str) -> Result<(Config, MakeTlsConnector)> {
+        let (database_url, url_mode) = split_ssl_mode(database_url)?;
+        let mode = url_mode.unwrap_or(self.default_mode);
+        let mut config = Config::from_str(&database_url)
+            .map_err(|err| PersistenceError::database(format!("invalid database url: {err}")))?;
+        config.ssl_mode(mode.postgres_mode());
+        Ok((config, self.connector(mode)?))
+    }
+
+    fn connector(&self, mode: SslMode) -> Result<MakeTlsConnector> {
+        let mut builder = TlsConnector::builder();
+        if let Some(ca_file) = &self.ca_file {
+            let pem = std::fs::read(ca_file).map_err(|err| {
+                PersistenceError::Tls(format!("read CA file {}: {err}", ca_file.display()))
+            })?;
+            let certificate = Certificate::from_pem(&pem).map_err(|err| {
+                PersistenceError::Tls(format!("parse CA file {}: {err}", ca_file.display()))
+            })?;
+            builder.add_root_certificate(certificate);
+        }
+        let (verify_chain, verify_host) = match mode {
+            SslMode::Disable | SslMode::Prefer | SslMode::Require => {
+                (self.ca_file.is_some(), false)
+            }
+            SslMode::VerifyCa => (true, false),
+            SslMode::VerifyFull => (true, true),
+        };
+        let connector = builder
+            .danger_accept_invalid_certs(!verify_chain)
+            .danger_accept_invalid_hostnames(!verify_host)
+            .build()
+            .map_err(|err| PersistenceError::Tls(format!("build tls connector: {err}")))?;
+        Ok(MakeTlsConnector::new(connector))
+    }
+}
+
+/// Takes the `sslmode` out of `database_url` (URL or `key=value` form) and
+/// returns the URL without it, so the `postgres` crate never sees the
+/// `verify-*` values it rejects.
+fn split_ssl_mode(database_url: &str) -> Result<(String, Option<SslMode>)> {
+    const KEY: &str = "sslmode=";
+    let mut search_from = 0;
+    while let Some(found) = database_url[search_from..].find(KEY) {
+        let start = search_from + found;
+        let value_start = start + KEY.len();
+        let at_boundary = database_url[..start]
+            .chars()
+            .next_back()
+            .is_none_or(|c| c == '?' || c == '&' || c.is_whitespace());
+        if !at_boundary {
+            search_from = value_start;
+            continue;
+        }
+        let value_end = database_url[value_start..]
+            .find(|c: char| c == '&' || c.is_whitespace())
+            .map_or(database_url.len(), |len| value_start + len);
+        let mode = database_url[value_start..value_end].parse()?;
+        // Drop the parameter along with one adjoining separator.
+        let (cut_start, cut_end) = if database_url[value_end..].starts_with(['&', ' ']) {
+            (start, value_end + 1)
+        } else if database_url[..start].ends_with('&') {
+            (start - 1, value_end)
+        } else {
+            (start, value_end)
+        };
+        let mut stripped = String::with_capacity(database_url.len());
+        stripped.push_str(&database_url[..cut_start]);
+        stripped.push_str(&database_url[cut_end..]);
+        let stripped = stripped.trim().trim_end_matches('?').to_string();
+        return Ok((stripped, Some(mode)));
+    }
+    Ok((database_url.to_string(), None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ssl_mode_is_read_and_removed_from_both_url_forms() {
+        let cases = [
+            (
+                "postgres://u:p@db:5432/sidereal?sslmode=verify-full",
+                "postgres://u:p@db:5432/sidereal",
+                Some(SslMode::VerifyFull),
+            ),
+            (
+                "postgres://u:p@db/sidereal?sslmode=require&application_name=replication",
+                "postgres://u:p@db/sidereal?application_name=replication",
+                Some(SslMode::Require),
+            ),
+            (
+                "postgres://u:p@db/sidereal?application_name=x&sslmode=verify-ca",
+                "postgres://u:p@db/sidereal?application_name=x",
+                Some(SslMode::VerifyCa),
+            ),
+            (
+                "host=db user=u sslmode=disable dbname=sidereal",
+                "host=db user=u dbname=sidereal",
+                Some(SslMode::Disable),
+            ),
+            (
+                "postgres://u:p@db/sidereal?application_name=notsslmode=x",
+                "postgres://u:p@db/sidereal?application_name=notsslmode=x",
+                None,
+            ),
+        ];
+        for (url, stripped, mode) in cases {
+            assert_eq!(
+                split_ssl_mode(url).expect("sslmode should parse"),
+                (stripped.to_string(), mode),
+                "{url}"
+            );
+        }
+        assert!(matches!(
+            split_ssl_mode("postgres://db/sidereal?sslmode=bogus"),
+            Err(PersistenceError::Tls(_))
+        ));
+    }
+
+    #[test]
+    fn url_mode_overrides_the_default() {
+        let tls = TlsConfig::new().with_default_mode(SslMode::Disable);
+        let (config, _) = tls
+            .resolve("postgres://u@db/sidereal?sslmode=verify-full")
+            .expect("config should resolve");
+        assert_eq!(config.get_ssl_mode(), PgSslMode::Require);
+        let (config, _) = tls
+            .resolve("postgres://u@db/sidereal")
+            .expect("config should resolve");
+        assert_eq!(config.get_ssl_mode(), PgSslMode::Disable);
+    }
+}
@@ -0,0 +1,58 @@
+use sidereal_persistence::cypher::{
+    cypher_literal, cypher_set_clauses, escape_cypher_string, sanitize_identifier, sanitize_labels,
+};
+
+#[test]
+fn escaping_neutralizes_embedded_quotes_and_backslashes() {
+    assert_eq!(escape_cypher_string("o'brien"), "o\\'brien");
+    assert_eq!(escape_cypher_string("a\\b"), "a\\\\b");
+    // A trailing backslash must not be able to swallow the closing quote.
+    assert_eq!(escape_cypher_string("x\\'"), "x\\\\\\'");
+    assert_eq!(
+        cypher_literal(&serde_json::json!("'}) DETACH DELETE n //")),
+        "'\\'}) DETACH DELETE n //'"
+    );
+    assert_eq!(escape_cypher_string(""), "");
+}
+
+#[test]
+fn unicode_values_are_preserved() {
+    assert_eq!(escape_cypher_string("Σιδηρεαλ ✦ 星"), "Σιδηρεαλ ✦ 星");
+    assert_eq!(
+        cypher_literal(&serde_json::json!(["Ωmega", "l'été"])),
+        "['Ωmega','l\\'été']"
+    );
+}
+
+#[test]
+fn labels_and_keys_are_reduced_to_identifier_characters() {
+    let labels = vec![
+        "Ship".to_string(),
+        String::new(),
+        "Bad Label`)-[:X]->(".to_string(),
+        "ünïcode".to_string(),
+        "---".to_string(),
+    ];
+    assert_eq!(sanitize_labels(&labels), vec!["Ship", "BadLabelX", "ncode"]);
+    assert!(sanitize_labels(&[]).is_empty());
+    assert_eq!(sanitize_identifier("fuel_kg"), "fuel_kg");
+    assert_eq!(sanitize_identifier("é"), "");
+}
+
+#[test]
+fn set_clauses_sanitize_keys_and_skip_empty_ones() {
+    let clauses = cypher_set_clauses(
+        "c",
+        &serde_json::json!({"fuel kg": 1.5, "name": "it's", "!!": true}),
+    );
+    assert_eq!(clauses, vec!["c.fuelkg=1.5", "c.name='it\\'s'"]);
+    assert!(cypher_set_clauses("c", &serde_json::json!([1, 2])).is_empty());
+}
+
+#[test]
+fn map_literals_skip_keys_that_sanitize_to_nothing() {
+    assert_eq!(
+        cypher_literal(&serde_json::json!({"ok": null, "#": 1})),
+        "{ok:null}"
+    );
+}
@@ -1,5 +1,10 @@
+use sidereal_core::world_seed::WorldSeed;
 use sidereal_net::{WorldComponentDelta, WorldDeltaEntity};
-use sidereal_persistence::GraphPersistence;
+use sidereal_persistence::sink::CypherSink;
+use sidereal_persistence::{
+    CREATED_AT_KEY, CypherWriter, GraphEntityRecord, GraphPersistence, LAST_SEEN_KEY,
+    PersistenceError, STREAM_BATCH_ROWS, SnapshotRestore,
+};
 use uuid::Uuid;
 
 fn test_database_url() -> String {
@@ -41,6 +46,8 @@ fn make_ship_batch(ship_id: &str, hardpoint_id: &str, engine_id: &str) -> Vec<Wo
                 },
             ],
             removed: false,
+            removed_component_ids: Vec::new(),
+            removal_reason: None,
         },
         WorldDeltaEntity {
             entity_id: hardpoint_id.to_string(),
@@ -57,6 +64,8 @@ fn make_ship_batch(ship_id: &str, hardpoint_id: &str, engine_id: &str) -> Vec<Wo
                 properties: serde_json::json!({"hardpoint_id": "engine_main", "offset_m": [0.0, 0.0, -4.0]}),
             }],
             removed: false,
+            removed_component_ids: Vec::new(),
+            removal_reason: None,
         },
         WorldDeltaEntity {
             entity_id: engine_id.to_string(),
@@ -72,10 +81,12 @@ fn make_ship_batch(ship_id: &str, hardpoint_id: &str, engine_id: &str) -> Vec<Wo
                 properties: serde_json::json!({
                     "thrust_n": 280000.0,
                     "burn_rate_kg_s": 18.0,
-                    "thrust_dir": [0.0, 0.0, 1.0]
+                    "thrust_dir": [0.0, 1.0, 0.0]
                 }),
             }],
             removed: false,
+            removed_component_ids: Vec::new(),
+            removal_reason: None,
         },
     ]
 }
@@ -126,6 +137,8 @@ fn graph_persistence_full_lifecycle_ship_hardpoint_engine() {
         properties: serde_json::json!({}),
         components: Vec::new(),
         removed: true,
+        removed_component_ids: Vec::new(),
+        removal_reason: None,
     });
     persistence
         .persist_world_delta(&updates, 101)
@@ -148,3 +161,1364 @@ fn graph_persistence_full_lifecycle_ship_hardpoint_engine() {
 
     persistence.drop_graph().expect("test graph should drop");
 }
+
+#[test]
+fn component_removal_delta_deletes_only_named_component() {
+    let database_url = test_database_url();
+    let graph_name = unique_graph_name("sidereal_persistence_component_removal");
+    let mut persistence = match GraphPersistence::connect_with_graph(&database_url, &graph_name) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("skipping component removal test; postgres unavailable: {err}");
+            return;
+        }
+    };
+    if let Err(err) = persistence.ensure_schema() {
+        eprintln!("skipping component removal test; AGE schema unavailable: {err}");
+        return;
+    }
+
+    let ship_id = format!("ship:{}", Uuid::new_v4());
+    let hardpoint_id = format!("hardpoint:{}", Uuid::new_v4());
+    let engine_id = format!("engine:{}", Uuid::new_v4());
+    persistence
+        .persist_world_delta(&make_ship_batch(&ship_id, &hardpoint_id, &engine_id), 200)
+        .expect("initial world delta should persist");
+
+    let removed_component_id = format!("{ship_id}:flight_computer");
+    persistence
+        .persist_world_delta(
+            &[WorldDeltaEntity {
+                entity_id: ship_id.clone(),
+                labels: Vec::new(),
+                properties: serde_json::json!({}),
+                components: Vec::new(),
+                removed: false,
+                removed_component_ids: vec![removed_component_id.clone()],
+                removal_reason: None,
+            }],
+            201,
+        )
+        .expect("component removal delta should persist");
+
+    let after = persistence
+        .load_graph_records()
+        .expect("load graph records should succeed");
+    let ship = after
+        .iter()
+        .find(|r| r.entity_id == ship_id)
+        .expect("ship should still exist");
+    let mut component_ids = ship
+        .components
+        .iter()
+        .map(|c| c.component_id.clone())
+        .collect::<Vec<_>>();
+    component_ids.sort();
+    assert_eq!(
+        component_ids,
+        vec![
+            format!("{ship_id}:display_name"),
+            format!("{ship_id}:health_pool"),
+        ]
+    );
+    assert_eq!(ship.properties["name"], "ISS Persistence");
+    assert_eq!(
+        after
+            .iter()
+            .find(|r| r.entity_id == engine_id)
+            .expect("engine should still exist")
+            .components
+            .len(),
+        1
+    );
+
+    persistence.drop_graph().expect("test graph should drop");
+}
+
+fn inventory_component(container_id: &str, item_ids: &[&str]) -> WorldComponentDelta {
+    WorldComponentDelta {
+        component_id: format!("{container_id}:inventory"),
+        component_kind: "inventory".to_string(),
+        properties: serde_json::json!({
+            "entries": item_ids
+                .iter()
+                .map(|id| serde_json::json!({"item_entity_id": id, "quantity": 2, "unit_mass_kg": 10.0}))
+                .collect::<Vec<_>>(),
+        }),
+    }
+}
+
+fn container(entity_id: &str, item_ids: &[&str]) -> WorldDeltaEntity {
+    WorldDeltaEntity {
+        entity_id: entity_id.to_string(),
+        labels: vec!["Entity".to_string(), "Ship".to_string()],
+        properties: serde_json::json!({"name": entity_id}),
+        components: vec![inventory_component(entity_id, item_ids)],
+        removed: false,
+        removed_component_ids: Vec::new(),
+        removal_reason: None,
+    }
+}
+
+#[test]
+fn structured_inventory_items_are_queryable_by_item_id() {
+    let database_url = test_database_url();
+    let graph_name = unique_graph_name("sidereal_persistence_inventory_items");
+    let mut persistence = match GraphPersistence::connect_with_graph(&database_url, &graph_name) {
+        Ok(v) => v.with_structured_inventory(2),
+        Err(err) => {
+            eprintln!("skipping structured inventory test; postgres unavailable: {err}");
+            return;
+        }
+    };
+    if let Err(err) = persistence.ensure_schema() {
+        eprintln!("skipping structured inventory test; AGE schema unavailable: {err}");
+        return;
+    }
+
+    let ore = Uuid::new_v4().to_string();
+    let ammo = Uuid::new_v4().to_string();
+    let hauler = format!("ship:{}", Uuid::new_v4());
+    let miner = format!("ship:{}", Uuid::new_v4());
+    // Single-entry inventory stays below the threshold and is persisted as JSON only.
+    let scout = format!("ship:{}", Uuid::new_v4());
+    persistence
+        .persist_world_delta(
+            &[
+                container(&hauler, &[&ore, &ammo]),
+                container(&miner, &[&ore, &Uuid::new_v4().to_string()]),
+                container(&scout, &[&ore]),
+            ],
+            400,
+        )
+        .expect("inventory delta should persist");
+
+    let mut expected = vec![hauler.clone(), miner.clone()];
+    expected.sort();
+    assert_eq!(
+        persistence
+            .find_containers_with_item(&ore)
+            .expect("item query should succeed"),
+        expected
+    );
+    assert_eq!(
+        persistence
+            .find_containers_with_item(&ammo)
+            .expect("item query should succeed"),
+        vec![hauler.clone()]
+    );
+
+    let records = persistence
+        .load_graph_records()
+        .expect("load graph records should succeed");
+    let scout_record = records
+        .iter()
+        .find(|r| r.entity_id == scout)
+        .expect("scout should persist");
+    assert_eq!(scout_record.components.len(), 1);
+    assert_eq!(scout_record.components[0].component_kind, "inventory");
+
+    // Dropping an item from the hauler removes its structured node.
+    persistence
+        .persist_world_delta(
+            &[container(&hauler, &[&ore, &Uuid::new_v4().to_string()])],
+            401,
+        )
+        .expect("updated inventory should persist");
+    assert!(
+        persistence
+            .find_containers_with_item(&ammo)
+            .expect("item query should succeed")
+            .is_empty()
+    );
+
+    persistence.drop_graph().expect("test graph should drop");
+}
+
+#[test]
+fn set_entity_position_warps_offline_entity() {
+    let database_url = test_database_url();
+    let graph_name = unique_graph_name("sidereal_persistence_warp");
+    let mut persistence = match GraphPersistence::connect_with_graph(&database_url, &graph_name) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("skipping warp test; postgres unavailable: {err}");
+            return;
+        }
+    };
+    if let Err(err) = persistence.ensure_schema() {
+        eprintln!("skipping warp test; AGE schema unavailable: {err}");
+        return;
+    }
+
+    let ship_id = format!("ship:{}", Uuid::new_v4());
+    let hardpoint_id = format!("hardpoint:{}", Uuid::new_v4());
+    let engine_id = format!("engine:{}", Uuid::new_v4());
+    persistence
+        .persist_world_delta(&make_ship_batch(&ship_id, &hardpoint_id, &engine_id), 500)
+        .expect("initial world delta should persist");
+
+    persistence
+        .set_entity_position(&ship_id, [-2500.0, 40.0, 0.5], [0.0, 0.0, 0.0])
+        .expect("warp should succeed");
+
+    let after = persistence
+        .load_graph_records()
+        .expect("load graph records should succeed");
+    let ship = after
+        .iter()
+        .find(|r| r.entity_id == ship_id)
+        .expect("ship should still exist");
+    assert_eq!(
+        ship.properties["position_m"],
+        serde_json::json!([-2500.0, 40.0, 0.5])
+    );
+    assert_eq!(
+        ship.properties["velocity_mps"],
+        serde_json::json!([0.0, 0.0, 0.0])
+    );
+    assert_eq!(ship.properties["name"], "ISS Persistence");
+    assert_eq!(ship.components.len(), 3);
+
+    let missing = format!("ship:{}", Uuid::new_v4());
+    assert!(matches!(
+        persistence.set_entity_position(&missing, [0.0; 3], [0.0; 3]),
+        Err(PersistenceError::NotFound(id)) if id == missing
+    ));
+
+    persistence.drop_graph().expect("test graph should drop");
+}
+
+#[test]
+fn entities_near_a_point_are_found_by_3d_distance() {
+    let database_url = test_database_url();
+    let graph_name = unique_graph_name("sidereal_persistence_near");
+    let mut persistence = match GraphPersistence::connect_with_graph(&database_url, &graph_name) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("skipping radius query test; postgres unavailable: {err}");
+            return;
+        }
+    };
+    if let Err(err) = persistence.ensure_schema() {
+        eprintln!("skipping radius query test; AGE schema unavailable: {err}");
+        return;
+    }
+
+    let center = [1000.0, -200.0, 50.0];
+    let at_offset = |name: &str, offset: [f32; 3]| WorldDeltaEntity {
+        entity_id: format!("{name}:{}", Uuid::new_v4()),
+        labels: vec!["Entity".to_string(), "Ship".to_string()],
+        properties: serde_json::json!({
+            "position_m": [center[0] + offset[0], center[1] + offset[1], center[2] + offset[2]],
+        }),
+        components: vec![WorldComponentDelta {
+            component_id: format!("{name}:health_pool"),
+            component_kind: "health_pool".to_string(),
+            properties: serde_json::json!({"hp": 10.0}),
+        }],
+        removed: false,
+        removed_component_ids: Vec::new(),
+        removal_reason: None,
+    };
+    // 3-4-0 puts `near` exactly 50 m out; `far` is 50 m out on z alone but
+    // also 40 m on y, so only a full 3D distance excludes it.
+    let near = at_offset("ship", [30.0, 40.0, 0.0]);
+    let nearest = at_offset("ship", [0.0, 0.0, -5.0]);
+    let far = at_offset("ship", [0.0, 40.0, 50.0]);
+    let unplaced = WorldDeltaEntity {
+        properties: serde_json::json!({"name": "No Position"}),
+        ..at_offset("ship", [0.0; 3])
+    };
+    persistence
+        .persist_world_delta(&[near.clone(), nearest.clone(), far.clone(), unplaced], 600)
+        .expect("world delta should persist");
+
+    let ids_near = |persistence: &mut GraphPersistence, radius_m: f32| {
+        persistence
+            .load_entities_near(center, radius_m)
+            .expect("radius query should succeed")
+            .into_iter()
+            .map(|record| {
+                assert_eq!(record.components.len(), 1);
+                record.entity_id
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ids_near(&mut persistence, 1.0), Vec::<String>::new());
+    assert_eq!(
+        ids_near(&mut persistence, 50.0),
+        vec![nearest.entity_id.clone(), near.entity_id.clone()]
+    );
+    assert_eq!(
+        ids_near(&mut persistence, 70.0),
+        vec![nearest.entity_id, near.entity_id, far.entity_id]
+    );
+    assert!(ids_near(&mut persistence, -1.0).is_empty());
+
+    persistence.drop_graph().expect("test graph should drop");
+}
+
+#[test]
+fn restore_to_snapshot_removes_only_later_ticks() {
+    let database_url = test_database_url();
+    let graph_name = unique_graph_name("sidereal_persistence_restore");
+    let mut persistence = match GraphPersistence::connect_with_graph(&database_url, &graph_name) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("skipping snapshot restore test; postgres unavailable: {err}");
+            return;
+        }
+    };
+    if let Err(err) = persistence.ensure_schema() {
+        eprintln!("skipping snapshot restore test; AGE schema unavailable: {err}");
+        return;
+    }
+
+    let first_batch = make_ship_batch(
+        &format!("ship:{}", Uuid::new_v4()),
+        &format!("hardpoint:{}", Uuid::new_v4()),
+        &format!("engine:{}", Uuid::new_v4()),
+    );
+    persistence
+        .persist_world_delta(&first_batch, 700)
+        .expect("first tick should persist");
+    // Marker tables are shared across test graphs, so pick out this one.
+    persistence
+        .persist_snapshot_marker(700, first_batch.len())
+        .expect("snapshot marker should persist");
+    let marker = persistence
+        .load_snapshot_markers()
+        .expect("snapshot markers should load")
+        .into_iter()
+        .rev()
+        .find(|marker| marker.snapshot_tick == 700)
+        .expect("marker should be listed");
+    assert_eq!(marker.entity_count, 3);
+
+    let second_batch = make_ship_batch(
+        &format!("ship:{}", Uuid::new_v4()),
+        &format!("hardpoint:{}", Uuid::new_v4()),
+        &format!("engine:{}", Uuid::new_v4()),
+    );
+    persistence
+        .persist_world_delta(&second_batch, 701)
+        .expect("second tick should persist");
+    assert_eq!(
+        persistence
+            .load_graph_records()
+            .expect("load graph records should succeed")
+            .len(),
+        6
+    );
+
+    let restored = persistence
+        .restore_to_snapshot(marker.snapshot_id)
+        .expect("restore should succeed");
+    assert_eq!(
+        restored,
+        SnapshotRestore {
+            snapshot_tick: 700,
+            entities_removed: 3,
+            components_removed: 5,
+        }
+    );
+    let mut remaining = persistence
+        .load_graph_records()
+        .expect("load graph records should succeed")
+        .into_iter()
+        .map(|record| {
+            assert!(!record.components.is_empty());
+            record.entity_id
+        })
+        .collect::<Vec<_>>();
+    remaining.sort();
+    let mut first_ids = first_batch
+        .iter()
+        .map(|update| update.entity_id.clone())
+        .collect::<Vec<_>>();
+    first_ids.sort();
+    assert_eq!(remaining, first_ids);
+
+    assert!(matches!(
+        persistence.restore_to_snapshot(i64::MAX),
+        Err(PersistenceError::NotFound(_))
+    ));
+
+    persistence.drop_graph().expect("test graph should drop");
+}
+
+#[test]
+fn created_at_is_set_on_create_and_kept_on_update() {
+    let database_url = test_database_url();
+    let graph_name = unique_graph_name("sidereal_persistence_created_at");
+    let mut persistence = match GraphPersistence::connect_with_graph(&database_url, &graph_name) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("skipping created_at test; postgres unavailable: {err}");
+            return;
+        }
+    };
+    if let Err(err) = persistence.ensure_schema() {
+        eprintln!("skipping created_at test; AGE schema unavailable: {err}");
+        return;
+    }
+
+    let ship_id = format!("ship:{}", Uuid::new_v4());
+    let hardpoint_id = format!("hardpoint:{}", Uuid::new_v4());
+    let engine_id = format!("engine:{}", Uuid::new_v4());
+    persistence
+        .persist_world_delta(&make_ship_batch(&ship_id, &hardpoint_id, &engine_id), 600)
+        .expect("initial world delta should persist");
+
+    let created_at = |persistence: &mut GraphPersistence| {
+        persistence
+            .load_graph_records()
+            .expect("load graph records should succeed")
+            .into_iter()
+            .find(|r| r.entity_id == ship_id)
+            .expect("ship should exist")
+            .created_at_epoch_s()
+            .expect("ship should have a creation timestamp")
+    };
+    let first = created_at(&mut persistence);
+
+    // Neither a later tick nor a caller echoing a different value may move it.
+    let mut update = make_ship_batch(&ship_id, &hardpoint_id, &engine_id);
+    update[0].properties[CREATED_AT_KEY] = serde_json::json!(1);
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    persistence
+        .persist_world_delta(&update, 601)
+        .expect("update world delta should persist");
+    assert_eq!(created_at(&mut persistence), first);
+
+    assert!(
+        persistence
+            .find_entities_created_before(first)
+            .expect("age query should succeed")
+            .is_empty()
+    );
+    let older = persistence
+        .find_entities_created_before(first + 1)
+        .expect("age query should succeed");
+    assert!(older.contains(&ship_id));
+    assert!(older.contains(&engine_id));
+
+    persistence.drop_graph().expect("test graph should drop");
+}
+
+#[test]
+fn single_entity_load_returns_one_record_with_all_components() {
+    let database_url = test_database_url();
+    let graph_name = unique_graph_name("sidereal_persistence_single_load");
+    let mut persistence = match GraphPersistence::connect_with_graph(&database_url, &graph_name) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("skipping single entity load test; postgres unavailable: {err}");
+            return;
+        }
+    };
+    if let Err(err) = persistence.ensure_schema() {
+        eprintln!("skipping single entity load test; AGE schema unavailable: {err}");
+        return;
+    }
+
+    let ship_id = format!("ship:{}", Uuid::new_v4());
+    let engine_id = format!("engine:{}", Uuid::new_v4());
+    let batch = make_ship_batch(
+        &ship_id,
+        &format!("hardpoint:{}", Uuid::new_v4()),
+        &engine_id,
+    );
+    persistence
+        .persist_world_delta(&batch, 500)
+        .expect("world delta should persist");
+
+    let ship = persistence
+        .load_graph_record(&ship_id)
+        .expect("single load should succeed")
+        .expect("ship should exist");
+    let full = persistence
+        .load_graph_records()
+        .expect("load graph records should succeed")
+        .into_iter()
+        .find(|record| record.entity_id == ship_id)
+        .expect("ship should be in the full load");
+    let component_ids = |record: &GraphEntityRecord| {
+        let mut ids = record
+            .components
+            .iter()
+            .map(|c| c.component_id.clone())
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    };
+    assert_eq!(ship.components.len(), 3);
+    assert_eq!(component_ids(&ship), component_ids(&full));
+    assert_eq!(ship.properties, full.properties);
+    assert_eq!(ship.labels, full.labels);
+
+    let engine = persistence
+        .load_graph_record(&engine_id)
+        .expect("single load should succeed")
+        .expect("engine should exist");
+    assert_eq!(engine.components.len(), 1);
+    assert!(
+        persistence
+            .load_graph_record("ship:missing")
+            .expect("single load should succeed")
+            .is_none()
+    );
+
+    persistence.drop_graph().expect("test graph should drop");
+}
+
+#[test]
+fn player_records_include_the_player_and_their_ships_only() {
+    let database_url = test_database_url();
+    let graph_name = unique_graph_name("sidereal_persistence_player_records");
+    let mut persistence = match GraphPersistence::connect_with_graph(&database_url, &graph_name) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("skipping player records test; postgres unavailable: {err}");
+            return;
+        }
+    };
+    if let Err(err) = persistence.ensure_schema() {
+        eprintln!("skipping player records test; AGE schema unavailable: {err}");
+        return;
+    }
+
+    let player_id = format!("player:{}", Uuid::new_v4());
+    let ship_id = format!("ship:{}", Uuid::new_v4());
+    let hardpoint_id = format!("hardpoint:{}", Uuid::new_v4());
+    let engine_id = format!("engine:{}", Uuid::new_v4());
+    let mut batch = make_ship_batch(&ship_id, &hardpoint_id, &engine_id);
+    batch[0].properties["player_entity_id"] = serde_json::json!(player_id);
+    batch.push(WorldDeltaEntity {
+        entity_id: player_id.clone(),
+        labels: vec!["Entity".to_string(), "Player".to_string()],
+        properties: serde_json::json!({ "player_entity_id": player_id }),
+        components: vec![WorldComponentDelta {
+            component_id: format!("{player_id}:display_name"),
+            component_kind: "display_name".to_string(),
+            properties: serde_json::json!({"value": "Pilot"}),
+        }],
+        removed: false,
+        removed_component_ids: Vec::new(),
+        removal_reason: None,
+    });
+    persistence
+        .persist_world_delta(&batch, 700)
+        .expect("player world delta should persist");
+
+    let records = persistence
+        .load_player_records(&player_id)
+        .expect("player records should load");
+    let mut ids = records
+        .iter()
+        .map(|record| record.entity_id.clone())
+        .collect::<Vec<_>>();
+    ids.sort();
+    let mut expected = vec![player_id.clone(), ship_id.clone()];
+    expected.sort();
+    assert_eq!(ids, expected);
+    let ship = records
+        .iter()
+        .find(|record| record.entity_id == ship_id)
+        .expect("ship should load");
+    assert!(
+        ship.components
+            .iter()
+            .any(|c| c.component_kind == "display_name")
+    );
+
+    let unknown = format!("player:{}", Uuid::new_v4());
+    assert!(
+        persistence
+            .load_player_records(&unknown)
+            .expect("unknown player query should succeed")
+            .is_empty()
+    );
+
+    persistence.drop_graph().expect("test graph should drop");
+}
+
+#[test]
+fn records_since_tick_return_only_newer_persists() {
+    let database_url = test_database_url();
+    let graph_name = unique_graph_name("sidereal_persistence_since_tick");
+    let mut persistence = match GraphPersistence::connect_with_graph(&database_url, &graph_name) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("skipping records since test; postgres unavailable: {err}");
+            return;
+        }
+    };
+    if let Err(err) = persistence.ensure_schema() {
+        eprintln!("skipping records since test; AGE schema unavailable: {err}");
+        return;
+    }
+
+    let old_batch = make_ship_batch(
+        &format!("ship:{}", Uuid::new_v4()),
+        &format!("hardpoint:{}", Uuid::new_v4()),
+        &format!("engine:{}", Uuid::new_v4()),
+    );
+    let new_ship_id = format!("ship:{}", Uuid::new_v4());
+    let new_batch = make_ship_batch(
+        &new_ship_id,
+        &format!("hardpoint:{}", Uuid::new_v4()),
+        &format!("engine:{}", Uuid::new_v4()),
+    );
+    persistence
+        .persist_world_delta(&old_batch, 100)
+        .expect("old batch should persist");
+    persistence
+        .persist_world_delta(&new_batch, 200)
+        .expect("new batch should persist");
+
+    let ids_since = |persistence: &mut GraphPersistence, tick: u64| {
+        let mut ids = persistence
+            .load_graph_records_since(tick)
+            .expect("records since tick should load")
+            .into_iter()
+            .map(|record| record.entity_id)
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    };
+    let batch_ids = |batch: &[WorldDeltaEntity]| {
+        let mut ids = batch
+            .iter()
+            .map(|update| update.entity_id.clone())
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    };
+
+    assert_eq!(ids_since(&mut persistence, 150), batch_ids(&new_batch));
+    assert_eq!(ids_since(&mut persistence, 100).len(), 6);
+    assert!(ids_since(&mut persistence, 201).is_empty());
+
+    let new_ship = persistence
+        .load_graph_records_since(200)
+        .expect("records since tick should load")
+        .into_iter()
+        .find(|record| record.entity_id == new_ship_id)
+        .expect("new ship should load");
+    assert_eq!(new_ship.components.len(), new_batch[0].components.len());
+
+    persistence.drop_graph().expect("test graph should drop");
+}
+
+#[test]
+fn label_filter_excludes_non_matching_entities() {
+    let database_url = test_database_url();
+    let graph_name = unique_graph_name("sidereal_persistence_label_filter");
+    let mut persistence = match GraphPersistence::connect_with_graph(&database_url, &graph_name) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("skipping label filter test; postgres unavailable: {err}");
+            return;
+        }
+    };
+    if let Err(err) = persistence.ensure_schema() {
+        eprintln!("skipping label filter test; AGE schema unavailable: {err}");
+        return;
+    }
+
+    let old_ship_id = format!("ship:{}", Uuid::new_v4());
+    let old_hardpoint_id = format!("hardpoint:{}", Uuid::new_v4());
+    persistence
+        .persist_world_delta(
+            &make_ship_batch(
+                &old_ship_id,
+                &old_hardpoint_id,
+                &format!("engine:{}", Uuid::new_v4()),
+            ),
+            100,
+        )
+        .expect("old batch should persist");
+    let new_ship_id = format!("ship:{}", Uuid::new_v4());
+    persistence
+        .persist_world_delta(
+            &make_ship_batch(
+                &new_ship_id,
+                &format!("hardpoint:{}", Uuid::new_v4()),
+                &format!("engine:{}", Uuid::new_v4()),
+            ),
+            200,
+        )
+        .expect("new batch should persist");
+
+    let mut ids_for = |labels: &[&str], since_tick: Option<u64>| {
+        let mut ids = persistence
+            .load_graph_records_filtered(labels, since_tick)
+            .expect("filtered records should load")
+            .into_iter()
+            .map(|record| {
+                assert!(
+                    labels.is_empty() || record.labels.iter().any(|l| labels.contains(&l.as_str())),
+                    "{} does not carry {labels:?}",
+                    record.entity_id
+                );
+                assert!(!record.components.is_empty());
+                record.entity_id
+            })
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    };
+
+    let mut ships = vec![old_ship_id.clone(), new_ship_id.clone()];
+    ships.sort();
+    assert_eq!(ids_for(&["Ship"], None), ships);
+    assert_eq!(ids_for(&["Ship"], Some(150)), vec![new_ship_id]);
+    assert_eq!(ids_for(&["Ship", "Hardpoint"], None).len(), 4);
+    assert!(ids_for(&["Ship", "Hardpoint"], None).contains(&old_hardpoint_id));
+    assert!(ids_for(&["Asteroid"], None).is_empty());
+    assert_eq!(ids_for(&[], None).len(), 6);
+
+    persistence.drop_graph().expect("test graph should drop");
+}
+
+fn nested_component_payload() -> serde_json::Value {
+    serde_json::json!({
+        "sidereal_game::Loadout": {
+            "racks": [
+                {"slots": [{"id": "a", "mods": {"heat": 1.5, "tags": ["x", "y"]}}]},
+                {"slots": []},
+            ],
+            "profile": {"name": "O'Neil \"Mk2\"", "limits": {"max": 4.0}},
+        }
+    })
+}
+
+fn persist_and_load_nested(
+    persistence: &mut GraphPersistence,
+    entity_id: &str,
+) -> serde_json::Value {
+    let component_id = format!("{entity_id}:loadout");
+    persistence
+        .persist_world_delta(
+            &[WorldDeltaEntity {
+                entity_id: entity_id.to_string(),
+                labels: vec!["Entity".to_string()],
+                properties: serde_json::json!({}),
+                components: vec![WorldComponentDelta {
+                    component_id: component_id.clone(),
+                    component_kind: "loadout".to_string(),
+                    properties: nested_component_payload(),
+                }],
+                removed: false,
+                removed_component_ids: Vec::new(),
+                removal_reason: None,
+            }],
+            500,
+        )
+        .expect("nested component should persist");
+    persistence
+        .load_graph_records()
+        .expect("load graph records should succeed")
+        .into_iter()
+        .find(|r| r.entity_id == entity_id)
+        .and_then(|r| {
+            r.components
+                .into_iter()
+                .find(|c| c.component_id == component_id)
+        })
+        .expect("nested component should load")
+        .properties
+}
+
+#[test]
+fn nested_component_roundtrip_per_key_and_json_modes() {
+    let database_url = test_database_url();
+    let graph_name = unique_graph_name("sidereal_persistence_component_json");
+    let mut persistence = match GraphPersistence::connect_with_graph(&database_url, &graph_name) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("skipping component json test; postgres unavailable: {err}");
+            return;
+        }
+    };
+    if let Err(err) = persistence.ensure_schema() {
+        eprintln!("skipping component json test; AGE schema unavailable: {err}");
+        return;
+    }
+
+    let expected = nested_component_payload();
+    let inner = &expected["sidereal_game::Loadout"];
+
+    // Per-key mode flattens the payload into SET clauses: keys lose non-identifier
+    // characters and bookkeeping properties are mixed in.
+    let per_key = persist_and_load_nested(&mut persistence, &format!("ship:{}", Uuid::new_v4()));
+    assert!(per_key.get("sidereal_game::Loadout").is_none());
+    assert_eq!(&per_key["sidereal_gameLoadout"], inner);
+    assert!(per_key.get("component_kind").is_some());
+
+    // JSON mode stores the payload verbatim and restores it exactly.
+    let mut persistence = persistence.with_component_json_threshold(1);
+    let json_mode = persist_and_load_nested(&mut persistence, &format!("ship:{}", Uuid::new_v4()));
+    assert_eq!(json_mode, expected);
+
+    persistence.drop_graph().expect("test graph should drop");
+}
+
+#[test]
+fn player_presence_flips_online_flag_and_stamps_last_seen() {
+    let database_url = test_database_url();
+    let graph_name = unique_graph_name("sidereal_persistence_presence");
+    let mut persistence = match GraphPersistence::connect_with_graph(&database_url, &graph_name) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("skipping player presence test; postgres unavailable: {err}");
+            return;
+        }
+    };
+    if let Err(err) = persistence.ensure_schema() {
+        eprintln!("skipping player presence test; AGE schema unavailable: {err}");
+        return;
+    }
+
+    let player_id = format!("player:{}", Uuid::new_v4());
+    persistence
+        .persist_graph_records(
+            &[GraphEntityRecord {
+                entity_id: player_id.clone(),
+                labels: vec!["Entity".to_string(), "Player".to_string()],
+                properties: serde_json::json!({ LAST_SEEN_KEY: 1 }),
+                components: Vec::new(),
+            }],
+            1,
+        )
+        .expect("player should persist");
+    let load_player = |persistence: &mut GraphPersistence| {
+        persistence
+            .load_player_records(&player_id)
+            .expect("player should load")
+            .into_iter()
+            .find(|record| record.entity_id == player_id)
+            .expect("player should exist")
+    };
+    assert!(!load_player(&mut persistence).is_online());
+
+    persistence
+        .set_player_online(&player_id, true)
+        .expect("authenticating should mark the player online");
+    let online = load_player(&mut persistence);
+    assert!(online.is_online());
+    let online_seen = online.last_seen_epoch_s().expect("last seen should be set");
+    assert!(online_seen > 1);
+
+    persistence
+        .set_player_online(&player_id, false)
+        .expect("disconnecting should mark the player offline");
+    let offline = load_player(&mut persistence);
+    assert!(!offline.is_online());
+    let offline_seen = offline
+        .last_seen_epoch_s()
+        .expect("last seen should be set");
+    assert!(offline_seen >= online_seen);
+
+    assert!(matches!(
+        persistence.set_player_online("player:missing", true),
+        Err(PersistenceError::NotFound(_))
+    ));
+
+    persistence
+        .set_player_online(&player_id, true)
+        .expect("player should come back online");
+    assert_eq!(
+        persistence
+            .clear_players_online()
+            .expect("boot should clear presence"),
+        1
+    );
+    assert!(!load_player(&mut persistence).is_online());
+    assert_eq!(
+        persistence
+            .clear_players_online()
+            .expect("second clear should succeed"),
+        0
+    );
+
+    persistence.drop_graph().expect("test graph should drop");
+}
+
+#[test]
+fn world_seed_initializes_once_and_reloads() {
+    let database_url = test_database_url();
+    let graph_name = unique_graph_name("sidereal_persistence_world_seed");
+    let mut persistence = match GraphPersistence::connect_with_graph(&database_url, &graph_name) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("skipping world seed test; postgres unavailable: {err}");
+            return;
+        }
+    };
+    if let Err(err) = persistence.ensure_schema() {
+        eprintln!("skipping world seed test; AGE schema unavailable: {err}");
+        return;
+    }
+
+    assert_eq!(
+        persistence.load_world_seed().expect("seed should load"),
+        None
+    );
+    let initialized = persistence
+        .load_or_init_world_seed()
+        .expect("seed should initialize");
+    assert_eq!(
+        persistence
+            .load_or_init_world_seed()
+            .expect("seed should load"),
+        initialized
+    );
+    assert_eq!(
+        persistence.load_world_seed().expect("seed should load"),
+        Some(initialized)
+    );
+    assert!(
+        persistence
+            .load_graph_records()
+            .expect("load graph records should succeed")
+            .is_empty(),
+        "the config node is not an entity"
+    );
+
+    persistence
+        .store_world_seed(WorldSeed(u64::MAX))
+        .expect("seed should store");
+    let mut reconnected = GraphPersistence::connect_with_graph(&database_url, &graph_name)
+        .expect("reconnect should succeed");
+    assert_eq!(
+        reconnected.load_world_seed().expect("seed should load"),
+        Some(WorldSeed(u64::MAX))
+    );
+    assert_eq!(
+        reconnected
+            .load_or_init_world_seed()
+            .expect("seed should load"),
+        WorldSeed(u64::MAX)
+    );
+
+    drop(reconnected);
+    persistence.drop_graph().expect("test graph should drop");
+}
+
+#[test]
+fn hostile_entity_ids_round_trip_intact() {
+    let database_url = test_database_url();
+    let graph_name = unique_graph_name("sidereal_persistence_hostile_ids");
+    let mut persistence = match GraphPersistence::connect_with_graph(&database_url, &graph_name) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("skipping hostile id test; postgres unavailable: {err}");
+            return;
+        }
+    };
+    if let Err(err) = persistence.ensure_schema() {
+        eprintln!("skipping hostile id test; AGE schema unavailable: {err}");
+        return;
+    }
+
+    let ship_id = format!("ship:o'brien$$ RETURN 1 $$\\{}", Uuid::new_v4());
+    let bystander_id = format!("ship:{}", Uuid::new_v4());
+    let mut batch = make_ship_batch(
+        &bystander_id,
+        &format!("hardpoint:{}", Uuid::new_v4()),
+        &format!("engine:{}", Uuid::new_v4()),
+    );
+    batch.truncate(1);
+    batch.push(WorldDeltaEntity {
+        entity_id: ship_id.clone(),
+        labels: vec!["Entity".to_string(), "Ship".to_string()],
+        properties: serde_json::json!({"name": "O'Brien"}),
+        components: vec![WorldComponentDelta {
+            component_id: format!("{ship_id}:health_pool"),
+            component_kind: "health_pool".to_string(),
+            properties: serde_json::json!({"hp": 40.0, "max_hp": 100.0}),
+        }],
+        removed: false,
+        removed_component_ids: Vec::new(),
+        removal_reason: None,
+    });
+    persistence
+        .persist_world_delta(&batch, 300)
+        .expect("hostile ids should persist");
+
+    let records = persistence
+        .load_graph_records()
+        .expect("load graph records should succeed");
+    assert_eq!(records.len(), 2, "no statement escaped into extra nodes");
+    let ship = records
+        .iter()
+        .find(|r| r.entity_id == ship_id)
+        .expect("hostile id should read back verbatim");
+    assert_eq!(ship.properties["name"], "O'Brien");
+    assert_eq!(ship.components.len(), 1);
+    assert_eq!(
+        ship.components[0].component_id,
+        format!("{ship_id}:health_pool")
+    );
+    assert_eq!(
+        records
+            .iter()
+            .find(|r| r.entity_id == bystander_id)
+            .expect("bystander should be untouched")
+            .components
+            .len(),
+        3
+    );
+
+    persistence
+        .persist_world_delta(
+            &[WorldDeltaEntity {
+                entity_id: ship_id.clone(),
+                labels: Vec::new(),
+                properties: serde_json::json!({}),
+                components: Vec::new(),
+                removed: true,
+                removed_component_ids: Vec::new(),
+                removal_reason: None,
+            }],
+            301,
+        )
+        .expect("hostile id removal should persist");
+    let after = persistence
+        .load_graph_records()
+        .expect("load graph records should succeed");
+    assert_eq!(
+        after
+            .iter()
+            .map(|r| r.entity_id.as_str())
+            .collect::<Vec<_>>(),
+        [bystander_id.as_str()]
+    );
+
+    persistence.drop_graph().expect("test graph should drop");
+}
+
+/// Runs statements on a real connection, but swaps the component MERGE
+/// for invalid Cypher so the database fails mid-batch.
+struct FailingComponentMerge(postgres::Client);
+
+impl CypherSink for FailingComponentMerge {
+    fn batch_execute(&mut self, sql: &str, action: &'static str) -> Result<(), PersistenceError> {
+        CypherSink::batch_execute(&mut self.0, sql, action)
+    }
+
+    fn execute_cypher(&mut self, graph_name: &str, cypher: &str) -> Result<(), PersistenceError> {
+        self.0.execute_cypher(graph_name, cypher)
+    }
+
+    fn execute_cypher_with_params(
+        &mut self,
+        graph_name: &str,
+        cypher: &str,
+        params: &serde_json::Value,
+    ) -> Result<(), PersistenceError> {
+        let cypher = if cypher.contains("UNWIND $components") {
+            "MATCH (e:Entity {entity_id: $entity_id}) UNWIND $components AS component \
+             MERGE (c:Component {component_id: component.component_id}) SET c.broken="
+        } else {
+            cypher
+        };
+        self.0
+            .execute_cypher_with_params(graph_name, cypher, params)
+    }
+}
+
+#[test]
+fn failed_world_delta_leaves_no_partial_writes() {
+    let database_url = test_database_url();
+    let graph_name = unique_graph_name("sidereal_persistence_delta_rollback");
+    let mut persistence = match GraphPersistence::connect_with_graph(&database_url, &graph_name) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("skipping delta rollback test; postgres unavailable: {err}");
+            return;
+        }
+    };
+    if let Err(err) = persistence.ensure_schema() {
+        eprintln!("skipping delta rollback test; AGE schema unavailable: {err}");
+        return;
+    }
+
+    let ship_id = format!("ship:{}", Uuid::new_v4());
+    let hardpoint_id = format!("hardpoint:{}", Uuid::new_v4());
+    let engine_id = format!("engine:{}", Uuid::new_v4());
+    let batch = make_ship_batch(&ship_id, &hardpoint_id, &engine_id);
+    persistence
+        .persist_world_delta(&batch, 400)
+        .expect("initial world delta should persist");
+    let before = persistence
+        .load_graph_records()
+        .expect("load graph records should succeed");
+
+    let mut changed = batch.clone();
+    changed[0].properties["name"] = serde_json::json!("ISS Half Written");
+    changed[0].components.truncate(1);
+    changed.push(WorldDeltaEntity {
+        entity_id: format!("ship:{}", Uuid::new_v4()),
+        labels: vec!["Entity".to_string(), "Ship".to_string()],
+        properties: serde_json::json!({"name": "Never Written"}),
+        components: Vec::new(),
+        removed: false,
+        removed_component_ids: Vec::new(),
+        removal_reason: None,
+    });
+    let mut sink = FailingComponentMerge(
+        postgres::Client::connect(&database_url, postgres::NoTls).expect("second connection"),
+    );
+    let err = CypherWriter::new(&graph_name)
+        .persist_world_delta(&mut sink, &changed, 401)
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            PersistenceError::Database {
+                rolled_back: true,
+                ..
+            }
+        ),
+        "{err}"
+    );
+
+    let after = persistence
+        .load_graph_records()
+        .expect("load graph records should succeed");
+    let sorted = |mut records: Vec<GraphEntityRecord>| {
+        records.sort_by(|a, b| a.entity_id.cmp(&b.entity_id));
+        for record in &mut records {
+            record
+                .components
+                .sort_by(|a, b| a.component_id.cmp(&b.component_id));
+        }
+        records
+    };
+    assert_eq!(sorted(after), sorted(before));
+
+    drop(sink);
+    persistence.drop_graph().expect("test graph should drop");
+}
+
+#[test]
+fn ping_and_age_check_succeed_on_a_live_connection() {
+    let database_url = test_database_url();
+    let mut persistence = match GraphPersistence::connect(&database_url) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("skipping health check test; postgres unavailable: {err}");
+            return;
+        }
+    };
+    persistence.ping().expect("ping should succeed");
+    assert!(
+        persistence
+            .age_available()
+            .expect("age availability should load"),
+        "test database should have the age extension installed"
+    );
+}
+
+/// Entity ids at the far end of `entity_id`'s outgoing `edge_label` edges.
+fn outgoing_edge_targets(
+    database_url: &str,
+    graph_name: &str,
+    entity_id: &str,
+    edge_label: &str,
+) -> Vec<String> {
+    let mut client =
+        postgres::Client::connect(database_url, postgres::NoTls).expect("edge query connection");
+    client
+        .batch_execute("LOAD 'age'; SET search_path = ag_catalog, \"$user\", public;")
+        .expect("age should load");
+    let query = format!(
+        "SELECT target::text FROM cypher('{graph_name}', $$ \
+         MATCH (:Entity {{entity_id: '{entity_id}'}})-[:{edge_label}]->(t:Entity) \
+         RETURN t.entity_id $$) AS (target agtype)"
+    );
+    client
+        .query(&query, &[])
+        .expect("edge query should succeed")
+        .iter()
+        .map(|row| row.get::<_, String>(0).trim_matches('"').to_string())
+        .collect()
+}
+
+#[test]
+fn moving_a_module_replaces_its_mounted_on_edge() {
+    let database_url = test_database_url();
+    let graph_name = unique_graph_name("sidereal_persistence_edge_move");
+    let mut persistence = match GraphPersistence::connect_with_graph(&database_url, &graph_name) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("skipping edge move test; postgres unavailable: {err}");
+            return;
+        }
+    };
+    if let Err(err) = persistence.ensure_schema() {
+        eprintln!("skipping edge move test; AGE schema unavailable: {err}");
+        return;
+    }
+
+    let hardpoint_a = format!("hardpoint:{}", Uuid::new_v4());
+    let hardpoint_b = format!("hardpoint:{}", Uuid::new_v4());
+    let mut batch = make_ship_batch(&format!("ship:{}", Uuid::new_v4()), &hardpoint_a, "unused");
+    batch.truncate(2);
+    batch.extend(
+        make_ship_batch(&format!("ship:{}", Uuid::new_v4()), &hardpoint_b, "unused")
+            .into_iter()
+            .take(2),
+    );
+    let engine_id = format!("engine:{}", Uuid::new_v4());
+    let engine = |mounted_on: &str| WorldDeltaEntity {
+        entity_id: engine_id.clone(),
+        labels: vec!["Entity".to_string(), "Engine".to_string()],
+        properties: serde_json::json!({"mounted_on_entity_id": mounted_on}),
+        components: Vec::new(),
+        removed: false,
+        removed_component_ids: Vec::new(),
+        removal_reason: None,
+    };
+    batch.push(engine(&hardpoint_a));
+    persistence
+        .persist_world_delta(&batch, 1)
+        .expect("ships and engine should persist");
+    assert_eq!(
+        outgoing_edge_targets(&database_url, &graph_name, &engine_id, "MOUNTED_ON"),
+        [hardpoint_a.as_str()]
+    );
+
+    persistence
+        .persist_world_delta(&[engine(&hardpoint_b)], 2)
+        .expect("moved engine should persist");
+    assert_eq!(
+        outgoing_edge_targets(&database_url, &graph_name, &engine_id, "MOUNTED_ON"),
+        [hardpoint_b.as_str()]
+    );
+    let ship_a = batch[0].entity_id.clone();
+    assert_eq!(
+        outgoing_edge_targets(&database_url, &graph_name, &ship_a, "HAS_HARDPOINT"),
+        [hardpoint_a],
+        "re-persisting the engine leaves other entities' edges alone"
+    );
+
+    persistence.drop_graph().expect("test graph should drop");
+}
+
+#[test]
+fn streamed_records_match_the_batch_load() {
+    let database_url = test_database_url();
+    let graph_name = unique_graph_name("sidereal_persistence_stream");
+    let mut persistence = match GraphPersistence::connect_with_graph(&database_url, &graph_name) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("skipping stream test; postgres unavailable: {err}");
+            return;
+        }
+    };
+    if let Err(err) = persistence.ensure_schema() {
+        eprintln!("skipping stream test; AGE schema unavailable: {err}");
+        return;
+    }
+
+    // More rows than one cursor batch, so entities straddle fetches.
+    let mut batch = Vec::new();
+    for _ in 0..(STREAM_BATCH_ROWS as usize / 4 + 10) {
+        batch.extend(make_ship_batch(
+            &format!("ship:{}", Uuid::new_v4()),
+            &format!("hardpoint:{}", Uuid::new_v4()),
+            &format!("engine:{}", Uuid::new_v4()),
+        ));
+    }
+    batch.push(WorldDeltaEntity {
+        entity_id: format!("asteroid:{}", Uuid::new_v4()),
+        labels: vec!["Entity".to_string(), "Asteroid".to_string()],
+        properties: serde_json::json!({"radius_m": 40.0}),
+        components: Vec::new(),
+        removed: false,
+        removed_component_ids: Vec::new(),
+        removal_reason: None,
+    });
+    persistence
+        .persist_world_delta(&batch, 1)
+        .expect("world delta should persist");
+
+    let loaded = persistence
+        .load_graph_records()
+        .expect("load graph records should succeed");
+    let streamed = persistence
+        .stream_graph_records()
+        .expect("stream should open")
+        .collect::<Result<Vec<_>, _>>()
+        .expect("stream should read every record");
+    // Component order within an entity is not defined by either query.
+    let sorted = |mut records: Vec<GraphEntityRecord>| {
+        for record in &mut records {
+            record
+                .components
+                .sort_by(|a, b| a.component_id.cmp(&b.component_id));
+        }
+        records
+    };
+    assert_eq!(streamed.len(), batch.len());
+    assert_eq!(sorted(streamed), sorted(loaded));
+
+    persistence.drop_graph().expect("test graph should drop");
+}
+
+#[test]
+fn persist_load_cycles_are_byte_stable() {
+    let database_url = test_database_url();
+    let graph_name = unique_graph_name("sidereal_persistence_round_trip");
+    let mut persistence = match GraphPersistence::connect_with_graph(&database_url, &graph_name) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("skipping round trip test; postgres unavailable: {err}");
+            return;
+        }
+    };
+    if let Err(err) = persistence.ensure_schema() {
+        eprintln!("skipping round trip test; AGE schema unavailable: {err}");
+        return;
+    }
+
+    let ship_id = format!("ship:{}", Uuid::new_v4());
+    let mut batch = make_ship_batch(&ship_id, "unused", "unused");
+    batch.truncate(1);
+    batch[0].labels = vec!["Ship".to_string(), "Entity".to_string(), "Ship".to_string()];
+    batch[0].components.reverse();
+    persistence
+        .persist_world_delta(&batch, 10)
+        .expect("world delta should persist");
+    let first = persistence
+        .load_graph_record(&ship_id)
+        .expect("load should succeed")
+        .expect("ship should exist");
+
+    persistence
+        .persist_graph_records(std::slice::from_ref(&first), 10)
+        .expect("loaded record should persist");
+    let second = persistence
+        .load_graph_record(&ship_id)
+        .expect("load should succeed")
+        .expect("ship should exist");
+
+    assert_eq!(
+        serde_json::to_string(&second).expect("record should serialize"),
+        serde_json::to_string(&first).expect("record should serialize")
+    );
+    assert_eq!(first.labels, ["Entity", "Ship"]);
+    assert!(
+        first
+            .components
+            .windows(2)
+            .all(|pair| pair[0].component_id < pair[1].component_id)
+    );
+
+    persistence.drop_graph().expect("test graph should drop");
+}
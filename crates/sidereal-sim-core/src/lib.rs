@@ -3,6 +3,7 @@
 /// All movement/control logic must be deterministic and match between client and server.
 /// No ECS queries, resources, or side effects - pure functions only.
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
 pub struct InputSnapshot {
@@ -10,20 +11,187 @@ pub struct InputSnapshot {
     pub thrust_reverse: bool,
     pub yaw_left: bool,
     pub yaw_right: bool,
+    #[serde(default)]
+    pub brake: bool,
+    #[serde(default)]
+    pub strafe_left: bool,
+    #[serde(default)]
+    pub strafe_right: bool,
+    /// Pitch and roll are only read by `step_entity_kinematics_3d`.
+    #[serde(default)]
+    pub pitch_up: bool,
+    #[serde(default)]
+    pub pitch_down: bool,
+    #[serde(default)]
+    pub roll_left: bool,
+    #[serde(default)]
+    pub roll_right: bool,
 }
 
 impl InputSnapshot {
     pub fn is_neutral(&self) -> bool {
-        !self.thrust_forward && !self.thrust_reverse && !self.yaw_left && !self.yaw_right
+        !self.thrust_forward
+            && !self.thrust_reverse
+            && !self.yaw_left
+            && !self.yaw_right
+            && !self.brake
+            && !self.strafe_left
+            && !self.strafe_right
+            && !self.pitch_up
+            && !self.pitch_down
+            && !self.roll_left
+            && !self.roll_right
     }
 }
 
+/// One input sample and the tick it was taken on.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct RecordedInput<T = InputSnapshot> {
+    pub tick: u64,
+    pub input: T,
+}
+
+/// Tick-ordered input log for reproducing a session. Generic over the sample
+/// so callers can record richer inputs (e.g. whole network messages).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InputRecording<T = InputSnapshot> {
+    pub frames: Vec<RecordedInput<T>>,
+}
+
+impl<T> Default for InputRecording<T> {
+    fn default() -> Self {
+        Self { frames: Vec::new() }
+    }
+}
+
+impl<T> InputRecording<T> {
+    /// Appends a sample. Ticks must not go backwards; an out-of-order sample
+    /// is rejected and `false` returned.
+    pub fn push(&mut self, tick: u64, input: T) -> bool {
+        if self.frames.last().is_some_and(|last| tick < last.tick) {
+            return false;
+        }
+        self.frames.push(RecordedInput { tick, input });
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+}
+
+/// Inputs the client has simulated but the server has not confirmed yet,
+/// one per tick. On an authoritative state for tick `t`, `acknowledge(t)`
+/// drops what the server has applied and `pending_after(t)` hands back the
+/// rest to `resimulate` from the server's state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayBuffer {
+    inputs: VecDeque<RecordedInput>,
+    capacity: usize,
+}
+
+impl Default for ReplayBuffer {
+    /// 128 ticks, about two seconds at 60 Hz.
+    fn default() -> Self {
+        Self::with_capacity(128)
+    }
+}
+
+impl ReplayBuffer {
+    /// Keeps at most `capacity` inputs, dropping the oldest.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            inputs: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Records the input simulated for `tick`. Ticks must increase; a tick at
+    /// or before the newest one is rejected and `false` returned.
+    pub fn push(&mut self, tick: u64, input: InputSnapshot) -> bool {
+        if self.inputs.back().is_some_and(|last| tick <= last.tick) {
+            return false;
+        }
+        if self.inputs.len() == self.capacity {
+            self.inputs.pop_front();
+        }
+        self.inputs.push_back(RecordedInput { tick, input });
+        true
+    }
+
+    /// Drops every input at or before `tick`, which the server has applied.
+    pub fn acknowledge(&mut self, tick: u64) {
+        while self
+            .inputs
+            .front()
+            .is_some_and(|oldest| oldest.tick <= tick)
+        {
+            self.inputs.pop_front();
+        }
+    }
+
+    /// The inputs for `tick + 1` onward, in order, or `None` when they are
+    /// not contiguous (a tick was skipped or already evicted), in which case
+    /// replaying would not reproduce the server and the caller should snap.
+    pub fn pending_after(&self, tick: u64) -> Option<Vec<InputSnapshot>> {
+        let pending = self
+            .inputs
+            .iter()
+            .filter(|recorded| recorded.tick > tick)
+            .collect::<Vec<_>>();
+        let contiguous = (tick + 1..)
+            .zip(&pending)
+            .all(|(expected, recorded)| recorded.tick == expected);
+        if !contiguous {
+            return None;
+        }
+        Some(pending.into_iter().map(|recorded| recorded.input).collect())
+    }
+
+    /// Newest recorded tick.
+    pub fn latest_tick(&self) -> Option<u64> {
+        self.inputs.back().map(|recorded| recorded.tick)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+}
+
+/// Replays `inputs`, one per tick of `dt_s`, from `from_state` with
+/// `step_entity_kinematics`. Given the server's state for a tick and the
+/// inputs after it, this reproduces the server's later states exactly.
+pub fn resimulate(
+    from_state: &EntityKinematics,
+    inputs: impl IntoIterator<Item = InputSnapshot>,
+    tuning: &ControlTuning,
+    dt_s: f32,
+) -> EntityKinematics {
+    inputs.into_iter().fold(*from_state, |state, input| {
+        step_entity_kinematics(&state, input, tuning, dt_s)
+    })
+}
+
 /// Kinematic state for any controllable entity (ships, missiles, stations, asteroids, etc.)
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub struct EntityKinematics {
     pub position_m: [f32; 3],
     pub velocity_mps: [f32; 3],
     pub heading_rad: f32,
+    /// Unit quaternion `[x, y, z, w]` rotating the body frame (+Y forward,
+    /// +X right, +Z up) into the world. Integrated by
+    /// `step_entity_kinematics_3d`; the 2D step leaves it alone.
+    #[serde(default = "identity_orientation")]
+    pub orientation: [f32; 4],
 }
 
 impl Default for EntityKinematics {
@@ -32,17 +200,39 @@ impl Default for EntityKinematics {
             position_m: [0.0, 0.0, 0.0],
             velocity_mps: [0.0, 0.0, 0.0],
             heading_rad: 0.0,
+            orientation: identity_orientation(),
         }
     }
 }
 
+impl EntityKinematics {
+    /// World-space direction the nose points, from `orientation`.
+    pub fn body_forward(&self) -> [f32; 3] {
+        quat_rotate(self.orientation, [0.0, 1.0, 0.0])
+    }
+}
+
+fn identity_orientation() -> [f32; 4] {
+    [0.0, 0.0, 0.0, 1.0]
+}
+
 /// Control tuning parameters for any controllable entity
 #[derive(Debug, Clone, Copy)]
 pub struct ControlTuning {
     /// Thrust acceleration in m/s²
     pub thrust_accel_mps2: f32,
+    /// Lateral (maneuvering thruster) acceleration in m/s²
+    pub strafe_accel_mps2: f32,
+    /// Speed shed per second while braking, in m/s², on top of drag
+    pub brake_decel_per_s: f32,
+    /// Speed ceiling in m/s, enforced after drag; `None` leaves speed to drag
+    pub max_speed_mps: Option<f32>,
     /// Yaw rate in rad/s
     pub yaw_rate_rad_per_s: f32,
+    /// Pitch rate in rad/s (3D stepping only)
+    pub pitch_rate_rad_per_s: f32,
+    /// Roll rate in rad/s (3D stepping only)
+    pub roll_rate_rad_per_s: f32,
     /// Drag coefficient (0-1 fraction per second)
     pub drag_per_s: f32,
 }
@@ -51,7 +241,12 @@ impl Default for ControlTuning {
     fn default() -> Self {
         Self {
             thrust_accel_mps2: 14.0,
+            strafe_accel_mps2: 6.0,
+            brake_decel_per_s: 10.0,
+            max_speed_mps: Some(30.0),
             yaw_rate_rad_per_s: 1.8,
+            pitch_rate_rad_per_s: 1.5,
+            roll_rate_rad_per_s: 2.5,
             drag_per_s: 0.4,
         }
     }
@@ -67,7 +262,12 @@ impl ControlTuning {
     pub fn asteroid_with_engine() -> Self {
         Self {
             thrust_accel_mps2: 2.0,
+            strafe_accel_mps2: 0.2,
+            brake_decel_per_s: 0.5,
+            max_speed_mps: Some(10.0),
             yaw_rate_rad_per_s: 0.3,
+            pitch_rate_rad_per_s: 0.3,
+            roll_rate_rad_per_s: 0.3,
             drag_per_s: 0.1,
         }
     }
@@ -76,10 +276,26 @@ impl ControlTuning {
     pub fn missile() -> Self {
         Self {
             thrust_accel_mps2: 50.0,
+            strafe_accel_mps2: 0.5,
+            brake_decel_per_s: 0.0,
+            max_speed_mps: Some(600.0),
             yaw_rate_rad_per_s: 4.0,
+            pitch_rate_rad_per_s: 4.0,
+            roll_rate_rad_per_s: 6.0,
             drag_per_s: 0.05,
         }
     }
+
+    /// Takes drag from an entity's persisted linear damping (the per-second
+    /// coefficient fed to Avian's `LinearDamping`). Over one tick both remove
+    /// the same fraction of velocity to first order, so prediction matches the
+    /// server's physics for that entity.
+    pub fn with_linear_damping(self, linear_damping_per_s: f32) -> Self {
+        Self {
+            drag_per_s: linear_damping_per_s.max(0.0),
+            ..self
+        }
+    }
 }
 
 /// Step entity kinematics forward by one timestep (deterministic)
@@ -105,24 +321,22 @@ pub fn step_entity_kinematics(
     let forward = [next.heading_rad.sin(), next.heading_rad.cos(), 0.0];
 
     // 3. Apply thrust acceleration
-    let thrust_accel = if input.thrust_forward {
-        tuning.thrust_accel_mps2
-    } else if input.thrust_reverse {
-        -tuning.thrust_accel_mps2 * 0.7 // Reverse is 70% power
-    } else {
-        0.0
-    };
+    let thrust_accel = thrust_accel_mps2(input, tuning);
 
     // 4. Integrate velocity
-    for (i, component) in forward.iter().enumerate() {
-        next.velocity_mps[i] += component * thrust_accel * dt_s;
+    let right = [next.heading_rad.cos(), -next.heading_rad.sin(), 0.0];
+    let strafe_accel = strafe_accel_mps2(input, tuning);
+    for i in 0..3 {
+        next.velocity_mps[i] += (forward[i] * thrust_accel + right[i] * strafe_accel) * dt_s;
     }
+    apply_brake(&mut next.velocity_mps, input, tuning, dt_s);
 
     // 5. Apply drag
     let drag_factor = (1.0 - tuning.drag_per_s * dt_s).clamp(0.0, 1.0);
     for i in 0..3 {
         next.velocity_mps[i] *= drag_factor;
     }
+    clamp_speed(&mut next.velocity_mps, tuning);
 
     // 6. Integrate position
     for i in 0..3 {
@@ -132,6 +346,430 @@ pub fn step_entity_kinematics(
     next
 }
 
+/// Step entity kinematics forward by one timestep with full 3D attitude
+/// (deterministic). Yaw, pitch and roll rates are applied about the body
+/// axes and integrated into `orientation`; thrust, drag and position then
+/// work as in `step_entity_kinematics`, along the body-forward vector.
+/// Yaw keeps the 2D sign convention (`yaw_left` turns the nose toward +X
+/// when level), and `heading_rad` is kept as the yaw of the nose so 2D
+/// consumers (motion checks, intercept aim) keep working.
+pub fn step_entity_kinematics_3d(
+    state: &EntityKinematics,
+    input: InputSnapshot,
+    tuning: &ControlTuning,
+    dt_s: f32,
+) -> EntityKinematics {
+    let mut next = *state;
+
+    // 1. Body-frame angular velocity: x = pitch (right axis), y = roll
+    // (forward axis), z = yaw (up axis).
+    let axis_rate = |positive: bool, negative: bool, rate: f32| {
+        if positive {
+            rate
+        } else if negative {
+            -rate
+        } else {
+            0.0
+        }
+    };
+    let angular_rad_per_s = [
+        axis_rate(
+            input.pitch_up,
+            input.pitch_down,
+            tuning.pitch_rate_rad_per_s,
+        ),
+        axis_rate(
+            input.roll_right,
+            input.roll_left,
+            tuning.roll_rate_rad_per_s,
+        ),
+        axis_rate(input.yaw_right, input.yaw_left, tuning.yaw_rate_rad_per_s),
+    ];
+
+    // 2. Integrate orientation
+    let rate_rad_per_s = length(angular_rad_per_s);
+    let angle_rad = rate_rad_per_s * dt_s;
+    if angle_rad > 0.0 {
+        let axis_scale = (angle_rad * 0.5).sin() / rate_rad_per_s;
+        let delta = [
+            angular_rad_per_s[0] * axis_scale,
+            angular_rad_per_s[1] * axis_scale,
+            angular_rad_per_s[2] * axis_scale,
+            (angle_rad * 0.5).cos(),
+        ];
+        next.orientation = quat_normalize(quat_mul(state.orientation, delta));
+    }
+    let forward = next.body_forward();
+    if forward[0] != 0.0 || forward[1] != 0.0 {
+        next.heading_rad = forward[0].atan2(forward[1]);
+    }
+
+    // 3. Apply thrust along the nose and strafe along the right wing
+    let thrust_accel = thrust_accel_mps2(input, tuning);
+    let right = quat_rotate(next.orientation, [1.0, 0.0, 0.0]);
+    let strafe_accel = strafe_accel_mps2(input, tuning);
+    for i in 0..3 {
+        next.velocity_mps[i] += (forward[i] * thrust_accel + right[i] * strafe_accel) * dt_s;
+    }
+    apply_brake(&mut next.velocity_mps, input, tuning, dt_s);
+
+    // 4. Apply drag
+    let drag_factor = (1.0 - tuning.drag_per_s * dt_s).clamp(0.0, 1.0);
+    for i in 0..3 {
+        next.velocity_mps[i] *= drag_factor;
+    }
+    clamp_speed(&mut next.velocity_mps, tuning);
+
+    // 5. Integrate position
+    for i in 0..3 {
+        next.position_m[i] += next.velocity_mps[i] * dt_s;
+    }
+
+    next
+}
+
+/// Step entity kinematics forward by one timestep with classic fourth-order
+/// Runge-Kutta (deterministic). Same inputs, thrust and drag model as
+/// `step_entity_kinematics`, but drag acts continuously (`dv/dt = thrust -
+/// drag * v`) and thrust follows the heading as it turns through the step,
+/// instead of the one-shot Euler updates, so large `dt_s` or strong drag stay
+/// close to the exact solution. The heading ends where the Euler step puts it.
+pub fn step_entity_kinematics_rk4(
+    state: &EntityKinematics,
+    input: InputSnapshot,
+    tuning: &ControlTuning,
+    dt_s: f32,
+) -> EntityKinematics {
+    let yaw_rate = if input.yaw_left {
+        tuning.yaw_rate_rad_per_s
+    } else if input.yaw_right {
+        -tuning.yaw_rate_rad_per_s
+    } else {
+        0.0
+    };
+    let thrust_accel = thrust_accel_mps2(input, tuning);
+    let strafe_accel = strafe_accel_mps2(input, tuning);
+    // Acceleration at `t_s` into the step with velocity `v`.
+    let accel = |t_s: f32, v: [f32; 3]| -> [f32; 3] {
+        let heading = state.heading_rad + yaw_rate * t_s;
+        let (sin, cos) = heading.sin_cos();
+        [
+            sin * thrust_accel + cos * strafe_accel - tuning.drag_per_s * v[0],
+            cos * thrust_accel - sin * strafe_accel - tuning.drag_per_s * v[1],
+            -tuning.drag_per_s * v[2],
+        ]
+    };
+    let offset = |base: [f32; 3], slope: [f32; 3], h: f32| -> [f32; 3] {
+        std::array::from_fn(|i| base[i] + slope[i] * h)
+    };
+
+    // Stage velocities respect the speed cap too, so the position they
+    // integrate never moves faster than the clamped velocity allows.
+    let clamped = |mut v: [f32; 3]| -> [f32; 3] {
+        clamp_speed(&mut v, tuning);
+        v
+    };
+
+    let half_dt = dt_s * 0.5;
+    // Braking is applied up front; it only ever shortens the velocity, so
+    // it cannot feed back into the smooth thrust/drag solution below.
+    let mut v0 = state.velocity_mps;
+    apply_brake(&mut v0, input, tuning, dt_s);
+    let k1_x = clamped(v0);
+    let k1_v = accel(0.0, k1_x);
+    let k2_x = clamped(offset(v0, k1_v, half_dt));
+    let k2_v = accel(half_dt, k2_x);
+    let k3_x = clamped(offset(v0, k2_v, half_dt));
+    let k3_v = accel(half_dt, k3_x);
+    let k4_x = clamped(offset(v0, k3_v, dt_s));
+    let k4_v = accel(dt_s, k4_x);
+
+    let mut next = *state;
+    next.heading_rad += yaw_rate * dt_s;
+    next.velocity_mps = v0;
+    for i in 0..3 {
+        next.velocity_mps[i] += dt_s / 6.0 * (k1_v[i] + 2.0 * k2_v[i] + 2.0 * k3_v[i] + k4_v[i]);
+        next.position_m[i] += dt_s / 6.0 * (k1_x[i] + 2.0 * k2_x[i] + 2.0 * k3_x[i] + k4_x[i]);
+    }
+    clamp_speed(&mut next.velocity_mps, tuning);
+    next
+}
+
+/// Signed main-engine acceleration; reverse is 70% power and braking cuts
+/// the main engine.
+fn thrust_accel_mps2(input: InputSnapshot, tuning: &ControlTuning) -> f32 {
+    if input.brake {
+        0.0
+    } else if input.thrust_forward {
+        tuning.thrust_accel_mps2
+    } else if input.thrust_reverse {
+        -tuning.thrust_accel_mps2 * 0.7
+    } else {
+        0.0
+    }
+}
+
+/// While braking, removes up to `brake_decel_per_s * dt_s` of speed against
+/// the direction of travel, stopping at zero rather than reversing.
+fn apply_brake(
+    velocity_mps: &mut [f32; 3],
+    input: InputSnapshot,
+    tuning: &ControlTuning,
+    dt_s: f32,
+) {
+    if !input.brake {
+        return;
+    }
+    let speed = length(*velocity_mps);
+    if speed <= 0.0 {
+        return;
+    }
+    let remaining = (speed - tuning.brake_decel_per_s.max(0.0) * dt_s).max(0.0);
+    let scale = remaining / speed;
+    for component in velocity_mps.iter_mut() {
+        *component *= scale;
+    }
+}
+
+/// Scales velocity down to `max_speed_mps` when it is set and exceeded,
+/// keeping its direction.
+fn clamp_speed(velocity_mps: &mut [f32; 3], tuning: &ControlTuning) {
+    let Some(max_speed) = tuning.max_speed_mps else {
+        return;
+    };
+    let max_speed = max_speed.max(0.0);
+    let speed = length(*velocity_mps);
+    if speed > max_speed {
+        let scale = max_speed / speed;
+        for component in velocity_mps.iter_mut() {
+            *component *= scale;
+        }
+    }
+}
+
+/// Signed lateral acceleration; positive pushes toward the right vector.
+fn strafe_accel_mps2(input: InputSnapshot, tuning: &ControlTuning) -> f32 {
+    if input.strafe_right {
+        tuning.strafe_accel_mps2
+    } else if input.strafe_left {
+        -tuning.strafe_accel_mps2
+    } else {
+        0.0
+    }
+}
+
+/// Hamilton product `a * b` of `[x, y, z, w]` quaternions.
+fn quat_mul(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    [
+        a[3] * b[0] + a[0] * b[3] + a[1] * b[2] - a[2] * b[1],
+        a[3] * b[1] - a[0] * b[2] + a[1] * b[3] + a[2] * b[0],
+        a[3] * b[2] + a[0] * b[1] - a[1] * b[0] + a[2] * b[3],
+        a[3] * b[3] - a[0] * b[0] - a[1] * b[1] - a[2] * b[2],
+    ]
+}
+
+fn quat_normalize(q: [f32; 4]) -> [f32; 4] {
+    let norm = (q[0] * q[0] + q[1] * q[1] + q[2] * q[2] + q[3] * q[3]).sqrt();
+    if norm > 0.0 {
+        q.map(|c| c / norm)
+    } else {
+        identity_orientation()
+    }
+}
+
+/// Rotates `v` by the unit quaternion `q`.
+fn quat_rotate(q: [f32; 4], v: [f32; 3]) -> [f32; 3] {
+    // v' = v + w t + u x t, with u the vector part and t = 2 (u x v).
+    let u = [q[0], q[1], q[2]];
+    let t = cross(u, v).map(|c| 2.0 * c);
+    let u_cross_t = cross(u, t);
+    std::array::from_fn(|i| v[i] + q[3] * t[i] + u_cross_t[i])
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// Upper bounds on how far one step may move an entity, used by the server to
+/// reject motion its engines cannot produce.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MotionLimits {
+    /// Maximum acceleration the engines can deliver, in m/s²
+    pub max_accel_mps2: f32,
+    /// Maximum yaw rate in rad/s
+    pub max_yaw_rate_rad_per_s: f32,
+    /// Drag coefficient (0-1 fraction per second)
+    pub drag_per_s: f32,
+    /// Multiplier (>= 1) applied to every limit to absorb integration error
+    pub tolerance: f32,
+}
+
+impl MotionLimits {
+    pub fn from_tuning(tuning: &ControlTuning, tolerance: f32) -> Self {
+        Self {
+            // Main engine (or brakes) and maneuvering thrusters can fire
+            // together.
+            max_accel_mps2: tuning
+                .thrust_accel_mps2
+                .max(tuning.brake_decel_per_s)
+                .hypot(tuning.strafe_accel_mps2),
+            max_yaw_rate_rad_per_s: tuning.yaw_rate_rad_per_s,
+            drag_per_s: tuning.drag_per_s,
+            tolerance,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MotionViolation {
+    /// Velocity changed faster than thrust plus drag allows.
+    ExcessAcceleration { observed_mps2: f32, limit_mps2: f32 },
+    /// Heading changed faster than the yaw rate allows.
+    ExcessYawRate {
+        observed_rad_per_s: f32,
+        limit_rad_per_s: f32,
+    },
+    /// Position moved further than the step's velocities can cover.
+    Teleport { displacement_m: f32, limit_m: f32 },
+}
+
+impl std::fmt::Display for MotionViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ExcessAcceleration {
+                observed_mps2,
+                limit_mps2,
+            } => write!(
+                f,
+                "acceleration {observed_mps2:.2} m/s² exceeds {limit_mps2:.2} m/s²"
+            ),
+            Self::ExcessYawRate {
+                observed_rad_per_s,
+                limit_rad_per_s,
+            } => write!(
+                f,
+                "yaw rate {observed_rad_per_s:.2} rad/s exceeds {limit_rad_per_s:.2} rad/s"
+            ),
+            Self::Teleport {
+                displacement_m,
+                limit_m,
+            } => write!(
+                f,
+                "moved {displacement_m:.2} m, at most {limit_m:.2} m reachable"
+            ),
+        }
+    }
+}
+
+/// Checks whether `next` is reachable from `prev` in `dt_s` under `limits`.
+/// Drag is credited before comparing the velocity change against thrust, so a
+/// coasting entity never trips the acceleration check.
+pub fn check_motion_transition(
+    prev: &EntityKinematics,
+    next: &EntityKinematics,
+    limits: &MotionLimits,
+    dt_s: f32,
+) -> Result<(), MotionViolation> {
+    if dt_s <= 0.0 {
+        return Ok(());
+    }
+    let tolerance = limits.tolerance.max(1.0);
+    let drag_factor = (1.0 - limits.drag_per_s * dt_s).clamp(0.0, 1.0);
+
+    let accel_mps2 = length(std::array::from_fn(|i| {
+        next.velocity_mps[i] - prev.velocity_mps[i] * drag_factor
+    })) / dt_s;
+    let accel_limit = limits.max_accel_mps2 * tolerance;
+    if accel_mps2 > accel_limit {
+        return Err(MotionViolation::ExcessAcceleration {
+            observed_mps2: accel_mps2,
+            limit_mps2: accel_limit,
+        });
+    }
+
+    let heading_delta = (next.heading_rad - prev.heading_rad + std::f32::consts::PI)
+        .rem_euclid(std::f32::consts::TAU)
+        - std::f32::consts::PI;
+    let yaw_rate = heading_delta.abs() / dt_s;
+    let yaw_limit = limits.max_yaw_rate_rad_per_s * tolerance;
+    if yaw_rate > yaw_limit {
+        return Err(MotionViolation::ExcessYawRate {
+            observed_rad_per_s: yaw_rate,
+            limit_rad_per_s: yaw_limit,
+        });
+    }
+
+    let displacement_m = length(std::array::from_fn(|i| {
+        next.position_m[i] - prev.position_m[i]
+    }));
+    let max_speed = length(prev.velocity_mps).max(length(next.velocity_mps));
+    let displacement_limit = max_speed * dt_s * tolerance;
+    if displacement_m > displacement_limit {
+        return Err(MotionViolation::Teleport {
+            displacement_m,
+            limit_m: displacement_limit,
+        });
+    }
+    Ok(())
+}
+
+/// Heading the shooter must face for a projectile fired at
+/// `projectile_speed_mps` (relative to the shooter, which it inherits the
+/// velocity of) to meet `target`, assuming both keep their current velocity.
+/// Works in the XY plane using the same convention as
+/// `step_entity_kinematics`: heading 0 faces +Y and positive headings turn
+/// toward +X. Returns `None` when no intercept exists, e.g. the target
+/// outruns the projectile.
+pub fn intercept_heading(
+    shooter: &EntityKinematics,
+    target: &EntityKinematics,
+    projectile_speed_mps: f32,
+) -> Option<f32> {
+    if projectile_speed_mps.is_nan() || projectile_speed_mps <= 0.0 {
+        return None;
+    }
+    let rx = target.position_m[0] - shooter.position_m[0];
+    let ry = target.position_m[1] - shooter.position_m[1];
+    let vx = target.velocity_mps[0] - shooter.velocity_mps[0];
+    let vy = target.velocity_mps[1] - shooter.velocity_mps[1];
+    if rx == 0.0 && ry == 0.0 {
+        return Some(shooter.heading_rad);
+    }
+
+    // |r + v t| = s t  =>  (v.v - s^2) t^2 + 2 (r.v) t + r.r = 0
+    let a = vx * vx + vy * vy - projectile_speed_mps * projectile_speed_mps;
+    let b = 2.0 * (rx * vx + ry * vy);
+    let c = rx * rx + ry * ry;
+    let time_s = if a.abs() < f32::EPSILON {
+        // Target closes at exactly projectile speed: only linear closing works.
+        (b < 0.0).then(|| -c / b)?
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        let t1 = (-b - root) / (2.0 * a);
+        let t2 = (-b + root) / (2.0 * a);
+        [t1, t2]
+            .into_iter()
+            .filter(|t| *t > 0.0)
+            .min_by(f32::total_cmp)?
+    };
+
+    let aim_x = rx + vx * time_s;
+    let aim_y = ry + vy * time_s;
+    Some(aim_x.atan2(aim_y))
+}
+
+fn length(v: [f32; 3]) -> f32 {
+    (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt()
+}
+
 /// Legacy single-axis velocity integration (kept for compatibility)
 pub fn integrate_forward_velocity_mps(
     current_velocity_mps: f32,
@@ -164,6 +802,7 @@ mod tests {
             position_m: [0.0, 0.0, 0.0],
             velocity_mps: [10.0, 0.0, 0.0],
             heading_rad: 0.0,
+            ..Default::default()
         };
         let input = InputSnapshot::default();
         let tuning = ControlTuning::default();
@@ -191,6 +830,204 @@ mod tests {
         assert!(next.velocity_mps[1] > 0.0); // Forward is Y axis (heading=0)
     }
 
+    #[test]
+    fn strafe_pushes_sideways_without_turning() {
+        let tuning = ControlTuning::corvette();
+        let heading_rad = 0.6;
+        let state = EntityKinematics {
+            heading_rad,
+            ..Default::default()
+        };
+        let strafe_right = InputSnapshot {
+            strafe_right: true,
+            ..Default::default()
+        };
+
+        let next = step_entity_kinematics(&state, strafe_right, &tuning, 1.0);
+
+        assert_eq!(next.heading_rad, heading_rad);
+        let forward = [heading_rad.sin(), heading_rad.cos()];
+        let along_nose = next.velocity_mps[0] * forward[0] + next.velocity_mps[1] * forward[1];
+        assert!(along_nose.abs() < 1e-5);
+        let right = [heading_rad.cos(), -heading_rad.sin()];
+        let sideways = next.velocity_mps[0] * right[0] + next.velocity_mps[1] * right[1];
+        let expected = tuning.strafe_accel_mps2 * (1.0 - tuning.drag_per_s);
+        assert!((sideways - expected).abs() < 1e-4);
+
+        let strafe_left = InputSnapshot {
+            strafe_left: true,
+            ..Default::default()
+        };
+        let left = step_entity_kinematics(&state, strafe_left, &tuning, 1.0);
+        assert_eq!(left.velocity_mps, next.velocity_mps.map(|v| -v));
+        assert!(ControlTuning::missile().strafe_accel_mps2 < tuning.strafe_accel_mps2);
+    }
+
+    #[test]
+    fn rk4_tracks_analytic_drag_where_euler_drifts() {
+        // Unclamped, so the analytic drag solution applies throughout.
+        let tuning = ControlTuning {
+            max_speed_mps: None,
+            ..ControlTuning::corvette()
+        };
+        let input = InputSnapshot {
+            thrust_forward: true,
+            ..Default::default()
+        };
+        let dt = 0.1;
+        let (accel, drag) = (tuning.thrust_accel_mps2 as f64, tuning.drag_per_s as f64);
+        let v0 = -20.0f64;
+        // dv/dt = a - k v  =>  v(t) = a/k + (v0 - a/k) e^(-k t)
+        let analytic = |t: f64| {
+            let terminal = accel / drag;
+            let decay = (-drag * t).exp();
+            let velocity = terminal + (v0 - terminal) * decay;
+            let position = terminal * t + (v0 - terminal) * (1.0 - decay) / drag;
+            (position, velocity)
+        };
+
+        let start = EntityKinematics {
+            velocity_mps: [0.0, v0 as f32, 0.0],
+            ..Default::default()
+        };
+        let (mut euler, mut rk4) = (start, start);
+        let (mut euler_err, mut rk4_err) = (0.0f64, 0.0f64);
+        for step in 1..=1000 {
+            euler = step_entity_kinematics(&euler, input, &tuning, dt);
+            rk4 = step_entity_kinematics_rk4(&rk4, input, &tuning, dt);
+            let (position, velocity) = analytic(step as f64 * dt as f64);
+            euler_err = euler_err.max((euler.position_m[1] as f64 - position).abs());
+            rk4_err = rk4_err.max((rk4.position_m[1] as f64 - position).abs());
+            assert!((rk4.velocity_mps[1] as f64 - velocity).abs() < 1e-3);
+        }
+        assert!(euler_err > 50.0, "euler drift {euler_err}");
+        assert!(rk4_err < 0.5, "rk4 drift {rk4_err}");
+        assert_eq!(rk4.position_m[0], 0.0);
+    }
+
+    #[test]
+    fn rk4_turns_like_euler_and_is_repeatable() {
+        let tuning = ControlTuning::corvette();
+        let input = InputSnapshot {
+            thrust_forward: true,
+            yaw_left: true,
+            strafe_right: true,
+            ..Default::default()
+        };
+        let run = |step: fn(
+            &EntityKinematics,
+            InputSnapshot,
+            &ControlTuning,
+            f32,
+        ) -> EntityKinematics| {
+            let mut state = EntityKinematics::default();
+            for _ in 0..300 {
+                state = step(&state, input, &tuning, 1.0 / 60.0);
+            }
+            state
+        };
+        let (euler, rk4) = (run(step_entity_kinematics), run(step_entity_kinematics_rk4));
+        assert_eq!(rk4, run(step_entity_kinematics_rk4));
+        assert_eq!(rk4.heading_rad, euler.heading_rad);
+        for i in 0..3 {
+            assert!((rk4.position_m[i] - euler.position_m[i]).abs() < 1.0);
+        }
+    }
+
+    #[test]
+    fn brake_stops_faster_than_drag_without_overshooting() {
+        let tuning = ControlTuning::corvette();
+        let dt = 1.0 / 30.0;
+        let moving = EntityKinematics {
+            velocity_mps: [6.0, 8.0, 0.0],
+            ..Default::default()
+        };
+        let brake = InputSnapshot {
+            brake: true,
+            // Braking overrides the main engine.
+            thrust_forward: true,
+            ..Default::default()
+        };
+        let (mut braking, mut coasting) = (moving, moving);
+        let mut stopped_at = None;
+        for tick in 1..=60 {
+            braking = step_entity_kinematics(&braking, brake, &tuning, dt);
+            coasting = step_entity_kinematics(&coasting, InputSnapshot::default(), &tuning, dt);
+            // Never past zero: still heading the way it was moving.
+            assert!(braking.velocity_mps[0] >= 0.0 && braking.velocity_mps[1] >= 0.0);
+            assert!((braking.velocity_mps[0] * 8.0 - braking.velocity_mps[1] * 6.0).abs() < 1e-4);
+            if stopped_at.is_none() && length(braking.velocity_mps) < 1e-3 {
+                stopped_at = Some(tick);
+            }
+        }
+        // 10 m/s at 10 m/s² plus drag: stopped within a second.
+        assert!(stopped_at.is_some_and(|tick| tick <= 30), "{stopped_at:?}");
+        assert_eq!(braking.velocity_mps, [0.0, 0.0, 0.0]);
+        assert!(length(coasting.velocity_mps) > 4.0);
+
+        let rk4 = (0..60).fold(moving, |state, _| {
+            step_entity_kinematics_rk4(&state, brake, &tuning, dt)
+        });
+        assert_eq!(rk4.velocity_mps, [0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn sustained_thrust_levels_off_at_max_speed() {
+        // No drag, so only the clamp bounds speed.
+        let tuning = ControlTuning::corvette().with_linear_damping(0.0);
+        let max_speed = tuning.max_speed_mps.expect("corvette has a top speed");
+        let input = InputSnapshot {
+            thrust_forward: true,
+            strafe_right: true,
+            ..Default::default()
+        };
+        let mut state = EntityKinematics::default();
+        let mut previous_speed = 0.0;
+        for _ in 0..600 {
+            state = step_entity_kinematics(&state, input, &tuning, 1.0 / 30.0);
+            let speed = length(state.velocity_mps);
+            assert!(speed <= max_speed * (1.0 + 1e-6), "{speed} > {max_speed}");
+            assert!(speed >= previous_speed - 1e-4);
+            previous_speed = speed;
+        }
+        assert!((previous_speed - max_speed).abs() < 1e-3);
+
+        // RK4 at a coarse step: neither the end velocity nor the distance
+        // covered in one step may exceed the cap.
+        let dt = 0.5;
+        let mut state = EntityKinematics::default();
+        for _ in 0..120 {
+            let next = step_entity_kinematics_rk4(&state, input, &tuning, dt);
+            let displacement: [f32; 3] =
+                std::array::from_fn(|i| next.position_m[i] - state.position_m[i]);
+            assert!(length(next.velocity_mps) <= max_speed * (1.0 + 1e-6));
+            assert!(
+                length(displacement) <= max_speed * dt * (1.0 + 1e-5),
+                "{} > {}",
+                length(displacement),
+                max_speed * dt
+            );
+            state = next;
+        }
+
+        let unbounded = ControlTuning {
+            max_speed_mps: None,
+            ..tuning
+        };
+        let runaway = (0..600).fold(EntityKinematics::default(), |state, _| {
+            step_entity_kinematics(&state, input, &unbounded, 1.0 / 30.0)
+        });
+        assert!(length(runaway.velocity_mps) > 4.0 * max_speed);
+
+        let presets = [
+            ControlTuning::asteroid_with_engine(),
+            ControlTuning::corvette(),
+            ControlTuning::missile(),
+        ]
+        .map(|preset| preset.max_speed_mps.expect("presets have a top speed"));
+        assert!(presets[0] < presets[1] && presets[1] < presets[2]);
+    }
+
     #[test]
     fn yaw_changes_heading() {
         let state = EntityKinematics::default();
@@ -223,6 +1060,353 @@ mod tests {
         assert_eq!(result1, result2);
     }
 
+    fn assert_vec_near(actual: [f32; 3], expected: [f32; 3]) {
+        for i in 0..3 {
+            assert!(
+                (actual[i] - expected[i]).abs() < 1e-4,
+                "{actual:?} != {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn level_3d_yaw_and_thrust_match_the_2d_step() {
+        let tuning = ControlTuning::corvette();
+        let input = InputSnapshot {
+            thrust_forward: true,
+            yaw_left: true,
+            strafe_right: true,
+            ..Default::default()
+        };
+        let mut flat = EntityKinematics::default();
+        let mut full = EntityKinematics::default();
+        for _ in 0..30 {
+            flat = step_entity_kinematics(&flat, input, &tuning, 1.0 / 30.0);
+            full = step_entity_kinematics_3d(&full, input, &tuning, 1.0 / 30.0);
+        }
+        assert!((full.heading_rad - flat.heading_rad).abs() < 1e-4);
+        assert_vec_near(full.velocity_mps, flat.velocity_mps);
+        assert_vec_near(full.position_m, flat.position_m);
+    }
+
+    #[test]
+    fn pitch_turns_thrust_out_of_the_plane() {
+        let tuning = ControlTuning::corvette();
+        let pitch_up = InputSnapshot {
+            pitch_up: true,
+            ..Default::default()
+        };
+        let quarter_turn_s = std::f32::consts::FRAC_PI_2 / tuning.pitch_rate_rad_per_s;
+        let pitched = step_entity_kinematics_3d(
+            &EntityKinematics::default(),
+            pitch_up,
+            &tuning,
+            quarter_turn_s,
+        );
+        assert_vec_near(pitched.body_forward(), [0.0, 0.0, 1.0]);
+
+        let climbing = step_entity_kinematics_3d(
+            &pitched,
+            InputSnapshot {
+                thrust_forward: true,
+                ..Default::default()
+            },
+            &tuning,
+            1.0,
+        );
+        assert!(climbing.velocity_mps[2] > 0.0);
+        assert!(climbing.velocity_mps[0].abs() < 1e-4);
+        assert!(climbing.velocity_mps[1].abs() < 1e-4);
+    }
+
+    #[test]
+    fn roll_keeps_the_nose_and_tilts_the_wings() {
+        let tuning = ControlTuning::corvette();
+        let rolled = step_entity_kinematics_3d(
+            &EntityKinematics::default(),
+            InputSnapshot {
+                roll_right: true,
+                ..Default::default()
+            },
+            &tuning,
+            0.5,
+        );
+        assert_vec_near(rolled.body_forward(), [0.0, 1.0, 0.0]);
+        let right_wing = quat_rotate(rolled.orientation, [1.0, 0.0, 0.0]);
+        assert!(right_wing[2] < 0.0, "right wing should dip: {right_wing:?}");
+        assert_eq!(rolled.heading_rad, 0.0);
+    }
+
+    #[test]
+    fn identical_3d_inputs_are_bit_identical() {
+        let tuning = ControlTuning::corvette();
+        let inputs = [
+            InputSnapshot {
+                thrust_forward: true,
+                pitch_up: true,
+                roll_left: true,
+                ..Default::default()
+            },
+            InputSnapshot {
+                yaw_right: true,
+                pitch_down: true,
+                ..Default::default()
+            },
+            InputSnapshot {
+                thrust_reverse: true,
+                roll_right: true,
+                yaw_left: true,
+                ..Default::default()
+            },
+        ];
+        let run = || {
+            let mut state = EntityKinematics {
+                velocity_mps: [3.0, -2.0, 1.0],
+                ..Default::default()
+            };
+            for tick in 0..300 {
+                state = step_entity_kinematics_3d(&state, inputs[tick % 3], &tuning, 1.0 / 30.0);
+            }
+            state
+        };
+        let (first, second) = (run(), run());
+        assert_eq!(
+            first.position_m.map(f32::to_bits),
+            second.position_m.map(f32::to_bits)
+        );
+        assert_eq!(
+            first.velocity_mps.map(f32::to_bits),
+            second.velocity_mps.map(f32::to_bits)
+        );
+        assert_eq!(
+            first.orientation.map(f32::to_bits),
+            second.orientation.map(f32::to_bits)
+        );
+        assert_eq!(first.heading_rad.to_bits(), second.heading_rad.to_bits());
+        let norm = first.orientation.iter().map(|c| c * c).sum::<f32>();
+        assert!((norm - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn simulated_steps_pass_motion_check() {
+        let tuning = ControlTuning::corvette();
+        let limits = MotionLimits::from_tuning(&tuning, 1.05);
+        let input = InputSnapshot {
+            thrust_forward: true,
+            yaw_left: true,
+            strafe_left: true,
+            ..Default::default()
+        };
+        let mut state = EntityKinematics {
+            velocity_mps: [30.0, -5.0, 0.0],
+            ..Default::default()
+        };
+        for _ in 0..120 {
+            let next = step_entity_kinematics(&state, input, &tuning, 1.0 / 30.0);
+            assert_eq!(
+                check_motion_transition(&state, &next, &limits, 1.0 / 30.0),
+                Ok(())
+            );
+            state = next;
+        }
+    }
+
+    #[test]
+    fn acceleration_beyond_engine_capability_is_rejected() {
+        let tuning = ControlTuning::corvette();
+        let limits = MotionLimits::from_tuning(&tuning, 1.05);
+        let prev = EntityKinematics::default();
+        let next = EntityKinematics {
+            velocity_mps: [0.0, tuning.thrust_accel_mps2 * 2.0, 0.0],
+            position_m: [0.0, tuning.thrust_accel_mps2 * 2.0, 0.0],
+            ..Default::default()
+        };
+        assert!(matches!(
+            check_motion_transition(&prev, &next, &limits, 1.0),
+            Err(MotionViolation::ExcessAcceleration { .. })
+        ));
+    }
+
+    #[test]
+    fn position_jump_without_velocity_is_a_teleport() {
+        let limits = MotionLimits::from_tuning(&ControlTuning::corvette(), 1.05);
+        let prev = EntityKinematics {
+            velocity_mps: [10.0, 0.0, 0.0],
+            ..Default::default()
+        };
+        let next = EntityKinematics {
+            position_m: [500.0, 0.0, 0.0],
+            velocity_mps: [6.0, 0.0, 0.0],
+            ..Default::default()
+        };
+        assert!(matches!(
+            check_motion_transition(&prev, &next, &limits, 1.0),
+            Err(MotionViolation::Teleport { .. })
+        ));
+    }
+
+    fn at(position_m: [f32; 3], velocity_mps: [f32; 3]) -> EntityKinematics {
+        EntityKinematics {
+            position_m,
+            velocity_mps,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn stationary_target_is_aimed_at_directly() {
+        let shooter = at([0.0, 0.0, 0.0], [0.0, 0.0, 0.0]);
+        let ahead = at([0.0, 500.0, 0.0], [0.0, 0.0, 0.0]);
+        let right = at([300.0, 0.0, 0.0], [0.0, 0.0, 0.0]);
+        let heading = intercept_heading(&shooter, &ahead, 800.0).expect("intercept");
+        assert!(heading.abs() < 1e-6);
+        let heading = intercept_heading(&shooter, &right, 800.0).expect("intercept");
+        assert!((heading - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn crossing_target_gets_a_lead_angle() {
+        let shooter = at([0.0, 0.0, 0.0], [0.0, 0.0, 0.0]);
+        // 1000 m ahead, crossing left to right at 100 m/s; projectile 500 m/s.
+        let target = at([0.0, 1000.0, 0.0], [100.0, 0.0, 0.0]);
+        let heading = intercept_heading(&shooter, &target, 500.0).expect("intercept");
+        assert!(heading > 0.0, "should lead toward +X, got {heading}");
+
+        // The projectile and the target arrive at the same point.
+        let speed_sq = 500.0f32 * 500.0 - 100.0 * 100.0;
+        let time_s = 1000.0 / speed_sq.sqrt();
+        let expected = (100.0 * time_s).atan2(1000.0);
+        assert!((heading - expected).abs() < 1e-4);
+    }
+
+    #[test]
+    fn shooter_velocity_is_inherited_by_the_projectile() {
+        // Shooter and target drift together, so no lead is needed.
+        let shooter = at([0.0, 0.0, 0.0], [40.0, 0.0, 0.0]);
+        let target = at([0.0, 400.0, 0.0], [40.0, 0.0, 0.0]);
+        let heading = intercept_heading(&shooter, &target, 300.0).expect("intercept");
+        assert!(heading.abs() < 1e-6);
+    }
+
+    #[test]
+    fn target_faster_than_the_projectile_and_fleeing_is_unreachable() {
+        let shooter = at([0.0, 0.0, 0.0], [0.0, 0.0, 0.0]);
+        let fleeing = at([0.0, 500.0, 0.0], [0.0, 400.0, 0.0]);
+        assert_eq!(intercept_heading(&shooter, &fleeing, 300.0), None);
+        assert_eq!(intercept_heading(&shooter, &fleeing, 0.0), None);
+    }
+
+    #[test]
+    fn heading_wraparound_is_not_a_yaw_violation() {
+        let tuning = ControlTuning::corvette();
+        let limits = MotionLimits::from_tuning(&tuning, 1.05);
+        let prev = EntityKinematics {
+            heading_rad: std::f32::consts::PI - 0.01,
+            ..Default::default()
+        };
+        let next = EntityKinematics {
+            heading_rad: -std::f32::consts::PI + 0.01,
+            ..Default::default()
+        };
+        assert_eq!(check_motion_transition(&prev, &next, &limits, 0.1), Ok(()));
+
+        let spun = EntityKinematics {
+            heading_rad: prev.heading_rad - 1.0,
+            ..prev
+        };
+        assert!(matches!(
+            check_motion_transition(&prev, &spun, &limits, 0.1),
+            Err(MotionViolation::ExcessYawRate { .. })
+        ));
+    }
+
+    #[test]
+    fn input_recording_keeps_tick_order() {
+        let forward = InputSnapshot {
+            thrust_forward: true,
+            ..Default::default()
+        };
+        let mut recording = InputRecording::default();
+        assert!(recording.push(3, forward));
+        assert!(recording.push(3, InputSnapshot::default()));
+        assert!(recording.push(7, forward));
+        assert!(!recording.push(5, forward));
+
+        let ticks = recording.frames.iter().map(|f| f.tick).collect::<Vec<_>>();
+        assert_eq!(ticks, vec![3, 3, 7]);
+        assert_eq!(recording.frames[0].input, forward);
+        assert_eq!(recording.len(), 3);
+    }
+
+    #[test]
+    fn replaying_unacknowledged_inputs_reaches_the_server_state() {
+        let tuning = ControlTuning::corvette();
+        let dt = 1.0 / 30.0;
+        let input_at = |tick: u64| InputSnapshot {
+            thrust_forward: !tick.is_multiple_of(5),
+            yaw_left: tick % 7 < 3,
+            strafe_right: tick.is_multiple_of(11),
+            ..Default::default()
+        };
+
+        // The server applies every input; the client records the same ones.
+        let mut server_states = vec![EntityKinematics::default()];
+        let mut buffer = ReplayBuffer::default();
+        for tick in 1..=40 {
+            let previous = server_states[server_states.len() - 1];
+            server_states.push(step_entity_kinematics(
+                &previous,
+                input_at(tick),
+                &tuning,
+                dt,
+            ));
+            assert!(buffer.push(tick, input_at(tick)));
+        }
+
+        // Server confirms tick 25; the client rebases and replays 26..=40.
+        buffer.acknowledge(25);
+        assert_eq!(buffer.len(), 15);
+        let pending = buffer.pending_after(25).expect("inputs are contiguous");
+        let replayed = resimulate(&server_states[25], pending, &tuning, dt);
+        assert_eq!(replayed, server_states[40]);
+    }
+
+    #[test]
+    fn replay_buffer_refuses_gaps_and_stale_ticks() {
+        let mut buffer = ReplayBuffer::with_capacity(3);
+        assert!(buffer.push(1, InputSnapshot::default()));
+        assert!(!buffer.push(1, InputSnapshot::default()));
+        assert!(buffer.push(2, InputSnapshot::default()));
+        assert!(buffer.push(4, InputSnapshot::default()));
+        assert_eq!(buffer.pending_after(0), None, "tick 3 is missing");
+        assert_eq!(buffer.pending_after(3).map(|inputs| inputs.len()), Some(1));
+        assert_eq!(buffer.pending_after(4), Some(Vec::new()));
+
+        assert!(buffer.push(5, InputSnapshot::default()));
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.pending_after(0), None, "tick 1 was evicted");
+        assert_eq!(buffer.latest_tick(), Some(5));
+    }
+
+    #[test]
+    fn drag_from_linear_damping_tracks_avian_decay() {
+        let damping_per_s = 0.9;
+        let tuning = ControlTuning::corvette().with_linear_damping(damping_per_s);
+        assert_eq!(tuning.drag_per_s, damping_per_s);
+        let clamped = ControlTuning::corvette().with_linear_damping(-1.0);
+        assert_eq!(clamped.drag_per_s, 0.0);
+
+        let dt = 1.0 / 30.0;
+        let state = EntityKinematics {
+            velocity_mps: [10.0, 0.0, 0.0],
+            ..Default::default()
+        };
+        let next = step_entity_kinematics(&state, InputSnapshot::default(), &tuning, dt);
+        // Avian scales velocity by 1 / (1 + dt * damping) each step.
+        let avian_velocity = 10.0 / (1.0 + dt * damping_per_s);
+        assert!((next.velocity_mps[0] - avian_velocity).abs() < 1e-2);
+    }
+
     #[test]
     fn control_tuning_presets_are_distinct() {
         let corvette = ControlTuning::corvette();
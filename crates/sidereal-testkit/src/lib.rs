@@ -0,0 +1,484 @@
+//! Builders for the worlds tests otherwise assemble by hand: a game `App`,
+//! ships and modules spawned into it, and the `WorldStateDelta`s replication
+//! streams about them. Intended as a dev-dependency.
+
+use bevy::prelude::*;
+use serde_json::json;
+use sidereal_core::entity_ids::TypedEntityId;
+use sidereal_game::{
+    BaseMassKg, Engine, EntityGuid, Hardpoint, HealthPool, Inventory, MassDirty, ModuleMassKg,
+    ModuleTag, MountedOn, OwnerId, PositionM, ShipTag, SiderealGamePlugin, VelocityMps,
+};
+use sidereal_net::{WorldComponentDelta, WorldDeltaEntity, WorldStateDelta};
+use uuid::Uuid;
+
+/// A headless `App` with `SiderealGamePlugin`: the component registry,
+/// reflection and the FixedUpdate flight systems, without rendering or
+/// physics.
+pub fn game_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, SiderealGamePlugin));
+    app
+}
+
+/// A ship, spawned into a `World` or described as a replication update.
+#[derive(Debug, Clone)]
+pub struct ShipBuilder {
+    guid: Uuid,
+    owner: Option<String>,
+    position: Vec3,
+    velocity: Vec3,
+    health: HealthPool,
+    base_mass_kg: f32,
+}
+
+impl Default for ShipBuilder {
+    fn default() -> Self {
+        Self {
+            guid: Uuid::new_v4(),
+            owner: None,
+            position: Vec3::ZERO,
+            velocity: Vec3::ZERO,
+            health: HealthPool {
+                current: 1000.0,
+                maximum: 1000.0,
+            },
+            base_mass_kg: 15_000.0,
+        }
+    }
+}
+
+impl ShipBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_guid(mut self, guid: Uuid) -> Self {
+        self.guid = guid;
+        self
+    }
+
+    /// Owner as a player entity id, e.g. `"player:<uuid>"`.
+    pub fn owned_by(mut self, player_entity_id: impl Into<String>) -> Self {
+        self.owner = Some(player_entity_id.into());
+        self
+    }
+
+    pub fn at(mut self, position: Vec3) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn moving(mut self, velocity: Vec3) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    pub fn with_health(mut self, current: f32, maximum: f32) -> Self {
+        self.health = HealthPool { current, maximum };
+        self
+    }
+
+    pub fn with_base_mass_kg(mut self, base_mass_kg: f32) -> Self {
+        self.base_mass_kg = base_mass_kg;
+        self
+    }
+
+    pub fn guid(&self) -> Uuid {
+        self.guid
+    }
+
+    pub fn entity_id(&self) -> String {
+        TypedEntityId::ship(self.guid).to_string()
+    }
+
+    pub fn spawn(&self, world: &mut World) -> Entity {
+        let mut entity = world.spawn((
+            EntityGuid(self.guid),
+            ShipTag,
+            PositionM(self.position),
+            VelocityMps(self.velocity),
+            self.health.clone(),
+            BaseMassKg(self.base_mass_kg),
+            Inventory::default(),
+            MassDirty,
+        ));
+        if let Some(owner) = &self.owner {
+            entity.insert(OwnerId(owner.clone()));
+        }
+        entity.id()
+    }
+
+    /// The ship as replication streams it: spatial state and health in
+    /// `properties`, the owner as an `owner_id` component.
+    pub fn to_delta(&self) -> WorldDeltaEntity {
+        let entity_id = self.entity_id();
+        let mut properties = json!({
+            "entity_id": entity_id,
+            "position_m": self.position.to_array(),
+            "velocity_mps": self.velocity.to_array(),
+            "health": self.health.current,
+        });
+        let mut components = Vec::new();
+        if let Some(owner) = &self.owner {
+            properties["player_entity_id"] = json!(owner);
+            components.push(WorldComponentDelta {
+                component_id: format!("{entity_id}:owner_id"),
+                component_kind: "owner_id".to_string(),
+                properties: json!(owner),
+            });
+        }
+        WorldDeltaEntity {
+            entity_id,
+            labels: vec!["Entity".to_string(), "Ship".to_string()],
+            properties,
+            components,
+            removed: false,
+            removed_component_ids: Vec::new(),
+            removal_reason: None,
+        }
+    }
+}
+
+/// A hardpoint entity on a ship, as hydration spawns them: a child of the
+/// ship that modules mount onto.
+#[derive(Debug, Clone)]
+pub struct HardpointBuilder {
+    guid: Uuid,
+    ship_entity_id: String,
+    hardpoint_id: String,
+    offset_m: Vec3,
+}
+
+impl HardpointBuilder {
+    pub fn on(ship: &ShipBuilder, hardpoint_id: impl Into<String>) -> Self {
+        Self {
+            guid: Uuid::new_v4(),
+            ship_entity_id: ship.entity_id(),
+            hardpoint_id: hardpoint_id.into(),
+            offset_m: Vec3::ZERO,
+        }
+    }
+
+    pub fn with_guid(mut self, guid: Uuid) -> Self {
+        self.guid = guid;
+        self
+    }
+
+    pub fn at_offset(mut self, offset_m: Vec3) -> Self {
+        self.offset_m = offset_m;
+        self
+    }
+
+    pub fn guid(&self) -> Uuid {
+        self.guid
+    }
+
+    pub fn entity_id(&self) -> String {
+        TypedEntityId::hardpoint(self.guid).to_string()
+    }
+
+    /// Spawns the hardpoint as a child of `ship`, the ship entity spawned
+    /// from the builder this hardpoint was made `on`.
+    pub fn spawn(&self, world: &mut World, ship: Entity) -> Entity {
+        world
+            .spawn((
+                EntityGuid(self.guid),
+                Hardpoint {
+                    hardpoint_id: self.hardpoint_id.clone(),
+                    offset_m: self.offset_m,
+                },
+                ChildOf(ship),
+            ))
+            .id()
+    }
+
+    /// The hardpoint as replication streams it, parented to its ship.
+    pub fn to_delta(&self) -> WorldDeltaEntity {
+        let entity_id = self.entity_id();
+        let offset_m = self.offset_m.to_array();
+        WorldDeltaEntity {
+            properties: json!({
+                "hardpoint_id": self.hardpoint_id,
+                "offset_m": offset_m,
+                "parent_entity_id": self.ship_entity_id,
+                "owner_entity_id": self.ship_entity_id,
+            }),
+            components: vec![WorldComponentDelta {
+                component_id: format!("{entity_id}:hardpoint"),
+                component_kind: "hardpoint".to_string(),
+                properties: json!({
+                    "hardpoint_id": self.hardpoint_id,
+                    "offset_m": offset_m,
+                }),
+            }],
+            entity_id,
+            labels: vec!["Entity".to_string(), "Hardpoint".to_string()],
+            removed: false,
+            removed_component_ids: Vec::new(),
+            removal_reason: None,
+        }
+    }
+}
+
+/// A module mounted on a ship, either directly or on one of its hardpoint
+/// entities, optionally an engine.
+#[derive(Debug, Clone)]
+pub struct ModuleBuilder {
+    guid: Uuid,
+    /// Guid and entity id of what `MountedOn` points at.
+    mount_guid: Uuid,
+    mount_entity_id: String,
+    hardpoint_id: String,
+    mass_kg: f32,
+    engine: Option<Engine>,
+}
+
+impl ModuleBuilder {
+    /// Mounted straight on `ship`, as freshly spawned corvettes mount theirs.
+    pub fn mounted_on(ship: &ShipBuilder, hardpoint_id: impl Into<String>) -> Self {
+        Self::new(ship.guid(), ship.entity_id(), hardpoint_id.into())
+    }
+
+    /// Mounted on a hardpoint entity, as hydrated ships mount theirs.
+    pub fn mounted_on_hardpoint(hardpoint: &HardpointBuilder) -> Self {
+        Self::new(
+            hardpoint.guid(),
+            hardpoint.entity_id(),
+            hardpoint.hardpoint_id.clone(),
+        )
+    }
+
+    fn new(mount_guid: Uuid, mount_entity_id: String, hardpoint_id: String) -> Self {
+        Self {
+            guid: Uuid::new_v4(),
+            mount_guid,
+            mount_entity_id,
+            hardpoint_id,
+            mass_kg: 500.0,
+            engine: None,
+        }
+    }
+
+    pub fn with_guid(mut self, guid: Uuid) -> Self {
+        self.guid = guid;
+        self
+    }
+
+    pub fn with_mass_kg(mut self, mass_kg: f32) -> Self {
+        self.mass_kg = mass_kg;
+        self
+    }
+
+    pub fn with_engine(mut self, engine: Engine) -> Self {
+        self.engine = Some(engine);
+        self
+    }
+
+    pub fn guid(&self) -> Uuid {
+        self.guid
+    }
+
+    pub fn entity_id(&self) -> String {
+        TypedEntityId::module(self.guid).to_string()
+    }
+
+    pub fn spawn(&self, world: &mut World) -> Entity {
+        let mut entity = world.spawn((
+            EntityGuid(self.guid),
+            ModuleTag,
+            MountedOn {
+                parent_entity_id: self.mount_guid,
+                hardpoint_id: self.hardpoint_id.clone(),
+            },
+            ModuleMassKg(self.mass_kg),
+        ));
+        if let Some(engine) = &self.engine {
+            entity.insert(engine.clone());
+        }
+        entity.id()
+    }
+
+    /// The module as replication streams it, with the mount in `properties`
+    /// the way loadout summaries read it.
+    pub fn to_delta(&self) -> WorldDeltaEntity {
+        let entity_id = self.entity_id();
+        let components = self
+            .engine
+            .iter()
+            .map(|engine| WorldComponentDelta {
+                component_id: format!("{entity_id}:engine"),
+                component_kind: "engine".to_string(),
+                properties: serde_json::to_value(engine).unwrap_or_default(),
+            })
+            .collect();
+        WorldDeltaEntity {
+            properties: json!({
+                "entity_id": entity_id,
+                "mounted_on_entity_id": self.mount_entity_id,
+                "hardpoint_id": self.hardpoint_id,
+            }),
+            entity_id,
+            labels: vec!["Entity".to_string(), "Module".to_string()],
+            components,
+            removed: false,
+            removed_component_ids: Vec::new(),
+            removal_reason: None,
+        }
+    }
+}
+
+/// Collects updates into a `WorldStateDelta` in the order they are added.
+#[derive(Debug, Clone, Default)]
+pub struct WorldDeltaBuilder {
+    updates: Vec<WorldDeltaEntity>,
+}
+
+impl WorldDeltaBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn entity(mut self, update: WorldDeltaEntity) -> Self {
+        self.updates.push(update);
+        self
+    }
+
+    pub fn ship(self, ship: &ShipBuilder) -> Self {
+        self.entity(ship.to_delta())
+    }
+
+    pub fn hardpoint(self, hardpoint: &HardpointBuilder) -> Self {
+        self.entity(hardpoint.to_delta())
+    }
+
+    pub fn module(self, module: &ModuleBuilder) -> Self {
+        self.entity(module.to_delta())
+    }
+
+    /// A removal of `entity_id`, as replication sends for despawned entities.
+    pub fn removed(self, entity_id: impl Into<String>) -> Self {
+        self.entity(WorldDeltaEntity {
+            entity_id: entity_id.into(),
+            labels: Vec::new(),
+            properties: json!({}),
+            components: Vec::new(),
+            removed: true,
+            removed_component_ids: Vec::new(),
+            removal_reason: None,
+        })
+    }
+
+    pub fn build(self) -> WorldStateDelta {
+        WorldStateDelta {
+            updates: self.updates,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builders_spawn_entities_with_expected_components() {
+        let mut app = game_app();
+        assert!(
+            app.world()
+                .contains_resource::<sidereal_game::GeneratedComponentRegistry>()
+        );
+
+        let ship = ShipBuilder::new()
+            .owned_by("player:pilot")
+            .at(Vec3::new(10.0, -4.0, 0.0))
+            .with_health(640.0, 1000.0);
+        let engine = Engine {
+            thrust_n: 250_000.0,
+            burn_rate_kg_s: 2.5,
+            thrust_dir: Vec3::Y,
+        };
+        let module = ModuleBuilder::mounted_on(&ship, "aft").with_engine(engine.clone());
+        let ship_entity = ship.spawn(app.world_mut());
+        let module_entity = module.spawn(app.world_mut());
+
+        let world = app.world();
+        assert_eq!(
+            world.get::<EntityGuid>(ship_entity).map(|g| g.0),
+            Some(ship.guid())
+        );
+        assert!(world.get::<ShipTag>(ship_entity).is_some());
+        assert_eq!(
+            world.get::<PositionM>(ship_entity).map(|p| p.0),
+            Some(Vec3::new(10.0, -4.0, 0.0))
+        );
+        assert_eq!(
+            world.get::<HealthPool>(ship_entity).map(|h| h.current),
+            Some(640.0)
+        );
+        assert_eq!(
+            world.get::<OwnerId>(ship_entity).map(|o| o.0.as_str()),
+            Some("player:pilot")
+        );
+        let mount = world
+            .get::<MountedOn>(module_entity)
+            .expect("module should be mounted");
+        assert_eq!(mount.parent_entity_id, ship.guid());
+        assert_eq!(mount.hardpoint_id, "aft");
+        assert_eq!(world.get::<Engine>(module_entity), Some(&engine));
+
+        let delta = WorldDeltaBuilder::new()
+            .ship(&ship)
+            .module(&module)
+            .removed("ship:gone")
+            .build();
+        assert_eq!(delta.updates.len(), 3);
+        assert_eq!(delta.updates[0].derived_kind(), "Ship");
+        assert_eq!(delta.updates[0].components[0].component_kind, "owner_id");
+        assert_eq!(
+            delta.updates[1].properties["mounted_on_entity_id"],
+            json!(ship.entity_id())
+        );
+        assert!(delta.updates[2].removed);
+    }
+
+    #[test]
+    fn modules_mount_through_hardpoint_entities() {
+        let mut app = game_app();
+        let ship = ShipBuilder::new();
+        let hardpoint = HardpointBuilder::on(&ship, "dorsal").at_offset(Vec3::new(0.0, 1.5, 0.0));
+        let module = ModuleBuilder::mounted_on_hardpoint(&hardpoint);
+        let ship_entity = ship.spawn(app.world_mut());
+        let hardpoint_entity = hardpoint.spawn(app.world_mut(), ship_entity);
+        let module_entity = module.spawn(app.world_mut());
+
+        let world = app.world();
+        assert_eq!(
+            world.get::<ChildOf>(hardpoint_entity).map(ChildOf::parent),
+            Some(ship_entity)
+        );
+        assert_eq!(
+            world.get::<Hardpoint>(hardpoint_entity).map(|h| h.offset_m),
+            Some(Vec3::new(0.0, 1.5, 0.0))
+        );
+        let mount = world
+            .get::<MountedOn>(module_entity)
+            .expect("module should be mounted");
+        assert_eq!(mount.parent_entity_id, hardpoint.guid());
+        assert_eq!(mount.hardpoint_id, "dorsal");
+
+        let delta = WorldDeltaBuilder::new()
+            .ship(&ship)
+            .hardpoint(&hardpoint)
+            .module(&module)
+            .build();
+        assert_eq!(
+            delta.updates[1].properties["parent_entity_id"],
+            json!(ship.entity_id())
+        );
+        assert_eq!(
+            delta.updates[2].properties["mounted_on_entity_id"],
+            json!(hardpoint.entity_id())
+        );
+        assert_eq!(delta.updates[2].properties["hardpoint_id"], json!("dorsal"));
+    }
+}